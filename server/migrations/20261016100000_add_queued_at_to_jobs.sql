@@ -0,0 +1,9 @@
+-- Track when a job was published to the analysis queue so pending jobs
+-- orphaned by a broker outage can be re-published
+ALTER TABLE jobs ADD COLUMN queued_at TIMESTAMPTZ;
+
+-- Existing jobs predate tracking; assume they were queued
+UPDATE jobs SET queued_at = created_at;
+
+CREATE INDEX idx_jobs_unqueued_pending ON jobs(created_at)
+    WHERE status = 'pending' AND queued_at IS NULL;
@@ -0,0 +1,13 @@
+-- Audit trail of job state changes made outside the normal worker flow
+CREATE TABLE job_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    job_id BIGINT NOT NULL REFERENCES jobs(job_id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    from_status job_status NOT NULL,
+    to_status job_status NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    message TEXT,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_job_events_job_id ON job_events(job_id, created_at);
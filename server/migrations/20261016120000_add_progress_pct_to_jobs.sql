@@ -0,0 +1,3 @@
+-- Latest progress reported by the worker while a job is processing
+ALTER TABLE jobs ADD COLUMN progress_pct SMALLINT
+    CHECK (progress_pct BETWEEN 0 AND 100);
@@ -0,0 +1,2 @@
+-- Model version used when an analysis request doesn't name one
+ALTER TABLE users ADD COLUMN preferred_model_version VARCHAR(50);
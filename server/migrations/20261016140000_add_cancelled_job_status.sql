@@ -0,0 +1,2 @@
+-- Jobs stopped by their owner before a result arrived
+ALTER TYPE job_status ADD VALUE IF NOT EXISTS 'cancelled';
@@ -0,0 +1,4 @@
+-- Links a retry to the failed job it replaces; each failed job is retried at most once
+ALTER TABLE jobs ADD COLUMN retry_of BIGINT REFERENCES jobs(job_id) ON DELETE SET NULL;
+
+CREATE UNIQUE INDEX idx_jobs_retry_of ON jobs(retry_of) WHERE retry_of IS NOT NULL;
@@ -0,0 +1,10 @@
+-- User-defined tags on images, such as "favorite"
+CREATE TABLE image_tags (
+    image_id BIGINT NOT NULL REFERENCES images(image_id) ON DELETE CASCADE,
+    tag VARCHAR(32) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (image_id, tag)
+);
+
+-- Listing a folder filtered by tag
+CREATE INDEX idx_image_tags_tag ON image_tags(tag, image_id);
@@ -0,0 +1,8 @@
+-- Access tokens revoked before they expire (on logout), keyed by their `jti` claim
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Purging entries for tokens that have expired anyway
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
@@ -0,0 +1,2 @@
+-- Storage key of the image's generated thumbnail; NULL until one is generated
+ALTER TABLE images ADD COLUMN thumbnail_key TEXT;
@@ -0,0 +1,43 @@
+-- When a folder's image listing last changed, for conditional listings.
+-- Kept by triggers so every write counts: uploads, renames, moves (both
+-- folders), merges, restores, tag and metadata edits, and hard deletes,
+-- which leave no image row behind to date them.
+ALTER TABLE folders ADD COLUMN images_modified_at TIMESTAMPTZ;
+
+UPDATE folders f
+SET images_modified_at = (
+    SELECT MAX(GREATEST(i.uploaded_at, i.deleted_at))
+    FROM images i
+    WHERE i.folder_id = f.folder_id
+);
+
+CREATE FUNCTION touch_image_folders() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE folders SET images_modified_at = NOW() WHERE folder_id = OLD.folder_id;
+    END IF;
+    IF TG_OP = 'INSERT' OR (TG_OP = 'UPDATE' AND NEW.folder_id <> OLD.folder_id) THEN
+        UPDATE folders SET images_modified_at = NOW() WHERE folder_id = NEW.folder_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER images_touch_folders
+    AFTER INSERT OR UPDATE OR DELETE ON images
+    FOR EACH ROW EXECUTE FUNCTION touch_image_folders();
+
+CREATE FUNCTION touch_tagged_image_folder() RETURNS trigger AS $$
+BEGIN
+    UPDATE folders f
+    SET images_modified_at = NOW()
+    FROM images i
+    WHERE i.image_id = CASE WHEN TG_OP = 'DELETE' THEN OLD.image_id ELSE NEW.image_id END
+      AND f.folder_id = i.folder_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER image_tags_touch_folder
+    AFTER INSERT OR DELETE ON image_tags
+    FOR EACH ROW EXECUTE FUNCTION touch_tagged_image_folder();
@@ -0,0 +1,21 @@
+-- Originals the server cannot decode are marked so thumbnail requests serve
+-- them directly instead of downloading and decoding them every time.
+ALTER TABLE images ADD COLUMN thumbnail_unavailable BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Thumbnail bookkeeping doesn't change what a folder listing shows
+CREATE OR REPLACE FUNCTION touch_image_folders() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND to_jsonb(NEW) - 'thumbnail_key' - 'thumbnail_unavailable'
+            = to_jsonb(OLD) - 'thumbnail_key' - 'thumbnail_unavailable' THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE folders SET images_modified_at = NOW() WHERE folder_id = OLD.folder_id;
+    END IF;
+    IF TG_OP = 'INSERT' OR (TG_OP = 'UPDATE' AND NEW.folder_id <> OLD.folder_id) THEN
+        UPDATE folders SET images_modified_at = NOW() WHERE folder_id = NEW.folder_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,9 @@
+-- Signatures of worker requests already accepted, so a captured request
+-- can't be replayed while its timestamp is still within the allowed skew
+CREATE TABLE worker_request_signatures (
+    signature TEXT PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Purging entries whose timestamp would be rejected as stale anyway
+CREATE INDEX idx_worker_request_signatures_expires_at ON worker_request_signatures(expires_at);
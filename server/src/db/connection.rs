@@ -1,12 +1,44 @@
+use std::ops::Deref;
+
 use secrecy::ExposeSecret;
 use sqlx::postgres::{PgPool, PgPoolOptions};
 
 use crate::config::settings::DatabaseConfig;
 
+/// Pool for read-only queries (listings and aggregates)
+///
+/// Points at the read replica when `database.read_url` is set, otherwise
+/// shares the primary pool. Derefs to `PgPool`, so repository methods accept it.
+#[derive(Clone)]
+pub struct ReadPool(pub PgPool);
+
+impl Deref for ReadPool {
+    type Target = PgPool;
+
+    fn deref(&self) -> &PgPool {
+        &self.0
+    }
+}
+
 pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
+    connect(config, config.url.expose_secret()).await
+}
+
+/// Create the read pool, falling back to `primary` when no read URL is configured
+pub async fn create_read_pool(
+    config: &DatabaseConfig,
+    primary: &PgPool,
+) -> Result<ReadPool, sqlx::Error> {
+    match &config.read_url {
+        Some(read_url) => Ok(ReadPool(connect(config, read_url.expose_secret()).await?)),
+        None => Ok(ReadPool(primary.clone())),
+    }
+}
+
+async fn connect(config: &DatabaseConfig, url: &str) -> Result<PgPool, sqlx::Error> {
     PgPoolOptions::new()
         .max_connections(config.max_connections)
         .min_connections(config.min_connections)
-        .connect(config.url.expose_secret())
+        .connect(url)
         .await
 }
@@ -1 +1,4 @@
 pub mod connection;
+pub mod pool_metrics;
+
+pub use connection::ReadPool;
@@ -0,0 +1,33 @@
+//! Pool Metrics
+//!
+//! Samples connection pool usage into the `db_pool_connections` gauges so
+//! pool-timeout 503s can be correlated with saturation.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::metrics::{self, DB_POOL_CONNECTIONS};
+
+const HELP: &str = "Database pool connections by state";
+
+/// Sample the pool every `interval`, forever
+pub async fn run(pool: PgPool, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval.max(Duration::from_secs(1)));
+    loop {
+        ticker.tick().await;
+        record(&pool);
+    }
+}
+
+/// Record the pool's current connection counts in the metrics registry
+pub fn record(pool: &PgPool) {
+    let total = pool.size();
+    let idle = pool.num_idle() as u32;
+    let active = total.saturating_sub(idle);
+
+    let registry = metrics::registry();
+    for (state, count) in [("total", total), ("idle", idle), ("active", active)] {
+        registry.set_gauge(DB_POOL_CONNECTIONS, HELP, &[("state", state)], count as f64);
+    }
+}
@@ -2,9 +2,12 @@
 //!
 //! Centralized error handling and standard API response format.
 
-use serde::Serialize;
+use actix_web::{HttpMessage, HttpRequest};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::middleware::request_id::RequestId;
+
 /// Standard API response wrapper
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ApiResponse<T: Serialize> {
@@ -28,17 +31,99 @@ impl<T: Serialize> ApiResponse<T> {
         ApiResponse {
             success: false,
             data: None,
-            error: Some(ApiError {
-                code: code.into(),
-                message: message.into(),
-            }),
+            error: Some(ApiError::new(code, message)),
+        }
+    }
+
+    /// `INTERNAL_ERROR` response tagged with the request id and a timestamp
+    pub fn internal_error(req: &HttpRequest, message: impl Into<String>) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            error: Some(ApiError::for_request(req, "INTERNAL_ERROR", message)),
         }
     }
 }
 
 /// API error structure
-#[derive(Debug, Clone, Serialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ApiError {
     pub code: String,
     pub message: String,
+    /// Id of the failed request, also logged and sent as `X-Request-Id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// When the error occurred (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        ApiError {
+            code: code.into(),
+            message: message.into(),
+            request_id: None,
+            timestamp: None,
+        }
+    }
+
+    /// Error tagged with the id the request-id middleware stored in the
+    /// request extensions, or a fresh id if the middleware did not run
+    pub fn for_request(
+        req: &HttpRequest,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        let request_id = req.extensions().get::<RequestId>().cloned().unwrap_or_else(|| {
+            let id = RequestId::generate();
+            tracing::error!(request_id = %id.0, "Request failed without a request id");
+            id
+        });
+
+        ApiError {
+            request_id: Some(request_id.0),
+            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+            ..ApiError::new(code, message)
+        }
+    }
+}
+
+/// Whether a database error is a unique-constraint violation (SQLSTATE 23505)
+///
+/// An insert that lost a race with a concurrent one trips the constraint, so
+/// callers can report it as a 409 conflict instead of a 500.
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(db_error) if db_error.is_unique_violation())
+}
+
+/// RFC 9457 problem details, used for errors when the envelope is disabled
+/// or the client sends `Accept: application/problem+json`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Same code as `ApiError::code`
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+impl ProblemDetails {
+    pub fn from_api_error(status: u16, title: impl Into<String>, error: ApiError) -> Self {
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: title.into(),
+            status,
+            detail: error.message,
+            code: error.code,
+            request_id: error.request_id,
+            timestamp: error.timestamp,
+        }
+    }
 }
@@ -1,3 +1,3 @@
 pub mod error;
 
-pub use error::{ApiError, ApiResponse};
+pub use error::{is_unique_violation, ApiError, ApiResponse, ProblemDetails};
@@ -3,29 +3,131 @@
 //! Request and Response DTOs for AI Analysis endpoints.
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+use crate::config::settings::DetectionOverflowPolicy;
+use crate::domain::ApiError;
+use crate::models::job::{Job, JobStatus};
+
+/// Maximum length of a worker-provided result summary
+pub const MAX_RESULT_SUMMARY_LENGTH: u64 = 10_000;
+
+/// Maximum length of an operator's note when manually resolving a job
+pub const MAX_RESOLVE_MESSAGE_LENGTH: u64 = 1_000;
+
+/// Largest display width or height detections can be scaled to
+pub const MAX_SCALED_DIMENSION: u32 = 10_000;
+
+/// Beyond this many decimals an f64 confidence has nothing left to round
+const MAX_CONFIDENCE_DECIMALS: u32 = 15;
+
+/// Round a confidence score to `decimals` places for output
+///
+/// Stored scores keep full precision; only response values are rounded.
+pub fn round_confidence(value: f64, decimals: u32) -> f64 {
+    if decimals >= MAX_CONFIDENCE_DECIMALS || !value.is_finite() {
+        return value;
+    }
+    let factor = 10_f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
 
 // ============================================================================
 // Request DTOs
 // ============================================================================
 
+/// Model version used when neither the request nor the user's preference names one
+pub const DEFAULT_MODEL_VERSION: &str = "v1.0.0";
+
 /// Request to analyze an image
-#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
 pub struct AnalyzeImageRequest {
-    /// AI model version to use (optional, defaults to latest)
+    /// AI model version to use (optional, defaults to the user's preferred
+    /// version, then latest)
+    #[serde(default)]
+    pub model_version: Option<String>,
+    /// Queue a new job even if one for this image and model is still pending
+    /// or processing (by default that job is returned instead), or if
+    /// `analysis.block_reanalysis` would reject an image already analyzed
+    /// with this model
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn default_model_version() -> String {
+    DEFAULT_MODEL_VERSION.to_string()
+}
+
+/// Request to analyze several images in one call
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct BatchAnalyzeRequest {
+    /// Images to analyze (may span multiple folders), at most `limits.max_batch_size`
+    #[validate(length(min = 1, message = "image_ids must not be empty"))]
+    pub image_ids: Vec<i64>,
+    /// AI model version to use for every job (optional, defaults to latest)
     #[serde(default = "default_model_version")]
     pub model_version: String,
 }
 
-fn default_model_version() -> String {
-    "v1.0.0".to_string()
+/// Query parameters for submitting an image for analysis
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct AnalyzeImageQuery {
+    /// Deprecated alias for the body's `force`; either one enables it
+    #[serde(default)]
+    pub force: bool,
 }
 
-impl Default for AnalyzeImageRequest {
-    fn default() -> Self {
-        Self {
-            model_version: default_model_version(),
-        }
+/// Query parameters for fetching an analysis result
+#[derive(Debug, Clone, Default, Deserialize, Validate, IntoParams)]
+pub struct JobResultQuery {
+    /// Only count boxes at or above this confidence, overriding the model's default
+    #[validate(range(min = 0.0, max = 1.0, message = "min_confidence must be between 0 and 1"))]
+    pub min_confidence: Option<f64>,
+}
+
+/// Display size to scale detection coordinates to
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
+pub struct ScaledDetectionsQuery {
+    /// Target width in pixels
+    #[validate(range(min = 1, max = MAX_SCALED_DIMENSION, message = "width must be between 1 and 10000"))]
+    pub width: u32,
+    /// Target height in pixels
+    #[validate(range(min = 1, max = MAX_SCALED_DIMENSION, message = "height must be between 1 and 10000"))]
+    pub height: u32,
+}
+
+/// Query parameters for listing the user's jobs run with one model version
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
+pub struct ListJobsQuery {
+    /// Only list jobs run with this model version
+    #[param(example = "v1.0.0")]
+    #[validate(length(min = 1, message = "model_version must not be empty"))]
+    pub model_version: String,
+    /// Only list jobs in this status
+    #[param(example = "completed")]
+    #[validate(custom(function = "validate_job_status"))]
+    pub status: Option<String>,
+    /// Jobs per page (default: 20, max: 100)
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    pub limit: Option<i64>,
+    /// Jobs to skip (default: 0)
+    #[param(minimum = 0, default = 0)]
+    pub offset: Option<i64>,
+}
+
+impl ListJobsQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(20).clamp(1, 100)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// The status filter, once validated
+    pub fn status(&self) -> Option<JobStatus> {
+        self.status.as_deref().and_then(|status| status.parse().ok())
     }
 }
 
@@ -44,6 +146,43 @@ pub struct AnalyzeImageResponse {
     pub created_at: String,
 }
 
+/// A job queued as part of a batch analysis request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchAnalyzeJob {
+    pub image_id: i64,
+    pub job_id: i64,
+    pub status: String,
+    pub status_url: String,
+}
+
+/// An image that could not be queued in a batch analysis request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchAnalyzeError {
+    pub image_id: i64,
+    pub code: String,
+    pub message: String,
+}
+
+/// Response for a batch analysis request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchAnalyzeResponse {
+    pub ai_model_version: String,
+    pub jobs: Vec<BatchAnalyzeJob>,
+    pub errors: Vec<BatchAnalyzeError>,
+}
+
+/// Response for re-submitting a folder's failed jobs
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RetryFailedJobsResponse {
+    pub folder_id: i32,
+    /// Number of fresh jobs created
+    pub requeued: usize,
+    /// Number of failed jobs that could not be retried
+    pub skipped: usize,
+    pub jobs: Vec<BatchAnalyzeJob>,
+    pub errors: Vec<BatchAnalyzeError>,
+}
+
 /// Job status response
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct JobStatusResponse {
@@ -57,12 +196,44 @@ pub struct JobStatusResponse {
     pub finished_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Latest progress reported by the worker (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_pct: Option<i16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result_url: Option<String>,
 }
 
-/// Cell counts in analysis result
+impl From<Job> for JobStatusResponse {
+    fn from(job: Job) -> Self {
+        let result_url = (job.status == JobStatus::Completed)
+            .then(|| format!("/api/v1/jobs/{}/result", job.job_id));
+
+        Self {
+            job_id: job.job_id,
+            image_id: job.image_id,
+            status: job.status.to_string(),
+            ai_model_version: job.ai_model_version,
+            started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+            finished_at: job.finished_at.map(|dt| dt.to_rfc3339()),
+            error_message: job.error_message,
+            progress_pct: job.progress_pct,
+            result_url,
+        }
+    }
+}
+
+/// A page of the user's jobs, newest first
 #[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobListResponse {
+    pub jobs: Vec<JobStatusResponse>,
+    /// Jobs matching the filters across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Cell counts in analysis result
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CellCounts {
     pub viable: i32,
     pub apoptosis: i32,
@@ -92,6 +263,74 @@ pub struct BoundingBox {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RawDetectionData {
     pub bounding_boxes: Vec<BoundingBox>,
+    /// Set when the worker reported more boxes than `max_detections` and
+    /// only the highest-confidence ones were kept
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl RawDetectionData {
+    /// Select at most `limit` boxes to draw, highest confidence first
+    ///
+    /// Returns the selected boxes and how many were omitted, so an overlay
+    /// renderer can stay bounded on pathological results and note the rest.
+    ///
+    /// Time complexity: O(n log n) for n bounding boxes
+    pub fn top_boxes(&self, limit: usize) -> (Vec<&BoundingBox>, usize) {
+        let mut boxes: Vec<&BoundingBox> = self.bounding_boxes.iter().collect();
+        boxes.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        let omitted = boxes.len().saturating_sub(limit);
+        boxes.truncate(limit);
+        (boxes, omitted)
+    }
+
+    /// Count boxes per class, keeping only those at or above `threshold`
+    ///
+    /// Workers label viable cells `normal`; unknown classes count as other.
+    pub fn counts_at(&self, threshold: f64) -> CellCounts {
+        let mut counts = CellCounts {
+            viable: 0,
+            apoptosis: 0,
+            other: 0,
+        };
+        for bbox in self.bounding_boxes.iter().filter(|b| b.confidence >= threshold) {
+            match bbox.class.as_str() {
+                "normal" | "viable" => counts.viable += 1,
+                "apoptosis" => counts.apoptosis += 1,
+                _ => counts.other += 1,
+            }
+        }
+        counts
+    }
+
+    /// Boxes with coordinates rescaled from `from` to `to` (width, height)
+    ///
+    /// Coordinates are rounded to the nearest pixel.
+    pub fn scaled(&self, from: (u32, u32), to: (u32, u32)) -> Vec<BoundingBox> {
+        let scale_x = to.0 as f64 / from.0 as f64;
+        let scale_y = to.1 as f64 / from.1 as f64;
+        let scale = |value: i32, factor: f64| (value as f64 * factor).round() as i32;
+
+        self.bounding_boxes
+            .iter()
+            .map(|bbox| BoundingBox {
+                class: bbox.class.clone(),
+                confidence: bbox.confidence,
+                x: scale(bbox.x, scale_x),
+                y: scale(bbox.y, scale_y),
+                width: scale(bbox.width, scale_x),
+                height: scale(bbox.height, scale_y),
+            })
+            .collect()
+    }
+
+    /// Round every box's confidence to `decimals` places for output
+    pub fn round_confidences(&mut self, decimals: u32) {
+        for bbox in &mut self.bounding_boxes {
+            bbox.confidence = round_confidence(bbox.confidence, decimals);
+        }
+    }
 }
 
 /// Analysis result response
@@ -104,6 +343,10 @@ pub struct AnalysisResultResponse {
     pub total_cells: i32,
     pub avg_confidence_score: f64,
     pub percentages: CellPercentages,
+    /// Minimum box confidence the counts were computed at; absent when the
+    /// stored counts are reported as-is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence_threshold: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_data: Option<RawDetectionData>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -111,6 +354,18 @@ pub struct AnalysisResultResponse {
     pub analyzed_at: String,
 }
 
+/// Detections rescaled to a display size
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScaledDetectionsResponse {
+    pub job_id: i64,
+    pub image_id: i64,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub width: u32,
+    pub height: u32,
+    pub bounding_boxes: Vec<BoundingBox>,
+}
+
 /// Analysis history response for an image
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ImageAnalysisHistoryResponse {
@@ -132,3 +387,240 @@ pub struct AnalysisHistorySummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finished_at: Option<String>,
 }
+
+/// Summed cell counts across many analyses
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CellTotals {
+    pub viable: i64,
+    pub apoptosis: i64,
+    pub other: i64,
+}
+
+/// Lifetime analysis totals across all of a user's folders
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnalysisTotalsResponse {
+    pub total_jobs: i64,
+    pub completed_jobs: i64,
+    pub failed_jobs: i64,
+    pub cells: CellTotals,
+    /// Mean of per-analysis confidence scores (0 when there are none)
+    pub mean_confidence: f64,
+}
+
+// ============================================================================
+// Worker DTOs
+// ============================================================================
+
+/// A single analysis result reported by a worker
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct JobResultEntry {
+    pub job_id: i64,
+    #[validate(custom(function = "validate_cell_counts"))]
+    pub counts: CellCounts,
+    #[validate(range(min = 0.0, max = 1.0, message = "avg_confidence must be between 0 and 1"))]
+    pub avg_confidence: f64,
+    /// Detection output; must match `RawDetectionData`
+    #[validate(custom(function = "validate_raw_data"))]
+    #[schema(value_type = Option<RawDetectionData>)]
+    pub raw_data: Option<serde_json::Value>,
+    #[validate(length(max = MAX_RESULT_SUMMARY_LENGTH, message = "summary is too long"))]
+    pub summary: Option<String>,
+}
+
+impl JobResultEntry {
+    /// Hold `raw_data` to at most `max` bounding boxes (0 disables)
+    ///
+    /// Under `Truncate` the highest-confidence boxes are kept and the data is
+    /// marked `truncated`; under `Reject` the reported box count is returned
+    /// as the error. Expects a validated entry.
+    pub fn cap_detections(
+        &mut self,
+        max: usize,
+        policy: DetectionOverflowPolicy,
+    ) -> Result<(), usize> {
+        let Some(raw_data) = self.raw_data.as_mut() else {
+            return Ok(());
+        };
+        let count = raw_data
+            .get("bounding_boxes")
+            .and_then(serde_json::Value::as_array)
+            .map_or(0, Vec::len);
+        if max == 0 || count <= max {
+            return Ok(());
+        }
+
+        match policy {
+            DetectionOverflowPolicy::Reject => Err(count),
+            DetectionOverflowPolicy::Truncate => {
+                let data = serde_json::from_value::<RawDetectionData>(raw_data.clone())
+                    .map_err(|_| count)?;
+                let (kept, _) = data.top_boxes(max);
+                let (Ok(kept), Some(fields)) = (serde_json::to_value(kept), raw_data.as_object_mut())
+                else {
+                    return Err(count);
+                };
+                // Any other fields the worker sent are kept as they are
+                fields.insert("bounding_boxes".to_string(), kept);
+                fields.insert("truncated".to_string(), serde_json::Value::Bool(true));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Outcome of ingesting one entry of a worker result batch
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobResultIngestOutcome {
+    pub job_id: i64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+/// Response for a worker result batch
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchJobResultsResponse {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<JobResultIngestOutcome>,
+}
+
+// ============================================================================
+// Admin DTOs
+// ============================================================================
+
+/// Terminal status an operator can force a job into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobResolution {
+    Completed,
+    Failed,
+}
+
+/// Request to manually resolve a job the worker abandoned
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResolveJobRequest {
+    #[schema(example = "failed")]
+    pub outcome: JobResolution,
+    /// Stored on the job when failing it; recorded in the job event either way
+    #[validate(length(max = MAX_RESOLVE_MESSAGE_LENGTH, message = "error_message is too long"))]
+    pub error_message: Option<String>,
+}
+
+/// Analysis queue depth and consumers, for operational dashboards
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueueHealthResponse {
+    pub queue: String,
+    /// Messages ready for delivery
+    pub message_count: u32,
+    /// Workers consuming the queue
+    pub consumer_count: u32,
+}
+
+// ============================================================================
+// Validators
+// ============================================================================
+
+fn validate_cell_counts(counts: &CellCounts) -> Result<(), ValidationError> {
+    if counts.viable < 0 || counts.apoptosis < 0 || counts.other < 0 {
+        return Err(ValidationError::new("Cell counts cannot be negative"));
+    }
+
+    Ok(())
+}
+
+fn validate_job_status(status: &str) -> Result<(), ValidationError> {
+    status
+        .parse::<JobStatus>()
+        .map(|_| ())
+        .map_err(|_| ValidationError::new("status must be one of pending, processing, completed, failed, cancelled"))
+}
+
+fn validate_raw_data(raw_data: &serde_json::Value) -> Result<(), ValidationError> {
+    let data = serde_json::from_value::<RawDetectionData>(raw_data.clone())
+        .map_err(|_| ValidationError::new("raw_data does not match the detection format"))?;
+
+    let all_valid = data.bounding_boxes.iter().all(|bbox| {
+        (0.0..=1.0).contains(&bbox.confidence) && bbox.width >= 0 && bbox.height >= 0
+    });
+    if !all_valid {
+        return Err(ValidationError::new("raw_data contains an invalid bounding box"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(confidence: f64) -> BoundingBox {
+        BoundingBox {
+            class: "viable".to_string(),
+            confidence,
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        }
+    }
+
+    #[test]
+    fn test_top_boxes_keeps_highest_confidence() {
+        let data = RawDetectionData {
+            bounding_boxes: vec![bbox(0.2), bbox(0.9), bbox(0.5), bbox(0.7)],
+            truncated: false,
+        };
+
+        let (boxes, omitted) = data.top_boxes(2);
+        let confidences: Vec<f64> = boxes.iter().map(|b| b.confidence).collect();
+        assert_eq!(confidences, vec![0.9, 0.7]);
+        assert_eq!(omitted, 2);
+    }
+
+    #[test]
+    fn test_top_boxes_under_limit() {
+        let data = RawDetectionData {
+            bounding_boxes: vec![bbox(0.4), bbox(0.6)],
+            truncated: false,
+        };
+
+        let (boxes, omitted) = data.top_boxes(500);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(omitted, 0);
+    }
+
+    #[test]
+    fn test_counts_at_threshold() {
+        let mut low = bbox(0.3);
+        low.class = "apoptosis".to_string();
+        let mut other = bbox(0.8);
+        other.class = "debris".to_string();
+        let data = RawDetectionData {
+            bounding_boxes: vec![bbox(0.9), bbox(0.5), low, other],
+            truncated: false,
+        };
+
+        let counts = data.counts_at(0.5);
+        assert_eq!((counts.viable, counts.apoptosis, counts.other), (2, 0, 1));
+
+        let counts = data.counts_at(0.0);
+        assert_eq!((counts.viable, counts.apoptosis, counts.other), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_round_confidence() {
+        assert_eq!(round_confidence(0.8732000000001, 4), 0.8732);
+        assert_eq!(round_confidence(0.98765, 2), 0.99);
+        assert_eq!(round_confidence(0.123456789, 20), 0.123456789);
+
+        let mut data = RawDetectionData {
+            bounding_boxes: vec![bbox(0.91234567)],
+            truncated: false,
+        };
+        data.round_confidences(3);
+        assert_eq!(data.bounding_boxes[0].confidence, 0.912);
+    }
+}
@@ -3,6 +3,8 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::services::password_denylist;
+
 /// Custom deserializer to trim whitespace
 fn trim_whitespace<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
@@ -53,6 +55,15 @@ fn validate_strong_password(password: &str) -> Result<(), validator::ValidationE
     Ok(())
 }
 
+/// Reject passwords on the configured denylist (see `services::password_denylist`)
+fn validate_not_denylisted(password: &str) -> Result<(), validator::ValidationError> {
+    if password_denylist::contains(password) {
+        return Err(validator::ValidationError::new("password_denylisted"));
+    }
+
+    Ok(())
+}
+
 /// Register request DTO
 #[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
 pub struct RegisterRequest {
@@ -61,6 +72,7 @@ pub struct RegisterRequest {
     pub username: String,
 
     #[validate(custom(function = "validate_strong_password", message = "Password must be at least 12 characters and contain uppercase, lowercase, digit, and special character"))]
+    #[validate(custom(function = "validate_not_denylisted", message = "Password is too common; choose a less guessable one"))]
     pub password: String,
 }
 
@@ -74,6 +86,17 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+/// Change password request DTO
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+
+    #[validate(custom(function = "validate_strong_password", message = "Password must be at least 12 characters and contain uppercase, lowercase, digit, and special character"))]
+    #[validate(custom(function = "validate_not_denylisted", message = "Password is too common; choose a less guessable one"))]
+    pub new_password: String,
+}
+
 /// User info for responses (without password hash)
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct UserResponse {
@@ -105,3 +128,9 @@ pub struct LoginResponse {
 pub struct LogoutResponse {
     pub message: String,
 }
+
+/// Change password response DTO
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChangePasswordResponse {
+    pub message: String,
+}
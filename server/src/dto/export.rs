@@ -0,0 +1,29 @@
+//! Data Export DTOs
+//!
+//! Response Data Transfer Objects for user data export endpoints.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Data export status response
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DataExportResponse {
+    #[schema(value_type = String, format = "uuid")]
+    pub export_id: Uuid,
+    /// pending, processing, completed or failed
+    pub status: String,
+    /// URL to poll for progress
+    pub status_url: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// Presigned URL of the ZIP archive, once completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    /// Download URL expiration time (RFC3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
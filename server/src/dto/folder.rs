@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 use utoipa::ToSchema;
 use validator::{Validate, ValidationError};
 
@@ -20,6 +21,22 @@ pub struct UpdateFolderRequest {
     pub folder_name: String,
 }
 
+/// Copy folder request
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct CopyFolderRequest {
+    /// Name of the new folder
+    #[validate(custom(function = "validate_folder_name"))]
+    pub new_name: String,
+}
+
+/// Merge folder request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct MergeFolderRequest {
+    /// Folder whose images move into the target; it is deleted afterwards
+    #[schema(example = 3)]
+    pub source_folder_id: i32,
+}
+
 // ============================================================================
 // Response DTOs
 // ============================================================================
@@ -53,11 +70,21 @@ pub struct DeleteFolderResponse {
 // Validators
 // ============================================================================
 
-fn validate_folder_name(name: &str) -> Result<(), ValidationError> {
-    let trimmed = name.trim();
-    
-    // 1. Check if empty after trim
-    if trimmed.is_empty() {
+/// Normalize a folder name for validation and storage
+///
+/// Applies NFC (so "e" + combining acute and precomposed "é" are the same name)
+/// and trims Unicode whitespace and invisible format characters from both ends.
+pub fn normalize_folder_name(name: &str) -> String {
+    let normalized: String = name.nfc().collect();
+    normalized.trim_matches(is_blank).to_string()
+}
+
+fn validate_folder_name(raw_name: &str) -> Result<(), ValidationError> {
+    let normalized = normalize_folder_name(raw_name);
+    let name = normalized.as_str();
+
+    // 1. Check if empty after normalization (covers whitespace and zero-width only names)
+    if name.chars().all(is_blank) {
         return Err(ValidationError::new("Folder name cannot be empty or whitespace only"));
     }
 
@@ -85,6 +112,17 @@ fn validate_folder_name(name: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Unicode whitespace plus zero-width/format characters that render as nothing
+fn is_blank(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(c,
+            '\u{200B}'..='\u{200D}' | // Zero-width space, non-joiner, joiner
+            '\u{2060}'               | // Word joiner
+            '\u{FEFF}'               | // Zero-width no-break space (BOM)
+            '\u{180E}'                 // Mongolian vowel separator
+        )
+}
+
 fn is_emoji(c: char) -> bool {
     matches!(c,
         '\u{1F600}'..='\u{1F64F}' | // Emoticons
@@ -98,3 +136,41 @@ fn is_emoji(c: char) -> bool {
         '\u{1F018}'..='\u{1F27F}'   // Miscellaneous Symbols and Arrows etc
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_zero_width_only_names() {
+        assert!(validate_folder_name("\u{200B}").is_err());
+        assert!(validate_folder_name(" \u{200B}\u{FEFF} \u{3000}").is_err());
+        assert!(validate_folder_name("\u{00A0}\u{2060}").is_err());
+    }
+
+    #[test]
+    fn test_normalize_trims_unicode_whitespace() {
+        assert_eq!(normalize_folder_name("\u{200B} Samples\u{3000}"), "Samples");
+        assert_eq!(normalize_folder_name("Day 1 \u{200D}"), "Day 1");
+        assert!(validate_folder_name("\u{200B}Samples").is_ok());
+    }
+
+    #[test]
+    fn test_normalize_composes_combining_characters() {
+        // "e" + COMBINING ACUTE ACCENT becomes precomposed "é"
+        let decomposed = "Caf\u{0065}\u{0301}";
+        let precomposed = "Caf\u{00E9}";
+
+        assert_eq!(normalize_folder_name(decomposed), precomposed);
+        assert!(validate_folder_name(decomposed).is_ok());
+    }
+
+    #[test]
+    fn test_existing_checks_still_apply() {
+        assert!(validate_folder_name("../etc").is_err());
+        assert!(validate_folder_name("bad\0name").is_err());
+        assert!(validate_folder_name("cells \u{1F600}").is_err());
+        assert!(validate_folder_name(&"a".repeat(256)).is_err());
+        assert!(validate_folder_name("Experiment 42").is_ok());
+    }
+}
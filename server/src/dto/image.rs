@@ -2,8 +2,19 @@
 //!
 //! Request and Response Data Transfer Objects for image endpoints.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
 use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+use crate::domain::ApiError;
+use crate::models::ImageMetadata;
+use crate::services::image_service::ALLOWED_MIME_TYPES;
+
+/// Longest tag accepted on an image
+pub const MAX_TAG_LENGTH: usize = 32;
 
 // ============================================================================
 // Request DTOs
@@ -16,6 +27,13 @@ pub struct RenameImageRequest {
     pub new_filename: String,
 }
 
+/// Move image request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct MoveImageRequest {
+    #[schema(example = 2)]
+    pub target_folder_id: i32,
+}
+
 /// Request presigned URL for direct S3 upload
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct RequestUploadRequest {
@@ -66,19 +84,144 @@ pub struct PresignedDownloadResponse {
     pub expires_at: String,
 }
 
+/// Request presigned download URLs for several images at once
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct BatchDownloadUrlRequest {
+    /// Images to sign (all must be owned by the caller), at most `limits.max_batch_size`
+    #[validate(length(min = 1, message = "image_ids must not be empty"))]
+    pub image_ids: Vec<i64>,
+}
+
+/// Presigned download URLs keyed by image ID, in ascending ID order
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchDownloadUrlResponse {
+    pub urls: BTreeMap<i64, String>,
+    /// Expiration time shared by every URL (RFC3339)
+    pub expires_at: String,
+}
+
+/// Metadata fields to set on one image
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+pub struct ImageMetadataUpdate {
+    pub image_id: i64,
+    /// Fields of `ImageMetadata` to set; fields left out keep their value
+    #[validate(custom(function = "validate_image_metadata"))]
+    #[schema(value_type = Object, example = json!({ "captured_at": "2026-03-01T09:30:00Z" }))]
+    pub metadata: serde_json::Value,
+}
+
+impl ImageMetadataUpdate {
+    /// The validated metadata, normalized for merging into the stored JSON
+    pub fn patch(&self) -> Option<serde_json::Value> {
+        let metadata = ImageMetadata::deserialize(&self.metadata).ok()?;
+        serde_json::to_value(metadata).ok()
+    }
+}
+
+/// Set metadata on several images at once
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct BulkMetadataUpdateRequest {
+    /// Applied in order, at most `limits.max_batch_size`
+    #[validate(length(min = 1, message = "updates must not be empty"), nested)]
+    pub updates: Vec<ImageMetadataUpdate>,
+}
+
+/// Outcome of one entry of a bulk metadata update
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImageMetadataUpdateOutcome {
+    pub image_id: i64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+/// Response for a bulk metadata update, with outcomes in request order
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkMetadataUpdateResponse {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<ImageMetadataUpdateOutcome>,
+}
+
+/// Tags on an image after adding or removing one
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImageTagsResponse {
+    pub image_id: i64,
+    /// Tags on the image, alphabetically
+    #[schema(example = json!(["favorite"]))]
+    pub tags: Vec<String>,
+}
+
+/// Response with a read-only link to one image
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ShareLinkResponse {
+    /// Signed token naming the image and expiry
+    pub token: String,
+    /// Path serving the image without authentication
+    #[schema(example = "/api/v1/shared/images/42.1760620800.9f2c...")]
+    pub url: String,
+    /// Link expiration time (RFC3339)
+    pub expires_at: String,
+}
+
+/// Request to generate missing thumbnails for one batch of images
+#[derive(Debug, Clone, Default, Deserialize, Validate, ToSchema)]
+pub struct ThumbnailBackfillRequest {
+    /// Resume after this image ID (`next_after_image_id` of the previous batch)
+    pub after_image_id: Option<i64>,
+    /// Images to process in this batch (default: 20, max: 100)
+    #[validate(range(min = 1, max = 100, message = "limit must be between 1 and 100"))]
+    pub limit: Option<i32>,
+}
+
+impl ThumbnailBackfillRequest {
+    pub fn limit(&self) -> i32 {
+        self.limit.unwrap_or(20)
+    }
+}
+
+/// Outcome of one thumbnail backfill batch
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ThumbnailBackfillResponse {
+    /// Thumbnails generated
+    pub generated: usize,
+    /// Images skipped because they could not be read or decoded
+    pub skipped: usize,
+    /// Pass as `after_image_id` to continue; None once every image was visited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_after_image_id: Option<i64>,
+}
+
 // ============================================================================
 // Query Parameters
 // ============================================================================
 
 /// Query parameters for paginated image listing
-#[derive(Debug, Clone, Deserialize, IntoParams)]
+///
+/// Numbers and flags accept strings too, as they arrive when this struct is
+/// flattened into another query.
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
 pub struct PaginationQuery {
     /// Page number (1-indexed, default: 1)
+    #[serde(default, deserialize_with = "optional_from_str")]
     #[param(minimum = 1, default = 1)]
     pub page: Option<i32>,
     /// Items per page (default: 20, max: 100)
+    #[serde(default, deserialize_with = "optional_from_str")]
     #[param(minimum = 1, maximum = 100, default = 20)]
     pub limit: Option<i32>,
+    /// Only list images of this MIME type
+    #[param(example = "image/tiff")]
+    #[validate(custom(function = "validate_mime_type_filter"))]
+    pub mime_type: Option<String>,
+    /// Also list soft-deleted images (admin only)
+    #[serde(default, deserialize_with = "from_str")]
+    #[param(default = false)]
+    pub include_deleted: bool,
+    /// Only list images carrying this tag
+    #[param(example = "favorite")]
+    #[validate(custom(function = "validate_tag"))]
+    pub tag: Option<String>,
 }
 
 impl PaginationQuery {
@@ -95,8 +238,50 @@ impl PaginationQuery {
     }
 }
 
-/// Query parameters for cursor-based pagination (more efficient for large datasets)
+/// Query parameters for listing all of a user's images
 #[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct AllImagesQuery {
+    /// Page number (1-indexed, default: 1)
+    #[param(minimum = 1, default = 1)]
+    pub page: Option<i32>,
+    /// Items per page (default: 20, max: 100)
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    pub limit: Option<i32>,
+}
+
+impl AllImagesQuery {
+    pub fn page(&self) -> i32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn limit(&self) -> i32 {
+        self.limit.unwrap_or(20).clamp(1, 100)
+    }
+
+    pub fn offset(&self) -> i64 {
+        ((self.page() - 1) * self.limit()) as i64
+    }
+}
+
+/// Query parameters for searching a folder's images by filename
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
+pub struct ImageSearchQuery {
+    /// Case-insensitive substring of the filename; `%` and `_` match literally
+    #[param(example = "scan")]
+    #[validate(length(min = 1, max = 255, message = "q must be 1-255 characters"))]
+    pub q: String,
+    /// Paging and the same filters as the folder listing
+    ///
+    /// `IntoParams` cannot flatten, so handlers list `PaginationQuery` next to
+    /// this type in `params(...)` instead.
+    #[serde(flatten)]
+    #[validate(nested)]
+    #[param(ignore, value_type = Object)]
+    pub pagination: PaginationQuery,
+}
+
+/// Query parameters for cursor-based pagination (more efficient for large datasets)
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
 pub struct CursorPaginationQuery {
     /// Cursor for pagination (RFC3339 timestamp of last seen item)
     /// If not provided, returns from the beginning (most recent)
@@ -104,6 +289,14 @@ pub struct CursorPaginationQuery {
     /// Items per page (default: 20, max: 100)
     #[param(minimum = 1, maximum = 100, default = 20)]
     pub limit: Option<i32>,
+    /// Only list images of this MIME type
+    #[param(example = "image/tiff")]
+    #[validate(custom(function = "validate_mime_type_filter"))]
+    pub mime_type: Option<String>,
+    /// Also report the total number of matching images (costs an extra count query)
+    #[serde(default)]
+    #[param(default = false)]
+    pub include_total: bool,
 }
 
 impl CursorPaginationQuery {
@@ -117,6 +310,45 @@ impl CursorPaginationQuery {
     }
 }
 
+/// List images across several folders with cursor-based pagination
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct ListImagesRequest {
+    /// Folders to list (all must be owned by the caller), at most `limits.max_batch_size`
+    #[validate(length(min = 1, message = "folder_ids must not be empty"))]
+    pub folder_ids: Vec<i32>,
+    /// Items per page (default: 20, max: 100)
+    pub limit: Option<i32>,
+    /// Cursor for pagination (RFC3339 timestamp of last seen item)
+    pub cursor: Option<String>,
+}
+
+impl ListImagesRequest {
+    pub fn limit(&self) -> i32 {
+        self.limit.unwrap_or(20).clamp(1, 100)
+    }
+
+    /// Parse cursor as DateTime, returns None if invalid or not provided
+    pub fn cursor_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.cursor.as_ref().and_then(|c| chrono::DateTime::parse_from_rfc3339(c).ok().map(|dt| dt.with_timezone(&chrono::Utc)))
+    }
+}
+
+/// Query parameters for presigned download URLs
+///
+/// Lets the client force the headers the storage server responds with,
+/// e.g. to trigger a "Save as" dialog instead of inline display.
+#[derive(Debug, Clone, Default, Deserialize, Validate, IntoParams)]
+pub struct DownloadUrlQuery {
+    /// Content-Type the download should be served with
+    #[param(example = "image/png")]
+    #[validate(custom(function = "validate_response_content_type"))]
+    pub response_content_type: Option<String>,
+    /// Content-Disposition the download should be served with
+    #[param(example = "attachment; filename=\"cells.png\"")]
+    #[validate(custom(function = "validate_response_content_disposition"))]
+    pub response_content_disposition: Option<String>,
+}
+
 // ============================================================================
 // Response DTOs
 // ============================================================================
@@ -149,6 +381,28 @@ pub struct ImageMetadataResponse {
     pub width: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
+    /// When the photo was taken, from EXIF (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<String>,
+}
+
+impl ImageMetadataResponse {
+    /// Response metadata from an image's `metadata` column
+    ///
+    /// Returns `None` when the column holds none of the fields (e.g. `{}`),
+    /// so the `metadata` key is omitted instead of serialized as an empty object.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let meta = ImageMetadata::deserialize(value).ok()?;
+        if meta.width.is_none() && meta.height.is_none() && meta.captured_at.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            width: meta.width,
+            height: meta.height,
+            captured_at: meta.captured_at.map(|dt| dt.to_rfc3339()),
+        })
+    }
 }
 
 /// Single image response
@@ -156,6 +410,9 @@ pub struct ImageMetadataResponse {
 pub struct ImageResponse {
     pub image_id: i64,
     pub folder_id: i32,
+    /// Set only on the all-images listing, which spans folders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder_name: Option<String>,
     pub original_filename: String,
     pub file_size: i32,
     pub mime_type: String,
@@ -163,6 +420,11 @@ pub struct ImageResponse {
     pub metadata: Option<ImageMetadataResponse>,
     pub has_analysis: bool,
     pub uploaded_at: String,
+    /// Set only on admin listings that include soft-deleted images
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    /// Tags on the image, alphabetically
+    pub tags: Vec<String>,
 }
 
 /// List images response with pagination
@@ -183,6 +445,12 @@ pub struct CursorPaginationInfo {
     pub next_cursor: Option<String>,
     /// Number of items in this response
     pub count: i32,
+    /// Total number of matching images, present only when `include_total=true`
+    ///
+    /// Counted separately from the page, so it is approximate while images
+    /// are being uploaded or deleted concurrently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
 }
 
 /// List images response with cursor-based pagination
@@ -205,6 +473,8 @@ pub struct ImageDetailResponse {
     pub metadata: Option<ImageMetadataResponse>,
     pub analysis_history: Vec<AnalysisHistoryItem>,
     pub uploaded_at: String,
+    /// Tags on the image, alphabetically
+    pub tags: Vec<String>,
 }
 
 /// Analysis history item for image detail
@@ -223,3 +493,207 @@ pub struct AnalysisHistoryItem {
 pub struct DeleteImageResponse {
     pub message: String,
 }
+
+/// Upload formats and limits, so clients don't hardcode them
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UploadConstraintsResponse {
+    pub allowed_mime_types: Vec<String>,
+    /// File extensions matching the allowed MIME types, without the dot
+    pub allowed_extensions: Vec<String>,
+    pub max_file_size_bytes: u64,
+    pub max_filename_length: u64,
+    /// Smallest image dimensions accepted for analysis
+    pub min_image_width: u32,
+    pub min_image_height: u32,
+    /// Live images a folder may hold; absent when unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_images_per_folder: Option<i64>,
+}
+
+// ============================================================================
+// Validators
+// ============================================================================
+
+/// Check a tag: 1 to `MAX_TAG_LENGTH` lowercase letters, digits, `-` or `_`
+pub fn validate_tag(tag: &str) -> Result<(), ValidationError> {
+    if tag.is_empty() || tag.len() > MAX_TAG_LENGTH {
+        return Err(ValidationError::new("Tag must be 1-32 characters"));
+    }
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(ValidationError::new(
+            "Tag may only contain lowercase letters, digits, '-' and '_'",
+        ));
+    }
+    Ok(())
+}
+
+/// Deserialize a value given either as itself or as a string to parse
+///
+/// `serde(flatten)` hands query values on as strings, which the typed
+/// deserializers of numbers and bools would reject.
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value<T> {
+        Typed(T),
+        Text(String),
+    }
+
+    match Value::<T>::deserialize(deserializer)? {
+        Value::Typed(value) => Ok(value),
+        Value::Text(text) => text.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn optional_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    from_str(deserializer).map(Some)
+}
+
+fn validate_mime_type_filter(mime_type: &str) -> Result<(), ValidationError> {
+    if ALLOWED_MIME_TYPES.contains(&mime_type) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Unsupported MIME type filter"))
+    }
+}
+
+fn validate_response_content_type(content_type: &str) -> Result<(), ValidationError> {
+    if ALLOWED_MIME_TYPES.contains(&content_type) || content_type == "application/octet-stream" {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Unsupported response content type"))
+    }
+}
+
+fn validate_response_content_disposition(disposition: &str) -> Result<(), ValidationError> {
+    let (kind, params) = match disposition.split_once(';') {
+        Some((kind, params)) => (kind.trim(), Some(params.trim())),
+        None => (disposition.trim(), None),
+    };
+
+    if kind != "inline" && kind != "attachment" {
+        return Err(ValidationError::new(
+            "Content disposition must be 'inline' or 'attachment'",
+        ));
+    }
+
+    if let Some(params) = params {
+        // Only a single quoted filename parameter is accepted
+        let filename = params
+            .strip_prefix("filename=\"")
+            .and_then(|rest| rest.strip_suffix('"'))
+            .ok_or_else(|| ValidationError::new("Content disposition filename must be quoted"))?;
+
+        let is_safe = !filename.is_empty()
+            && filename.chars().count() <= 255
+            && !filename
+                .chars()
+                .any(|c| c == '"' || c == '\\' || c.is_control());
+        if !is_safe {
+            return Err(ValidationError::new("Content disposition filename is invalid"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Metadata must be an object of known `ImageMetadata` fields
+///
+/// Unknown and null fields are rejected rather than silently dropped.
+fn validate_image_metadata(metadata: &serde_json::Value) -> Result<(), ValidationError> {
+    let fields = metadata
+        .as_object()
+        .filter(|fields| !fields.is_empty())
+        .ok_or_else(|| ValidationError::new("metadata must be an object with at least one field"))?;
+
+    let parsed = ImageMetadata::deserialize(metadata)
+        .map_err(|_| ValidationError::new("metadata does not match the image metadata format"))?;
+    let known = serde_json::to_value(parsed)
+        .map_err(|_| ValidationError::new("metadata does not match the image metadata format"))?;
+    if known.as_object().map_or(0, |known| known.len()) != fields.len() {
+        return Err(ValidationError::new("metadata has unknown or null fields"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(content_type: Option<&str>, disposition: Option<&str>) -> DownloadUrlQuery {
+        DownloadUrlQuery {
+            response_content_type: content_type.map(String::from),
+            response_content_disposition: disposition.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_empty_metadata_omitted_from_image_json() {
+        assert!(ImageMetadataResponse::from_json(&serde_json::json!({})).is_none());
+        assert!(ImageMetadataResponse::from_json(&serde_json::json!({ "width": 10 })).is_some());
+
+        let image = ImageResponse {
+            image_id: 1,
+            folder_id: 1,
+            folder_name: None,
+            original_filename: "cells.jpg".to_string(),
+            file_size: 10,
+            mime_type: "image/jpeg".to_string(),
+            metadata: ImageMetadataResponse::from_json(&serde_json::json!({})),
+            has_analysis: false,
+            uploaded_at: String::new(),
+            deleted_at: None,
+            tags: Vec::new(),
+        };
+        let json = serde_json::to_value(&image).unwrap();
+        assert!(json.get("metadata").is_none());
+    }
+
+    #[test]
+    fn test_validate_tag() {
+        assert!(validate_tag("favorite").is_ok());
+        assert!(validate_tag("batch_2-b").is_ok());
+        assert!(validate_tag(&"a".repeat(MAX_TAG_LENGTH)).is_ok());
+
+        assert!(validate_tag("").is_err());
+        assert!(validate_tag(&"a".repeat(MAX_TAG_LENGTH + 1)).is_err());
+        assert!(validate_tag("Favorite").is_err());
+        assert!(validate_tag("two words").is_err());
+        assert!(validate_tag("ünicode").is_err());
+    }
+
+    #[test]
+    fn test_download_url_query_accepts_valid_overrides() {
+        assert!(query(None, None).validate().is_ok());
+        assert!(query(Some("image/png"), Some("inline")).validate().is_ok());
+        assert!(query(
+            Some("application/octet-stream"),
+            Some("attachment; filename=\"cells 01.png\"")
+        )
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_download_url_query_rejects_invalid_overrides() {
+        assert!(query(Some("text/html"), None).validate().is_err());
+        assert!(query(None, Some("form-data")).validate().is_err());
+        assert!(query(None, Some("attachment; filename=cells.png")).validate().is_err());
+        assert!(query(None, Some("attachment; filename=\"a\"b.png\"")).validate().is_err());
+        assert!(query(None, Some("attachment; filename=\"a\r\nX-Injected: 1\"")).validate().is_err());
+    }
+}
@@ -1,23 +1,35 @@
 pub mod analysis;
 pub mod auth;
+pub mod export;
 pub mod folder;
 pub mod image;
+pub mod user;
 
 pub use analysis::{
-    AnalysisHistorySummary, AnalysisResultResponse, AnalyzeImageRequest, AnalyzeImageResponse,
-    BoundingBox, CellCounts, CellPercentages, ImageAnalysisHistoryResponse, JobStatusResponse,
-    RawDetectionData,
+    AnalysisHistorySummary, AnalysisResultResponse, AnalysisTotalsResponse, AnalyzeImageRequest,
+    AnalyzeImageResponse, BatchAnalyzeError, BatchAnalyzeJob, BatchAnalyzeRequest,
+    BatchAnalyzeResponse, BatchJobResultsResponse, BoundingBox, CellCounts, CellPercentages,
+    CellTotals, ImageAnalysisHistoryResponse, JobResolution, JobResultEntry,
+    JobListResponse, JobResultIngestOutcome, JobStatusResponse, QueueHealthResponse, RawDetectionData,
+    ResolveJobRequest, RetryFailedJobsResponse, ScaledDetectionsResponse,
 };
 pub use auth::{
-    LoginRequest, LoginResponse, LogoutResponse, RegisterRequest, RegisterResponse, UserResponse,
+    ChangePasswordRequest, ChangePasswordResponse, LoginRequest, LoginResponse, LogoutResponse, RegisterRequest, RegisterResponse, UserResponse,
 };
+pub use export::DataExportResponse;
 pub use folder::{
-    CreateFolderRequest, DeleteFolderResponse, FolderListResponse, FolderResponse,
-    UpdateFolderRequest,
+    normalize_folder_name, CopyFolderRequest, CreateFolderRequest, DeleteFolderResponse,
+    FolderListResponse, FolderResponse, MergeFolderRequest, UpdateFolderRequest,
 };
 pub use image::{
-    AnalysisHistoryItem, ConfirmUploadRequest, CursorPaginationInfo, CursorPaginationQuery,
-    DeleteImageResponse, ImageDetailResponse, ImageListResponse, ImageListResponseV2,
-    ImageMetadataResponse, ImageResponse, PaginationInfo, PaginationQuery, PresignedDownloadResponse,
-    RenameImageRequest, RequestUploadRequest, RequestUploadResponse,
+    validate_tag, AllImagesQuery, AnalysisHistoryItem, BatchDownloadUrlRequest, BatchDownloadUrlResponse, BulkMetadataUpdateRequest,
+    BulkMetadataUpdateResponse, ConfirmUploadRequest, CursorPaginationInfo, CursorPaginationQuery,
+    DeleteImageResponse, DownloadUrlQuery, ImageDetailResponse, ImageListResponse, ImageListResponseV2,
+    ImageMetadataResponse, ImageMetadataUpdate, ImageMetadataUpdateOutcome, ImageResponse, ImageSearchQuery,
+    ImageTagsResponse, ListImagesRequest, MoveImageRequest, PaginationInfo,
+    PaginationQuery, PresignedDownloadResponse, RenameImageRequest, RequestUploadRequest, RequestUploadResponse,
+    ShareLinkResponse, ThumbnailBackfillRequest, ThumbnailBackfillResponse, UploadConstraintsResponse,
+};
+pub use user::{
+    AccountBreakdownResponse, PreferencesResponse, ProfileResponse, UpdatePreferencesRequest,
 };
@@ -0,0 +1,45 @@
+//! User DTOs
+//!
+//! Request and Response Data Transfer Objects for the authenticated user's
+//! own settings.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::UserResponse;
+
+/// The authenticated user's profile
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProfileResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_model_version: Option<String>,
+}
+
+/// Update the authenticated user's preferences
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdatePreferencesRequest {
+    /// Model version used when an analysis request omits one; `null` clears it
+    #[schema(example = "v1.0.0")]
+    pub preferred_model_version: Option<String>,
+}
+
+/// The authenticated user's preferences
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PreferencesResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_model_version: Option<String>,
+}
+
+/// The authenticated user's images and jobs, counted by kind
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountBreakdownResponse {
+    /// Live images per MIME type
+    pub images_by_mime_type: BTreeMap<String, i64>,
+    /// Jobs on live images per status; statuses without jobs are omitted
+    pub jobs_by_status: BTreeMap<String, i64>,
+}
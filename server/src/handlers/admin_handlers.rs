@@ -0,0 +1,300 @@
+//! Admin Handlers
+//!
+//! Operator-only endpoints. Routes are wrapped in `AdminGuard`.
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::config::settings::AppConfig;
+use crate::domain::ApiResponse;
+use crate::dto::{
+    JobResolution, JobStatusResponse, QueueHealthResponse, ResolveJobRequest, ThumbnailBackfillRequest,
+    ThumbnailBackfillResponse,
+};
+use crate::handlers::image_handlers::store_thumbnail;
+use crate::middleware::AuthenticatedUser;
+use crate::models::job::JobStatus;
+use crate::repositories::{ImageRepository, JobRepository, ResolveJobOutcome};
+use crate::services::{RabbitmqService, StorageBackend};
+
+// ============================================================================
+// Effective Configuration
+// ============================================================================
+
+/// Get the effective server configuration with secrets redacted
+///
+/// Secret values are replaced by `{ "is_set": bool, "length": n }`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/config",
+    tag = "Administration",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Effective configuration (secrets redacted)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn get_effective_config(
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    tracing::info!("Admin {} viewed effective configuration", user.username);
+
+    HttpResponse::Ok().json(ApiResponse::success(config.get_ref()))
+}
+
+// ============================================================================
+// Queue Health
+// ============================================================================
+
+/// Get the analysis queue's depth and consumer count
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/queue-health",
+    tag = "Administration",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Queue statistics", body = ApiResponse<QueueHealthResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 503, description = "Message broker unavailable")
+    )
+)]
+pub async fn get_queue_health(
+    config: web::Data<AppConfig>,
+    rabbitmq: web::Data<RabbitmqService>,
+) -> HttpResponse {
+    let queue = &config.rabbitmq.analysis_queue;
+
+    match rabbitmq.queue_stats(queue).await {
+        Ok(stats) => HttpResponse::Ok().json(ApiResponse::success(QueueHealthResponse {
+            queue: queue.clone(),
+            message_count: stats.message_count,
+            consumer_count: stats.consumer_count,
+        })),
+        Err(e) => {
+            tracing::warn!("Failed to read stats for queue '{}': {:?}", queue, e);
+            HttpResponse::ServiceUnavailable().json(ApiResponse::<()>::error(
+                "QUEUE_UNAVAILABLE",
+                "Message broker is unavailable",
+            ))
+        }
+    }
+}
+
+// ============================================================================
+// Resolve Stuck Job
+// ============================================================================
+
+/// Force-complete or force-fail a job the worker abandoned
+///
+/// Manual override for operators: any pending or processing job can be
+/// moved to a terminal status. The change is recorded as a job event.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jobs/{job_id}/resolve",
+    tag = "Administration",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = i64, Path, description = "Job ID")
+    ),
+    request_body = ResolveJobRequest,
+    responses(
+        (status = 200, description = "Job resolved", body = ApiResponse<JobStatusResponse>),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Job not found"),
+        (status = 409, description = "Job already finished")
+    )
+)]
+pub async fn resolve_job(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    body: web::Json<ResolveJobRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let job_id = path.into_inner();
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let to_status = match request.outcome {
+        JobResolution::Completed => JobStatus::Completed,
+        JobResolution::Failed => JobStatus::Failed,
+    };
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            tracing::error!("Failed to start transaction: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to resolve job"));
+        }
+    };
+
+    let outcome = JobRepository::force_resolve(
+        &mut tx,
+        job_id,
+        to_status,
+        request.error_message.as_deref(),
+        &user.username,
+    )
+    .await;
+
+    let job = match outcome {
+        Ok(ResolveJobOutcome::Resolved(job)) => job,
+        Ok(ResolveJobOutcome::JobNotFound) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+        }
+        Ok(ResolveJobOutcome::AlreadyFinished(status)) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "CONFLICT",
+                format!("Job is already {}", status),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Failed to resolve job {}: {:?}", job_id, e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to resolve job"));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        tracing::error!("Failed to commit job resolution: {:?}", e);
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::internal_error(&req, "Failed to resolve job"));
+    }
+
+    tracing::info!("Admin {} resolved job {} as {}", user.username, job_id, job.status);
+
+    let result_url = if job.status == JobStatus::Completed {
+        Some(format!("/api/v1/jobs/{}/result", job.job_id))
+    } else {
+        None
+    };
+
+    HttpResponse::Ok().json(ApiResponse::success(JobStatusResponse {
+        job_id: job.job_id,
+        image_id: job.image_id,
+        status: job.status.to_string(),
+        ai_model_version: job.ai_model_version,
+        started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+        finished_at: job.finished_at.map(|dt| dt.to_rfc3339()),
+        error_message: job.error_message,
+        progress_pct: job.progress_pct,
+        result_url,
+    }))
+}
+
+// ============================================================================
+// Thumbnail Backfill
+// ============================================================================
+
+/// Generate thumbnails for one batch of images uploaded without one
+///
+/// Walks all users' images in ID order; repeat with the returned
+/// `next_after_image_id` until it is absent. Images that cannot be decoded
+/// are skipped, marked so later batches leave them out, and keep being
+/// served as the original.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/thumbnails/backfill",
+    tag = "Administration",
+    security(("bearer_auth" = [])),
+    request_body = ThumbnailBackfillRequest,
+    responses(
+        (status = 200, description = "Batch processed", body = ApiResponse<ThumbnailBackfillResponse>),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn backfill_thumbnails(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<ThumbnailBackfillRequest>,
+) -> HttpResponse {
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let limit = request.limit();
+    let images = match ImageRepository::find_without_thumbnail(
+        pool.get_ref(),
+        request.after_image_id.unwrap_or(0),
+        limit,
+    )
+    .await
+    {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to find images without thumbnails: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to backfill thumbnails"));
+        }
+    };
+
+    let next_after_image_id = if images.len() == limit as usize {
+        images.last().map(|image| image.image_id)
+    } else {
+        None
+    };
+
+    let format = config.storage.thumbnail_format;
+    let (mut generated, mut skipped) = (0, 0);
+    for image in &images {
+        let original = match storage.get(&image.file_path).await {
+            Ok((bytes, _)) => bytes,
+            Err(e) => {
+                tracing::warn!("Failed to read image {} for thumbnail: {:?}", image.image_id, e);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        match store_thumbnail(pool.get_ref(), storage.get_ref(), image, &original, format).await {
+            Some(_) => generated += 1,
+            None => skipped += 1,
+        }
+    }
+
+    tracing::info!("Thumbnail backfill generated {} and skipped {}", generated, skipped);
+
+    HttpResponse::Ok().json(ApiResponse::success(ThumbnailBackfillResponse {
+        generated,
+        skipped,
+        next_after_image_id,
+    }))
+}
@@ -2,46 +2,1223 @@
 //!
 //! AI Analysis endpoints with RabbitMQ integration for asynchronous processing.
 
+use std::collections::{HashMap, HashSet};
+
+use actix_web::http::header::{LOCATION, RETRY_AFTER};
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::config::settings::{AnalysisConfig, AppConfig};
+use crate::db::ReadPool;
+use crate::handlers::{check_batch_size, validation_error, ValidationKind};
+use crate::domain::ApiResponse;
+use crate::dto::analysis::{
+    round_confidence, AnalysisHistorySummary, DEFAULT_MODEL_VERSION, AnalysisResultResponse, AnalysisTotalsResponse, AnalyzeImageQuery,
+    AnalyzeImageRequest, AnalyzeImageResponse, BatchAnalyzeError, BatchAnalyzeJob, BatchAnalyzeRequest,
+    BatchAnalyzeResponse, CellCounts, CellPercentages, CellTotals, ImageAnalysisHistoryResponse,
+    JobListResponse, JobResultQuery, JobStatusResponse, ListJobsQuery, RawDetectionData, RetryFailedJobsResponse,
+    ScaledDetectionsQuery, ScaledDetectionsResponse,
+};
+use crate::middleware::AuthenticatedUser;
+use crate::models::job::{AnalysisResult, Job};
+use crate::models::Image;
+use crate::repositories::{
+    AnalysisResultRepository, CancelJobOutcome, CreateJobOutcome, FolderRepository, ImageRepository, JobRepository,
+    RetryJobOutcome, UserRepository,
+};
+use crate::dto::PresignedDownloadResponse;
+use crate::services::{
+    AnalysisJobMessage, ImageService, RabbitmqError, RabbitmqService, ResponseOverrides, StorageBackend,
+    StorageError,
+};
+
+// ============================================================================
+// Job Submission
+// ============================================================================
+
+/// Seconds a client is told to wait before retrying when the queue is full
+const QUEUE_FULL_RETRY_AFTER_SECS: u64 = 30;
+
+/// Refuse a submission that would take the pending backlog past
+/// `analysis.max_pending_jobs`
+///
+/// `adding` is the number of jobs the request would create; a batch that
+/// doesn't fit is refused whole rather than partly queued.
+async fn check_pending_capacity(
+    req: &HttpRequest,
+    pool: &PgPool,
+    analysis: &AnalysisConfig,
+    adding: usize,
+) -> Result<(), HttpResponse> {
+    if analysis.max_pending_jobs <= 0 || adding == 0 {
+        return Ok(());
+    }
+
+    match JobRepository::count_pending(pool).await {
+        Ok(pending) if pending + adding as i64 > analysis.max_pending_jobs => {
+            tracing::warn!(
+                "Analysis queue full ({} pending), rejecting {} new job(s)",
+                pending,
+                adding
+            );
+            Err(HttpResponse::ServiceUnavailable()
+                .insert_header((RETRY_AFTER, QUEUE_FULL_RETRY_AFTER_SECS.to_string()))
+                .json(ApiResponse::<()>::error(
+                    "QUEUE_FULL",
+                    "Analysis queue is at capacity; try again later",
+                )))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to count pending jobs: {:?}", e);
+            Err(HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to check the analysis queue")))
+        }
+    }
+}
+
+/// Reasons a single analysis job could not be submitted
+enum SubmitJobError {
+    Create(sqlx::Error),
+    Queue(RabbitmqError),
+    /// The broker was unreachable; the job stays pending for the re-publisher
+    QueueUnavailable(Job, RabbitmqError),
+}
+
+/// Create a job for an (already ownership-verified) image and publish it to RabbitMQ.
+/// If the broker is unreachable the job is left pending for the background
+/// re-publisher; any other publish failure marks the job as failed.
+async fn submit_analysis_job(
+    pool: &PgPool,
+    rabbitmq: &RabbitmqService,
+    image: &Image,
+    model_version: &str,
+) -> Result<Job, SubmitJobError> {
+    let job = JobRepository::create(pool, image.image_id, model_version)
+        .await
+        .map_err(SubmitJobError::Create)?;
+
+    queue_analysis_job(pool, rabbitmq, image, job).await
+}
+
+/// Publish an already created job for `image` to RabbitMQ
+async fn queue_analysis_job(
+    pool: &PgPool,
+    rabbitmq: &RabbitmqService,
+    image: &Image,
+    job: Job,
+) -> Result<Job, SubmitJobError> {
+    // Publish job to RabbitMQ for Python model worker to process
+    let message = AnalysisJobMessage::for_job(&job, image.file_path.clone());
+
+    if let Err(e) = rabbitmq.publish_analysis_job(message).await {
+        if e.is_unavailable() {
+            return Err(SubmitJobError::QueueUnavailable(job, e));
+        }
+        // Mark job as failed since we couldn't queue it
+        let _ = JobRepository::fail(pool, job.job_id, "Failed to queue analysis job").await;
+        return Err(SubmitJobError::Queue(e));
+    }
+
+    // At worst a missed mark means the re-publisher queues the job again
+    if let Err(e) = JobRepository::mark_queued(pool, job.job_id).await {
+        tracing::warn!("Failed to mark job {} as queued: {:?}", job.job_id, e);
+    }
+
+    Ok(job)
+}
+
+// ============================================================================
+// Analyze Image (Submit for Analysis)
+// ============================================================================
+
+/// Parse the optional analyze request body
+///
+/// An empty body selects the defaults; a body that is present must be JSON
+/// and parse, so client mistakes aren't silently replaced by defaults.
+fn parse_analyze_body(req: &HttpRequest, body: &[u8]) -> Result<AnalyzeImageRequest, String> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(AnalyzeImageRequest::default());
+    }
+
+    let is_json = match req.mime_type() {
+        Ok(Some(mime)) => mime.subtype() == "json" || mime.suffix().is_some_and(|s| s == "json"),
+        _ => false,
+    };
+    if !is_json {
+        return Err("Request body must be sent as application/json".to_string());
+    }
+
+    serde_json::from_slice(body).map_err(|e| format!("Invalid JSON body: {}", e))
+}
+
+/// Submit an image for AI analysis via RabbitMQ
+///
+/// If a job for the image and model is still pending or processing, that job
+/// is returned with 200 instead of queueing another.
+///
+/// With `analysis.block_reanalysis` set, an image already analyzed with the
+/// requested model gets 409 `ALREADY_ANALYZED` (with the result's URL in
+/// `Location`).
+///
+/// Setting `force` in the body skips both checks. The `?force=true` query
+/// parameter is a deprecated alias for it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/images/{image_id}/analyze",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID"),
+        AnalyzeImageQuery
+    ),
+    request_body = AnalyzeImageRequest,
+    responses(
+        (status = 200, description = "Unfinished job for this image and model returned instead", body = ApiResponse<AnalyzeImageResponse>),
+        (status = 202, description = "Analysis job created", body = ApiResponse<AnalyzeImageResponse>),
+        (status = 400, description = "Body present but not valid JSON (INVALID_BODY)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found"),
+        (status = 409, description = "Already analyzed with this model (ALREADY_ANALYZED)"),
+        (status = 422, description = "Image too small to analyze (IMAGE_UNSUITABLE)"),
+        (status = 503, description = "Analysis queue unavailable (job left pending and queued once it recovers) or full (QUEUE_FULL, no job created)")
+    )
+)]
+pub async fn analyze_image(
+    pool: web::Data<PgPool>,
+    rabbitmq: web::Data<RabbitmqService>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    query: web::Query<AnalyzeImageQuery>,
+    body: web::Bytes,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let image_id = path.into_inner();
+    let request = match parse_analyze_body(&req, &body) {
+        Ok(request) => request,
+        Err(message) => {
+            return validation_error(ValidationKind::Malformed, "INVALID_BODY", message);
+        }
+    };
+
+    // Verify image ownership and get image details
+    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify image"));
+        }
+        Ok(Some(img)) => img,
+    };
+
+    // An omitted version falls back to the user's preference, then the default
+    let model_version = match request.model_version {
+        Some(version) => version,
+        None => match UserRepository::find_by_id(pool.get_ref(), user.user_id).await {
+            Ok(found) => found
+                .and_then(|u| u.preferred_model_version)
+                .unwrap_or_else(|| DEFAULT_MODEL_VERSION.to_string()),
+            Err(e) => {
+                tracing::error!("Failed to load user preferences: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to create analysis job"));
+            }
+        },
+    };
+
+    // Don't spend worker time on images too small to analyze
+    if let Err(e) = ImageService::check_analysis_suitability(&image, &config.analysis) {
+        return validation_error(ValidationKind::Unprocessable, "IMAGE_UNSUITABLE", e.to_string());
+    }
+
+    let force = request.force || query.force;
+    if config.analysis.block_reanalysis && !force {
+        match existing_result_job(pool.get_ref(), image_id, user.user_id, &model_version)
+            .await
+        {
+            Ok(Some(job_id)) => {
+                let result_url = format!("/api/v1/jobs/{}/result", job_id);
+                return HttpResponse::Conflict()
+                    .insert_header((LOCATION, result_url.clone()))
+                    .json(ApiResponse::<()>::error(
+                        "ALREADY_ANALYZED",
+                        format!(
+                            "Image already analyzed with model {}; see {} or set force in the body",
+                            model_version, result_url
+                        ),
+                    ));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("Failed to check existing results: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to create analysis job"));
+            }
+        }
+    }
+
+    // Backpressure: don't flood the workers beyond the configured backlog
+    if let Err(response) = check_pending_capacity(&req, pool.get_ref(), &config.analysis, 1).await {
+        return response;
+    }
+
+    // A double-tap shouldn't start a second analysis of the same image
+    let created = if force {
+        JobRepository::create(pool.get_ref(), image_id, &model_version).await
+    } else {
+        match JobRepository::create_unless_active(pool.get_ref(), image_id, &model_version).await {
+            Ok(CreateJobOutcome::Created(job)) => Ok(job),
+            Ok(CreateJobOutcome::AlreadyActive(job)) => {
+                tracing::info!("Image {} already has unfinished job {}", image_id, job.job_id);
+                return HttpResponse::Ok().json(ApiResponse::success(analyze_image_response(job)));
+            }
+            Err(e) => Err(e),
+        }
+    };
+
+    // Queue the new job for the model worker
+    let queued = match created {
+        Ok(job) => queue_analysis_job(pool.get_ref(), &rabbitmq, &image, job).await,
+        Err(e) => Err(SubmitJobError::Create(e)),
+    };
+    let job = match queued {
+        Ok(job) => job,
+        Err(SubmitJobError::Create(e)) => {
+            tracing::error!("Failed to create job: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to create analysis job"));
+        }
+        Err(SubmitJobError::Queue(e)) => {
+            tracing::error!("Failed to publish job to RabbitMQ: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error("QUEUE_ERROR", "Failed to submit analysis job"));
+        }
+        Err(SubmitJobError::QueueUnavailable(job, e)) => {
+            tracing::warn!("RabbitMQ unavailable, job {} left pending: {:?}", job.job_id, e);
+            return HttpResponse::ServiceUnavailable()
+                .insert_header((RETRY_AFTER, config.rabbitmq.republish_interval_secs.to_string()))
+                .json(ApiResponse::<()>::error(
+                    "QUEUE_UNAVAILABLE",
+                    format!(
+                        "Analysis queue unavailable; job {} will be queued when it recovers",
+                        job.job_id
+                    ),
+                ));
+        }
+    };
+
+    tracing::info!("Analysis job {} queued for image {}", job.job_id, image_id);
+
+    HttpResponse::Accepted().json(ApiResponse::success(analyze_image_response(job)))
+}
+
+fn analyze_image_response(job: Job) -> AnalyzeImageResponse {
+    AnalyzeImageResponse {
+        job_id: job.job_id,
+        image_id: job.image_id,
+        status: job.status.to_string(),
+        ai_model_version: job
+            .ai_model_version
+            .unwrap_or_else(|| DEFAULT_MODEL_VERSION.to_string()),
+        status_url: format!("/api/v1/jobs/{}", job.job_id),
+        created_at: job
+            .created_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+    }
+}
+
+/// Job of the image's latest completed result from `model_version`, even if
+/// another model analyzed it since
+async fn existing_result_job(
+    pool: &PgPool,
+    image_id: i64,
+    user_id: Uuid,
+    model_version: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    AnalysisResultRepository::find_latest_job_id_for_model(pool, image_id, user_id, model_version)
+        .await
+}
+
+// ============================================================================
+// Batch Analyze Images
+// ============================================================================
+
+/// Submit several images (possibly from different folders) for AI analysis
+///
+/// Each image is ownership-checked individually; images that are missing or
+/// belong to another user, or are too small to analyze, are reported in
+/// `errors` while the rest are queued. If the broker is unreachable, created
+/// jobs are still listed and stay pending until the re-publisher queues them.
+#[utoipa::path(
+    post,
+    path = "/api/v1/analyze/batch",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    request_body = BatchAnalyzeRequest,
+    responses(
+        (status = 202, description = "Analysis jobs created", body = ApiResponse<BatchAnalyzeResponse>),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 503, description = "Analysis queue full (QUEUE_FULL, no jobs created)")
+    )
+)]
+pub async fn batch_analyze_images(
+    pool: web::Data<PgPool>,
+    rabbitmq: web::Data<RabbitmqService>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<BatchAnalyzeRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    // Validate request
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if let Err(response) = check_batch_size(&config.limits, request.image_ids.len()) {
+        return response;
+    }
+
+    // Drop duplicate IDs while keeping the caller's order
+    let mut seen = HashSet::new();
+    let image_ids: Vec<i64> = request
+        .image_ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect();
+
+    // Verify ownership of all requested images in one query
+    let owned: HashMap<i64, Image> =
+        match ImageRepository::find_by_ids(pool.get_ref(), &image_ids, user.user_id).await {
+            Ok(images) => images.into_iter().map(|img| (img.image_id, img)).collect(),
+            Err(e) => {
+                tracing::error!("Failed to verify images: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to verify images"));
+            }
+        };
+
+    let adding = owned
+        .values()
+        .filter(|image| ImageService::check_analysis_suitability(image, &config.analysis).is_ok())
+        .count();
+    if let Err(response) = check_pending_capacity(&req, pool.get_ref(), &config.analysis, adding).await {
+        return response;
+    }
+
+    let mut jobs = Vec::with_capacity(owned.len());
+    let mut errors = Vec::new();
+
+    for image_id in image_ids {
+        let image = match owned.get(&image_id) {
+            Some(img) => img,
+            None => {
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "NOT_FOUND".to_string(),
+                    message: "Image not found".to_string(),
+                });
+                continue;
+            }
+        };
+
+        if let Err(e) = ImageService::check_analysis_suitability(image, &config.analysis) {
+            errors.push(BatchAnalyzeError {
+                image_id,
+                code: "IMAGE_UNSUITABLE".to_string(),
+                message: e.to_string(),
+            });
+            continue;
+        }
+
+        let job = match submit_analysis_job(pool.get_ref(), &rabbitmq, image, &request.model_version)
+            .await
+        {
+            Ok(job) => job,
+            Err(SubmitJobError::QueueUnavailable(job, e)) => {
+                // The job exists and will be queued by the re-publisher
+                tracing::warn!("RabbitMQ unavailable, job {} left pending: {:?}", job.job_id, e);
+                job
+            }
+            Err(SubmitJobError::Create(e)) => {
+                tracing::error!("Failed to create job for image {}: {:?}", image_id, e);
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: "Failed to create analysis job".to_string(),
+                });
+                continue;
+            }
+            Err(SubmitJobError::Queue(e)) => {
+                tracing::error!("Failed to publish job for image {} to RabbitMQ: {:?}", image_id, e);
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "QUEUE_ERROR".to_string(),
+                    message: "Failed to submit analysis job".to_string(),
+                });
+                continue;
+            }
+        };
+
+        jobs.push(BatchAnalyzeJob {
+            image_id,
+            job_id: job.job_id,
+            status: job.status.to_string(),
+            status_url: format!("/api/v1/jobs/{}", job.job_id),
+        });
+    }
+
+    tracing::info!(
+        "Batch analysis: {} job(s) queued, {} rejected",
+        jobs.len(),
+        errors.len()
+    );
+
+    HttpResponse::Accepted().json(ApiResponse::success(BatchAnalyzeResponse {
+        ai_model_version: request.model_version,
+        jobs,
+        errors,
+    }))
+}
+
+// ============================================================================
+// Retry Failed Jobs in a Folder
+// ============================================================================
+
+/// Re-submit every failed analysis in a folder
+///
+/// Creates a fresh job for each image and model version whose latest job
+/// failed. Images whose file is gone from storage are reported in `errors`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/folders/{folder_id}/retry-failed",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID")
+    ),
+    responses(
+        (status = 202, description = "Failed jobs re-submitted", body = ApiResponse<RetryFailedJobsResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Folder not found"),
+        (status = 503, description = "Analysis queue full (QUEUE_FULL, no jobs created)")
+    )
+)]
+pub async fn retry_failed_jobs(
+    pool: web::Data<PgPool>,
+    rabbitmq: web::Data<RabbitmqService>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let folder_id = path.into_inner();
+
+    match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify folder: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folder"));
+        }
+        Ok(Some(_)) => {}
+    }
+
+    let failed = match JobRepository::find_failed_in_folder(pool.get_ref(), folder_id).await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            tracing::error!("Failed to find failed jobs: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to find failed jobs"));
+        }
+    };
+
+    let image_ids: Vec<i64> = failed.iter().map(|job| job.image_id).collect();
+    let images: HashMap<i64, Image> =
+        match ImageRepository::find_by_ids(pool.get_ref(), &image_ids, user.user_id).await {
+            Ok(images) => images.into_iter().map(|img| (img.image_id, img)).collect(),
+            Err(e) => {
+                tracing::error!("Failed to load images for retry: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to load images"));
+            }
+        };
+
+    let adding = failed
+        .iter()
+        .filter(|job| images.contains_key(&job.image_id))
+        .count();
+    if let Err(response) = check_pending_capacity(&req, pool.get_ref(), &config.analysis, adding).await {
+        return response;
+    }
+
+    let mut jobs = Vec::with_capacity(failed.len());
+    let mut errors = Vec::new();
+
+    for failed_job in failed {
+        let image_id = failed_job.image_id;
+        let Some(image) = images.get(&image_id) else {
+            continue;
+        };
+
+        match storage.object_size(&image.file_path).await {
+            Ok(_) => {}
+            Err(StorageError::NotFound(_)) => {
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "FILE_MISSING".to_string(),
+                    message: "Image file no longer exists in storage".to_string(),
+                });
+                continue;
+            }
+            Err(e) => {
+                tracing::error!("Failed to check file for image {}: {:?}", image_id, e);
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "STORAGE_ERROR".to_string(),
+                    message: "Failed to check image file".to_string(),
+                });
+                continue;
+            }
+        }
+
+        let model_version = failed_job
+            .ai_model_version
+            .unwrap_or_else(|| DEFAULT_MODEL_VERSION.to_string());
+
+        let job = match submit_analysis_job(pool.get_ref(), &rabbitmq, image, &model_version).await {
+            Ok(job) => job,
+            Err(SubmitJobError::QueueUnavailable(job, e)) => {
+                tracing::warn!("RabbitMQ unavailable, job {} left pending: {:?}", job.job_id, e);
+                job
+            }
+            Err(SubmitJobError::Create(e)) => {
+                tracing::error!("Failed to create retry job for image {}: {:?}", image_id, e);
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: "Failed to create analysis job".to_string(),
+                });
+                continue;
+            }
+            Err(SubmitJobError::Queue(e)) => {
+                tracing::error!("Failed to publish retry job for image {}: {:?}", image_id, e);
+                errors.push(BatchAnalyzeError {
+                    image_id,
+                    code: "QUEUE_ERROR".to_string(),
+                    message: "Failed to submit analysis job".to_string(),
+                });
+                continue;
+            }
+        };
+
+        jobs.push(BatchAnalyzeJob {
+            image_id,
+            job_id: job.job_id,
+            status: job.status.to_string(),
+            status_url: format!("/api/v1/jobs/{}", job.job_id),
+        });
+    }
+
+    tracing::info!(
+        "Retried failed jobs in folder {}: {} re-submitted, {} skipped",
+        folder_id,
+        jobs.len(),
+        errors.len()
+    );
+
+    HttpResponse::Accepted().json(ApiResponse::success(RetryFailedJobsResponse {
+        folder_id,
+        requeued: jobs.len(),
+        skipped: errors.len(),
+        jobs,
+        errors,
+    }))
+}
+
+// ============================================================================
+// Check Job Status
+// ============================================================================
+
+/// Get the status of an analysis job
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs/{job_id}",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = i64, Path, description = "Job ID")
+    ),
+    responses(
+        (status = 200, description = "Job status", body = ApiResponse<JobStatusResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn get_job_status(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let job_id = path.into_inner();
+
+    let job = match JobRepository::find_by_id(pool.get_ref(), job_id, user.user_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get job: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get job status"));
+        }
+    };
+
+    HttpResponse::Ok().json(ApiResponse::success(JobStatusResponse::from(job)))
+}
+
+// ============================================================================
+// List Jobs By Model Version
+// ============================================================================
+
+/// List the user's jobs run with a model version, newest first
+///
+/// Covers jobs on every live image the user owns, across folders, so runs
+/// of different model versions can be compared.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/jobs",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(ListJobsQuery),
+    responses(
+        (status = 200, description = "Page of jobs", body = ApiResponse<JobListResponse>),
+        (status = 400, description = "Missing model version or unknown status"),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn list_my_jobs(
+    pool: web::Data<ReadPool>,
+    req: HttpRequest,
+    query: web::Query<ListJobsQuery>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    if let Err(errors) = query.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let status = query.status();
+    let (limit, offset) = (query.limit(), query.offset());
+
+    let total = match JobRepository::count_for_user_filtered(
+        pool.get_ref(),
+        user.user_id,
+        &query.model_version,
+        status.clone(),
+    )
+    .await
+    {
+        Ok(total) => total,
+        Err(e) => {
+            tracing::error!("Failed to count jobs: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list jobs"));
+        }
+    };
+
+    let jobs = match JobRepository::list_for_user_filtered(
+        pool.get_ref(),
+        user.user_id,
+        &query.model_version,
+        status,
+        limit,
+        offset,
+    )
+    .await
+    {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            tracing::error!("Failed to list jobs: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list jobs"));
+        }
+    };
+
+    HttpResponse::Ok().json(ApiResponse::success(JobListResponse {
+        jobs: jobs.into_iter().map(JobStatusResponse::from).collect(),
+        total,
+        limit,
+        offset,
+    }))
+}
+
+// ============================================================================
+// Cancel Job
+// ============================================================================
+
+/// Cancel a pending or processing analysis job
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs/{job_id}/cancel",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = i64, Path, description = "Job ID")
+    ),
+    responses(
+        (status = 200, description = "Job cancelled", body = ApiResponse<JobStatusResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Job not found"),
+        (status = 409, description = "Job already finished")
+    )
+)]
+pub async fn cancel_job(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let job_id = path.into_inner();
+
+    let job = match JobRepository::cancel(pool.get_ref(), job_id, user.user_id).await {
+        Ok(CancelJobOutcome::Cancelled(job)) => job,
+        Ok(CancelJobOutcome::JobNotFound) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+        }
+        Ok(CancelJobOutcome::AlreadyFinished(status)) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "CONFLICT",
+                format!("Job is already {}", status),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Failed to cancel job {}: {:?}", job_id, e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to cancel job"));
+        }
+    };
+
+    tracing::info!("User {} cancelled job {}", user.user_id, job_id);
+
+    HttpResponse::Ok().json(ApiResponse::success(JobStatusResponse {
+        job_id: job.job_id,
+        image_id: job.image_id,
+        status: job.status.to_string(),
+        ai_model_version: job.ai_model_version,
+        started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+        finished_at: job.finished_at.map(|dt| dt.to_rfc3339()),
+        error_message: job.error_message,
+        progress_pct: job.progress_pct,
+        result_url: None,
+    }))
+}
+
+// ============================================================================
+// Retry Job
+// ============================================================================
+
+/// Re-run a failed analysis job
+///
+/// Creates a new job for the same image and model version, linked to the
+/// failed one through `retry_of`. A failed job can be retried once.
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs/{job_id}/retry",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = i64, Path, description = "ID of the failed job")
+    ),
+    responses(
+        (status = 202, description = "Retry job created", body = ApiResponse<AnalyzeImageResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Job or its image not found"),
+        (status = 409, description = "Job not failed, or already retried"),
+        (status = 503, description = "Analysis queue unavailable (job left pending and queued once it recovers) or full (QUEUE_FULL, no job created)")
+    )
+)]
+pub async fn retry_job(
+    pool: web::Data<PgPool>,
+    rabbitmq: web::Data<RabbitmqService>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let job_id = path.into_inner();
+
+    let original = match JobRepository::find_by_id(pool.get_ref(), job_id, user.user_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get job: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retry job"));
+        }
+    };
+
+    // The retry needs the image's storage key, and a deleted image can't be re-run
+    let image =
+        match ImageRepository::find_by_id(pool.get_ref(), original.image_id, user.user_id).await {
+            Ok(Some(image)) => image,
+            Ok(None) => {
+                return HttpResponse::NotFound()
+                    .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+            }
+            Err(e) => {
+                tracing::error!("Failed to verify image: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to retry job"));
+            }
+        };
+
+    if let Err(response) = check_pending_capacity(&req, pool.get_ref(), &config.analysis, 1).await {
+        return response;
+    }
+
+    let job = match JobRepository::create_retry(pool.get_ref(), job_id, user.user_id).await {
+        Ok(RetryJobOutcome::Created(job)) => job,
+        Ok(RetryJobOutcome::JobNotFound) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+        }
+        Ok(RetryJobOutcome::NotFailed(status)) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "CONFLICT",
+                format!("Only failed jobs can be retried; job is {}", status),
+            ));
+        }
+        Ok(RetryJobOutcome::AlreadyRetried(retry_id)) => {
+            return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "ALREADY_RETRIED",
+                format!("Job was already retried as job {}", retry_id),
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Failed to create retry of job {}: {:?}", job_id, e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retry job"));
+        }
+    };
+
+    let job = match queue_analysis_job(pool.get_ref(), &rabbitmq, &image, job).await {
+        Ok(job) => job,
+        Err(SubmitJobError::Create(e)) => {
+            tracing::error!("Failed to create job: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retry job"));
+        }
+        Err(SubmitJobError::Queue(e)) => {
+            tracing::error!("Failed to publish job to RabbitMQ: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::error("QUEUE_ERROR", "Failed to submit analysis job"));
+        }
+        Err(SubmitJobError::QueueUnavailable(job, e)) => {
+            tracing::warn!("RabbitMQ unavailable, job {} left pending: {:?}", job.job_id, e);
+            return HttpResponse::ServiceUnavailable()
+                .insert_header((RETRY_AFTER, config.rabbitmq.republish_interval_secs.to_string()))
+                .json(ApiResponse::<()>::error(
+                    "QUEUE_UNAVAILABLE",
+                    format!(
+                        "Analysis queue unavailable; job {} will be queued when it recovers",
+                        job.job_id
+                    ),
+                ));
+        }
+    };
+
+    tracing::info!("Job {} retried as job {}", job_id, job.job_id);
+
+    HttpResponse::Accepted().json(ApiResponse::success(analyze_image_response(job)))
+}
+
+// ============================================================================
+// Get Analysis Result
+// ============================================================================
+
+/// Get the result of a completed analysis job
+///
+/// Counts and percentages include only boxes at or above the confidence
+/// threshold: `min_confidence` if given, else the default configured for the
+/// job's model version. Without either, the stored counts are returned.
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs/{job_id}/result",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = i64, Path, description = "Job ID"),
+        JobResultQuery
+    ),
+    responses(
+        (status = 200, description = "Analysis result", body = ApiResponse<AnalysisResultResponse>),
+        (status = 400, description = "Malformed query string"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Result not found"),
+        (status = 422, description = "min_confidence out of range")
+    )
+)]
+pub async fn get_job_result(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    query: web::Query<JobResultQuery>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    if let Err(errors) = query.validate() {
+        return validation_error(
+            ValidationKind::Unprocessable,
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        );
+    }
+
+    let job_id = path.into_inner();
+
+    let (result, image_id) =
+        match AnalysisResultRepository::find_by_job_id(pool.get_ref(), job_id, user.user_id).await {
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                return HttpResponse::NotFound()
+                    .json(ApiResponse::<()>::error("NOT_FOUND", "Analysis result not found"));
+            }
+            Err(e) => {
+                tracing::error!("Failed to get result: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
+            }
+        };
+
+    let threshold =
+        match result_threshold(pool.get_ref(), &config, &query, job_id, user.user_id).await {
+            Ok(threshold) => threshold,
+            Err(e) => {
+                tracing::error!("Failed to get job for result: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
+            }
+        };
+
+    HttpResponse::Ok().json(ApiResponse::success(build_result_response(
+        result,
+        image_id,
+        config.analysis.confidence_decimals,
+        threshold,
+    )))
+}
+
+/// Confidence threshold to count a job's result at: `min_confidence` if
+/// given, else the default configured for the job's model version
+async fn result_threshold(
+    pool: &PgPool,
+    config: &AppConfig,
+    query: &JobResultQuery,
+    job_id: i64,
+    user_id: Uuid,
+) -> Result<Option<f64>, sqlx::Error> {
+    if let Some(min_confidence) = query.min_confidence {
+        return Ok(Some(min_confidence));
+    }
+    if config.analysis.confidence_thresholds.is_empty() {
+        return Ok(None);
+    }
+
+    let job = JobRepository::find_by_id(pool, job_id, user_id).await?;
+    Ok(job
+        .and_then(|job| job.ai_model_version)
+        .and_then(|version| config.analysis.confidence_threshold_for(&version)))
+}
+
+/// Assemble the API representation of a stored analysis result
+///
+/// With a `confidence_threshold`, counts are recomputed from the detected
+/// boxes at that threshold; results without box data keep their stored
+/// counts. Confidence scores are rounded to `confidence_decimals` places.
+pub(crate) fn build_result_response(
+    result: AnalysisResult,
+    image_id: i64,
+    confidence_decimals: u32,
+    confidence_threshold: Option<f64>,
+) -> AnalysisResultResponse {
+    let mut raw_data = result.raw_data.clone().and_then(|data| {
+        match serde_json::from_value::<RawDetectionData>(data.clone()) {
+            Ok(d) => Some(d),
+            Err(e) => {
+                tracing::error!("Failed to parse raw_data for result_id {}: {:?}. Data: {:?}", result.result_id, e, data);
+                None
+            }
+        }
+    });
+
+    let (counts, confidence_threshold) = match (confidence_threshold, &raw_data) {
+        (Some(threshold), Some(data)) => (data.counts_at(threshold), Some(threshold)),
+        _ => (
+            CellCounts {
+                viable: result.count_viable,
+                apoptosis: result.count_apoptosis,
+                other: result.count_other,
+            },
+            None,
+        ),
+    };
+
+    let total_cells = counts.viable + counts.apoptosis + counts.other;
+    let total_f = total_cells as f64;
+
+    let percentages = if total_cells > 0 {
+        CellPercentages {
+            viable: (counts.viable as f64 / total_f) * 100.0,
+            apoptosis: (counts.apoptosis as f64 / total_f) * 100.0,
+            other: (counts.other as f64 / total_f) * 100.0,
+        }
+    } else {
+        CellPercentages {
+            viable: 0.0,
+            apoptosis: 0.0,
+            other: 0.0,
+        }
+    };
 
-use crate::domain::ApiResponse;
-use crate::dto::analysis::{
-    AnalysisHistorySummary, AnalysisResultResponse, AnalyzeImageRequest, AnalyzeImageResponse,
-    CellCounts, CellPercentages, ImageAnalysisHistoryResponse, JobStatusResponse,
-    RawDetectionData,
-};
-use crate::middleware::AuthenticatedUser;
-use crate::models::job::JobStatus;
-use crate::repositories::{AnalysisResultRepository, ImageRepository, JobRepository};
-use crate::services::{AnalysisJobMessage, RabbitmqService};
+    if let Some(data) = raw_data.as_mut() {
+        data.round_confidences(confidence_decimals);
+    }
+
+    AnalysisResultResponse {
+        result_id: result.result_id,
+        job_id: result.job_id,
+        image_id,
+        counts,
+        total_cells,
+        avg_confidence_score: round_confidence(
+            result.avg_confidence_score.unwrap_or(0.0),
+            confidence_decimals,
+        ),
+        percentages,
+        confidence_threshold,
+        raw_data,
+        summary_data: result.summary_data,
+        analyzed_at: result
+            .analyzed_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+    }
+}
 
 // ============================================================================
-// Analyze Image (Submit for Analysis)
+// Get Latest Image Result
 // ============================================================================
 
-/// Submit an image for AI analysis via RabbitMQ
+/// Get the result of an image's most recent completed analysis
+///
+/// Counts follow the same confidence threshold rules as the job result
+/// endpoint.
 #[utoipa::path(
-    post,
-    path = "/api/v1/images/{image_id}/analyze",
+    get,
+    path = "/api/v1/images/{image_id}/latest-result",
     tag = "AI Analysis",
     security(("bearer_auth" = [])),
     params(
-        ("image_id" = i64, Path, description = "Image ID")
+        ("image_id" = i64, Path, description = "Image ID"),
+        JobResultQuery
     ),
-    request_body = AnalyzeImageRequest,
     responses(
-        (status = 202, description = "Analysis job created", body = ApiResponse<AnalyzeImageResponse>),
+        (status = 200, description = "Latest analysis result", body = ApiResponse<AnalysisResultResponse>),
+        (status = 400, description = "Malformed query string"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Image not found")
+        (status = 404, description = "Image not found or never analyzed successfully"),
+        (status = 422, description = "min_confidence out of range")
     )
 )]
-pub async fn analyze_image(
+pub async fn get_latest_image_result(
     pool: web::Data<PgPool>,
-    rabbitmq: web::Data<RabbitmqService>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
-    body: Option<web::Json<AnalyzeImageRequest>>,
+    query: web::Query<JobResultQuery>,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
         Some(u) => u.clone(),
@@ -51,91 +1228,81 @@ pub async fn analyze_image(
         }
     };
 
-    let image_id = path.into_inner();
-    let request = body.map(|b| b.into_inner()).unwrap_or_default();
-
-    // Verify image ownership and get image details
-    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
-        Ok(None) => {
-            return HttpResponse::NotFound()
-                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
-        }
-        Err(e) => {
-            tracing::error!("Failed to verify image: {:?}", e);
-            return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify image"));
-        }
-        Ok(Some(img)) => img,
-    };
-
-    // Create job
-    let job = match JobRepository::create(pool.get_ref(), image_id, &request.model_version).await {
-        Ok(job) => job,
-        Err(e) => {
-            tracing::error!("Failed to create job: {:?}", e);
-            return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to create analysis job"));
-        }
-    };
+    if let Err(errors) = query.validate() {
+        return validation_error(
+            ValidationKind::Unprocessable,
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        );
+    }
 
-    // Publish job to RabbitMQ for Python model worker to process
-    let message = AnalysisJobMessage {
-        job_id: job.job_id,
-        image_id: job.image_id,
-        s3_key: image.file_path.clone(),
-        model_version: request.model_version.clone(),
-        created_at: job
-            .created_at
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default(),
-    };
+    let image_id = path.into_inner();
 
-    if let Err(e) = rabbitmq.publish_analysis_job(message).await {
-        tracing::error!("Failed to publish job to RabbitMQ: {:?}", e);
-        // Mark job as failed since we couldn't queue it
-        let _ = JobRepository::fail(pool.get_ref(), job.job_id, "Failed to queue analysis job").await;
-        return HttpResponse::InternalServerError()
-            .json(ApiResponse::<()>::error("QUEUE_ERROR", "Failed to submit analysis job"));
-    }
+    let result =
+        match AnalysisResultRepository::find_latest_for_image(pool.get_ref(), image_id, user.user_id)
+            .await
+        {
+            Ok(Some(result)) => result,
+            Ok(None) => {
+                return HttpResponse::NotFound()
+                    .json(ApiResponse::<()>::error("NOT_FOUND", "No completed analysis found"));
+            }
+            Err(e) => {
+                tracing::error!("Failed to get latest result: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
+            }
+        };
 
-    tracing::info!("Analysis job {} queued for image {}", job.job_id, image_id);
+    let threshold =
+        match result_threshold(pool.get_ref(), &config, &query, result.job_id, user.user_id).await {
+            Ok(threshold) => threshold,
+            Err(e) => {
+                tracing::error!("Failed to get job for result: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
+            }
+        };
 
-    HttpResponse::Accepted().json(ApiResponse::success(AnalyzeImageResponse {
-        job_id: job.job_id,
-        image_id: job.image_id,
-        status: job.status.to_string(),
-        ai_model_version: request.model_version,
-        status_url: format!("/api/v1/jobs/{}", job.job_id),
-        created_at: job
-            .created_at
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default(),
-    }))
+    HttpResponse::Ok().json(ApiResponse::success(build_result_response(
+        result,
+        image_id,
+        config.analysis.confidence_decimals,
+        threshold,
+    )))
 }
 
 // ============================================================================
-// Check Job Status
+// Get Scaled Detections
 // ============================================================================
 
-/// Get the status of an analysis job
+/// Get a result's bounding boxes rescaled to a display size
+///
+/// Coordinates are mapped from the original image dimensions, which must be
+/// known, to `width` x `height`.
 #[utoipa::path(
     get,
-    path = "/api/v1/jobs/{job_id}",
+    path = "/api/v1/jobs/{job_id}/detections/scaled",
     tag = "AI Analysis",
     security(("bearer_auth" = [])),
     params(
-        ("job_id" = i64, Path, description = "Job ID")
+        ("job_id" = i64, Path, description = "Job ID"),
+        ScaledDetectionsQuery
     ),
     responses(
-        (status = 200, description = "Job status", body = ApiResponse<JobStatusResponse>),
+        (status = 200, description = "Scaled detections", body = ApiResponse<ScaledDetectionsResponse>),
+        (status = 400, description = "Malformed query string"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Job not found")
+        (status = 404, description = "Result not found"),
+        (status = 422, description = "Target dimensions out of range, or original dimensions unknown")
     )
 )]
-pub async fn get_job_status(
+pub async fn get_scaled_detections(
     pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
+    query: web::Query<ScaledDetectionsQuery>,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
         Some(u) => u.clone(),
@@ -145,60 +1312,106 @@ pub async fn get_job_status(
         }
     };
 
+    if let Err(errors) = query.validate() {
+        return validation_error(
+            ValidationKind::Unprocessable,
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        );
+    }
+
     let job_id = path.into_inner();
 
-    let job = match JobRepository::find_by_id(pool.get_ref(), job_id, user.user_id).await {
-        Ok(Some(job)) => job,
+    let (result, image_id) =
+        match AnalysisResultRepository::find_by_job_id(pool.get_ref(), job_id, user.user_id).await {
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                return HttpResponse::NotFound()
+                    .json(ApiResponse::<()>::error("NOT_FOUND", "Analysis result not found"));
+            }
+            Err(e) => {
+                tracing::error!("Failed to get result: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
+            }
+        };
+
+    let dimensions = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(image)) => image.dimensions(),
         Ok(None) => {
             return HttpResponse::NotFound()
-                .json(ApiResponse::<()>::error("NOT_FOUND", "Job not found"));
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
         }
         Err(e) => {
-            tracing::error!("Failed to get job: {:?}", e);
+            tracing::error!("Failed to get image for result: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get job status"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
         }
     };
 
-    let result_url = if job.status == JobStatus::Completed {
-        Some(format!("/api/v1/jobs/{}/result", job_id))
-    } else {
-        None
+    let (original_width, original_height) = match dimensions {
+        Some((width, height)) if width > 0 && height > 0 => (width, height),
+        _ => {
+            return validation_error(
+                ValidationKind::Unprocessable,
+                "DIMENSIONS_UNKNOWN",
+                "Original image dimensions are not known",
+            );
+        }
     };
 
-    HttpResponse::Ok().json(ApiResponse::success(JobStatusResponse {
-        job_id: job.job_id,
-        image_id: job.image_id,
-        status: job.status.to_string(),
-        ai_model_version: job.ai_model_version,
-        started_at: job.started_at.map(|dt| dt.to_rfc3339()),
-        finished_at: job.finished_at.map(|dt| dt.to_rfc3339()),
-        error_message: job.error_message,
-        result_url,
+    // Results without (parseable) box data have nothing to scale
+    let mut raw_data = result
+        .raw_data
+        .and_then(|data| serde_json::from_value::<RawDetectionData>(data).ok())
+        .unwrap_or(RawDetectionData {
+            bounding_boxes: Vec::new(),
+            truncated: false,
+        });
+    raw_data.round_confidences(config.analysis.confidence_decimals);
+    let bounding_boxes =
+        raw_data.scaled((original_width, original_height), (query.width, query.height));
+
+    HttpResponse::Ok().json(ApiResponse::success(ScaledDetectionsResponse {
+        job_id,
+        image_id,
+        original_width,
+        original_height,
+        width: query.width,
+        height: query.height,
+        bounding_boxes,
     }))
 }
 
 // ============================================================================
-// Get Analysis Result
+// Get Overlay URL
 // ============================================================================
 
-/// Get the result of a completed analysis job
+/// Get a presigned URL for a job's result overlay
+///
+/// The overlay (the analyzed image with its detections outlined) is rendered
+/// on first request and cached in storage, so the client can load it
+/// directly from there.
 #[utoipa::path(
     get,
-    path = "/api/v1/jobs/{job_id}/result",
+    path = "/api/v1/jobs/{job_id}/overlay-url",
     tag = "AI Analysis",
     security(("bearer_auth" = [])),
     params(
         ("job_id" = i64, Path, description = "Job ID")
     ),
     responses(
-        (status = 200, description = "Analysis result", body = ApiResponse<AnalysisResultResponse>),
+        (status = 200, description = "Presigned overlay URL", body = ApiResponse<PresignedDownloadResponse>),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Result not found")
+        (status = 404, description = "Analysis result not found"),
+        (status = 422, description = "Overlay cannot be drawn on this image"),
+        (status = 501, description = "Storage backend does not support presigned URLs")
     )
 )]
-pub async fn get_job_result(
+pub async fn get_overlay_url(
     pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
 ) -> HttpResponse {
@@ -222,58 +1435,124 @@ pub async fn get_job_result(
             Err(e) => {
                 tracing::error!("Failed to get result: {:?}", e);
                 return HttpResponse::InternalServerError()
-                    .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get result"));
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get result"));
             }
         };
 
-    let total_cells = result.count_viable + result.count_apoptosis + result.count_other;
-    let total_f = total_cells as f64;
+    let overlay_key = ImageService::overlay_key(job_id);
 
-    let percentages = if total_cells > 0 {
-        CellPercentages {
-            viable: (result.count_viable as f64 / total_f) * 100.0,
-            apoptosis: (result.count_apoptosis as f64 / total_f) * 100.0,
-            other: (result.count_other as f64 / total_f) * 100.0,
+    match storage.object_size(&overlay_key).await {
+        Ok(_) => {}
+        Err(StorageError::NotFound(_)) => {
+            if let Err(response) =
+                render_overlay(&req, &pool, storage.get_ref(), &config, result, image_id, user.user_id, &overlay_key).await
+            {
+                return response;
+            }
         }
-    } else {
-        CellPercentages {
-            viable: 0.0,
-            apoptosis: 0.0,
-            other: 0.0,
+        Err(e) => {
+            tracing::error!("Failed to check cached overlay: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get overlay"));
         }
-    };
+    }
 
-    let raw_data = result.raw_data.clone().and_then(|data| {
-        match serde_json::from_value::<RawDetectionData>(data.clone()) {
-            Ok(d) => Some(d),
-            Err(e) => {
-                tracing::error!("Failed to parse raw_data for result_id {}: {:?}. Data: {:?}", result.result_id, e, data);
-                None
-            }
+    let presigned_url = match storage.presign_get(&overlay_key, &ResponseOverrides::default()).await {
+        Ok(url) => url,
+        Err(StorageError::Unsupported(msg)) => {
+            return HttpResponse::NotImplemented()
+                .json(ApiResponse::<()>::error("NOT_SUPPORTED", msg));
         }
-    });
+        Err(e) => {
+            tracing::error!("Failed to generate presigned overlay URL: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to generate overlay URL"));
+        }
+    };
 
-    HttpResponse::Ok().json(ApiResponse::success(AnalysisResultResponse {
-        result_id: result.result_id,
-        job_id: result.job_id,
-        image_id,
-        counts: CellCounts {
-            viable: result.count_viable,
-            apoptosis: result.count_apoptosis,
-            other: result.count_other,
-        },
-        total_cells,
-        avg_confidence_score: result.avg_confidence_score.unwrap_or(0.0),
-        percentages,
-        raw_data,
-        summary_data: result.summary_data,
-        analyzed_at: result
-            .analyzed_at
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default(),
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(storage.presign_expiry_secs() as i64);
+
+    HttpResponse::Ok().json(ApiResponse::success(PresignedDownloadResponse {
+        url: presigned_url,
+        expires_at: expires_at.to_rfc3339(),
     }))
 }
 
+/// Draw a result's detections on its image and store it under `overlay_key`
+///
+/// Only the `overlay.max_boxes` most confident boxes are drawn; the overlay
+/// notes how many were left out.
+#[allow(clippy::too_many_arguments)]
+async fn render_overlay(
+    req: &HttpRequest,
+    pool: &PgPool,
+    storage: &dyn StorageBackend,
+    config: &AppConfig,
+    result: AnalysisResult,
+    image_id: i64,
+    user_id: Uuid,
+    overlay_key: &str,
+) -> Result<(), HttpResponse> {
+    let internal_error = || {
+        HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::internal_error(req, "Failed to render overlay"))
+    };
+
+    let image = match ImageRepository::find_by_id(pool, image_id, user_id).await {
+        Ok(Some(image)) => image,
+        Ok(None) => {
+            return Err(HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found")));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get image for overlay: {:?}", e);
+            return Err(internal_error());
+        }
+    };
+
+    let (bytes, _) = storage.get(&image.file_path).await.map_err(|e| {
+        tracing::error!("Failed to read image {} for overlay: {:?}", image_id, e);
+        internal_error()
+    })?;
+
+    // Results without (parseable) box data render as the plain image
+    let raw_data = result
+        .raw_data
+        .and_then(|data| serde_json::from_value::<RawDetectionData>(data).ok())
+        .unwrap_or(RawDetectionData {
+            bounding_boxes: Vec::new(),
+            truncated: false,
+        });
+    let max_boxes = config.overlay.max_boxes;
+
+    // Decoding and re-encoding is CPU-bound, so keep it off the async runtime
+    let rendered = tokio::task::spawn_blocking(move || {
+        let (boxes, omitted) = raw_data.top_boxes(max_boxes);
+        ImageService::render_overlay(&bytes, &boxes, omitted)
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Overlay rendering panicked: {:?}", e);
+        internal_error()
+    })?;
+
+    let Some(rendered) = rendered else {
+        return Err(validation_error(
+            ValidationKind::Unprocessable,
+            "OVERLAY_UNSUPPORTED",
+            "Overlays can only be drawn on JPEG images",
+        ));
+    };
+
+    storage
+        .upload(overlay_key, &rendered, "image/jpeg")
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to store overlay {}: {:?}", overlay_key, e);
+            internal_error()
+        })
+}
+
 // ============================================================================
 // Get Image Analysis History
 // ============================================================================
@@ -294,7 +1573,8 @@ pub async fn get_job_result(
     )
 )]
 pub async fn get_analysis_history(
-    pool: web::Data<PgPool>,
+    pool: web::Data<ReadPool>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
 ) -> HttpResponse {
@@ -317,7 +1597,7 @@ pub async fn get_analysis_history(
         Err(e) => {
             tracing::error!("Failed to verify image: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify image"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify image"));
         }
         Ok(Some(_)) => {}
     }
@@ -328,11 +1608,12 @@ pub async fn get_analysis_history(
             Err(e) => {
                 tracing::error!("Failed to get analysis history: {:?}", e);
                 return HttpResponse::InternalServerError()
-                    .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get history"));
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get history"));
             }
         };
 
     let total = history.len() as i64;
+    let confidence_decimals = config.analysis.confidence_decimals;
     let analyses: Vec<AnalysisHistorySummary> = history
         .into_iter()
         .map(|(job, result)| {
@@ -341,7 +1622,10 @@ pub async fn get_analysis_history(
                 apoptosis: r.count_apoptosis,
                 other: r.count_other,
             });
-            let avg_confidence = result.as_ref().and_then(|r| r.avg_confidence_score);
+            let avg_confidence = result
+                .as_ref()
+                .and_then(|r| r.avg_confidence_score)
+                .map(|score| round_confidence(score, confidence_decimals));
 
             AnalysisHistorySummary {
                 job_id: job.job_id,
@@ -360,3 +1644,152 @@ pub async fn get_analysis_history(
         total,
     }))
 }
+
+// ============================================================================
+// Analysis Totals
+// ============================================================================
+
+/// Get lifetime analysis totals across all of the user's folders
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/analysis-totals",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Analysis totals", body = ApiResponse<AnalysisTotalsResponse>),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn get_analysis_totals(pool: web::Data<ReadPool>, req: HttpRequest) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let totals =
+        match AnalysisResultRepository::totals_by_user_id(pool.get_ref(), user.user_id).await {
+            Ok(t) => t,
+            Err(e) => {
+                tracing::error!("Failed to get analysis totals: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to get totals"));
+            }
+        };
+
+    HttpResponse::Ok().json(ApiResponse::success(AnalysisTotalsResponse {
+        total_jobs: totals.total_jobs,
+        completed_jobs: totals.completed_jobs,
+        failed_jobs: totals.failed_jobs,
+        cells: CellTotals {
+            viable: totals.viable_cells,
+            apoptosis: totals.apoptosis_cells,
+            other: totals.other_cells,
+        },
+        mean_confidence: totals.mean_confidence,
+    }))
+}
+
+// ============================================================================
+// Stream Folder Results (NDJSON)
+// ============================================================================
+
+/// Results fetched from the database per streamed chunk
+const NDJSON_PAGE_SIZE: i64 = 100;
+
+/// Stream all completed analysis results in a folder as newline-delimited JSON
+///
+/// Each line is one `AnalysisResultResponse`. Results are read page by page, so
+/// memory stays bounded regardless of folder size.
+#[utoipa::path(
+    get,
+    path = "/api/v1/folders/{folder_id}/results.ndjson",
+    tag = "AI Analysis",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID")
+    ),
+    responses(
+        (status = 200, description = "One analysis result per line", content_type = "application/x-ndjson"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Folder not found")
+    )
+)]
+pub async fn stream_folder_results(
+    pool: web::Data<ReadPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let folder_id = path.into_inner();
+
+    // Verify folder ownership before any results are streamed
+    match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify folder: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folder"));
+        }
+        Ok(Some(_)) => {}
+    }
+
+    let pool = pool.get_ref().clone();
+    let confidence_decimals = config.analysis.confidence_decimals;
+
+    // State is the result_id to continue after; None once the last page was sent
+    let stream = futures::stream::unfold(Some(0_i64), move |cursor| {
+        let pool = pool.clone();
+        async move {
+            let after_result_id = cursor?;
+            let rows = match AnalysisResultRepository::find_completed_by_folder_page(
+                &pool,
+                folder_id,
+                after_result_id,
+                NDJSON_PAGE_SIZE,
+            )
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    // Headers are already sent, so the best we can do is abort the body
+                    tracing::error!("Failed to stream folder results: {:?}", e);
+                    let error = actix_web::error::ErrorInternalServerError("Failed to stream results");
+                    return Some((Err(error), None));
+                }
+            };
+
+            let last_result_id = rows.last()?.0.result_id;
+            let next_cursor = (rows.len() as i64 == NDJSON_PAGE_SIZE).then_some(last_result_id);
+
+            let mut chunk = Vec::new();
+            for (result, image_id) in rows {
+                let response = build_result_response(result, image_id, confidence_decimals, None);
+                if let Err(e) = serde_json::to_writer(&mut chunk, &response) {
+                    tracing::error!("Failed to serialize analysis result: {:?}", e);
+                    continue;
+                }
+                chunk.push(b'\n');
+            }
+
+            Some((Ok::<_, actix_web::Error>(web::Bytes::from(chunk)), next_cursor))
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(stream)
+}
@@ -1,10 +1,15 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use sqlx::PgPool;
 use validator::Validate;
 
 use crate::config::settings::JwtConfig;
 use crate::domain::ApiResponse;
-use crate::dto::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
+use crate::dto::{
+    ChangePasswordRequest, ChangePasswordResponse, LoginRequest, LoginResponse, RegisterRequest,
+    RegisterResponse,
+};
+use crate::middleware::{AuthenticatedToken, AuthenticatedUser};
+use crate::repositories::RevokedTokenRepository;
 use crate::services::{AuthError, AuthService};
 
 /// Register a new user
@@ -22,6 +27,7 @@ use crate::services::{AuthError, AuthService};
     )
 )]
 pub async fn register(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     body: web::Json<RegisterRequest>,
 ) -> HttpResponse {
@@ -44,8 +50,8 @@ pub async fn register(
         }
         Err(e) => {
             tracing::error!("Registration error: {:?}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
-                "INTERNAL_ERROR",
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::internal_error(
+                &req,
                 "An error occurred during registration",
             ))
         }
@@ -67,6 +73,7 @@ pub async fn register(
     )
 )]
 pub async fn login(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     jwt_config: web::Data<JwtConfig>,
     body: web::Json<LoginRequest>,
@@ -89,8 +96,8 @@ pub async fn login(
         }
         Err(e) => {
             tracing::error!("Login error: {:?}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(
-                "INTERNAL_ERROR",
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::internal_error(
+                &req,
                 "An error occurred during login",
             ))
         }
@@ -99,9 +106,9 @@ pub async fn login(
 
 /// Logout user
 ///
-/// Stateless logout - instructs client to discard tokens.
-/// The server does not maintain session state, so the client is responsible
-/// for removing the tokens from storage.
+/// Revokes the access token the request was made with, so it stops working
+/// immediately instead of at expiry. The client should still discard its
+/// tokens.
 #[utoipa::path(
     post,
     path = "/api/v1/auth/logout",
@@ -114,8 +121,87 @@ pub async fn login(
         (status = 401, description = "Unauthorized - Invalid or missing token")
     )
 )]
-pub async fn logout() -> HttpResponse {
+pub async fn logout(pool: web::Data<PgPool>, req: HttpRequest) -> HttpResponse {
+    // Tokens issued before revocation existed carry no ID and run to expiry
+    let token = req.extensions().get::<AuthenticatedToken>().copied();
+
+    if let Some(token) = token {
+        if let Err(e) = RevokedTokenRepository::revoke(pool.get_ref(), token.jti, token.expires_at).await {
+            tracing::error!("Failed to revoke token: {:?}", e);
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::internal_error(
+                &req,
+                "An error occurred during logout",
+            ));
+        }
+    }
+
     HttpResponse::Ok().json(ApiResponse::success(crate::dto::LogoutResponse {
         message: "Logged out successfully. Please discard your tokens.".to_string(),
     }))
 }
+
+/// Change password
+///
+/// Replaces the caller's password after verifying the current one.
+/// Tokens already issued stay valid until they expire.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-password",
+    tag = "Authentication",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed", body = ApiResponse<ChangePasswordResponse>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized or current password incorrect"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn change_password(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    body: web::Json<ChangePasswordRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    // Validate request
+    if let Err(errors) = body.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    match AuthService::change_password(pool.get_ref(), user.user_id, body.into_inner()).await {
+        Ok(()) => {
+            tracing::info!(user_id = %user.user_id, jti = ?user.jti, "Password changed");
+            HttpResponse::Ok().json(ApiResponse::success(ChangePasswordResponse {
+                message: "Password changed successfully".to_string(),
+            }))
+        }
+        Err(AuthError::InvalidCredentials) => {
+            HttpResponse::Unauthorized().json(ApiResponse::<()>::error(
+                "INVALID_CREDENTIALS",
+                "Current password is incorrect",
+            ))
+        }
+        Err(AuthError::UserNotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "User not found"))
+        }
+        Err(e) => {
+            tracing::error!("Change password error: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::internal_error(
+                &req,
+                "An error occurred while changing the password",
+            ))
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Data Export Handlers
+//!
+//! Lets users download everything they have stored (data portability).
+//! Archives are generated in the background; clients poll the export until
+//! it completes and then download it from a presigned URL.
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::ApiResponse;
+use crate::dto::DataExportResponse;
+use crate::middleware::AuthenticatedUser;
+use crate::models::job::JobStatus;
+use crate::models::DataExport;
+use crate::repositories::DataExportRepository;
+use crate::services::{ExportService, ResponseOverrides, StorageBackend, StorageError};
+
+/// Build the status response for an export without a download link
+fn export_response(export: &DataExport) -> DataExportResponse {
+    DataExportResponse {
+        export_id: export.export_id,
+        status: export.status.to_string(),
+        status_url: format!("/api/v1/me/export/{}", export.export_id),
+        created_at: export
+            .created_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        finished_at: export.finished_at.map(|dt| dt.to_rfc3339()),
+        download_url: None,
+        expires_at: None,
+        error_message: export.error_message.clone(),
+    }
+}
+
+// ============================================================================
+// Request Export
+// ============================================================================
+
+/// Request an archive of all the user's data
+///
+/// Returns immediately with an export ID; the ZIP (a `manifest.json` of
+/// folders, images and analysis results plus the image files) is generated
+/// in the background.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/export",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Export started", body = ApiResponse<DataExportResponse>),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn request_data_export(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let export = match DataExportRepository::create(pool.get_ref(), user.user_id).await {
+        Ok(export) => export,
+        Err(e) => {
+            tracing::error!("Failed to create data export: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to start export"));
+        }
+    };
+
+    // Generation can take a while for large accounts, so keep it off the request path
+    actix_web::rt::spawn(ExportService::run(
+        pool.get_ref().clone(),
+        storage.into_inner(),
+        export.export_id,
+        user.user_id,
+    ));
+
+    tracing::info!("Data export {} requested by user {}", export.export_id, user.user_id);
+
+    HttpResponse::Accepted().json(ApiResponse::success(export_response(&export)))
+}
+
+// ============================================================================
+// Export Status
+// ============================================================================
+
+/// Get a data export's status, with a download URL once completed
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/export/{export_id}",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    params(
+        ("export_id" = String, Path, format = "uuid", description = "Export ID")
+    ),
+    responses(
+        (status = 200, description = "Export status", body = ApiResponse<DataExportResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Export not found")
+    )
+)]
+pub async fn get_data_export(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let export_id = path.into_inner();
+
+    let export = match DataExportRepository::find_by_id(pool.get_ref(), export_id, user.user_id).await {
+        Ok(Some(export)) => export,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Export not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get data export: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get export"));
+        }
+    };
+
+    let mut response = export_response(&export);
+
+    if let (JobStatus::Completed, Some(key)) = (&export.status, &export.storage_key) {
+        let overrides = ResponseOverrides {
+            content_type: Some("application/zip".to_string()),
+            content_disposition: Some("attachment; filename=\"data-export.zip\"".to_string()),
+        };
+
+        match storage.presign_get(key, &overrides).await {
+            Ok(url) => {
+                let expires_at = chrono::Utc::now()
+                    + chrono::Duration::seconds(storage.presign_expiry_secs() as i64);
+                response.download_url = Some(url);
+                response.expires_at = Some(expires_at.to_rfc3339());
+            }
+            Err(StorageError::Unsupported(msg)) => {
+                tracing::warn!("Cannot link export {}: {}", export_id, msg);
+            }
+            Err(e) => {
+                tracing::error!("Failed to generate export download URL: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to generate download URL"));
+            }
+        }
+    }
+
+    HttpResponse::Ok().json(ApiResponse::success(response))
+}
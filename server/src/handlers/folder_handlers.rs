@@ -2,17 +2,46 @@
 //!
 //! CRUD operations for folders with ownership verification.
 
+use std::sync::Arc;
+
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use sqlx::PgPool;
 use validator::Validate;
 
+use crate::config::settings::AppConfig;
+use crate::db::ReadPool;
 use crate::domain::ApiResponse;
+use crate::handlers::image_handlers::check_storage_quota;
 use crate::dto::{
-    CreateFolderRequest, DeleteFolderResponse, FolderListResponse, FolderResponse,
-    UpdateFolderRequest,
+    normalize_folder_name, CopyFolderRequest, CreateFolderRequest, DeleteFolderResponse,
+    FolderListResponse, FolderResponse, MergeFolderRequest, UpdateFolderRequest,
 };
 use crate::middleware::AuthenticatedUser;
-use crate::repositories::FolderRepository;
+use crate::models::Folder;
+use crate::repositories::{
+    FolderRepository, ImageRepository, MergeFolderOutcome, PurgeFolderOutcome,
+};
+use crate::services::{ImageService, S3StorageService, StorageBackend, StorageError};
+
+/// Build a folder listing from folders paired with their image counts
+fn folder_list_response(folders: Vec<(Folder, i64)>) -> FolderListResponse {
+    let folders: Vec<FolderResponse> = folders
+        .into_iter()
+        .map(|(folder, image_count)| FolderResponse {
+            folder_id: folder.folder_id,
+            folder_name: folder.folder_name,
+            image_count,
+            created_at: folder
+                .created_at
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            deleted_at: folder.deleted_at.map(|dt| dt.to_rfc3339()),
+        })
+        .collect();
+
+    let total = folders.len() as i64;
+    FolderListResponse { folders, total }
+}
 
 // ============================================================================
 // List Folders
@@ -30,7 +59,7 @@ use crate::repositories::FolderRepository;
     )
 )]
 pub async fn list_folders(
-    pool: web::Data<PgPool>,
+    pool: web::Data<ReadPool>,
     req: HttpRequest,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
@@ -42,31 +71,48 @@ pub async fn list_folders(
     };
 
     match FolderRepository::find_by_user_id(pool.get_ref(), user.user_id).await {
-        Ok(folders) => {
-            let folder_responses: Vec<FolderResponse> = folders
-                .into_iter()
-                .map(|(folder, image_count)| FolderResponse {
-                    folder_id: folder.folder_id,
-                    folder_name: folder.folder_name,
-                    image_count,
-                    created_at: folder
-                        .created_at
-                        .map(|dt| dt.to_rfc3339())
-                        .unwrap_or_default(),
-                    deleted_at: folder.deleted_at.map(|dt| dt.to_rfc3339()),
-                })
-                .collect();
-
-            let total = folder_responses.len() as i64;
-            HttpResponse::Ok().json(ApiResponse::success(FolderListResponse {
-                folders: folder_responses,
-                total,
-            }))
-        }
+        Ok(folders) => HttpResponse::Ok().json(ApiResponse::success(folder_list_response(folders))),
         Err(e) => {
             tracing::error!("Failed to list folders: {:?}", e);
             HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to list folders"))
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list folders"))
+        }
+    }
+}
+
+// ============================================================================
+// List Trash
+// ============================================================================
+
+/// List the authenticated user's soft-deleted folders, most recently deleted first
+#[utoipa::path(
+    get,
+    path = "/api/v1/folders/trash",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Deleted folders", body = ApiResponse<FolderListResponse>),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn list_trash(
+    pool: web::Data<ReadPool>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    match FolderRepository::find_deleted_by_user_id(pool.get_ref(), user.user_id).await {
+        Ok(folders) => HttpResponse::Ok().json(ApiResponse::success(folder_list_response(folders))),
+        Err(e) => {
+            tracing::error!("Failed to list deleted folders: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list deleted folders"))
         }
     }
 }
@@ -111,7 +157,10 @@ pub async fn create_folder(
         ));
     }
 
-    match FolderRepository::create(pool.get_ref(), user.user_id, &request.folder_name).await {
+    // Store the normalized form so visually identical names compare equal
+    let folder_name = normalize_folder_name(&request.folder_name);
+
+    match FolderRepository::create(pool.get_ref(), user.user_id, &folder_name).await {
         Ok(folder) => HttpResponse::Created().json(ApiResponse::success(FolderResponse {
             folder_id: folder.folder_id,
             folder_name: folder.folder_name,
@@ -125,8 +174,89 @@ pub async fn create_folder(
         Err(e) => {
             tracing::error!("Failed to create folder: {:?}", e);
             HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to create folder"))
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to create folder"))
+        }
+    }
+}
+
+/// Create a folder unless the user already has one with that name
+///
+/// Idempotent: repeating the request returns the existing folder.
+#[utoipa::path(
+    put,
+    path = "/api/v1/folders",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    request_body = CreateFolderRequest,
+    responses(
+        (status = 200, description = "Folder already existed", body = ApiResponse<FolderResponse>),
+        (status = 201, description = "Folder created", body = ApiResponse<FolderResponse>),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn ensure_folder(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    body: web::Json<CreateFolderRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let folder_name = normalize_folder_name(&request.folder_name);
+
+    let (folder, created) =
+        match FolderRepository::find_or_create(pool.get_ref(), user.user_id, &folder_name).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                tracing::error!("Failed to create folder: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to create folder"));
+            }
+        };
+
+    let image_count = if created {
+        0
+    } else {
+        match FolderRepository::get_image_count(pool.get_ref(), folder.folder_id).await {
+            Ok(count) => count,
+            Err(e) => {
+                tracing::error!("Failed to count images: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to count images"));
+            }
         }
+    };
+
+    let response = FolderResponse {
+        folder_id: folder.folder_id,
+        folder_name: folder.folder_name,
+        image_count,
+        created_at: folder
+            .created_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        deleted_at: None,
+    };
+
+    if created {
+        HttpResponse::Created().json(ApiResponse::success(response))
+    } else {
+        HttpResponse::Ok().json(ApiResponse::success(response))
     }
 }
 
@@ -177,7 +307,9 @@ pub async fn rename_folder(
         ));
     }
 
-    match FolderRepository::update_name(pool.get_ref(), folder_id, user.user_id, &request.folder_name)
+    let folder_name = normalize_folder_name(&request.folder_name);
+
+    match FolderRepository::update_name(pool.get_ref(), folder_id, user.user_id, &folder_name)
         .await
     {
         Ok(Some(folder)) => {
@@ -203,7 +335,7 @@ pub async fn rename_folder(
         Err(e) => {
             tracing::error!("Failed to rename folder: {:?}", e);
             HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to rename folder"))
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to rename folder"))
         }
     }
 }
@@ -255,7 +387,476 @@ pub async fn delete_folder(
         Err(e) => {
             tracing::error!("Failed to delete folder: {:?}", e);
             HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to delete folder"))
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to delete folder"))
+        }
+    }
+}
+
+// ============================================================================
+// Restore Folder
+// ============================================================================
+
+/// Restore a soft-deleted folder and its images
+///
+/// Trashed images don't count towards the storage quota, so a restore that
+/// would take the user past it is refused with 413 `QUOTA_EXCEEDED`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/folders/{folder_id}/restore",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID")
+    ),
+    responses(
+        (status = 200, description = "Folder restored", body = ApiResponse<FolderResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Deleted folder not found"),
+        (status = 413, description = "Storage quota exceeded (QUOTA_EXCEEDED)")
+    )
+)]
+pub async fn restore_folder(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let folder_id = path.into_inner();
+
+    if config.upload.max_storage_bytes > 0 {
+        let restored_bytes =
+            match ImageRepository::deleted_bytes_in_folder(pool.get_ref(), folder_id, user.user_id).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::error!("Failed to sum trashed images: {:?}", e);
+                    return HttpResponse::InternalServerError()
+                        .json(ApiResponse::<()>::internal_error(&req, "Failed to restore folder"));
+                }
+            };
+        if let Err(response) = check_storage_quota(
+            &req,
+            pool.get_ref(),
+            config.upload.max_storage_bytes,
+            user.user_id,
+            restored_bytes,
+        )
+        .await
+        {
+            return response;
+        }
+    }
+
+    match FolderRepository::restore(pool.get_ref(), folder_id, user.user_id).await {
+        Ok(Some(folder)) => {
+            let image_count = FolderRepository::get_image_count(pool.get_ref(), folder_id)
+                .await
+                .unwrap_or(0);
+
+            HttpResponse::Ok().json(ApiResponse::success(FolderResponse {
+                folder_id: folder.folder_id,
+                folder_name: folder.folder_name,
+                image_count,
+                created_at: folder
+                    .created_at
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                deleted_at: None,
+            }))
+        }
+        Ok(None) => HttpResponse::NotFound()
+            .json(ApiResponse::<()>::error("NOT_FOUND", "Deleted folder not found")),
+        Err(e) => {
+            tracing::error!("Failed to restore folder: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to restore folder"))
+        }
+    }
+}
+
+// ============================================================================
+// Purge Folder
+// ============================================================================
+
+/// Permanently delete a trashed folder, its images and their files
+///
+/// Only folders already in the trash can be purged; a live folder gets 400
+/// `NOT_DELETED`. The folder must also have been deleted at least
+/// `trash.min_retention_hours` ago; otherwise 409 `RETENTION_PERIOD` is
+/// returned. Also served at `DELETE /api/v1/folders/{folder_id}/purge`.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/folders/{folder_id}/permanent",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID")
+    ),
+    responses(
+        (status = 200, description = "Folder permanently deleted", body = ApiResponse<DeleteFolderResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 400, description = "Folder is not in the trash"),
+        (status = 404, description = "Folder not found"),
+        (status = 409, description = "Folder deleted too recently")
+    )
+)]
+pub async fn purge_folder(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    storage: web::Data<dyn StorageBackend>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let folder_id = path.into_inner();
+    let min_retention = config.trash.min_retention();
+
+    let purged =
+        match FolderRepository::hard_delete(pool.get_ref(), folder_id, user.user_id, min_retention)
+            .await
+        {
+            Ok(PurgeFolderOutcome::Purged(purged)) => purged,
+            Ok(PurgeFolderOutcome::NotFound) => {
+                return HttpResponse::NotFound()
+                    .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+            }
+            Ok(PurgeFolderOutcome::NotDeleted) => {
+                return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+                    "NOT_DELETED",
+                    "Folder must be deleted before it can be purged",
+                ));
+            }
+            Ok(PurgeFolderOutcome::RetentionPeriod(purgeable_at)) => {
+                return HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                    "RETENTION_PERIOD",
+                    format!(
+                        "Folder can be permanently deleted after {}",
+                        purgeable_at.to_rfc3339()
+                    ),
+                ));
+            }
+            Err(e) => {
+                tracing::error!("Failed to purge folder: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to purge folder"));
+            }
+        };
+
+    // The rows are gone, so file cleanup is best-effort
+    let overlays = purged.job_ids.iter().map(|job_id| ImageService::overlay_key(*job_id));
+    let keys = purged
+        .file_paths
+        .iter()
+        .flat_map(|file_path| {
+            std::iter::once(file_path.clone()).chain(ImageService::derived_keys(file_path))
+        })
+        .chain(overlays);
+    for key in keys {
+        match storage.delete(&key).await {
+            Ok(()) | Err(StorageError::NotFound(_)) => {}
+            Err(e) => tracing::warn!("Failed to delete purged file {}: {:?}", key, e),
+        }
+    }
+
+    HttpResponse::Ok().json(ApiResponse::success(DeleteFolderResponse {
+        message: "Folder permanently deleted".to_string(),
+        deleted_images_count: purged.file_paths.len() as i64,
+    }))
+}
+
+// ============================================================================
+// Copy Folder
+// ============================================================================
+
+/// Copy a folder and its images into a new folder
+///
+/// Each image's file is copied to a new storage key. Analysis history is not
+/// copied. A copy that would take the user past the storage quota is refused
+/// with 413 `QUOTA_EXCEEDED`, and one of more than `limits.max_copy_images`
+/// images with 413 `FOLDER_TOO_LARGE`. Files already copied are deleted again
+/// if the copy fails or the request is cut short.
+#[utoipa::path(
+    post,
+    path = "/api/v1/folders/{folder_id}/copy",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID to copy")
+    ),
+    request_body = CopyFolderRequest,
+    responses(
+        (status = 201, description = "Folder copied", body = ApiResponse<FolderResponse>),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Folder not found"),
+        (status = 413, description = "Storage quota exceeded (QUOTA_EXCEEDED), or too many images to copy (FOLDER_TOO_LARGE)")
+    )
+)]
+pub async fn copy_folder(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    storage: web::Data<dyn StorageBackend>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    body: web::Json<CopyFolderRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let folder_id = path.into_inner();
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let folder_name = normalize_folder_name(&request.new_name);
+
+    match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify folder: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to copy folder"));
+        }
+    }
+
+    let images = match ImageRepository::find_all_by_folder_id(pool.get_ref(), folder_id).await {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to list images to copy: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to copy folder"));
+        }
+    };
+
+    let max_images = config.limits.max_copy_images;
+    if max_images > 0 && images.len() > max_images {
+        return HttpResponse::PayloadTooLarge().json(ApiResponse::<()>::error(
+            "FOLDER_TOO_LARGE",
+            format!(
+                "Folder has {} images; at most {} can be copied at once",
+                images.len(),
+                max_images
+            ),
+        ));
+    }
+
+    let copied_bytes = images.iter().map(|image| image.file_size as i64).sum();
+    if let Err(response) = check_storage_quota(
+        &req,
+        pool.get_ref(),
+        config.upload.max_storage_bytes,
+        user.user_id,
+        copied_bytes,
+    )
+    .await
+    {
+        return response;
+    }
+
+    // Copied files are deleted again unless the new folder is committed,
+    // even if the request is cut short by its deadline
+    let mut copied = CopiedFiles::new(storage.into_inner());
+    let mut copies = Vec::with_capacity(images.len());
+    let mut copy_error = None;
+    for image in images {
+        let (new_key, _) =
+            S3StorageService::generate_object_key(&image.original_filename, &image.mime_type);
+        // Tracked before copying, so a partially written object goes too
+        copied.keys.push(new_key.clone());
+        if let Err(e) = copied.storage.copy(&image.file_path, &new_key).await {
+            copy_error = Some(format!("image {}: {:?}", image.image_id, e));
+            break;
+        }
+        copies.push((image, new_key));
+    }
+
+    let created = match copy_error {
+        Some(reason) => Err(reason),
+        None => FolderRepository::create_copy(pool.get_ref(), user.user_id, &folder_name, &copies)
+            .await
+            .map_err(|e| format!("{:?}", e)),
+    };
+
+    match created {
+        Ok(folder) => {
+            copied.keys.clear();
+            HttpResponse::Created().json(ApiResponse::success(FolderResponse {
+                folder_id: folder.folder_id,
+                folder_name: folder.folder_name,
+                image_count: copies.len() as i64,
+                created_at: folder
+                    .created_at
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                deleted_at: None,
+            }))
+        }
+        Err(reason) => {
+            tracing::error!("Failed to copy folder {}: {}", folder_id, reason);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to copy folder"))
+        }
+    }
+}
+
+/// Storage keys written by a folder copy that isn't committed yet
+///
+/// Any keys still listed when dropped are deleted in the background, so a
+/// failed or abandoned copy leaves no orphaned files.
+struct CopiedFiles {
+    storage: Arc<dyn StorageBackend>,
+    keys: Vec<String>,
+}
+
+impl CopiedFiles {
+    fn new(storage: Arc<dyn StorageBackend>) -> Self {
+        Self {
+            storage,
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl Drop for CopiedFiles {
+    fn drop(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let (storage, keys) = (self.storage.clone(), std::mem::take(&mut self.keys));
+        tokio::spawn(async move {
+            for key in keys {
+                match storage.delete(&key).await {
+                    Ok(()) | Err(StorageError::NotFound(_)) => {}
+                    Err(e) => tracing::warn!("Failed to clean up copied file {}: {:?}", key, e),
+                }
+            }
+        });
+    }
+}
+
+// ============================================================================
+// Merge Folders
+// ============================================================================
+
+/// Move every image from a source folder into this one and delete the source
+///
+/// Filename collisions follow the upload duplicate-filename policy; under
+/// `reject` any collision aborts the merge and nothing moves. Nothing moves
+/// either if the target would end up over `upload.max_images_per_folder`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/folders/{folder_id}/merge",
+    tag = "Folder Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Target folder ID")
+    ),
+    request_body = MergeFolderRequest,
+    responses(
+        (status = 200, description = "Folders merged", body = ApiResponse<FolderResponse>),
+        (status = 400, description = "Source and target are the same folder"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Merged folder would exceed the image limit (FOLDER_IMAGE_LIMIT)"),
+        (status = 404, description = "Folder not found"),
+        (status = 409, description = "Duplicate filename rejected by upload policy")
+    )
+)]
+pub async fn merge_folder(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    body: web::Json<MergeFolderRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let target_id = path.into_inner();
+    let source_id = body.source_folder_id;
+
+    if source_id == target_id {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            "Cannot merge a folder into itself",
+        ));
+    }
+
+    let policy = config.upload.duplicate_filenames;
+    let outcome = FolderRepository::merge(
+        pool.get_ref(),
+        target_id,
+        source_id,
+        user.user_id,
+        config.upload.max_images_per_folder,
+        |filename, taken| ImageService::resolve_filename(policy, filename, taken),
+    )
+    .await;
+
+    match outcome {
+        Ok(MergeFolderOutcome::Merged(folder, image_count)) => {
+            HttpResponse::Ok().json(ApiResponse::success(FolderResponse {
+                folder_id: folder.folder_id,
+                folder_name: folder.folder_name,
+                image_count,
+                created_at: folder
+                    .created_at
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                deleted_at: None,
+            }))
+        }
+        Ok(MergeFolderOutcome::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"))
+        }
+        Ok(MergeFolderOutcome::DuplicateFilename(filename)) => {
+            HttpResponse::Conflict().json(ApiResponse::<()>::error(
+                "DUPLICATE_FILENAME",
+                format!("An image named '{}' already exists in the target folder", filename),
+            ))
+        }
+        Ok(MergeFolderOutcome::TooManyImages(max_images)) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error(
+                "FOLDER_IMAGE_LIMIT",
+                format!("Merged folder would exceed the maximum of {} images", max_images),
+            ))
+        }
+        Err(e) => {
+            tracing::error!("Failed to merge folder {} into {}: {:?}", source_id, target_id, e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to merge folders"))
         }
     }
 }
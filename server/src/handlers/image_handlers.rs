@@ -3,26 +3,243 @@
 //! CRUD operations for images with file upload support and ownership verification.
 
 use actix_multipart::Multipart;
+use actix_web::http::header::{HttpDate, IfModifiedSince, LastModified};
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
-use futures::StreamExt;
-use sqlx::PgPool;
+use chrono::SubsecRound;
+use futures::{Stream, StreamExt};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
-use crate::domain::ApiResponse;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::config::settings::{AppConfig, DuplicateFilenamePolicy, ThumbnailFormat, UploadConfig};
+use crate::db::ReadPool;
+use crate::handlers::{check_batch_size, payload_too_large, validation_error, ValidationKind};
+use crate::domain::{ApiError, ApiResponse};
+use crate::metrics;
 use crate::dto::{
-    AnalysisHistoryItem, ConfirmUploadRequest, CursorPaginationInfo, CursorPaginationQuery,
-    DeleteImageResponse, ImageDetailResponse, ImageListResponse, ImageListResponseV2,
-    ImageMetadataResponse, ImageResponse, PaginationInfo, PaginationQuery, PresignedDownloadResponse,
-    RenameImageRequest, RequestUploadRequest, RequestUploadResponse,
+    validate_tag, AllImagesQuery, AnalysisHistoryItem, BatchDownloadUrlRequest, BatchDownloadUrlResponse, BulkMetadataUpdateRequest,
+    BulkMetadataUpdateResponse, ConfirmUploadRequest, CursorPaginationInfo, CursorPaginationQuery,
+    DeleteImageResponse, DownloadUrlQuery, ImageDetailResponse, ImageListResponse, ImageListResponseV2,
+    ImageMetadataResponse, ImageMetadataUpdateOutcome, ImageResponse, ImageSearchQuery, ImageTagsResponse, ListImagesRequest, MoveImageRequest,
+    PaginationInfo, PaginationQuery, PresignedDownloadResponse, RenameImageRequest, RequestUploadRequest, RequestUploadResponse,
+    ShareLinkResponse, UploadConstraintsResponse,
 };
 use crate::middleware::AuthenticatedUser;
+use crate::models::Image;
 use crate::repositories::{FolderRepository, ImageRepository};
-use crate::services::ImageService;
+use crate::services::image_service::{
+    ALLOWED_MIME_TYPES, MAX_FILENAME_LENGTH, MAX_FILENAME_SUFFIX, MAX_FILE_SIZE,
+};
+use crate::services::multipart_guard::{MultipartGuard, MAX_MULTIPART_PARTS, MAX_PART_HEADER_BYTES};
+use crate::services::{
+    ImageService, ResponseOverrides, ShareLinkError, ShareLinkSigner, StorageBackend, StorageError,
+    UploadLimiter,
+};
+
+/// Apply the duplicate-filename policy to an upload's filename
+///
+/// Returns the name to store, or the error response to send.
+async fn resolve_upload_filename(
+    req: &HttpRequest,
+    pool: &PgPool,
+    policy: DuplicateFilenamePolicy,
+    folder_id: i32,
+    filename: &str,
+) -> Result<String, HttpResponse> {
+    let exists = |name: String| async move {
+        ImageRepository::filename_exists_in_folder(pool, folder_id, &name)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to check filename: {:?}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(req, "Failed to check filename"))
+            })
+    };
+
+    let duplicate = || {
+        HttpResponse::Conflict().json(ApiResponse::<()>::error(
+            "DUPLICATE_FILENAME",
+            "An image with this filename already exists in the folder",
+        ))
+    };
+
+    match policy {
+        DuplicateFilenamePolicy::Allow => Ok(filename.to_string()),
+        DuplicateFilenamePolicy::Reject => {
+            if exists(filename.to_string()).await? {
+                return Err(duplicate());
+            }
+            Ok(filename.to_string())
+        }
+        DuplicateFilenamePolicy::Suffix => {
+            if !exists(filename.to_string()).await? {
+                return Ok(filename.to_string());
+            }
+            for n in 1..=MAX_FILENAME_SUFFIX {
+                let candidate = ImageService::suffixed_filename(filename, n);
+                if !exists(candidate.clone()).await? {
+                    return Ok(candidate);
+                }
+            }
+            Err(duplicate())
+        }
+    }
+}
+
+/// Check an upload's target folder is owned by the user and not deleted
+///
+/// A folder in the trash gets 409 `FOLDER_DELETED` rather than 404, so the
+/// client can offer to restore it.
+async fn verify_upload_folder(
+    req: &HttpRequest,
+    pool: &PgPool,
+    folder_id: i32,
+    user_id: Uuid,
+) -> Result<(), HttpResponse> {
+    let lookup = match FolderRepository::find_by_id(pool, folder_id, user_id).await {
+        Ok(Some(_)) => return Ok(()),
+        Ok(None) => FolderRepository::find_deleted_by_id(pool, folder_id, user_id).await,
+        Err(e) => Err(e),
+    };
+
+    match lookup {
+        Ok(Some(_)) => Err(HttpResponse::Conflict().json(ApiResponse::<()>::error(
+            "FOLDER_DELETED",
+            "Folder is in the trash; restore it before uploading",
+        ))),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"))),
+        Err(e) => {
+            tracing::error!("Failed to verify folder: {:?}", e);
+            Err(HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to verify folder")))
+        }
+    }
+}
+
+/// Refuse an upload into a folder that already holds `max_images` live images
+///
+/// A limit of zero disables the check.
+async fn check_folder_capacity(
+    req: &HttpRequest,
+    pool: &PgPool,
+    max_images: i64,
+    folder_id: i32,
+) -> Result<(), HttpResponse> {
+    if max_images <= 0 {
+        return Ok(());
+    }
+
+    match ImageRepository::count_by_folder_id(pool, folder_id, None, None).await {
+        Ok(count) if count >= max_images => Err(HttpResponse::Forbidden().json(
+            ApiResponse::<()>::error(
+                "FOLDER_IMAGE_LIMIT",
+                format!("Folder already holds the maximum of {} images", max_images),
+            ),
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to count images: {:?}", e);
+            Err(HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to count images")))
+        }
+    }
+}
+
+/// Refuse adding `incoming_bytes` (an upload, copy or restore) that would
+/// take the user's stored images past `max_bytes`
+///
+/// A limit of zero disables the check. The check is not locked against the
+/// insert that follows it, so concurrent requests from one user can each
+/// pass and together overshoot the quota; the per-user upload limiter keeps
+/// that to a few requests' worth.
+pub(crate) async fn check_storage_quota(
+    req: &HttpRequest,
+    pool: &PgPool,
+    max_bytes: i64,
+    user_id: Uuid,
+    incoming_bytes: i64,
+) -> Result<(), HttpResponse> {
+    if max_bytes <= 0 {
+        return Ok(());
+    }
+
+    match ImageRepository::total_bytes_for_user(pool, user_id).await {
+        Ok(used) if used.saturating_add(incoming_bytes) > max_bytes => Err(HttpResponse::PayloadTooLarge().json(
+            ApiResponse::<()>::error(
+                "QUOTA_EXCEEDED",
+                format!(
+                    "Adding {} bytes would exceed the storage quota: {} of {} bytes used",
+                    incoming_bytes, used, max_bytes
+                ),
+            ),
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to sum stored bytes: {:?}", e);
+            Err(HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to check storage quota")))
+        }
+    }
+}
+
+/// Await the next item of an upload stream, or `None` if the client sent
+/// nothing for `timeout` (zero disables the limit)
+async fn next_before_timeout<S: Stream + Unpin>(
+    stream: &mut S,
+    timeout: Duration,
+) -> Result<Option<S::Item>, tokio::time::error::Elapsed> {
+    if timeout.is_zero() {
+        return Ok(stream.next().await);
+    }
+    tokio::time::timeout(timeout, stream.next()).await
+}
+
+/// Reject a multipart body that is malformed or breaks the structural limits
+fn malformed_multipart(reason: impl std::fmt::Display) -> HttpResponse {
+    validation_error(
+        ValidationKind::Malformed,
+        "MALFORMED_MULTIPART",
+        format!("Malformed multipart body: {}", reason),
+    )
+}
+
+fn too_many_uploads() -> HttpResponse {
+    HttpResponse::TooManyRequests().json(ApiResponse::<()>::error(
+        "TOO_MANY_UPLOADS",
+        "Too many uploads in progress; wait for one to finish",
+    ))
+}
+
+/// Whether the request declares a `Content-Length` above `limit`
+fn declared_length_exceeds(req: &HttpRequest, limit: usize) -> bool {
+    req.headers()
+        .get(actix_web::http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .is_some_and(|length| length > limit)
+}
+
+fn upload_timed_out() -> HttpResponse {
+    HttpResponse::RequestTimeout().json(ApiResponse::<()>::error(
+        "REQUEST_TIMEOUT",
+        "Upload stalled; no data received within the read timeout",
+    ))
+}
 
 // ============================================================================
 // List Images (Paginated)
 // ============================================================================
 
 /// List images in a folder with pagination
+///
+/// Admins may pass `include_deleted=true` to also see soft-deleted images,
+/// each with its `deleted_at`.
 #[utoipa::path(
     get,
     path = "/api/v1/folders/{folder_id}/images",
@@ -30,16 +247,21 @@ use crate::services::ImageService;
     security(("bearer_auth" = [])),
     params(
         ("folder_id" = i32, Path, description = "Folder ID"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified value from a previous listing"),
         PaginationQuery
     ),
     responses(
         (status = 200, description = "List of images", body = ApiResponse<ImageListResponse>),
+        (status = 304, description = "Folder unchanged since If-Modified-Since"),
+        (status = 400, description = "Unsupported MIME type filter"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "include_deleted requested by a non-admin"),
         (status = 404, description = "Folder not found")
     )
 )]
 pub async fn list_images(
-    pool: web::Data<PgPool>,
+    pool: web::Data<ReadPool>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i32>,
     query: web::Query<PaginationQuery>,
@@ -52,7 +274,22 @@ pub async fn list_images(
         }
     };
 
+    if let Err(errors) = query.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if query.include_deleted && !config.admin.is_admin(&user.username) {
+        tracing::warn!("Non-admin user {} requested deleted images", user.username);
+        return HttpResponse::Forbidden()
+            .json(ApiResponse::<()>::error("FORBIDDEN", "Admin access required"));
+    }
+
     let folder_id = path.into_inner();
+    let mime_type = query.mime_type.as_deref();
+    let tag = query.tag.as_deref();
 
     // Verify folder ownership
     match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
@@ -63,96 +300,121 @@ pub async fn list_images(
         Err(e) => {
             tracing::error!("Failed to verify folder: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify folder"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folder"));
         }
         Ok(Some(_)) => {}
     }
 
+    // Conditional request: skip the listing if nothing changed since the client's copy
+    let last_modified = match ImageRepository::folder_last_modified(pool.get_ref(), folder_id).await {
+        Ok(last_modified) => last_modified,
+        Err(e) => {
+            tracing::error!("Failed to get folder last modified time: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list images"));
+        }
+    };
+    // HTTP dates have whole-second precision; truncate so a round-tripped value compares equal
+    let last_modified = last_modified
+        .map(|dt| HttpDate::from(std::time::SystemTime::from(dt.trunc_subsecs(0))));
+
+    if let (Some(last_modified), Some(IfModifiedSince(since))) =
+        (last_modified, req.get_header::<IfModifiedSince>())
+    {
+        if last_modified <= since {
+            return HttpResponse::NotModified()
+                .insert_header(LastModified(last_modified))
+                .finish();
+        }
+    }
+
     // Get total count for pagination
-    let total = match ImageRepository::count_by_folder_id(pool.get_ref(), folder_id).await {
+    let total = if query.include_deleted {
+        ImageRepository::count_by_folder_id_with_deleted(pool.get_ref(), folder_id, mime_type, tag)
+            .await
+    } else {
+        ImageRepository::count_by_folder_id(pool.get_ref(), folder_id, mime_type, tag).await
+    };
+    let total = match total {
         Ok(count) => count,
         Err(e) => {
             tracing::error!("Failed to count images: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to count images"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to count images"));
         }
     };
 
     // Fetch paginated images
+    let (limit, offset) = (query.limit(), query.offset());
+    let images = if query.include_deleted {
+        ImageRepository::find_by_folder_id_with_deleted(
+            pool.get_ref(),
+            folder_id,
+            mime_type,
+            tag,
+            limit,
+            offset,
+        )
+        .await
+    } else {
+        ImageRepository::find_by_folder_id(pool.get_ref(), folder_id, mime_type, tag, limit, offset)
+            .await
+    };
+    let images = match images {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to list images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list images"));
+        }
+    };
+
     let images =
-        match ImageRepository::find_by_folder_id(pool.get_ref(), folder_id, query.limit(), query.offset()).await {
-            Ok(images) => images,
-            Err(e) => {
-                tracing::error!("Failed to list images: {:?}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to list images"));
-            }
-        };
+        image_responses(pool.get_ref(), images.into_iter().map(|image| (image, None)).collect()).await;
 
-    // Build response
-    let mut image_responses = Vec::with_capacity(images.len());
-    for image in images {
-        let has_analysis = ImageRepository::has_analysis(pool.get_ref(), image.image_id)
-            .await
-            .unwrap_or(false);
-
-        let metadata = image.metadata.as_ref().and_then(|m| {
-            serde_json::from_value::<crate::models::ImageMetadata>(m.clone())
-                .ok()
-                .map(|meta| ImageMetadataResponse {
-                    width: meta.width,
-                    height: meta.height,
-                })
-        });
-
-        image_responses.push(ImageResponse {
-            image_id: image.image_id,
-            folder_id: image.folder_id,
-            original_filename: image.original_filename,
-            file_size: image.file_size,
-            mime_type: image.mime_type,
-            metadata,
-            has_analysis,
-            uploaded_at: image
-                .uploaded_at
-                .map(|dt| dt.to_rfc3339())
-                .unwrap_or_default(),
-        });
+    let mut response = HttpResponse::Ok();
+    if let Some(last_modified) = last_modified {
+        response.insert_header(LastModified(last_modified));
     }
 
-    HttpResponse::Ok().json(ApiResponse::success(ImageListResponse {
-        images: image_responses,
+    response.json(ApiResponse::success(ImageListResponse {
+        images,
         pagination: PaginationInfo::new(query.page(), query.limit(), total),
     }))
 }
 
 // ============================================================================
-// Upload Image
+// Search Images
 // ============================================================================
 
-/// Upload a new image to a folder
+/// Search a folder's images by filename with pagination
+///
+/// `q` matches a case-insensitive substring of the original filename. The
+/// folder listing's filters apply too, `include_deleted` again being admin only.
 #[utoipa::path(
-    post,
-    path = "/api/v1/folders/{folder_id}/images",
+    get,
+    path = "/api/v1/folders/{folder_id}/images/search",
     tag = "Image Management",
     security(("bearer_auth" = [])),
     params(
-        ("folder_id" = i32, Path, description = "Folder ID")
+        ("folder_id" = i32, Path, description = "Folder ID"),
+        ImageSearchQuery,
+        PaginationQuery
     ),
-    request_body(content = Vec<u8>, content_type = "multipart/form-data"),
     responses(
-        (status = 201, description = "Image uploaded", body = ApiResponse<ImageResponse>),
-        (status = 400, description = "Invalid file"),
+        (status = 200, description = "Matching images", body = ApiResponse<ImageListResponse>),
+        (status = 400, description = "Missing or overlong search term, or unsupported filter"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "include_deleted requested by a non-admin"),
         (status = 404, description = "Folder not found")
     )
 )]
-pub async fn upload_image(
-    pool: web::Data<PgPool>,
-    s3_storage: web::Data<crate::services::S3StorageService>,
+pub async fn search_images(
+    pool: web::Data<ReadPool>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i32>,
-    mut payload: Multipart,
+    query: web::Query<ImageSearchQuery>,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
         Some(u) => u.clone(),
@@ -162,7 +424,23 @@ pub async fn upload_image(
         }
     };
 
+    if let Err(errors) = query.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    let filters = &query.pagination;
+    if filters.include_deleted && !config.admin.is_admin(&user.username) {
+        tracing::warn!("Non-admin user {} searched deleted images", user.username);
+        return HttpResponse::Forbidden()
+            .json(ApiResponse::<()>::error("FORBIDDEN", "Admin access required"));
+    }
+
     let folder_id = path.into_inner();
+    let mime_type = filters.mime_type.as_deref();
+    let tag = filters.tag.as_deref();
 
     // Verify folder ownership
     match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
@@ -173,15 +451,225 @@ pub async fn upload_image(
         Err(e) => {
             tracing::error!("Failed to verify folder: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify folder"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folder"));
         }
         Ok(Some(_)) => {}
     }
 
-    // Process multipart form data
+    let total = match ImageRepository::count_search_by_folder(
+        pool.get_ref(),
+        folder_id,
+        &query.q,
+        mime_type,
+        tag,
+        filters.include_deleted,
+    )
+    .await
+    {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::error!("Failed to count images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to count images"));
+        }
+    };
+
+    let images = match ImageRepository::search_by_folder(
+        pool.get_ref(),
+        folder_id,
+        &query.q,
+        mime_type,
+        tag,
+        filters.include_deleted,
+        filters.limit(),
+        filters.offset(),
+    )
+    .await
+    {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to search images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to search images"));
+        }
+    };
+
+    let images =
+        image_responses(pool.get_ref(), images.into_iter().map(|image| (image, None)).collect()).await;
+
+    HttpResponse::Ok().json(ApiResponse::success(ImageListResponse {
+        images,
+        pagination: PaginationInfo::new(filters.page(), filters.limit(), total),
+    }))
+}
+
+// ============================================================================
+// List All Images
+// ============================================================================
+
+/// List the authenticated user's images across all folders, newest first
+///
+/// Images in folders that are in the trash are left out. Each image carries
+/// its `folder_name`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/images",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(AllImagesQuery),
+    responses(
+        (status = 200, description = "List of images", body = ApiResponse<ImageListResponse>),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn list_all_images(
+    pool: web::Data<ReadPool>,
+    req: HttpRequest,
+    query: web::Query<AllImagesQuery>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let total = match ImageRepository::count_by_user_id(pool.get_ref(), user.user_id).await {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::error!("Failed to count images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to count images"));
+        }
+    };
+
+    let images = match ImageRepository::find_by_user_id(
+        pool.get_ref(),
+        user.user_id,
+        query.limit(),
+        query.offset(),
+    )
+    .await
+    {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to list images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list images"));
+        }
+    };
+
+    let images = image_responses(
+        pool.get_ref(),
+        images.into_iter().map(|(image, folder_name)| (image, Some(folder_name))).collect(),
+    )
+    .await;
+
+    HttpResponse::Ok().json(ApiResponse::success(ImageListResponse {
+        images,
+        pagination: PaginationInfo::new(query.page(), query.limit(), total),
+    }))
+}
+
+// ============================================================================
+// Upload Image
+// ============================================================================
+
+/// Upload a new image to a folder
+#[utoipa::path(
+    post,
+    path = "/api/v1/folders/{folder_id}/images",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID")
+    ),
+    request_body(content = Vec<u8>, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Image uploaded", body = ApiResponse<ImageResponse>),
+        (status = 400, description = "Invalid file, or malformed or oversized multipart structure (MALFORMED_MULTIPART)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Folder image limit reached (FOLDER_IMAGE_LIMIT)"),
+        (status = 404, description = "Folder not found"),
+        (status = 408, description = "Upload stream stalled past the read timeout"),
+        (status = 409, description = "Duplicate filename rejected by upload policy, or folder deleted (FOLDER_DELETED)"),
+        (status = 413, description = "File above `limits.max_upload_bytes` (PAYLOAD_TOO_LARGE), or storage quota exceeded (QUOTA_EXCEEDED)"),
+        (status = 429, description = "Too many uploads in progress for this user (TOO_MANY_UPLOADS)")
+    )
+)]
+pub async fn upload_image(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    limiter: web::Data<UploadLimiter>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    payload: web::Payload,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    // Allow for the most multipart framing the guard lets through
+    let upload_limit = config.limits.upload_limit();
+    if declared_length_exceeds(&req, upload_limit + MAX_PART_HEADER_BYTES * MAX_MULTIPART_PARTS) {
+        return payload_too_large(upload_limit);
+    }
+
+    // Held until the handler returns, whether the upload succeeds or not
+    let Some(_permit) = limiter.try_acquire(user.user_id, config.upload.max_concurrent_uploads)
+    else {
+        return too_many_uploads();
+    };
+
+    let boundary = match req.mime_type() {
+        Ok(Some(mime)) if mime.type_() == "multipart" => {
+            mime.get_param("boundary").map(|b| b.as_str().to_string())
+        }
+        _ => None,
+    };
+    let Some(boundary) = boundary else {
+        return malformed_multipart("expected multipart/form-data with a boundary");
+    };
+
+    // Bound part headers and part count before the parser buffers anything
+    let (guarded, violation) = MultipartGuard::new(payload, &boundary);
+    let mut payload = Multipart::new(req.headers(), guarded);
+
+    let folder_id = path.into_inner();
+
+    // Verify folder ownership
+    if let Err(response) = verify_upload_folder(&req, pool.get_ref(), folder_id, user.user_id).await {
+        return response;
+    }
+
+    if let Err(response) =
+        check_folder_capacity(&req, pool.get_ref(), config.upload.max_images_per_folder, folder_id).await
+    {
+        return response;
+    }
+
+    // Process multipart form data; each read is bounded so a client
+    // trickling bytes cannot hold the worker indefinitely
+    let read_timeout = Duration::from_millis(config.server.upload_read_timeout_ms);
     let mut file_data: Option<(String, String, Vec<u8>)> = None; // (filename, content_type, bytes)
 
-    while let Some(Ok(mut field)) = payload.next().await {
+    loop {
+        let mut field = match next_before_timeout(&mut payload, read_timeout).await {
+            Ok(Some(Ok(field))) => field,
+            Ok(Some(Err(e))) => match violation.get() {
+                Some(violation) => return malformed_multipart(violation),
+                None => return malformed_multipart(e),
+            },
+            Ok(None) => break,
+            Err(_) => return upload_timed_out(),
+        };
+
         // content_disposition() returns Option in newer versions
         let content_disposition = match field.content_disposition() {
             Some(cd) => cd,
@@ -200,8 +688,21 @@ pub async fn upload_image(
                 .unwrap_or_else(|| "application/octet-stream".to_string());
 
             let mut bytes = Vec::new();
-            while let Some(Ok(chunk)) = field.next().await {
-                bytes.extend_from_slice(&chunk);
+            loop {
+                match next_before_timeout(&mut field, read_timeout).await {
+                    Ok(Some(Ok(chunk))) => {
+                        if bytes.len() + chunk.len() > upload_limit {
+                            return payload_too_large(upload_limit);
+                        }
+                        bytes.extend_from_slice(&chunk);
+                    }
+                    Ok(Some(Err(e))) => match violation.get() {
+                        Some(violation) => return malformed_multipart(violation),
+                        None => return malformed_multipart(e),
+                    },
+                    Ok(None) => break,
+                    Err(_) => return upload_timed_out(),
+                }
             }
 
             file_data = Some((filename, content_type, bytes));
@@ -217,37 +718,112 @@ pub async fn upload_image(
         }
     };
 
-    // Validate file
-    if let Err(e) = ImageService::validate_file(&content_type, &bytes) {
-        return HttpResponse::BadRequest()
-            .json(ApiResponse::<()>::error("VALIDATION_ERROR", e.to_string()));
-    }
-
-    // Generate S3 object key
-    let (s3_key, _filename) = crate::services::S3StorageService::generate_object_key(&original_filename);
-
-    // Upload file to S3
-    if let Err(e) = s3_storage.upload_file(&s3_key, &bytes, &content_type).await {
-        tracing::error!("Failed to upload file to S3: {:?}", e);
-        return HttpResponse::InternalServerError()
-            .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to upload file to storage"));
+    store_upload(
+        &req,
+        pool.get_ref(),
+        storage.into_inner(),
+        &config,
+        user.user_id,
+        folder_id,
+        &original_filename,
+        &content_type,
+        &bytes,
+    )
+    .await
+}
+
+/// Display filename to store for an upload named `filename`
+///
+/// Kept as uploaded unless `upload.lowercase_extensions` is set.
+fn stored_filename(upload: &UploadConfig, filename: &str) -> String {
+    if upload.lowercase_extensions {
+        ImageService::lowercase_extension(filename)
+    } else {
+        filename.to_string()
     }
+}
 
-    // Extract metadata
-    let metadata = ImageService::extract_metadata(&bytes).map(|(width, height)| {
-        serde_json::json!({
-            "width": width,
-            "height": height
-        })
-    });
+/// Validate an uploaded file, store it, and register it in the folder
+///
+/// Shared by the multipart and raw-body upload endpoints once the bytes have
+/// been read.
+#[allow(clippy::too_many_arguments)]
+async fn store_upload(
+    req: &HttpRequest,
+    pool: &PgPool,
+    storage: Arc<dyn StorageBackend>,
+    config: &AppConfig,
+    user_id: Uuid,
+    folder_id: i32,
+    original_filename: &str,
+    content_type: &str,
+    bytes: &[u8],
+) -> HttpResponse {
+    let content_type = ImageService::canonical_mime_type(content_type);
+    let content_type = content_type.as_str();
+
+    // Validate file
+    if let Err(e) = ImageService::validate_file(content_type, bytes) {
+        return HttpResponse::BadRequest()
+            .json(ApiResponse::<()>::error("VALIDATION_ERROR", e.to_string()));
+    }
+
+    let original_filename = stored_filename(&config.upload, original_filename);
+    let original_filename = match resolve_upload_filename(
+        req,
+        pool,
+        config.upload.duplicate_filenames,
+        folder_id,
+        &original_filename,
+    )
+    .await
+    {
+        Ok(name) => name,
+        Err(response) => return response,
+    };
+
+    // Read metadata from the upload as sent, before any re-encode
+    let metadata = ImageService::image_metadata(bytes)
+        .and_then(|metadata| serde_json::to_value(metadata).ok());
+
+    // Recompress JPEGs when configured; decoding is CPU-bound, so run it
+    // off the async workers
+    let quality = config.upload.jpeg_quality;
+    let compressed = if quality > 0 && content_type == "image/jpeg" {
+        let original = bytes.to_vec();
+        tokio::task::spawn_blocking(move || ImageService::compress_jpeg(&original, quality))
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let bytes = compressed.as_deref().unwrap_or(bytes);
+
+    if let Err(response) =
+        check_storage_quota(req, pool, config.upload.max_storage_bytes, user_id, bytes.len() as i64).await
+    {
+        return response;
+    }
+
+    // Generate S3 object key
+    let (s3_key, _filename) =
+        crate::services::S3StorageService::generate_object_key(&original_filename, content_type);
+
+    // Upload file to storage
+    if let Err(e) = storage.upload(&s3_key, bytes, content_type).await {
+        tracing::error!("Failed to upload file to storage: {:?}", e);
+        return HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::internal_error(req, "Failed to upload file to storage"));
+    }
 
     // Create database record (store S3 key as file_path)
     let image = match ImageRepository::create(
-        pool.get_ref(),
+        pool,
         folder_id,
         &s3_key,
         &original_filename,
-        &content_type,
+        content_type,
         bytes.len() as i32,
         metadata.clone(),
     )
@@ -256,59 +832,714 @@ pub async fn upload_image(
         Ok(image) => image,
         Err(e) => {
             tracing::error!("Failed to create image record: {:?}", e);
-            // Try to clean up uploaded file from S3
-            let _ = s3_storage.delete_file(&s3_key).await;
+            // Try to clean up uploaded file from storage
+            let _ = storage.delete(&s3_key).await;
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to create image record"));
+                .json(ApiResponse::<()>::internal_error(req, "Failed to create image record"));
+        }
+    };
+
+    // The upload is stored; don't make the client wait for its thumbnail too
+    let thumbnail_format = config.storage.thumbnail_format;
+    let (thumbnail_pool, thumbnail_image, original) = (pool.clone(), image.clone(), bytes.to_vec());
+    tokio::spawn(async move {
+        store_thumbnail(&thumbnail_pool, storage.as_ref(), &thumbnail_image, &original, thumbnail_format)
+            .await;
+    });
+
+    let metadata_response = metadata.as_ref().and_then(ImageMetadataResponse::from_json);
+
+    HttpResponse::Created().json(ApiResponse::success(ImageResponse {
+        image_id: image.image_id,
+        folder_id: image.folder_id,
+        folder_name: None,
+        original_filename: image.original_filename,
+        file_size: image.file_size,
+        mime_type: image.mime_type,
+        metadata: metadata_response,
+        has_analysis: false,
+        uploaded_at: image
+            .uploaded_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        deleted_at: None,
+        tags: Vec::new(),
+    }))
+}
+
+
+// ============================================================================
+// Upload Image (Raw Body)
+// ============================================================================
+
+/// Header carrying the original filename of a raw-body upload
+const FILENAME_HEADER: &str = "X-Filename";
+
+/// Upload a new image to a folder as the raw request body
+///
+/// The body is the file itself; its type comes from `Content-Type` and its
+/// name from the `X-Filename` header.
+#[utoipa::path(
+    put,
+    path = "/api/v1/folders/{folder_id}/images/raw",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("folder_id" = i32, Path, description = "Folder ID"),
+        ("X-Filename" = Option<String>, Header, description = "Original filename (directory components are stripped)")
+    ),
+    request_body(content = Vec<u8>, content_type = "image/jpeg"),
+    responses(
+        (status = 201, description = "Image uploaded", body = ApiResponse<ImageResponse>),
+        (status = 400, description = "Invalid file"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Folder image limit reached (FOLDER_IMAGE_LIMIT)"),
+        (status = 404, description = "Folder not found"),
+        (status = 408, description = "Upload stream stalled past the read timeout"),
+        (status = 409, description = "Duplicate filename rejected by upload policy, or folder deleted (FOLDER_DELETED)"),
+        (status = 413, description = "File above `limits.max_upload_bytes` (PAYLOAD_TOO_LARGE), or storage quota exceeded (QUOTA_EXCEEDED)"),
+        (status = 429, description = "Too many uploads in progress for this user (TOO_MANY_UPLOADS)")
+    )
+)]
+pub async fn upload_image_raw(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    limiter: web::Data<UploadLimiter>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    mut payload: web::Payload,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let upload_limit = config.limits.upload_limit();
+    if declared_length_exceeds(&req, upload_limit) {
+        return payload_too_large(upload_limit);
+    }
+
+    // Held until the handler returns, whether the upload succeeds or not
+    let Some(_permit) = limiter.try_acquire(user.user_id, config.upload.max_concurrent_uploads)
+    else {
+        return too_many_uploads();
+    };
+
+    let folder_id = path.into_inner();
+
+    // Verify folder ownership
+    if let Err(response) = verify_upload_folder(&req, pool.get_ref(), folder_id, user.user_id).await {
+        return response;
+    }
+
+    if let Err(response) =
+        check_folder_capacity(&req, pool.get_ref(), config.upload.max_images_per_folder, folder_id).await
+    {
+        return response;
+    }
+
+    // Ignore parameters such as `; charset=...`
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let original_filename = req
+        .headers()
+        .get(FILENAME_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ImageService::sanitize_filename)
+        .unwrap_or_else(|| format!("upload.{}", ImageService::get_extension_from_mime(&content_type)));
+
+    // Read the body, bounded by the size limit and the per-read timeout
+    let read_timeout = Duration::from_millis(config.server.upload_read_timeout_ms);
+    let mut bytes = Vec::new();
+    loop {
+        let chunk = match next_before_timeout(&mut payload, read_timeout).await {
+            Ok(Some(Ok(chunk))) => chunk,
+            Ok(Some(Err(e))) => {
+                tracing::warn!("Failed to read raw upload body: {:?}", e);
+                return HttpResponse::BadRequest()
+                    .json(ApiResponse::<()>::error("VALIDATION_ERROR", "Failed to read upload body"));
+            }
+            Ok(None) => break,
+            Err(_) => return upload_timed_out(),
+        };
+
+        if bytes.len() + chunk.len() > upload_limit {
+            return payload_too_large(upload_limit);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    if bytes.is_empty() {
+        return HttpResponse::BadRequest()
+            .json(ApiResponse::<()>::error("VALIDATION_ERROR", "No file provided"));
+    }
+
+    store_upload(
+        &req,
+        pool.get_ref(),
+        storage.into_inner(),
+        &config,
+        user.user_id,
+        folder_id,
+        &original_filename,
+        &content_type,
+        &bytes,
+    )
+    .await
+}
+
+// ============================================================================
+// Get Image Details
+// ============================================================================
+
+/// Get details of a specific image
+#[utoipa::path(
+    get,
+    path = "/api/v1/images/{image_id}",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID")
+    ),
+    responses(
+        (status = 200, description = "Image details", body = ApiResponse<ImageDetailResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found")
+    )
+)]
+pub async fn get_image(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let image_id = path.into_inner();
+
+    // Find image with ownership verification
+    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(img)) => img,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get image"));
+        }
+    };
+
+    // Get analysis history
+    let history = match ImageRepository::get_analysis_history(pool.get_ref(), image_id).await {
+        Ok(h) => h,
+        Err(e) => {
+            tracing::error!("Failed to get analysis history: {:?}", e);
+            Vec::new()
+        }
+    };
+
+    let analysis_history: Vec<AnalysisHistoryItem> = history
+        .into_iter()
+        .map(|row| AnalysisHistoryItem {
+            job_id: row.job_id,
+            status: row.status,
+            ai_model_version: row.ai_model_version,
+            finished_at: row.finished_at.map(|dt| dt.to_rfc3339()),
+        })
+        .collect();
+
+    let tags = match ImageRepository::find_tags(pool.get_ref(), image_id).await {
+        Ok(tags) => tags,
+        Err(e) => {
+            tracing::error!("Failed to get image tags: {:?}", e);
+            Vec::new()
+        }
+    };
+
+    let metadata = image.metadata.as_ref().and_then(ImageMetadataResponse::from_json);
+
+    HttpResponse::Ok().json(ApiResponse::success(ImageDetailResponse {
+        image_id: image.image_id,
+        folder_id: image.folder_id,
+        original_filename: image.original_filename,
+        file_url: format!("/api/v1/images/{}/file", image.image_id),
+        file_size: image.file_size,
+        mime_type: image.mime_type,
+        metadata,
+        analysis_history,
+        uploaded_at: image
+            .uploaded_at
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        tags,
+    }))
+}
+
+// ============================================================================
+// Rename Image
+// ============================================================================
+
+/// Rename an image
+#[utoipa::path(
+    patch,
+    path = "/api/v1/images/{image_id}",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID")
+    ),
+    request_body = RenameImageRequest,
+    responses(
+        (status = 200, description = "Image renamed", body = ApiResponse<ImageResponse>),
+        (status = 400, description = "Invalid filename"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found")
+    )
+)]
+pub async fn rename_image(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    payload: web::Json<crate::dto::RenameImageRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let image_id = path.into_inner();
+    let new_filename = payload.new_filename.trim();
+
+    if new_filename.is_empty() {
+        return HttpResponse::BadRequest()
+            .json(ApiResponse::<()>::error("VALIDATION_ERROR", "Filename cannot be empty"));
+    }
+
+    // Check if image exists and user has ownership
+    match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify image"));
+        }
+        Ok(Some(_)) => {}
+    }
+
+    // Update filename
+    match ImageRepository::update_filename(pool.get_ref(), image_id, user.user_id, new_filename).await {
+        Ok(Some(())) => {
+            // Fetch updated image
+            match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+                Ok(Some(image)) => {
+                    let metadata =
+                        image.metadata.as_ref().and_then(ImageMetadataResponse::from_json);
+
+                    // Check analysis status
+                    let has_analysis = ImageRepository::has_analysis(pool.get_ref(), image.image_id)
+                        .await
+                        .unwrap_or(false);
+                    let tags = ImageRepository::find_tags(pool.get_ref(), image.image_id)
+                        .await
+                        .unwrap_or_default();
+
+                    HttpResponse::Ok().json(ApiResponse::success(ImageResponse {
+                        image_id: image.image_id,
+                        folder_id: image.folder_id,
+                        folder_name: None,
+                        original_filename: image.original_filename,
+                        file_size: image.file_size,
+                        mime_type: image.mime_type,
+                        metadata,
+                        has_analysis,
+                        uploaded_at: image
+                            .uploaded_at
+                            .map(|dt| dt.to_rfc3339())
+                            .unwrap_or_default(),
+                        deleted_at: None,
+                        tags,
+                    }))
+                },
+                 Err(e) => {
+                    tracing::error!("Failed to fetch updated image: {:?}", e);
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::<()>::internal_error(&req, "Failed to fetch updated image"))
+                }
+                Ok(None) => HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
+            }
+        },
+        Ok(None) => {
+             HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
+        }
+        Err(e) => {
+            tracing::error!("Failed to rename image: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to rename image"))
+        }
+    }
+}
+
+// ============================================================================
+// Move Image
+// ============================================================================
+
+/// Move an image into another folder
+///
+/// The destination's image limit and the duplicate-filename policy apply as
+/// for an upload into it: a full folder gets 403 `FOLDER_IMAGE_LIMIT`, and a
+/// clashing name is rejected or suffixed.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/images/{image_id}/move",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID")
+    ),
+    request_body = MoveImageRequest,
+    responses(
+        (status = 200, description = "Image moved", body = ApiResponse<ImageResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Target folder is full (FOLDER_IMAGE_LIMIT)"),
+        (status = 404, description = "Image or target folder not found"),
+        (status = 409, description = "Duplicate filename rejected by upload policy")
+    )
+)]
+pub async fn move_image(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    payload: web::Json<MoveImageRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let image_id = path.into_inner();
+    let target_folder_id = payload.target_folder_id;
+    let not_found = || {
+        HttpResponse::NotFound().json(ApiResponse::<()>::error(
+            "NOT_FOUND",
+            "Image or target folder not found",
+        ))
+    };
+
+    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(image)) => image,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            tracing::error!("Failed to get image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to move image"));
+        }
+    };
+
+    // Moving within the same folder changes nothing, so skip the upload checks
+    let filename = if image.folder_id == target_folder_id {
+        image.original_filename
+    } else {
+        match FolderRepository::find_by_id(pool.get_ref(), target_folder_id, user.user_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return not_found(),
+            Err(e) => {
+                tracing::error!("Failed to verify folder: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to move image"));
+            }
+        }
+
+        if let Err(response) = check_folder_capacity(
+            &req,
+            pool.get_ref(),
+            config.upload.max_images_per_folder,
+            target_folder_id,
+        )
+        .await
+        {
+            return response;
+        }
+
+        match resolve_upload_filename(
+            &req,
+            pool.get_ref(),
+            config.upload.duplicate_filenames,
+            target_folder_id,
+            &image.original_filename,
+        )
+        .await
+        {
+            Ok(name) => name,
+            Err(response) => return response,
+        }
+    };
+
+    let image = match ImageRepository::move_to_folder(
+        pool.get_ref(),
+        image_id,
+        user.user_id,
+        target_folder_id,
+        &filename,
+    )
+    .await
+    {
+        Ok(Some(image)) => image,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            tracing::error!("Failed to move image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to move image"));
+        }
+    };
+
+    let has_analysis = ImageRepository::has_analysis(pool.get_ref(), image.image_id)
+        .await
+        .unwrap_or(false);
+    let tags = ImageRepository::find_tags(pool.get_ref(), image.image_id)
+        .await
+        .unwrap_or_default();
+
+    HttpResponse::Ok().json(ApiResponse::success(ImageResponse {
+        metadata: image.metadata.as_ref().and_then(ImageMetadataResponse::from_json),
+        image_id: image.image_id,
+        folder_id: image.folder_id,
+        folder_name: None,
+        original_filename: image.original_filename,
+        file_size: image.file_size,
+        mime_type: image.mime_type,
+        has_analysis,
+        uploaded_at: image.uploaded_at.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+        deleted_at: None,
+        tags,
+    }))
+}
+
+// ============================================================================
+// Image Tags
+// ============================================================================
+
+/// Which way a tag request changes an image's tags
+#[derive(Clone, Copy)]
+enum TagChange {
+    Add,
+    Remove,
+}
+
+/// Add or remove one tag on an owned image and respond with its tags
+async fn change_image_tag(
+    pool: &PgPool,
+    req: &HttpRequest,
+    image_id: i64,
+    tag: &str,
+    change: TagChange,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    if let Err(e) = validate_tag(tag) {
+        return validation_error(ValidationKind::Unprocessable, "INVALID_TAG", e.code.to_string());
+    }
+
+    match ImageRepository::find_by_id(pool, image_id, user.user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to verify image"));
+        }
+    }
+
+    let changed = match change {
+        TagChange::Add => ImageRepository::add_tag(pool, image_id, tag).await,
+        TagChange::Remove => ImageRepository::remove_tag(pool, image_id, tag).await,
+    };
+    match changed {
+        // Adding a tag the image already has is a no-op
+        Ok(false) if matches!(change, TagChange::Remove) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image does not have this tag"));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!("Failed to update tags of image {}: {:?}", image_id, e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to update image tags"));
+        }
+    }
+
+    match ImageRepository::find_tags(pool, image_id).await {
+        Ok(tags) => HttpResponse::Ok().json(ApiResponse::success(ImageTagsResponse { image_id, tags })),
+        Err(e) => {
+            tracing::error!("Failed to get image tags: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(req, "Failed to get image tags"))
+        }
+    }
+}
+
+/// Tag an image
+///
+/// Tags are 1-32 lowercase letters, digits, `-` or `_`. Adding a tag the
+/// image already has succeeds without change.
+#[utoipa::path(
+    post,
+    path = "/api/v1/images/{image_id}/tags/{tag}",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID"),
+        ("tag" = String, Path, description = "Tag to add", example = "favorite")
+    ),
+    responses(
+        (status = 200, description = "Image tags after the change", body = ApiResponse<ImageTagsResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found"),
+        (status = 422, description = "Invalid tag (INVALID_TAG)")
+    )
+)]
+pub async fn add_image_tag(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<(i64, String)>,
+) -> HttpResponse {
+    let (image_id, tag) = path.into_inner();
+    change_image_tag(pool.get_ref(), &req, image_id, &tag, TagChange::Add).await
+}
+
+/// Remove a tag from an image
+#[utoipa::path(
+    delete,
+    path = "/api/v1/images/{image_id}/tags/{tag}",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID"),
+        ("tag" = String, Path, description = "Tag to remove", example = "favorite")
+    ),
+    responses(
+        (status = 200, description = "Image tags after the change", body = ApiResponse<ImageTagsResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found, or it does not have the tag"),
+        (status = 422, description = "Invalid tag (INVALID_TAG)")
+    )
+)]
+pub async fn remove_image_tag(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<(i64, String)>,
+) -> HttpResponse {
+    let (image_id, tag) = path.into_inner();
+    change_image_tag(pool.get_ref(), &req, image_id, &tag, TagChange::Remove).await
+}
+
+// ============================================================================
+// Delete Image (Soft Delete)
+// ============================================================================
+
+/// Delete an image (soft delete)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/images/{image_id}",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    params(
+        ("image_id" = i64, Path, description = "Image ID")
+    ),
+    responses(
+        (status = 200, description = "Image deleted", body = ApiResponse<DeleteImageResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Image not found")
+    )
+)]
+pub async fn delete_image(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
         }
     };
 
-    let metadata_response = metadata.and_then(|m| {
-        serde_json::from_value::<crate::models::ImageMetadata>(m)
-            .ok()
-            .map(|meta| ImageMetadataResponse {
-                width: meta.width,
-                height: meta.height,
-            })
-    });
+    let image_id = path.into_inner();
 
-    HttpResponse::Created().json(ApiResponse::success(ImageResponse {
-        image_id: image.image_id,
-        folder_id: image.folder_id,
-        original_filename: image.original_filename,
-        file_size: image.file_size,
-        mime_type: image.mime_type,
-        metadata: metadata_response,
-        has_analysis: false,
-        uploaded_at: image
-            .uploaded_at
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default(),
-    }))
+    // Soft delete with ownership verification
+    match ImageRepository::soft_delete(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(())) => HttpResponse::Ok().json(ApiResponse::success(DeleteImageResponse {
+            message: "Image deleted successfully".to_string(),
+        })),
+        Ok(None) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
+        }
+        Err(e) => {
+            tracing::error!("Failed to delete image: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to delete image"))
+        }
+    }
 }
 
-
 // ============================================================================
-// Get Image Details
+// Get Image File (Serve from S3)
 // ============================================================================
 
-/// Get details of a specific image
+/// Get image file content from S3 storage
 #[utoipa::path(
     get,
-    path = "/api/v1/images/{image_id}",
+    path = "/api/v1/images/{image_id}/file",
     tag = "Image Management",
     security(("bearer_auth" = [])),
     params(
         ("image_id" = i64, Path, description = "Image ID")
     ),
     responses(
-        (status = 200, description = "Image details", body = ApiResponse<ImageDetailResponse>),
+        (status = 200, description = "Image file content", content_type = "image/*"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Image not found")
     )
 )]
-pub async fn get_image(
+pub async fn get_image_file(
     pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
     req: HttpRequest,
     path: web::Path<i64>,
 ) -> HttpResponse {
@@ -332,80 +1563,64 @@ pub async fn get_image(
         Err(e) => {
             tracing::error!("Failed to get image: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get image"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get image"));
         }
     };
 
-    // Get analysis history
-    let history = match ImageRepository::get_analysis_history(pool.get_ref(), image_id).await {
-        Ok(h) => h,
+    // Get file from storage
+    let (bytes, content_type) = match storage.get(&image.file_path).await {
+        Ok(data) => data,
+        Err(StorageError::NotFound(_)) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image file not found in storage"));
+        }
         Err(e) => {
-            tracing::error!("Failed to get analysis history: {:?}", e);
-            Vec::new()
+            tracing::error!("Failed to get file from storage: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retrieve image file"));
         }
     };
 
-    let analysis_history: Vec<AnalysisHistoryItem> = history
-        .into_iter()
-        .map(|row| AnalysisHistoryItem {
-            job_id: row.job_id,
-            status: row.status,
-            ai_model_version: row.ai_model_version,
-            finished_at: row.finished_at.map(|dt| dt.to_rfc3339()),
-        })
-        .collect();
-
-    let metadata = image.metadata.as_ref().and_then(|m| {
-        serde_json::from_value::<crate::models::ImageMetadata>(m.clone())
-            .ok()
-            .map(|meta| ImageMetadataResponse {
-                width: meta.width,
-                height: meta.height,
-            })
-    });
-
-    HttpResponse::Ok().json(ApiResponse::success(ImageDetailResponse {
-        image_id: image.image_id,
-        folder_id: image.folder_id,
-        original_filename: image.original_filename,
-        file_url: format!("/api/v1/images/{}/file", image.image_id),
-        file_size: image.file_size,
-        mime_type: image.mime_type,
-        metadata,
-        analysis_history,
-        uploaded_at: image
-            .uploaded_at
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default(),
-    }))
+    // Return file with appropriate headers
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(("Cache-Control", "public, max-age=31536000"))
+        .insert_header((
+            "Content-Disposition",
+            format!("inline; filename=\"{}\"", image.original_filename),
+        ))
+        .body(bytes)
 }
 
 // ============================================================================
-// Rename Image
+// Get Image Thumbnail
 // ============================================================================
 
-/// Rename an image
+/// Get a downscaled thumbnail of an image
+///
+/// Thumbnails missing for older images are generated on first request.
+/// Images the server cannot decode are served as the original file, and
+/// marked so later requests skip the attempt.
 #[utoipa::path(
-    patch,
-    path = "/api/v1/images/{image_id}",
+    get,
+    path = "/api/v1/images/{image_id}/thumbnail",
     tag = "Image Management",
     security(("bearer_auth" = [])),
     params(
         ("image_id" = i64, Path, description = "Image ID")
     ),
-    request_body = RenameImageRequest,
     responses(
-        (status = 200, description = "Image renamed", body = ApiResponse<ImageResponse>),
-        (status = 400, description = "Invalid filename"),
+        (status = 200, description = "Thumbnail, or the original for unsupported formats", content_type = "image/*"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Image not found")
     )
 )]
-pub async fn rename_image(
+pub async fn get_image_thumbnail(
     pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
-    payload: web::Json<crate::dto::RenameImageRequest>,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
         Some(u) => u.clone(),
@@ -416,101 +1631,137 @@ pub async fn rename_image(
     };
 
     let image_id = path.into_inner();
-    let new_filename = payload.new_filename.trim();
-
-    if new_filename.is_empty() {
-        return HttpResponse::BadRequest()
-            .json(ApiResponse::<()>::error("VALIDATION_ERROR", "Filename cannot be empty"));
-    }
 
-    // Check if image exists and user has ownership
-    match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+    // Find image with ownership verification
+    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(img)) => img,
         Ok(None) => {
             return HttpResponse::NotFound()
                 .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
         }
         Err(e) => {
-            tracing::error!("Failed to verify image: {:?}", e);
+            tracing::error!("Failed to get image: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify image"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get image"));
+        }
+    };
+
+    let format = config.storage.thumbnail_format;
+    let thumbnail_key = ImageService::thumbnail_key(&image.file_path, format);
+
+    // A key in another format means `storage.thumbnail_format` changed since
+    if image.thumbnail_key.as_deref() == Some(thumbnail_key.as_str()) {
+        match storage.get(&thumbnail_key).await {
+            Ok((bytes, content_type)) => return thumbnail_response(content_type, bytes),
+            Err(StorageError::NotFound(_)) => {
+                tracing::warn!("Thumbnail {} missing from storage, regenerating", thumbnail_key);
+            }
+            Err(e) => {
+                tracing::error!("Failed to get thumbnail from storage: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to retrieve thumbnail"));
+            }
         }
-        Ok(Some(_)) => {}
     }
 
-    // Update filename
-    match ImageRepository::update_filename(pool.get_ref(), image_id, user.user_id, new_filename).await {
-        Ok(Some(())) => {
-            // Fetch updated image
-            match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
-                Ok(Some(image)) => {
-                     let metadata = image.metadata.as_ref().and_then(|m| {
-                        serde_json::from_value::<crate::models::ImageMetadata>(m.clone())
-                            .ok()
-                            .map(|meta| ImageMetadataResponse {
-                                width: meta.width,
-                                height: meta.height,
-                            })
-                    });
+    let (bytes, content_type) = match storage.get(&image.file_path).await {
+        Ok(data) => data,
+        Err(StorageError::NotFound(_)) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image file not found in storage"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get file from storage: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retrieve image file"));
+        }
+    };
 
-                    // Check analysis status
-                    let has_analysis = ImageRepository::has_analysis(pool.get_ref(), image.image_id)
-                        .await
-                        .unwrap_or(false);
+    if image.thumbnail_unavailable {
+        return thumbnail_response(content_type, bytes);
+    }
 
-                    HttpResponse::Ok().json(ApiResponse::success(ImageResponse {
-                        image_id: image.image_id,
-                        folder_id: image.folder_id,
-                        original_filename: image.original_filename,
-                        file_size: image.file_size,
-                        mime_type: image.mime_type,
-                        metadata,
-                        has_analysis,
-                        uploaded_at: image
-                            .uploaded_at
-                            .map(|dt| dt.to_rfc3339())
-                            .unwrap_or_default(),
-                    }))
-                },
-                 Err(e) => {
-                    tracing::error!("Failed to fetch updated image: {:?}", e);
-                    HttpResponse::InternalServerError()
-                        .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to fetch updated image"))
-                }
-                Ok(None) => HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
-            }
-        },
+    match store_thumbnail(pool.get_ref(), storage.get_ref(), &image, &bytes, format).await {
+        Some(thumbnail) => thumbnail_response(format.content_type().to_string(), thumbnail),
+        None => thumbnail_response(content_type, bytes),
+    }
+}
+
+fn thumbnail_response(content_type: String, bytes: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(("Cache-Control", "private, max-age=86400"))
+        .body(bytes)
+}
+
+/// Generate an image's thumbnail from its original bytes, store it and
+/// record its key
+///
+/// Returns the thumbnail, or `None` when the original cannot be decoded, in
+/// which case the image is marked so it isn't tried again. Storage and
+/// database failures are logged rather than returned, since a thumbnail that
+/// failed to persist is simply generated again next time.
+pub(crate) async fn store_thumbnail(
+    pool: &PgPool,
+    storage: &dyn StorageBackend,
+    image: &Image,
+    original: &[u8],
+    format: ThumbnailFormat,
+) -> Option<Vec<u8>> {
+    // Decoding and resizing is CPU-bound, so keep it off the async runtime
+    let original = original.to_vec();
+    let thumbnail = match tokio::task::spawn_blocking(move || ImageService::generate_thumbnail(&original, format)).await {
+        Ok(Some(thumbnail)) => thumbnail,
         Ok(None) => {
-             HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
+            tracing::debug!("No thumbnail for image {} ({})", image.image_id, image.mime_type);
+            if let Err(e) = ImageRepository::mark_thumbnail_unavailable(pool, image.image_id).await {
+                tracing::warn!("Failed to mark image {} as unthumbnailable: {:?}", image.image_id, e);
+            }
+            return None;
         }
         Err(e) => {
-            tracing::error!("Failed to rename image: {:?}", e);
-            HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to rename image"))
+            tracing::warn!("Thumbnail generation for image {} failed: {:?}", image.image_id, e);
+            return None;
         }
+    };
+
+    let thumbnail_key = ImageService::thumbnail_key(&image.file_path, format);
+    if let Err(e) = storage.upload(&thumbnail_key, &thumbnail, format.content_type()).await {
+        tracing::warn!("Failed to store thumbnail {}: {:?}", thumbnail_key, e);
+        return Some(thumbnail);
     }
+    if let Err(e) = ImageRepository::set_thumbnail_key(pool, image.image_id, &thumbnail_key).await {
+        tracing::warn!("Failed to record thumbnail for image {}: {:?}", image.image_id, e);
+    }
+
+    Some(thumbnail)
 }
 
 // ============================================================================
-// Delete Image (Soft Delete)
+// Share Image
 // ============================================================================
 
-/// Delete an image (soft delete)
+/// Create a read-only link to one image that works without authentication
+///
+/// The link expires after `share.link_expiry_minutes` and grants access to
+/// this image only.
 #[utoipa::path(
-    delete,
-    path = "/api/v1/images/{image_id}",
+    post,
+    path = "/api/v1/images/{image_id}/share",
     tag = "Image Management",
     security(("bearer_auth" = [])),
     params(
         ("image_id" = i64, Path, description = "Image ID")
     ),
     responses(
-        (status = 200, description = "Image deleted", body = ApiResponse<DeleteImageResponse>),
+        (status = 201, description = "Share link created", body = ApiResponse<ShareLinkResponse>),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Image not found")
     )
 )]
-pub async fn delete_image(
+pub async fn share_image(
     pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i64>,
 ) -> HttpResponse {
@@ -524,89 +1775,93 @@ pub async fn delete_image(
 
     let image_id = path.into_inner();
 
-    // Soft delete with ownership verification
-    match ImageRepository::soft_delete(pool.get_ref(), image_id, user.user_id).await {
-        Ok(Some(())) => HttpResponse::Ok().json(ApiResponse::success(DeleteImageResponse {
-            message: "Image deleted successfully".to_string(),
-        })),
+    match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
+        Ok(Some(_)) => {}
         Ok(None) => {
-            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"))
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
         }
         Err(e) => {
-            tracing::error!("Failed to delete image: {:?}", e);
-            HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to delete image"))
+            tracing::error!("Failed to get image: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to share image"));
         }
     }
-}
 
-// ============================================================================
-// Get Image File (Serve from S3)
-// ============================================================================
+    let expires_at = chrono::Utc::now() + config.share.link_expiry();
+    let token = ShareLinkSigner::new(&config.jwt).sign(image_id, expires_at);
 
-/// Get image file content from S3 storage
+    HttpResponse::Created().json(ApiResponse::success(ShareLinkResponse {
+        url: format!("/api/v1/shared/images/{}", token),
+        token,
+        expires_at: expires_at.to_rfc3339(),
+    }))
+}
+
+/// Serve the image a share link points at
 #[utoipa::path(
     get,
-    path = "/api/v1/images/{image_id}/file",
+    path = "/api/v1/shared/images/{token}",
     tag = "Image Management",
-    security(("bearer_auth" = [])),
     params(
-        ("image_id" = i64, Path, description = "Image ID")
+        ("token" = String, Path, description = "Share token")
     ),
     responses(
-        (status = 200, description = "Image file content", content_type = "image/*"),
-        (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Image not found")
+        (status = 200, description = "Image file", content_type = "image/*"),
+        (status = 403, description = "Share link invalid or expired"),
+        (status = 404, description = "Image no longer exists")
     )
 )]
-pub async fn get_image_file(
-    pool: web::Data<PgPool>,
-    s3_storage: web::Data<crate::services::S3StorageService>,
+pub async fn get_shared_image(
     req: HttpRequest,
-    path: web::Path<i64>,
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    path: web::Path<String>,
 ) -> HttpResponse {
-    let user = match req.extensions().get::<AuthenticatedUser>() {
-        Some(u) => u.clone(),
-        None => {
-            return HttpResponse::Unauthorized()
-                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+    let signer = ShareLinkSigner::new(&config.jwt);
+    let image_id = match signer.verify(&path.into_inner(), chrono::Utc::now()) {
+        Ok(image_id) => image_id,
+        Err(ShareLinkError::Expired) => {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::<()>::error("SHARE_LINK_EXPIRED", "Share link has expired"));
+        }
+        Err(_) => {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::<()>::error("INVALID_SHARE_LINK", "Share link is not valid"));
         }
     };
 
-    let image_id = path.into_inner();
-
-    // Find image with ownership verification
-    let image = match ImageRepository::find_by_id(pool.get_ref(), image_id, user.user_id).await {
-        Ok(Some(img)) => img,
+    let image = match ImageRepository::find_live_by_id(pool.get_ref(), image_id).await {
+        Ok(Some(image)) => image,
         Ok(None) => {
             return HttpResponse::NotFound()
                 .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
         }
         Err(e) => {
-            tracing::error!("Failed to get image: {:?}", e);
+            tracing::error!("Failed to get shared image: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get image"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get image"));
         }
     };
 
-    // Get file from S3
-    let (bytes, content_type) = match s3_storage.get_file(&image.file_path).await {
+    let (bytes, content_type) = match storage.get(&image.file_path).await {
         Ok(data) => data,
-        Err(crate::services::S3Error::NotFound(_)) => {
+        Err(StorageError::NotFound(_)) => {
             return HttpResponse::NotFound()
                 .json(ApiResponse::<()>::error("NOT_FOUND", "Image file not found in storage"));
         }
         Err(e) => {
-            tracing::error!("Failed to get file from S3: {:?}", e);
+            tracing::error!("Failed to get file from storage: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to retrieve image file"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to retrieve image file"));
         }
     };
 
-    // Return file with appropriate headers
+    // Private caching only: the link stops working when it expires
     HttpResponse::Ok()
         .content_type(content_type)
-        .insert_header(("Cache-Control", "public, max-age=31536000"))
+        .insert_header(("Cache-Control", "private, no-store"))
         .insert_header((
             "Content-Disposition",
             format!("inline; filename=\"{}\"", image.original_filename),
@@ -632,12 +1887,16 @@ pub async fn get_image_file(
         (status = 200, description = "Presigned upload URL generated", body = ApiResponse<RequestUploadResponse>),
         (status = 400, description = "Invalid request"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Folder not found")
+        (status = 404, description = "Folder not found"),
+        (status = 409, description = "Folder deleted (FOLDER_DELETED)"),
+        (status = 413, description = "Storage quota exceeded (QUOTA_EXCEEDED)"),
+        (status = 501, description = "Storage backend does not support presigned URLs")
     )
 )]
 pub async fn request_upload(
     pool: web::Data<PgPool>,
-    s3_storage: web::Data<crate::services::S3StorageService>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i32>,
     body: web::Json<RequestUploadRequest>,
@@ -653,25 +1912,16 @@ pub async fn request_upload(
     let folder_id = path.into_inner();
 
     // Verify folder ownership
-    match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
-        Ok(None) => {
-            return HttpResponse::NotFound()
-                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
-        }
-        Err(e) => {
-            tracing::error!("Failed to verify folder: {:?}", e);
-            return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify folder"));
-        }
-        Ok(Some(_)) => {}
+    if let Err(response) = verify_upload_folder(&req, pool.get_ref(), folder_id, user.user_id).await {
+        return response;
     }
 
     // Validate content type
-    let allowed_types = ["image/jpeg", "image/png", "image/tiff"];
-    if !allowed_types.contains(&body.content_type.as_str()) {
+    let content_type = ImageService::canonical_mime_type(&body.content_type);
+    if !ALLOWED_MIME_TYPES.contains(&content_type.as_str()) {
         return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
             "VALIDATION_ERROR",
-            "Invalid content type. Allowed: image/jpeg, image/png, image/tiff",
+            format!("Invalid content type. Allowed: {}", ALLOWED_MIME_TYPES.join(", ")),
         ));
     }
 
@@ -683,21 +1933,39 @@ pub async fn request_upload(
         ));
     }
 
+    // Checked again on confirm, since files may be stored in between
+    if let Err(response) = check_storage_quota(
+        &req,
+        pool.get_ref(),
+        config.upload.max_storage_bytes,
+        user.user_id,
+        body.file_size,
+    )
+    .await
+    {
+        return response;
+    }
+
     // Generate S3 key
-    let (s3_key, _filename) = crate::services::S3StorageService::generate_object_key(&body.filename);
+    let (s3_key, _filename) =
+        crate::services::S3StorageService::generate_object_key(&body.filename, &content_type);
 
     // Generate presigned PUT URL
-    let presigned_url = match s3_storage.presign_put(&s3_key, &body.content_type).await {
+    let presigned_url = match storage.presign_put(&s3_key, &content_type).await {
         Ok(url) => url,
+        Err(StorageError::Unsupported(msg)) => {
+            return HttpResponse::NotImplemented()
+                .json(ApiResponse::<()>::error("NOT_SUPPORTED", msg));
+        }
         Err(e) => {
             tracing::error!("Failed to generate presigned URL: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to generate upload URL"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to generate upload URL"));
         }
     };
 
     // Calculate expiry time
-    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(s3_storage.presign_expiry_secs() as i64);
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(storage.presign_expiry_secs() as i64);
 
     HttpResponse::Ok().json(ApiResponse::success(RequestUploadResponse {
         upload_token: s3_key, // The S3 key serves as the token
@@ -722,14 +1990,19 @@ pub async fn request_upload(
     request_body = ConfirmUploadRequest,
     responses(
         (status = 201, description = "Image registered", body = ApiResponse<ImageResponse>),
-        (status = 400, description = "Invalid request or file not found in storage"),
+        (status = 400, description = "Invalid request, file too large, or size mismatch (SIZE_MISMATCH)"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Folder not found")
+        (status = 403, description = "Folder image limit reached (FOLDER_IMAGE_LIMIT)"),
+        (status = 404, description = "Folder not found"),
+        (status = 409, description = "Duplicate filename rejected by upload policy, or folder deleted (FOLDER_DELETED)"),
+        (status = 413, description = "Storage quota exceeded (QUOTA_EXCEEDED)"),
+        (status = 422, description = "Uploaded file not found in storage (UPLOAD_NOT_FOUND)")
     )
 )]
 pub async fn confirm_upload(
     pool: web::Data<PgPool>,
-    s3_storage: web::Data<crate::services::S3StorageService>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
     req: HttpRequest,
     path: web::Path<i32>,
     body: web::Json<ConfirmUploadRequest>,
@@ -742,41 +2015,102 @@ pub async fn confirm_upload(
         }
     };
 
-    let folder_id = path.into_inner();
+    let folder_id = path.into_inner();
+
+    // Verify folder ownership
+    if let Err(response) = verify_upload_folder(&req, pool.get_ref(), folder_id, user.user_id).await {
+        return response;
+    }
+
+    if let Err(response) =
+        check_folder_capacity(&req, pool.get_ref(), config.upload.max_images_per_folder, folder_id).await
+    {
+        return response;
+    }
 
-    // Verify folder ownership
-    match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
-        Ok(None) => {
-            return HttpResponse::NotFound()
-                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+    // Verify the upload token looks like a valid S3 key
+    if !body.upload_token.starts_with("images/") {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            "Invalid upload token",
+        ));
+    }
+
+    // Verify the object exists (HEAD request) and matches what was declared
+    let actual_size = match storage.object_size(&body.upload_token).await {
+        Ok(size) => size,
+        Err(StorageError::NotFound(_)) | Err(StorageError::InvalidKey(_)) => {
+            return validation_error(
+                ValidationKind::Unprocessable,
+                "UPLOAD_NOT_FOUND",
+                "Uploaded file not found in storage",
+            );
         }
         Err(e) => {
-            tracing::error!("Failed to verify folder: {:?}", e);
+            tracing::error!("Failed to verify uploaded file: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify folder"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify uploaded file"));
         }
-        Ok(Some(_)) => {}
-    }
+    };
 
-    // Verify the upload token looks like a valid S3 key
-    if !body.upload_token.starts_with("images/") {
+    if actual_size > MAX_FILE_SIZE as u64 {
+        // Nothing will reference the object, so don't leave it in storage
+        if let Err(e) = storage.delete(&body.upload_token).await {
+            tracing::warn!("Failed to delete oversized upload {}: {:?}", body.upload_token, e);
+        }
         return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
             "VALIDATION_ERROR",
-            "Invalid upload token",
+            "File too large. Maximum size: 50MB",
+        ));
+    }
+
+    if !ImageService::upload_size_matches(body.file_size, actual_size) {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "SIZE_MISMATCH",
+            format!(
+                "Uploaded file is {} bytes but {} bytes were declared",
+                actual_size, body.file_size
+            ),
         ));
     }
 
-    // Optional: Verify file exists in S3 (HEAD request)
-    // For now, we trust the client and proceed
+    if let Err(response) = check_storage_quota(
+        &req,
+        pool.get_ref(),
+        config.upload.max_storage_bytes,
+        user.user_id,
+        actual_size as i64,
+    )
+    .await
+    {
+        // Nothing will reference the object, so don't leave it in storage
+        if let Err(e) = storage.delete(&body.upload_token).await {
+            tracing::warn!("Failed to delete over-quota upload {}: {:?}", body.upload_token, e);
+        }
+        return response;
+    }
+
+    let filename = match resolve_upload_filename(
+        &req,
+        pool.get_ref(),
+        config.upload.duplicate_filenames,
+        folder_id,
+        &stored_filename(&config.upload, &body.filename),
+    )
+    .await
+    {
+        Ok(name) => name,
+        Err(response) => return response,
+    };
 
     // Create database record
     let image = match ImageRepository::create(
         pool.get_ref(),
         folder_id,
         &body.upload_token, // S3 key as file_path
-        &body.filename,
-        &body.content_type,
-        body.file_size as i32,
+        &filename,
+        &ImageService::canonical_mime_type(&body.content_type),
+        actual_size as i32, // Stored size, not the declared one
         None, // No metadata extracted for presigned uploads
     )
     .await
@@ -785,13 +2119,14 @@ pub async fn confirm_upload(
         Err(e) => {
             tracing::error!("Failed to create image record: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to create image record"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to create image record"));
         }
     };
 
     HttpResponse::Created().json(ApiResponse::success(ImageResponse {
         image_id: image.image_id,
         folder_id: image.folder_id,
+        folder_name: None,
         original_filename: image.original_filename,
         file_size: image.file_size,
         mime_type: image.mime_type,
@@ -801,6 +2136,8 @@ pub async fn confirm_upload(
             .uploaded_at
             .map(|dt| dt.to_rfc3339())
             .unwrap_or_default(),
+        deleted_at: None,
+        tags: Vec::new(),
     }))
 }
 
@@ -815,19 +2152,23 @@ pub async fn confirm_upload(
     tag = "Image Management",
     security(("bearer_auth" = [])),
     params(
-        ("image_id" = i64, Path, description = "Image ID")
+        ("image_id" = i64, Path, description = "Image ID"),
+        DownloadUrlQuery
     ),
     responses(
         (status = 200, description = "Presigned download URL", body = ApiResponse<PresignedDownloadResponse>),
+        (status = 400, description = "Invalid response override"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Image not found")
+        (status = 404, description = "Image not found"),
+        (status = 501, description = "Storage backend does not support presigned URLs")
     )
 )]
 pub async fn get_image_download_url(
     pool: web::Data<PgPool>,
-    s3_storage: web::Data<crate::services::S3StorageService>,
+    storage: web::Data<dyn StorageBackend>,
     req: HttpRequest,
     path: web::Path<i64>,
+    query: web::Query<DownloadUrlQuery>,
 ) -> HttpResponse {
     let user = match req.extensions().get::<AuthenticatedUser>() {
         Some(u) => u.clone(),
@@ -837,6 +2178,14 @@ pub async fn get_image_download_url(
         }
     };
 
+    // Validate response overrides before they get signed into the URL
+    if let Err(errors) = query.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
     let image_id = path.into_inner();
 
     // Find image with ownership verification
@@ -849,22 +2198,32 @@ pub async fn get_image_download_url(
         Err(e) => {
             tracing::error!("Failed to get image: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to get image"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get image"));
         }
     };
 
+    let query = query.into_inner();
+    let overrides = ResponseOverrides {
+        content_type: query.response_content_type,
+        content_disposition: query.response_content_disposition,
+    };
+
     // Generate presigned GET URL
-    let presigned_url = match s3_storage.presign_get(&image.file_path).await {
+    let presigned_url = match storage.presign_get(&image.file_path, &overrides).await {
         Ok(url) => url,
+        Err(StorageError::Unsupported(msg)) => {
+            return HttpResponse::NotImplemented()
+                .json(ApiResponse::<()>::error("NOT_SUPPORTED", msg));
+        }
         Err(e) => {
             tracing::error!("Failed to generate presigned download URL: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to generate download URL"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to generate download URL"));
         }
     };
 
     // Calculate expiry time
-    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(s3_storage.presign_expiry_secs() as i64);
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(storage.presign_expiry_secs() as i64);
 
     HttpResponse::Ok().json(ApiResponse::success(PresignedDownloadResponse {
         url: presigned_url,
@@ -872,6 +2231,205 @@ pub async fn get_image_download_url(
     }))
 }
 
+// ============================================================================
+// Get Presigned Download URLs (Batch)
+// ============================================================================
+
+/// Get presigned download URLs for several images at once
+///
+/// URLs are generated concurrently, at most `limits.max_presign_concurrency`
+/// at a time, and returned keyed by image ID in ascending order.
+#[utoipa::path(
+    post,
+    path = "/api/v1/images/download-urls",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    request_body = BatchDownloadUrlRequest,
+    responses(
+        (status = 200, description = "Presigned download URLs", body = ApiResponse<BatchDownloadUrlResponse>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "One or more images not found"),
+        (status = 501, description = "Storage backend does not support presigned URLs")
+    )
+)]
+pub async fn get_image_download_urls(
+    pool: web::Data<ReadPool>,
+    storage: web::Data<dyn StorageBackend>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<BatchDownloadUrlRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if let Err(response) = check_batch_size(&config.limits, request.image_ids.len()) {
+        return response;
+    }
+
+    let mut image_ids = request.image_ids;
+    image_ids.sort_unstable();
+    image_ids.dedup();
+
+    // Find every image with ownership verification in one query
+    let images = match ImageRepository::find_by_ids(pool.get_ref(), &image_ids, user.user_id).await {
+        Ok(images) if images.len() == image_ids.len() => images,
+        Ok(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Image not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get images"));
+        }
+    };
+
+    // Bound how many signing calls run at once for this request
+    let permits = Semaphore::new(config.limits.max_presign_concurrency.max(1));
+    let overrides = ResponseOverrides::default();
+
+    let signed = futures::future::join_all(images.iter().map(|image| async {
+        let _permit = permits.acquire().await.expect("semaphore is never closed");
+
+        let started = Instant::now();
+        let url = storage.presign_get(&image.file_path, &overrides).await;
+        metrics::registry().observe(
+            metrics::PRESIGN_DURATION_SECONDS,
+            "Time taken to generate one presigned URL",
+            &[("outcome", if url.is_ok() { "success" } else { "failure" })],
+            started.elapsed().as_secs_f64(),
+        );
+
+        (image.image_id, url)
+    }))
+    .await;
+
+    let mut urls = BTreeMap::new();
+    for (image_id, url) in signed {
+        match url {
+            Ok(url) => {
+                urls.insert(image_id, url);
+            }
+            Err(StorageError::Unsupported(msg)) => {
+                return HttpResponse::NotImplemented()
+                    .json(ApiResponse::<()>::error("NOT_SUPPORTED", msg));
+            }
+            Err(e) => {
+                tracing::error!("Failed to generate presigned download URL: {:?}", e);
+                return HttpResponse::InternalServerError().json(ApiResponse::<()>::internal_error(
+                    &req,
+                    "Failed to generate download URLs",
+                ));
+            }
+        }
+    }
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(storage.presign_expiry_secs() as i64);
+
+    HttpResponse::Ok().json(ApiResponse::success(BatchDownloadUrlResponse {
+        urls,
+        expires_at: expires_at.to_rfc3339(),
+    }))
+}
+
+// ============================================================================
+// Bulk Metadata Update
+// ============================================================================
+
+/// Set metadata on several images at once
+///
+/// All updates are applied in one transaction. Images the caller doesn't
+/// own are reported as not found without failing the rest; the response
+/// reports each entry's outcome in request order.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/images/bulk-metadata",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    request_body = BulkMetadataUpdateRequest,
+    responses(
+        (status = 200, description = "Per-image update outcomes", body = ApiResponse<BulkMetadataUpdateResponse>),
+        (status = 400, description = "Invalid request data or oversized batch"),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn update_images_metadata(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<BulkMetadataUpdateRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if let Err(response) = check_batch_size(&config.limits, request.updates.len()) {
+        return response;
+    }
+
+    // Validated above, so every entry has a patch
+    let updates: Vec<(i64, serde_json::Value)> = request
+        .updates
+        .iter()
+        .filter_map(|update| Some((update.image_id, update.patch()?)))
+        .collect();
+
+    let applied = match ImageRepository::merge_metadata_bulk(pool.get_ref(), user.user_id, &updates).await {
+        Ok(applied) => applied,
+        Err(e) => {
+            tracing::error!("Failed to update image metadata: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to update image metadata"));
+        }
+    };
+
+    let results: Vec<ImageMetadataUpdateOutcome> = updates
+        .iter()
+        .zip(applied)
+        .map(|((image_id, _), applied)| ImageMetadataUpdateOutcome {
+            image_id: *image_id,
+            success: applied,
+            error: (!applied).then(|| ApiError::new("NOT_FOUND", "Image not found")),
+        })
+        .collect();
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+
+    HttpResponse::Ok().json(ApiResponse::success(BulkMetadataUpdateResponse {
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    }))
+}
+
 // ============================================================================
 // List Images V2 (Cursor-based Pagination)
 // ============================================================================
@@ -888,12 +2446,13 @@ pub async fn get_image_download_url(
     ),
     responses(
         (status = 200, description = "List of images with cursor pagination", body = ApiResponse<ImageListResponseV2>),
+        (status = 400, description = "Unsupported MIME type filter"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Folder not found")
     )
 )]
 pub async fn list_images_v2(
-    pool: web::Data<PgPool>,
+    pool: web::Data<ReadPool>,
     req: HttpRequest,
     path: web::Path<i32>,
     query: web::Query<CursorPaginationQuery>,
@@ -906,7 +2465,15 @@ pub async fn list_images_v2(
         }
     };
 
+    if let Err(errors) = query.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
     let folder_id = path.into_inner();
+    let mime_type = query.mime_type.as_deref();
 
     // Verify folder ownership
     match FolderRepository::find_by_id(pool.get_ref(), folder_id, user.user_id).await {
@@ -917,7 +2484,7 @@ pub async fn list_images_v2(
         Err(e) => {
             tracing::error!("Failed to verify folder: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to verify folder"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folder"));
         }
         Ok(Some(_)) => {}
     }
@@ -926,9 +2493,10 @@ pub async fn list_images_v2(
     let cursor = query.cursor_datetime();
 
     // Fetch images with cursor (repository fetches limit+1 to detect has_next)
-    let mut images = match ImageRepository::find_by_folder_id_cursor(
+    let images = match ImageRepository::find_by_folder_id_cursor(
         pool.get_ref(),
         folder_id,
+        mime_type,
         cursor,
         limit,
     )
@@ -938,60 +2506,210 @@ pub async fn list_images_v2(
         Err(e) => {
             tracing::error!("Failed to list images: {:?}", e);
             return HttpResponse::InternalServerError()
-                .json(ApiResponse::<()>::error("INTERNAL_ERROR", "Failed to list images"));
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list images"));
         }
     };
 
-    // Check if there are more items
-    let has_next = images.len() > limit as usize;
-    if has_next {
-        images.pop(); // Remove the extra item used for detection
-    }
-
-    // Determine next cursor
-    let next_cursor = if has_next {
-        images.last().and_then(|img| img.uploaded_at.map(|dt| dt.to_rfc3339()))
+    let total = if query.include_total {
+        match ImageRepository::count_by_folder_id(pool.get_ref(), folder_id, mime_type, None).await {
+            Ok(count) => Some(count),
+            Err(e) => {
+                tracing::error!("Failed to count images: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::<()>::internal_error(&req, "Failed to count images"));
+            }
+        }
     } else {
         None
     };
 
-    // Build response
-    let mut image_responses = Vec::with_capacity(images.len());
-    for image in images {
-        let has_analysis = ImageRepository::has_analysis(pool.get_ref(), image.image_id)
-            .await
-            .unwrap_or(false);
-
-        let metadata = image.metadata.as_ref().and_then(|m| {
-            serde_json::from_value::<crate::models::ImageMetadata>(m.clone())
-                .ok()
-                .map(|meta| ImageMetadataResponse {
-                    width: meta.width,
-                    height: meta.height,
-                })
-        });
-
-        image_responses.push(ImageResponse {
+    let mut response = cursor_page_response(pool.get_ref(), images, limit).await;
+    response.pagination.total = total;
+
+    HttpResponse::Ok().json(ApiResponse::success(response))
+}
+
+/// Build listing entries for `images`, fetching analysis flags and tags in bulk
+///
+/// Each image comes with its folder's name when the listing spans folders.
+/// Flags and tags that fail to load are left empty rather than failing the
+/// listing.
+async fn image_responses(pool: &PgPool, images: Vec<(Image, Option<String>)>) -> Vec<ImageResponse> {
+    let image_ids: Vec<i64> = images.iter().map(|(image, _)| image.image_id).collect();
+    let analyzed = ImageRepository::has_analysis_bulk(pool, &image_ids)
+        .await
+        .unwrap_or_default();
+    let mut tags = ImageRepository::find_tags_bulk(pool, &image_ids)
+        .await
+        .unwrap_or_default();
+
+    images
+        .into_iter()
+        .map(|(image, folder_name)| ImageResponse {
+            has_analysis: analyzed.get(&image.image_id).copied().unwrap_or(false),
+            metadata: image.metadata.as_ref().and_then(ImageMetadataResponse::from_json),
+            tags: tags.remove(&image.image_id).unwrap_or_default(),
             image_id: image.image_id,
             folder_id: image.folder_id,
+            folder_name,
             original_filename: image.original_filename,
             file_size: image.file_size,
             mime_type: image.mime_type,
-            metadata,
-            has_analysis,
             uploaded_at: image
                 .uploaded_at
                 .map(|dt| dt.to_rfc3339())
                 .unwrap_or_default(),
-        });
+            deleted_at: image.deleted_at.map(|dt| dt.to_rfc3339()),
+        })
+        .collect()
+}
+
+/// Build a cursor-paginated listing from a repository page of up to `limit + 1` images
+async fn cursor_page_response(pool: &PgPool, mut images: Vec<Image>, limit: i32) -> ImageListResponseV2 {
+    // Check if there are more items
+    let has_next = images.len() > limit as usize;
+    if has_next {
+        images.pop(); // Remove the extra item used for detection
     }
 
-    HttpResponse::Ok().json(ApiResponse::success(ImageListResponseV2 {
-        images: image_responses.clone(),
+    // Determine next cursor
+    let next_cursor = if has_next {
+        images.last().and_then(|img| img.uploaded_at.map(|dt| dt.to_rfc3339()))
+    } else {
+        None
+    };
+
+    let images = image_responses(pool, images.into_iter().map(|image| (image, None)).collect()).await;
+
+    let count = images.len() as i32;
+    ImageListResponseV2 {
+        images,
         pagination: CursorPaginationInfo {
             has_next,
             next_cursor,
-            count: image_responses.len() as i32,
+            count,
+            total: None,
         },
+    }
+}
+
+// ============================================================================
+// List Images Across Folders
+// ============================================================================
+
+/// List images from several folders as one cursor-paginated list
+///
+/// Images are merged across folders, newest upload first. Every folder must
+/// be owned by the caller; otherwise nothing is returned.
+#[utoipa::path(
+    post,
+    path = "/api/v1/images/list",
+    tag = "Image Management",
+    security(("bearer_auth" = [])),
+    request_body = ListImagesRequest,
+    responses(
+        (status = 200, description = "Images from all requested folders", body = ApiResponse<ImageListResponseV2>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "One or more folders not found")
+    )
+)]
+pub async fn list_images_multi(
+    pool: web::Data<ReadPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<ListImagesRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    if let Err(errors) = request.validate() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if let Err(response) = check_batch_size(&config.limits, request.folder_ids.len()) {
+        return response;
+    }
+
+    let mut folder_ids = request.folder_ids.clone();
+    folder_ids.sort_unstable();
+    folder_ids.dedup();
+
+    // Verify ownership of every folder in one query
+    match FolderRepository::find_owned_ids(pool.get_ref(), &folder_ids, user.user_id).await {
+        Ok(owned) if owned.len() == folder_ids.len() => {}
+        Ok(_) => {
+            return HttpResponse::NotFound()
+                .json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify folders: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to verify folders"));
+        }
+    }
+
+    let limit = request.limit();
+
+    let images = match ImageRepository::find_by_folder_ids_cursor(
+        pool.get_ref(),
+        &folder_ids,
+        request.cursor_datetime(),
+        limit,
+    )
+    .await
+    {
+        Ok(images) => images,
+        Err(e) => {
+            tracing::error!("Failed to list images: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to list images"));
+        }
+    };
+
+    HttpResponse::Ok().json(ApiResponse::success(cursor_page_response(pool.get_ref(), images, limit).await))
+}
+
+// ============================================================================
+// Upload Constraints
+// ============================================================================
+
+/// Get the accepted upload formats and limits
+///
+/// Public, so clients can validate files before the user signs in.
+#[utoipa::path(
+    get,
+    path = "/api/v1/upload/constraints",
+    tag = "Image Management",
+    responses(
+        (status = 200, description = "Upload constraints", body = ApiResponse<UploadConstraintsResponse>)
+    )
+)]
+pub async fn get_upload_constraints(config: web::Data<AppConfig>) -> HttpResponse {
+    let allowed_extensions = ALLOWED_MIME_TYPES
+        .iter()
+        .flat_map(|mime| ImageService::extensions_for_mime(mime))
+        .map(|ext| ext.to_string())
+        .collect();
+
+    HttpResponse::Ok().json(ApiResponse::success(UploadConstraintsResponse {
+        allowed_mime_types: ALLOWED_MIME_TYPES.iter().map(|m| m.to_string()).collect(),
+        allowed_extensions,
+        max_file_size_bytes: config.limits.upload_limit() as u64,
+        max_filename_length: MAX_FILENAME_LENGTH as u64,
+        min_image_width: config.analysis.min_image_width,
+        min_image_height: config.analysis.min_image_height,
+        max_images_per_folder: (config.upload.max_images_per_folder > 0)
+            .then_some(config.upload.max_images_per_folder),
     }))
 }
@@ -1,12 +1,110 @@
+pub mod admin_handlers;
 pub mod analysis_handlers;
 pub mod auth_handlers;
+pub mod export_handlers;
 pub mod folder_handlers;
 pub mod image_handlers;
+pub mod user_handlers;
+pub mod worker_handlers;
 
-pub use analysis_handlers::{analyze_image, get_analysis_history, get_job_result, get_job_status};
-pub use auth_handlers::{login, logout, register};
-pub use folder_handlers::{create_folder, delete_folder, list_folders, rename_folder};
+use actix_web::error::{InternalError, JsonPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse};
+
+use crate::config::settings::LimitsConfig;
+use crate::domain::ApiResponse;
+
+pub use admin_handlers::{backfill_thumbnails, get_effective_config, get_queue_health, resolve_job};
+pub use analysis_handlers::{
+    analyze_image, batch_analyze_images, cancel_job, get_analysis_history, get_analysis_totals,
+    get_job_result, get_job_status, get_latest_image_result, get_overlay_url, get_scaled_detections,
+    list_my_jobs, retry_failed_jobs, retry_job, stream_folder_results,
+};
+pub use auth_handlers::{change_password, login, logout, register};
+pub use export_handlers::{get_data_export, request_data_export};
+pub use folder_handlers::{
+    copy_folder, create_folder, delete_folder, ensure_folder, list_folders, merge_folder, purge_folder,
+    rename_folder, list_trash, restore_folder,
+};
 pub use image_handlers::{
-    confirm_upload, delete_image, get_image, get_image_download_url, get_image_file, list_images,
-    list_images_v2, rename_image, request_upload, upload_image,
+    add_image_tag, confirm_upload, delete_image, get_image, get_image_download_url, get_image_download_urls,
+    get_image_file, get_image_thumbnail, list_all_images, list_images,
+    get_upload_constraints, list_images_multi, list_images_v2, move_image, rename_image,
+    get_shared_image, remove_image_tag, request_upload, search_images, share_image, update_images_metadata, upload_image,
+    upload_image_raw,
 };
+pub use user_handlers::{get_account_breakdown, get_profile, update_preferences};
+pub use worker_handlers::ingest_job_results_batch;
+
+/// Reject a batch of `size` items above `limits.max_batch_size`
+///
+/// Every batch endpoint goes through this so they share one cap and one error.
+pub(crate) fn check_batch_size(limits: &LimitsConfig, size: usize) -> Result<(), HttpResponse> {
+    if size > limits.max_batch_size {
+        return Err(HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "BATCH_TOO_LARGE",
+            format!(
+                "Batch of {} items exceeds the maximum of {}",
+                size, limits.max_batch_size
+            ),
+        )));
+    }
+    Ok(())
+}
+
+/// JSON extractor config rejecting bodies above `limits.max_json_bytes`
+///
+/// Oversized bodies get a 413 `ApiResponse` as soon as their declared or
+/// streamed length passes the limit, without buffering the rest.
+pub fn json_config(limits: &LimitsConfig) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limits.max_json_bytes)
+        .error_handler(|err, _req| match err {
+            JsonPayloadError::OverflowKnownLength { limit, .. } | JsonPayloadError::Overflow { limit } => {
+                let response = payload_too_large(limit);
+                InternalError::from_response(err, response).into()
+            }
+            err => err.into(),
+        })
+}
+
+/// Limit for bodies read whole through `web::Bytes` or `String`
+pub fn payload_config(limits: &LimitsConfig) -> web::PayloadConfig {
+    web::PayloadConfig::new(limits.max_json_bytes)
+}
+
+/// 413 response for a body above `limit` bytes
+pub(crate) fn payload_too_large(limit: usize) -> HttpResponse {
+    HttpResponse::PayloadTooLarge().json(ApiResponse::<()>::error(
+        "PAYLOAD_TOO_LARGE",
+        format!("Request body exceeds the maximum of {} bytes", limit),
+    ))
+}
+
+/// What was wrong with a rejected request, which decides its status code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValidationKind {
+    /// The body or parameters could not be parsed at all (400)
+    Malformed,
+    /// Well-formed, but the values can't be acted on: out of range, pointing
+    /// at something missing, or unsuitable for the operation (422)
+    Unprocessable,
+}
+
+impl ValidationKind {
+    pub(crate) fn status(self) -> StatusCode {
+        match self {
+            ValidationKind::Malformed => StatusCode::BAD_REQUEST,
+            ValidationKind::Unprocessable => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+/// Error response for a request rejected as `kind`
+pub(crate) fn validation_error(
+    kind: ValidationKind,
+    code: &str,
+    message: impl Into<String>,
+) -> HttpResponse {
+    HttpResponse::build(kind.status()).json(ApiResponse::<()>::error(code, message))
+}
@@ -0,0 +1,184 @@
+//! User Handlers
+//!
+//! Settings the authenticated user manages for their own account.
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+
+use crate::config::settings::AppConfig;
+use crate::db::ReadPool;
+use crate::domain::ApiResponse;
+use crate::dto::{
+    AccountBreakdownResponse, PreferencesResponse, ProfileResponse, UpdatePreferencesRequest,
+    UserResponse,
+};
+use crate::middleware::AuthenticatedUser;
+use crate::repositories::{ImageRepository, JobRepository, UserRepository};
+
+// ============================================================================
+// Get Profile
+// ============================================================================
+
+/// Get the authenticated user's profile
+///
+/// Lets a client check that a stored token still belongs to a live account.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The user's profile", body = ApiResponse<ProfileResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_profile(pool: web::Data<PgPool>, req: HttpRequest) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    match UserRepository::find_by_id(pool.get_ref(), user.user_id).await {
+        Ok(Some(profile)) => HttpResponse::Ok().json(ApiResponse::success(ProfileResponse {
+            user: UserResponse {
+                user_id: profile.user_id,
+                username: profile.username,
+            },
+            created_at: profile
+                .created_at
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            preferred_model_version: profile.preferred_model_version,
+        })),
+        Ok(None) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "User not found"))
+        }
+        Err(e) => {
+            tracing::error!("Failed to get profile: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get profile"))
+        }
+    }
+}
+
+// ============================================================================
+// Update Preferences
+// ============================================================================
+
+/// Update the authenticated user's preferences
+///
+/// The preferred model version must be one of `analysis.model_versions`.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/me/preferences",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    request_body = UpdatePreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences updated", body = ApiResponse<PreferencesResponse>),
+        (status = 400, description = "Unknown model version (UNKNOWN_MODEL_VERSION)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn update_preferences(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    req: HttpRequest,
+    body: web::Json<UpdatePreferencesRequest>,
+) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let request = body.into_inner();
+
+    if let Some(version) = &request.preferred_model_version {
+        if !config.analysis.is_known_model(version) {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+                "UNKNOWN_MODEL_VERSION",
+                format!(
+                    "Unknown model version '{}'; expected one of: {}",
+                    version,
+                    config.analysis.model_versions.join(", ")
+                ),
+            ));
+        }
+    }
+
+    match UserRepository::set_preferred_model_version(
+        pool.get_ref(),
+        user.user_id,
+        request.preferred_model_version.as_deref(),
+    )
+    .await
+    {
+        Ok(true) => HttpResponse::Ok().json(ApiResponse::success(PreferencesResponse {
+            preferred_model_version: request.preferred_model_version,
+        })),
+        Ok(false) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "User not found"))
+        }
+        Err(e) => {
+            tracing::error!("Failed to update preferences: {:?}", e);
+            HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to update preferences"))
+        }
+    }
+}
+
+// ============================================================================
+// Account Breakdown
+// ============================================================================
+
+/// Count the user's images by MIME type and their jobs by status
+///
+/// Only live images in live folders are counted, like the analysis totals.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/breakdown",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Grouped counts", body = ApiResponse<AccountBreakdownResponse>),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn get_account_breakdown(pool: web::Data<ReadPool>, req: HttpRequest) -> HttpResponse {
+    let user = match req.extensions().get::<AuthenticatedUser>() {
+        Some(u) => u.clone(),
+        None => {
+            return HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"));
+        }
+    };
+
+    let counts = futures::try_join!(
+        ImageRepository::count_by_mime_type_for_user(pool.get_ref(), user.user_id),
+        JobRepository::count_by_status_for_user(pool.get_ref(), user.user_id),
+    );
+    let (images, jobs) = match counts {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::error!("Failed to get account breakdown: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::<()>::internal_error(&req, "Failed to get breakdown"));
+        }
+    };
+
+    HttpResponse::Ok().json(ApiResponse::success(AccountBreakdownResponse {
+        images_by_mime_type: images.into_iter().collect(),
+        jobs_by_status: jobs
+            .into_iter()
+            .map(|(status, count)| (status.to_string(), count))
+            .collect(),
+    }))
+}
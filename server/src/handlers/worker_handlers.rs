@@ -0,0 +1,153 @@
+//! Worker Handlers
+//!
+//! Endpoints called by analysis workers rather than users. Routes are wrapped
+//! in `WorkerAuth`, which checks the shared `X-Worker-Secret` header.
+
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::config::settings::{AnalysisConfig, AppConfig};
+use crate::domain::{ApiError, ApiResponse};
+use crate::dto::analysis::{BatchJobResultsResponse, JobResultEntry, JobResultIngestOutcome};
+use crate::handlers::check_batch_size;
+use crate::repositories::{JobRepository, RecordResultOutcome};
+
+// ============================================================================
+// Batch Result Ingestion
+// ============================================================================
+
+/// Store one worker-reported result in its own transaction
+///
+/// Errors are returned as the per-entry `ApiError` so the batch can carry on.
+async fn ingest_job_result(
+    pool: &PgPool,
+    analysis: &AnalysisConfig,
+    mut entry: JobResultEntry,
+) -> Result<i64, ApiError> {
+    let api_error = |code: &str, message: String| ApiError::new(code, message);
+
+    if let Err(errors) = entry.validate() {
+        return Err(api_error(
+            "VALIDATION_ERROR",
+            format!("Validation failed: {}", errors),
+        ));
+    }
+
+    if let Err(count) = entry.cap_detections(analysis.max_detections, analysis.detection_overflow) {
+        return Err(api_error(
+            "TOO_MANY_DETECTIONS",
+            format!(
+                "Result has {} bounding boxes; at most {} are accepted",
+                count, analysis.max_detections
+            ),
+        ));
+    }
+
+    let internal_error = |e: sqlx::Error| {
+        tracing::error!("Failed to record result for job {}: {:?}", entry.job_id, e);
+        api_error("INTERNAL_ERROR", "Failed to record result".to_string())
+    };
+
+    let mut tx = pool.begin().await.map_err(internal_error)?;
+
+    let outcome = JobRepository::record_result(
+        &mut tx,
+        entry.job_id,
+        entry.counts.viable,
+        entry.counts.apoptosis,
+        entry.counts.other,
+        entry.avg_confidence,
+        entry.raw_data.clone(),
+        entry.summary.clone(),
+    )
+    .await
+    .map_err(internal_error)?;
+
+    match outcome {
+        RecordResultOutcome::Recorded(result) => {
+            tx.commit().await.map_err(internal_error)?;
+            Ok(result.result_id)
+        }
+        // Dropping the transaction rolls it back
+        RecordResultOutcome::JobNotFound => {
+            Err(api_error("NOT_FOUND", "Job not found".to_string()))
+        }
+        RecordResultOutcome::AlreadyFinished(status) => Err(api_error(
+            "CONFLICT",
+            format!("Job is already {}", status),
+        )),
+    }
+}
+
+/// Ingest a batch of analysis results from a worker
+///
+/// Each entry is validated and stored in its own transaction, so one bad
+/// entry does not fail the rest. The response reports each job's outcome
+/// in request order; an entry with more than `analysis.max_detections`
+/// boxes fails with `TOO_MANY_DETECTIONS` or is truncated, per
+/// `analysis.detection_overflow`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs/results/batch",
+    tag = "Workers",
+    security(("worker_secret" = [])),
+    request_body = Vec<JobResultEntry>,
+    responses(
+        (status = 200, description = "Per-job ingestion outcomes", body = ApiResponse<BatchJobResultsResponse>),
+        (status = 400, description = "Empty or oversized batch"),
+        (status = 401, description = "Invalid worker credentials or signature, or a replayed signed request (REPLAYED_REQUEST)"),
+        (status = 413, description = "Signed body above `limits.max_json_bytes`"),
+        (status = 503, description = "Signed request could not be checked for replay")
+    )
+)]
+pub async fn ingest_job_results_batch(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    body: web::Json<Vec<JobResultEntry>>,
+) -> HttpResponse {
+    let entries = body.into_inner();
+
+    if entries.is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+            "VALIDATION_ERROR",
+            "Validation failed: batch must contain at least one result",
+        ));
+    }
+
+    if let Err(response) = check_batch_size(&config.limits, entries.len()) {
+        return response;
+    }
+
+    let mut results = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let job_id = entry.job_id;
+        let outcome = match ingest_job_result(pool.get_ref(), &config.analysis, entry).await {
+            Ok(result_id) => JobResultIngestOutcome {
+                job_id,
+                success: true,
+                result_id: Some(result_id),
+                error: None,
+            },
+            Err(error) => {
+                tracing::warn!("Rejected result for job {}: {}", job_id, error.message);
+                JobResultIngestOutcome {
+                    job_id,
+                    success: false,
+                    result_id: None,
+                    error: Some(error),
+                }
+            }
+        };
+        results.push(outcome);
+    }
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+
+    HttpResponse::Ok().json(ApiResponse::success(BatchJobResultsResponse {
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    }))
+}
@@ -7,8 +7,10 @@ pub mod db;
 pub mod domain;
 pub mod dto;
 pub mod handlers;
+pub mod metrics;
 pub mod middleware;
 pub mod models;
 pub mod repositories;
 pub mod routes;
-pub mod services;
\ No newline at end of file
+pub mod services;
+pub mod workers;
\ No newline at end of file
@@ -1,8 +1,8 @@
-use actix_cors::Cors;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 use crate::routes::ApiDoc;
 use std::io::Result;
+use std::time::Duration;
 
 #[cfg(not(target_env = "msvc"))]
 use jemallocator::Jemalloc;
@@ -11,7 +11,7 @@ use jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
-use actix_web::{web, App, HttpServer, middleware as actix_middleware};
+use actix_web::{http::Method, web, App, HttpServer, middleware as actix_middleware};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod config;
@@ -19,13 +19,14 @@ mod db;
 mod domain;
 mod dto;
 mod handlers;
+mod metrics;
 mod models;
 mod middleware;
 mod repositories;
 mod routes;
 mod services;
 // mod utils;
-// mod workers;
+mod workers;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -40,6 +41,12 @@ async fn main() -> Result<()> {
     let config = config::settings::AppConfig::build()
         .expect("Failed to load configuration");
 
+    if let Some(path) = &config.password.denylist_path {
+        let count = services::password_denylist::load(path)
+            .expect("Failed to load password denylist");
+        tracing::info!("Loaded {} denylisted passwords", count);
+    }
+
     let bind_address = format!("{}:{}", config.server.host, config.server.port);
 
     let pool = db::connection::create_pool(&config.database)
@@ -48,6 +55,21 @@ async fn main() -> Result<()> {
 
     tracing::info!("Database pool created");
 
+    let read_pool = db::connection::create_read_pool(&config.database, &pool)
+        .await
+        .expect("Failed to create read database pool");
+
+    if config.database.read_url.is_some() {
+        tracing::info!("Read replica pool created");
+    }
+
+    if config.database.pool_metrics_interval_secs > 0 {
+        actix_web::rt::spawn(db::pool_metrics::run(
+            pool.clone(),
+            Duration::from_secs(config.database.pool_metrics_interval_secs),
+        ));
+    }
+
     // Run database migrations
     tracing::info!("Running database migrations...");
     match sqlx::migrate!("./migrations").run(&pool).await {
@@ -59,11 +81,16 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Initialize S3 storage service
-    let s3_storage = services::S3StorageService::new(&config.storage)
-        .expect("Failed to create S3 storage service");
-    
-    tracing::info!("S3 storage service initialized: endpoint={}", config.storage.endpoint);
+    // Drop revoked tokens once they have expired anyway
+    actix_web::rt::spawn(services::RevokedTokenCleanup::run(
+        pool.clone(),
+        Duration::from_secs(config.jwt.revocation_cleanup_interval_secs),
+        config.jwt.clock_skew_secs,
+    ));
+
+    // Initialize storage backend (S3 or local filesystem, per STORAGE__BACKEND)
+    let storage = services::create_storage_backend(&config.storage)
+        .expect("Failed to create storage backend");
 
     // Initialize RabbitMQ service
     let rabbitmq_service = services::RabbitmqService::new(&config.rabbitmq)
@@ -76,28 +103,113 @@ async fn main() -> Result<()> {
         config.rabbitmq.analysis_queue
     );
 
+    // Re-publish jobs left pending by broker outages
+    actix_web::rt::spawn(services::JobRequeueService::run(
+        pool.clone(),
+        rabbitmq_service.clone(),
+        Duration::from_secs(config.rabbitmq.republish_interval_secs),
+    ));
+
+    // Reflect worker progress reports on job rows
+    if let Some(queue) = config.rabbitmq.progress_queue.clone() {
+        actix_web::rt::spawn(services::JobProgressConsumer::run(
+            pool.clone(),
+            rabbitmq_service.clone(),
+            queue,
+        ));
+    }
+
+    // Store finished analyses workers publish to the results queue
+    if let Some(queue) = config.rabbitmq.results_queue.clone() {
+        actix_web::rt::spawn(workers::ResultsConsumer::run(
+            pool.clone(),
+            config.analysis.clone(),
+            rabbitmq_service.clone(),
+            queue,
+            config.rabbitmq.results_dead_letter_queue.clone(),
+        ));
+    }
+
+    if let Err(e) = middleware::cors::validate(&config.cors) {
+        panic!("Invalid CORS configuration: {}", e);
+    }
+    if config.cors.allowed_origins.is_empty() {
+        tracing::warn!("CORS__ALLOWED_ORIGINS is not set; allowing requests from any origin");
+    }
+
     // Clone jwt_config for use in app_data
     let jwt_config = config.jwt.clone();
+    let admin_config = config.admin.clone();
+    let worker_config = config.worker.clone();
+    let app_config = config.clone();
+    // One limiter for all workers so the per-user cap is process-wide
+    let upload_limiter = web::Data::new(services::UploadLimiter::default());
+
+    let request_timeout = Duration::from_secs(config.server.request_timeout_secs);
+    let batch_request_timeout = Duration::from_secs(config.server.batch_request_timeout_secs);
+    let upload_request_timeout = Some(Duration::from_secs(config.server.upload_request_timeout_secs))
+        .filter(|timeout| !timeout.is_zero());
+    let client_request_timeout = Duration::from_millis(config.server.client_request_timeout_ms);
 
     HttpServer::new(move || {
-        // CORS configuration - allow all origins, methods, and headers
-        let cors = Cors::permissive();
+        let cors = middleware::cors::cors(&app_config.cors);
 
         let jwt_config_clone = jwt_config.clone();
+        let admin_config_clone = admin_config.clone();
+        let worker_config_clone = worker_config.clone();
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(read_pool.clone()))
             .app_data(web::Data::new(jwt_config.clone()))
-            .app_data(web::Data::new(s3_storage.clone()))
+            .app_data(web::Data::new(app_config.clone()))
+            .app_data(web::Data::from(storage.clone()))
             .app_data(web::Data::new(rabbitmq_service.clone()))
+            .app_data(upload_limiter.clone())
+            .app_data(handlers::json_config(&app_config.limits))
+            .app_data(handlers::payload_config(&app_config.limits))
+            // `?envelope=false` on GET requests returns bare payloads
+            .wrap(middleware::ResponseEnvelope::new())
+            .wrap(
+                middleware::RequestTimeout::new(request_timeout)
+                    .route_timeout("/analyze/batch", batch_request_timeout)
+                    // Whole files move through these, so they get longer
+                    .method_timeout(Method::POST, "/images", upload_request_timeout)
+                    .method_timeout(Method::PUT, "/images/raw", upload_request_timeout)
+                    .method_timeout(Method::POST, "/images/confirm-upload", upload_request_timeout)
+                    .method_timeout(Method::POST, "/copy", upload_request_timeout)
+                    .method_timeout(Method::POST, "/thumbnails/backfill", upload_request_timeout)
+                    // Streaming responses: duration depends on the client's bandwidth
+                    .exempt("/file")
+                    .exempt("/results.ndjson"),
+            )
             .wrap(cors)
+            // Runs before CORS so plain-HTTP requests never reach a handler
+            .wrap(
+                middleware::RequireHttps::new(app_config.server.clone())
+                    // Probes come from the orchestrator, not through the TLS proxy
+                    .exempt("/api/v1/health")
+                    .exempt("/api/v1/health/ready"),
+            )
             .wrap(middleware::SecurityHeaders::new())
             .wrap(actix_middleware::Logger::default())
-            .configure(|cfg| routes::configure_routes(cfg, jwt_config_clone))
+            // Outermost, so every log line for the request carries its id
+            .wrap(middleware::RequestIdMiddleware::new())
+            .configure(|cfg| {
+                routes::configure_routes(
+                    cfg,
+                    jwt_config_clone,
+                    admin_config_clone,
+                    worker_config_clone,
+                    &app_config.limits,
+                )
+            })
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}")
                     .url("/api-docs/openapi.json", ApiDoc::openapi())
             )
     })
+    // Bounds how long a client may take to send the request head
+    .client_request_timeout(client_request_timeout)
     .bind(&bind_address)?
     .run()
     .await
@@ -0,0 +1,202 @@
+//! Metrics
+//!
+//! Minimal in-process metrics registry, rendered in the Prometheus text
+//! exposition format at `GET /api/v1/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+/// Authentication failures, labelled by `reason` (the middleware error code)
+pub const AUTH_FAILURES_TOTAL: &str = "auth_failures_total";
+
+/// Database pool connections, labelled by `state` (`total`, `idle`, `active`)
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+
+/// Attempts to reopen the RabbitMQ publishing channel, labelled by `outcome`
+/// (`success`, `failure`)
+pub const RABBITMQ_RECONNECTS_TOTAL: &str = "rabbitmq_reconnects_total";
+
+/// Time taken to generate one presigned URL, labelled by `outcome`
+/// (`success`, `failure`)
+pub const PRESIGN_DURATION_SECONDS: &str = "presign_duration_seconds";
+
+type Labels = Vec<(&'static str, String)>;
+
+struct Family {
+    help: &'static str,
+    kind: &'static str,
+    series: BTreeMap<Labels, f64>,
+    /// Observations per label set, for summaries (`series` holds their sum)
+    counts: BTreeMap<Labels, u64>,
+}
+
+/// Named metric families, each holding one value per label set
+#[derive(Default)]
+pub struct Registry {
+    families: Mutex<BTreeMap<&'static str, Family>>,
+}
+
+impl Registry {
+    /// Add one to a counter, creating it on first use
+    pub fn increment_counter(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+    ) {
+        let labels = labels.iter().map(|(k, v)| (*k, v.to_string())).collect();
+        let mut families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            kind: "counter",
+            series: BTreeMap::new(),
+            counts: BTreeMap::new(),
+        });
+        *family.series.entry(labels).or_insert(0.0) += 1.0;
+    }
+
+    /// Set a gauge to `value`, creating it on first use
+    pub fn set_gauge(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        let labels = labels.iter().map(|(k, v)| (*k, v.to_string())).collect();
+        let mut families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            kind: "gauge",
+            series: BTreeMap::new(),
+            counts: BTreeMap::new(),
+        });
+        family.series.insert(labels, value);
+    }
+
+    /// Record one observation in a summary, creating it on first use
+    ///
+    /// Rendered as `{name}_sum` and `{name}_count`, without quantiles.
+    pub fn observe(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        let labels: Labels = labels.iter().map(|(k, v)| (*k, v.to_string())).collect();
+        let mut families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            kind: "summary",
+            series: BTreeMap::new(),
+            counts: BTreeMap::new(),
+        });
+        *family.series.entry(labels.clone()).or_insert(0.0) += value;
+        *family.counts.entry(labels).or_insert(0) += 1;
+    }
+
+    /// Render every metric in the Prometheus text format
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+
+        for (name, family) in families.iter() {
+            let _ = writeln!(out, "# HELP {} {}", name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind);
+            for (labels, value) in &family.series {
+                match family.counts.get(labels) {
+                    Some(count) => {
+                        let labels = format_labels(labels);
+                        let _ = writeln!(out, "{}_sum{} {}", name, labels, value);
+                        let _ = writeln!(out, "{}_count{} {}", name, labels, count);
+                    }
+                    None => {
+                        let _ = writeln!(out, "{}{} {}", name, format_labels(labels), value);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Process-wide registry
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::default)
+}
+
+fn format_labels(labels: &Labels) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", key, escaped)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counter_with_labels() {
+        let registry = Registry::default();
+        registry.increment_counter("requests_total", "Requests", &[("reason", "A")]);
+        registry.increment_counter("requests_total", "Requests", &[("reason", "A")]);
+        registry.increment_counter("requests_total", "Requests", &[("reason", "B")]);
+
+        let rendered = registry.render();
+
+        assert!(rendered.contains("# HELP requests_total Requests\n"));
+        assert!(rendered.contains("# TYPE requests_total counter\n"));
+        assert!(rendered.contains("requests_total{reason=\"A\"} 2\n"));
+        assert!(rendered.contains("requests_total{reason=\"B\"} 1\n"));
+    }
+
+    #[test]
+    fn test_gauge_keeps_latest_value() {
+        let registry = Registry::default();
+        registry.set_gauge("queue_depth", "Queue depth", &[("queue", "jobs")], 5.0);
+        registry.set_gauge("queue_depth", "Queue depth", &[("queue", "jobs")], 2.0);
+
+        let rendered = registry.render();
+
+        assert!(rendered.contains("# TYPE queue_depth gauge\n"));
+        assert!(rendered.contains("queue_depth{queue=\"jobs\"} 2\n"));
+        assert!(!rendered.contains("queue_depth{queue=\"jobs\"} 5\n"));
+    }
+
+    #[test]
+    fn test_summary_renders_sum_and_count() {
+        let registry = Registry::default();
+        registry.observe("work_seconds", "Work time", &[("outcome", "success")], 0.25);
+        registry.observe("work_seconds", "Work time", &[("outcome", "success")], 0.5);
+
+        let rendered = registry.render();
+
+        assert!(rendered.contains("# TYPE work_seconds summary\n"));
+        assert!(rendered.contains("work_seconds_sum{outcome=\"success\"} 0.75\n"));
+        assert!(rendered.contains("work_seconds_count{outcome=\"success\"} 2\n"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let registry = Registry::default();
+        registry.increment_counter("odd_total", "Odd labels", &[("value", "a\"b\\c")]);
+
+        assert!(registry.render().contains("odd_total{value=\"a\\\"b\\\\c\"} 1\n"));
+    }
+}
@@ -0,0 +1,101 @@
+//! Admin Authorization Middleware
+//!
+//! Restricts a scope to operators listed in `ADMIN__USERNAMES`.
+//! Must run after `AuthenticationMiddleware` (i.e. be wrapped before it),
+//! since it reads the `AuthenticatedUser` that middleware injects.
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpResponse,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::config::settings::AdminConfig;
+use crate::domain::ApiResponse;
+use crate::middleware::AuthenticatedUser;
+
+// ============================================================================
+// Admin Guard Middleware
+// ============================================================================
+
+/// Admin Guard Middleware Factory
+///
+/// Responds 403 unless the authenticated user is a configured admin.
+pub struct AdminGuard {
+    admin_config: AdminConfig,
+}
+
+impl AdminGuard {
+    pub fn new(admin_config: AdminConfig) -> Self {
+        Self { admin_config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AdminGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AdminGuardService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AdminGuardService {
+            service: Rc::new(service),
+            admin_config: self.admin_config.clone(),
+        })
+    }
+}
+
+pub struct AdminGuardService<S> {
+    service: Rc<S>,
+    admin_config: AdminConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for AdminGuardService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let username = req
+            .extensions()
+            .get::<AuthenticatedUser>()
+            .map(|user| user.username.clone());
+        let is_admin = username
+            .as_deref()
+            .is_some_and(|username| self.admin_config.is_admin(username));
+
+        Box::pin(async move {
+            if is_admin {
+                let res = service.call(req).await?;
+                return Ok(res.map_into_left_body());
+            }
+
+            let response = match username {
+                None => HttpResponse::Unauthorized()
+                    .json(ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required")),
+                Some(username) => {
+                    tracing::warn!("Non-admin user {} attempted to access {}", username, req.path());
+                    HttpResponse::Forbidden()
+                        .json(ApiResponse::<()>::error("FORBIDDEN", "Admin access required"))
+                }
+            };
+            Ok(req.into_response(response).map_into_right_body())
+        })
+    }
+}
@@ -0,0 +1,131 @@
+//! CORS
+//!
+//! Builds the `Cors` middleware from `CorsConfig`.
+
+use actix_cors::Cors;
+use actix_web::http::{header, Method, Uri};
+
+use crate::config::settings::CorsConfig;
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
+/// Methods `CORS__ALLOWED_METHODS` may list
+const KNOWN_METHODS: [Method; 9] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::HEAD,
+    Method::OPTIONS,
+    Method::CONNECT,
+    Method::TRACE,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum CorsConfigError {
+    #[error("origin {0:?} is not allowed; leave CORS__ALLOWED_ORIGINS empty to allow any origin")]
+    WildcardOrigin(String),
+    #[error("origin {0:?} must be a scheme and host such as https://app.example.com")]
+    InvalidOrigin(String),
+    #[error("unknown method {0:?} in CORS__ALLOWED_METHODS")]
+    UnknownMethod(String),
+}
+
+/// Check the configured origins and methods
+///
+/// Run once at startup: `cors` builds the middleware per worker and would
+/// otherwise panic on a bad origin or silently drop a misspelt method.
+pub fn validate(config: &CorsConfig) -> Result<(), CorsConfigError> {
+    for origin in &config.allowed_origins {
+        if origin.contains('*') {
+            return Err(CorsConfigError::WildcardOrigin(origin.clone()));
+        }
+        let valid = origin.parse::<Uri>().is_ok_and(|uri| {
+            matches!(uri.scheme_str(), Some("http" | "https"))
+                && uri.host().is_some()
+                && uri.path_and_query().is_none_or(|path| path.as_str() == "/")
+        });
+        if !valid {
+            return Err(CorsConfigError::InvalidOrigin(origin.clone()));
+        }
+    }
+
+    for method in &config.allowed_methods {
+        parse_method(method).ok_or_else(|| CorsConfigError::UnknownMethod(method.clone()))?;
+    }
+
+    Ok(())
+}
+
+fn parse_method(method: &str) -> Option<Method> {
+    KNOWN_METHODS
+        .into_iter()
+        .find(|known| known.as_str().eq_ignore_ascii_case(method.trim()))
+}
+
+/// CORS middleware allowing the configured origins and methods
+///
+/// Without configured origins any origin is allowed, which suits local
+/// development only. Expects a config that passed `validate`.
+pub fn cors(config: &CorsConfig) -> Cors {
+    if config.allowed_origins.is_empty() {
+        return Cors::permissive();
+    }
+
+    let methods: Vec<Method> = config
+        .allowed_methods
+        .iter()
+        .filter_map(|method| parse_method(method))
+        .collect();
+
+    let mut cors = config
+        .allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(methods)
+        .allow_any_header()
+        // Caching, downloads and support requests need these from scripts
+        .expose_headers([
+            header::ETAG,
+            header::LAST_MODIFIED,
+            header::CONTENT_DISPOSITION,
+            header::HeaderName::from_static(REQUEST_ID_HEADER),
+        ])
+        .max_age(3600);
+
+    if config.allow_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(origins: &[&str], methods: &[&str]) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allowed_methods: methods.iter().map(|m| m.to_string()).collect(),
+            allow_credentials: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_origins_and_known_methods() {
+        let config = config(&["https://app.example.com", "http://localhost:3000/"], &["get", "PATCH"]);
+        assert!(validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_origins_and_methods() {
+        for origin in ["*", "https://*.example.com", "app.example.com", "ftp://example.com", "https://example.com/app", "not a url"] {
+            assert!(validate(&config(&[origin], &["GET"])).is_err(), "{origin} accepted");
+        }
+        assert!(matches!(
+            validate(&config(&["https://app.example.com"], &["GET", "FETCH"])),
+            Err(CorsConfigError::UnknownMethod(method)) if method == "FETCH"
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! Response Envelope Middleware
+//!
+//! Lets integrations opt out of the `ApiResponse` envelope on read endpoints
+//! with `?envelope=false`. Successful responses become the bare `data` value;
+//! errors keep their HTTP status and become an `application/problem+json`
+//! body. Clients sending `Accept: application/problem+json` get that error
+//! shape on any endpoint while successes stay enveloped. The enveloped form
+//! stays the default.
+
+use actix_web::{
+    body::{to_bytes, BoxBody, EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorInternalServerError,
+    http::{header, Method},
+    web, Error, HttpResponse,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde::Deserialize;
+use std::rc::Rc;
+
+use crate::domain::{ApiError, ProblemDetails};
+
+/// Query parameter controlling the envelope
+#[derive(Debug, Deserialize)]
+struct EnvelopeQuery {
+    envelope: Option<bool>,
+}
+
+/// Whether the request opted out of the envelope (GET requests only)
+fn wants_bare_response(req: &ServiceRequest) -> bool {
+    req.method() == Method::GET
+        && web::Query::<EnvelopeQuery>::from_query(req.query_string())
+            .is_ok_and(|query| query.envelope == Some(false))
+}
+
+/// Whether the client accepts RFC 9457 problem details for errors
+fn accepts_problem_json(req: &ServiceRequest) -> bool {
+    req.headers()
+        .get_all(header::ACCEPT)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_type| media_type.split(';').next())
+        .any(|media_type| media_type.trim().eq_ignore_ascii_case("application/problem+json"))
+}
+
+/// Strip the envelope from a serialized `ApiResponse`
+///
+/// Returns `None` if the body isn't an `ApiResponse`, e.g. a 304 or a
+/// non-envelope JSON payload, so it can be passed through untouched.
+fn unwrap_envelope(status: u16, title: &str, body: &[u8]) -> Option<(serde_json::Value, &'static str)> {
+    let mut envelope: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let success = envelope.get("success")?.as_bool()?;
+
+    if success {
+        let data = envelope.get_mut("data").map(serde_json::Value::take);
+        return Some((data.unwrap_or(serde_json::Value::Null), "application/json"));
+    }
+
+    let error: ApiError = serde_json::from_value(envelope.get_mut("error")?.take()).ok()?;
+    let problem = ProblemDetails::from_api_error(status, title, error);
+    Some((serde_json::to_value(problem).ok()?, "application/problem+json"))
+}
+
+// ============================================================================
+// Response Envelope Middleware
+// ============================================================================
+
+/// Response Envelope Middleware Factory
+pub struct ResponseEnvelope;
+
+impl ResponseEnvelope {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ResponseEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseEnvelope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ResponseEnvelopeService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ResponseEnvelopeService {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct ResponseEnvelopeService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseEnvelopeService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let bare = wants_bare_response(&req);
+        let problem_json = accepts_problem_json(&req);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            let is_json = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .is_some_and(|ct| ct.as_bytes().starts_with(b"application/json"));
+            let is_error = res.status().is_client_error() || res.status().is_server_error();
+            if !(bare || (problem_json && is_error)) || !is_json {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let status = res.status();
+            let headers = res.headers().clone();
+            let body = to_bytes(res.into_body())
+                .await
+                .map_err(|e| ErrorInternalServerError(e.into().to_string()))?;
+
+            let title = status.canonical_reason().unwrap_or("Error");
+            let mut builder = HttpResponse::build(status);
+            for (name, value) in headers.iter() {
+                if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                    builder.append_header((name.clone(), value.clone()));
+                }
+            }
+
+            let response = match unwrap_envelope(status.as_u16(), title, &body) {
+                Some((payload, content_type)) => builder
+                    .insert_header((header::CONTENT_TYPE, content_type))
+                    .body(payload.to_string()),
+                // Not an envelope; send the original bytes back unchanged
+                None => builder
+                    .insert_header((header::CONTENT_TYPE, "application/json"))
+                    .body(body),
+            };
+
+            let res = ServiceResponse::new(req, response.map_into_boxed_body());
+            Ok(res.map_body(|_, body: BoxBody| EitherBody::right(body)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ApiResponse;
+    use actix_web::{http::StatusCode, test, App};
+
+    async fn folder() -> HttpResponse {
+        HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({ "folder_id": 7, "folder_name": "Study" })))
+    }
+
+    async fn missing() -> HttpResponse {
+        HttpResponse::NotFound().json(ApiResponse::<()>::error("NOT_FOUND", "Folder not found"))
+    }
+
+    #[actix_web::test]
+    async fn test_envelope_false_returns_bare_object() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ResponseEnvelope::new())
+                .route("/folder", web::get().to(folder)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/folder?envelope=false").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body, serde_json::json!({ "folder_id": 7, "folder_name": "Study" }));
+
+        // Default stays enveloped
+        let res = test::call_service(&app, test::TestRequest::get().uri("/folder").to_request()).await;
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["folder_id"], 7);
+    }
+
+    #[actix_web::test]
+    async fn test_envelope_false_errors_use_problem_json() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ResponseEnvelope::new())
+                .route("/missing", web::get().to(missing)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/missing?envelope=false").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+
+        let body = to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["code"], "NOT_FOUND");
+        assert_eq!(body["detail"], "Folder not found");
+    }
+
+    #[actix_web::test]
+    async fn test_accept_problem_json_changes_error_shape() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ResponseEnvelope::new())
+                .route("/missing", web::post().to(missing))
+                .route("/folder", web::get().to(folder)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/missing")
+            .insert_header((header::ACCEPT, "application/json;q=0.5, application/problem+json"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+
+        let body = to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["detail"], "Folder not found");
+        assert!(body.get("success").is_none());
+
+        // Successful responses keep the envelope
+        let req = test::TestRequest::get()
+            .uri("/folder")
+            .insert_header((header::ACCEPT, "application/problem+json"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["success"], true);
+
+        // Without the Accept header errors stay enveloped
+        let res = test::call_service(&app, test::TestRequest::post().uri("/missing").to_request()).await;
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+    }
+}
@@ -1,5 +1,18 @@
+pub mod admin;
 pub mod auth;
+pub mod cors;
+pub mod envelope;
+pub mod request_id;
+pub mod require_https;
 pub mod security_headers;
+pub mod timeout;
+pub mod worker_auth;
 
-pub use auth::{AuthenticationMiddleware, AuthenticatedUser};
+pub use admin::AdminGuard;
+pub use auth::{AuthenticatedToken, AuthenticationMiddleware, AuthenticatedUser};
+pub use envelope::ResponseEnvelope;
+pub use request_id::RequestIdMiddleware;
+pub use require_https::RequireHttps;
 pub use security_headers::SecurityHeaders;
+pub use timeout::RequestTimeout;
+pub use worker_auth::WorkerAuth;
@@ -0,0 +1,177 @@
+//! Request ID Middleware
+//!
+//! Tags every request with an id that is echoed in the `X-Request-Id`
+//! response header, attached to error bodies, and recorded on a tracing span
+//! so a user-reported id can be grepped in the logs.
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, HttpMessage,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use tracing::Instrument;
+
+/// Request and response header carrying the id
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied id that is reused instead of replaced
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Id of the current request, stored in the request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Reuse the client's id when it is short and plain, otherwise
+    /// generate a new one
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        value
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            })
+            .map(|id| RequestId(id.to_string()))
+            .unwrap_or_else(RequestId::generate)
+    }
+
+    pub fn generate() -> Self {
+        RequestId(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+// ============================================================================
+// Request ID Middleware
+// ============================================================================
+
+/// Request ID Middleware Factory
+#[derive(Clone, Default)]
+pub struct RequestIdMiddleware;
+
+impl RequestIdMiddleware {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestIdService {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct RequestIdService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = RequestId::from_header(req.headers().get(REQUEST_ID_HEADER));
+        let header = HeaderValue::from_str(&request_id.0).ok();
+        let span = tracing::info_span!("request", request_id = %request_id.0);
+        req.extensions_mut().insert(request_id);
+
+        let service = self.service.clone();
+
+        Box::pin(
+            async move {
+                let mut res = service.call(req).await?;
+                if let Some(header) = header {
+                    res.headers_mut()
+                        .insert(HeaderName::from_static(REQUEST_ID_HEADER), header);
+                }
+                Ok(res)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ApiResponse;
+    use actix_web::{http::StatusCode, test, web, App, HttpRequest, HttpResponse};
+
+    async fn failing_handler(req: HttpRequest) -> HttpResponse {
+        HttpResponse::InternalServerError()
+            .json(ApiResponse::<()>::internal_error(&req, "Failed to load image"))
+    }
+
+    #[actix_web::test]
+    async fn test_internal_error_carries_request_id() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestIdMiddleware::new())
+                .route("/fail", web::get().to(failing_handler)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/fail").to_request()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let header = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+        assert!(uuid::Uuid::parse_str(&header).is_ok());
+
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "INTERNAL_ERROR");
+        assert_eq!(body["error"]["request_id"], header);
+        let timestamp = body["error"]["timestamp"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_client_request_id_reused_when_plain() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestIdMiddleware::new())
+                .route("/fail", web::get().to(failing_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/fail")
+            .insert_header((REQUEST_ID_HEADER, "mobile-42"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "mobile-42");
+
+        let req = test::TestRequest::get()
+            .uri("/fail")
+            .insert_header((REQUEST_ID_HEADER, "bad id; rm -rf"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let header = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(header).is_ok());
+    }
+}
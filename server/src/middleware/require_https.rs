@@ -0,0 +1,256 @@
+//! HTTPS Enforcement Middleware
+//!
+//! With `SERVER__REQUIRE_HTTPS` set, plain-HTTP requests are either redirected
+//! to the same URL over HTTPS (308, so the method and body are kept) or
+//! rejected with 403, per `SERVER__HTTPS_ENFORCEMENT`. The scheme is taken from
+//! the connection, or from `X-Forwarded-Proto` when the peer is one of
+//! `SERVER__TRUSTED_PROXIES`; the header is ignored from anyone else, since a
+//! client could otherwise claim HTTPS itself. Exempted paths (the health
+//! probes, which orchestrators call over plain HTTP inside the cluster) are
+//! always passed through.
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, uri::Authority},
+    Error, HttpResponse,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::config::settings::{HttpsEnforcement, ServerConfig};
+use crate::domain::ApiResponse;
+
+/// Header a TLS-terminating proxy sets to the scheme the client used
+pub const FORWARDED_PROTO_HEADER: &str = "X-Forwarded-Proto";
+
+// ============================================================================
+// Require HTTPS Middleware
+// ============================================================================
+
+/// Require HTTPS Middleware Factory
+///
+/// Passes every request through unless `require_https` is set.
+pub struct RequireHttps {
+    server_config: ServerConfig,
+    exempt_paths: Rc<Vec<&'static str>>,
+}
+
+impl RequireHttps {
+    pub fn new(server_config: ServerConfig) -> Self {
+        Self {
+            server_config,
+            exempt_paths: Rc::new(Vec::new()),
+        }
+    }
+
+    /// Accept plain HTTP for exactly `path` (health probes)
+    pub fn exempt(mut self, path: &'static str) -> Self {
+        Rc::make_mut(&mut self.exempt_paths).push(path);
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireHttps
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireHttpsService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireHttpsService {
+            service: Rc::new(service),
+            server_config: self.server_config.clone(),
+            exempt_paths: self.exempt_paths.clone(),
+        })
+    }
+}
+
+pub struct RequireHttpsService<S> {
+    service: Rc<S>,
+    server_config: ServerConfig,
+    exempt_paths: Rc<Vec<&'static str>>,
+}
+
+impl<S> RequireHttpsService<S> {
+    /// Whether the client reached us over HTTPS
+    fn is_https(req: &ServiceRequest, config: &ServerConfig) -> bool {
+        let from_trusted_proxy = req
+            .peer_addr()
+            .is_some_and(|addr| config.is_trusted_proxy(addr.ip()));
+
+        if from_trusted_proxy {
+            // A proxy chain appends its scheme; the first is the client's
+            let forwarded = req
+                .headers()
+                .get(FORWARDED_PROTO_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(str::trim);
+            if let Some(proto) = forwarded {
+                return proto.eq_ignore_ascii_case("https");
+            }
+        }
+
+        req.app_config().secure()
+    }
+
+    /// The request URL over HTTPS on the default port, if the host is known
+    fn https_url(req: &ServiceRequest) -> Option<String> {
+        let host = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Authority>().ok())?;
+        let path = req
+            .uri()
+            .path_and_query()
+            .map(|path| path.as_str())
+            .unwrap_or("/");
+
+        Some(format!("https://{}{}", host.host(), path))
+    }
+
+    fn refuse(req: &ServiceRequest, enforcement: HttpsEnforcement) -> HttpResponse {
+        let url = match enforcement {
+            HttpsEnforcement::Redirect => Self::https_url(req),
+            HttpsEnforcement::Reject => None,
+        };
+
+        match url {
+            Some(url) => HttpResponse::PermanentRedirect()
+                .insert_header((header::LOCATION, url))
+                .finish(),
+            None => HttpResponse::Forbidden()
+                .json(ApiResponse::<()>::error("HTTPS_REQUIRED", "HTTPS is required")),
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RequireHttpsService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = &self.server_config;
+
+        let exempt = self.exempt_paths.contains(&req.path());
+
+        if config.require_https && !exempt && !Self::is_https(&req, config) {
+            let response = Self::refuse(&req, config.https_enforcement);
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App};
+
+    const PROXY: &str = "10.0.0.2:40000";
+    const CLIENT: &str = "203.0.113.7:40000";
+
+    fn server_config(enforcement: &str) -> ServerConfig {
+        serde_json::from_value(serde_json::json!({
+            "require_https": true,
+            "https_enforcement": enforcement,
+            "trusted_proxies": "10.0.0.1, 10.0.0.2"
+        }))
+        .unwrap()
+    }
+
+    fn request(peer: &str, proto: Option<&str>) -> test::TestRequest {
+        let req = test::TestRequest::get()
+            .uri("/api/v1/health?full=1")
+            .insert_header((header::HOST, "cells.example.com:8080"))
+            .peer_addr(peer.parse().unwrap());
+        match proto {
+            Some(proto) => req.insert_header((FORWARDED_PROTO_HEADER, proto)),
+            None => req,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_http_redirected_and_https_passes() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequireHttps::new(server_config("redirect")))
+                .route("/api/v1/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, request(PROXY, Some("http")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            res.headers().get(header::LOCATION).unwrap(),
+            "https://cells.example.com/api/v1/health?full=1"
+        );
+
+        let res = test::call_service(&app, request(PROXY, Some("https")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Only a trusted proxy can vouch for HTTPS
+        let res = test::call_service(&app, request(CLIENT, Some("https")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+
+    #[actix_web::test]
+    async fn test_http_rejected_when_configured() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequireHttps::new(server_config("reject")))
+                .route("/api/v1/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, request(CLIENT, None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "HTTPS_REQUIRED");
+
+        let res = test::call_service(&app, request(PROXY, Some("https")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_exempt_paths_accept_http() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequireHttps::new(server_config("reject")).exempt("/api/v1/health/ready"))
+                .route("/api/v1/health", web::get().to(HttpResponse::Ok))
+                .route("/api/v1/health/ready", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let probe = test::TestRequest::get()
+            .uri("/api/v1/health/ready")
+            .peer_addr(CLIENT.parse().unwrap());
+        let res = test::call_service(&app, probe.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Matching is exact, so other paths are still enforced
+        let res = test::call_service(&app, request(CLIENT, None).to_request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
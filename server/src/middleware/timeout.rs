@@ -0,0 +1,234 @@
+//! Request Timeout Middleware
+//!
+//! Bounds how long a handler may run before the client gets a response.
+//! A slow S3 or database call would otherwise hold the request (and its
+//! connection/pool resources) open long after the mobile client gave up.
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::InternalError,
+    http::Method,
+    Error, HttpResponse,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::domain::ApiResponse;
+
+// ============================================================================
+// Request Timeout Middleware
+// ============================================================================
+
+/// Request Timeout Middleware Factory
+///
+/// Wraps handler execution in `tokio::time::timeout`. Requests whose path ends
+/// with a registered suffix (and, if given, use its method) use that route's
+/// deadline instead of the default; streaming endpoints should be exempted
+/// since their duration depends on the client's bandwidth.
+#[derive(Clone)]
+pub struct RequestTimeout {
+    default_timeout: Duration,
+    /// (method, path suffix, deadline); no method matches any, and `None`
+    /// disables the deadline
+    route_timeouts: Vec<(Option<Method>, &'static str, Option<Duration>)>,
+}
+
+impl RequestTimeout {
+    pub fn new(default_timeout: Duration) -> Self {
+        Self {
+            default_timeout,
+            route_timeouts: Vec::new(),
+        }
+    }
+
+    /// Use a different deadline for paths ending with `path_suffix`
+    pub fn route_timeout(mut self, path_suffix: &'static str, timeout: Duration) -> Self {
+        self.route_timeouts.push((None, path_suffix, Some(timeout)));
+        self
+    }
+
+    /// Use a different deadline for `method` requests to paths ending with
+    /// `path_suffix`, where other methods share the path; `None` disables it
+    pub fn method_timeout(
+        mut self,
+        method: Method,
+        path_suffix: &'static str,
+        timeout: Option<Duration>,
+    ) -> Self {
+        self.route_timeouts.push((Some(method), path_suffix, timeout));
+        self
+    }
+
+    /// Never time out paths ending with `path_suffix` (streaming responses)
+    pub fn exempt(mut self, path_suffix: &'static str) -> Self {
+        self.route_timeouts.push((None, path_suffix, None));
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTimeoutService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTimeoutService {
+            service: Rc::new(service),
+            config: Rc::new(self.clone()),
+        })
+    }
+}
+
+pub struct RequestTimeoutService<S> {
+    service: Rc<S>,
+    config: Rc<RequestTimeout>,
+}
+
+impl<S> RequestTimeoutService<S> {
+    /// Deadline for a request (first matching route wins)
+    fn deadline_for(&self, method: &Method, path: &str) -> Option<Duration> {
+        self.config
+            .route_timeouts
+            .iter()
+            .find(|(route_method, suffix, _)| {
+                route_method.as_ref().is_none_or(|m| m == method) && path.ends_with(suffix)
+            })
+            .map(|(_, _, timeout)| *timeout)
+            .unwrap_or(Some(self.config.default_timeout))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let deadline = self.deadline_for(req.method(), req.path());
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let Some(deadline) = deadline else {
+                return service.call(req).await;
+            };
+
+            let method = req.method().clone();
+            let path = req.path().to_string();
+
+            match tokio::time::timeout(deadline, service.call(req)).await {
+                Ok(res) => res,
+                Err(_) => {
+                    tracing::warn!(
+                        "Request exceeded {}ms deadline: {} {}",
+                        deadline.as_millis(),
+                        method,
+                        path
+                    );
+                    // The request was moved into the dropped handler future, so the
+                    // 504 is returned as an error that actix renders as this response
+                    let response = HttpResponse::GatewayTimeout().json(ApiResponse::<()>::error(
+                        "GATEWAY_TIMEOUT",
+                        "The request took too long to process",
+                    ));
+                    Err(InternalError::from_response("request timed out", response).into())
+                }
+            }
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App};
+
+    async fn slow_handler() -> HttpResponse {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_slow_handler_returns_504() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestTimeout::new(Duration::from_millis(50)))
+                .route("/slow", web::get().to(slow_handler)),
+        )
+        .await;
+
+        let err = test::try_call_service(&app, test::TestRequest::get().uri("/slow").to_request())
+            .await
+            .expect_err("slow request should time out");
+
+        // This is the response actix sends for the error
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "GATEWAY_TIMEOUT");
+    }
+
+    #[actix_web::test]
+    async fn test_route_overrides_and_exemptions() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    RequestTimeout::new(Duration::from_millis(50))
+                        .route_timeout("/patient", Duration::from_secs(5))
+                        .exempt("/file"),
+                )
+                .route("/patient", web::get().to(slow_handler))
+                .route("/images/1/file", web::get().to(slow_handler)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/patient").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/images/1/file").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_method_timeout_leaves_other_methods_alone() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    RequestTimeout::new(Duration::from_millis(50))
+                        .method_timeout(Method::POST, "/images", None),
+                )
+                .route("/folders/1/images", web::post().to(slow_handler))
+                .route("/folders/1/images", web::get().to(slow_handler)),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, test::TestRequest::post().uri("/folders/1/images").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let listed =
+            test::try_call_service(&app, test::TestRequest::get().uri("/folders/1/images").to_request()).await;
+        assert!(listed.is_err(), "GET should keep the default deadline");
+    }
+}
@@ -0,0 +1,248 @@
+//! Worker Authentication Middleware
+//!
+//! Guards worker-facing endpoints with the shared secret from `WORKER__SECRET`.
+//! By default workers send it in the `X-Worker-Secret` header. With
+//! `WORKER__REQUIRE_SIGNATURE` set they instead sign each request: `X-Signature`
+//! holds the lowercase hex HMAC-SHA256, keyed by the secret, of
+//! `{timestamp}.{body}`, where the timestamp is the Unix time in `X-Timestamp`.
+//! Requests whose timestamp is more than `WORKER__SIGNATURE_MAX_SKEW_SECS` away
+//! from server time are rejected, so a captured callback can't be replayed later.
+//! Within that window each signature is accepted once: it is recorded in the
+//! database, and a request repeating it is rejected as a replay. A worker
+//! resending an identical body must therefore sign it with a new timestamp.
+//! Signed bodies are capped at `LIMITS__MAX_JSON_BYTES`, like JSON elsewhere.
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    web::{self, BytesMut},
+    Error, HttpMessage, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::rc::Rc;
+use subtle::ConstantTimeEq;
+
+use crate::config::settings::WorkerConfig;
+use crate::domain::ApiResponse;
+use crate::repositories::WorkerSignatureRepository;
+
+/// Header carrying the worker shared secret
+pub const WORKER_SECRET_HEADER: &str = "X-Worker-Secret";
+
+/// Header carrying the request signature
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Header carrying the Unix timestamp the signature covers
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+
+/// Signature a worker sends for `body` at Unix time `timestamp`
+pub fn sign_request(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// ============================================================================
+// Worker Auth Middleware
+// ============================================================================
+
+/// Reasons a worker request is turned away
+enum Rejection {
+    InvalidSecret,
+    InvalidSignature,
+    StaleTimestamp,
+    Replayed,
+    BodyTooLarge,
+    ReplayCheckFailed,
+}
+
+impl Rejection {
+    fn into_response(self) -> HttpResponse {
+        match self {
+            Rejection::InvalidSecret => HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("UNAUTHORIZED", "Invalid worker credentials")),
+            Rejection::InvalidSignature => HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("INVALID_SIGNATURE", "Invalid request signature")),
+            Rejection::StaleTimestamp => HttpResponse::Unauthorized().json(
+                ApiResponse::<()>::error("STALE_TIMESTAMP", "Request timestamp outside allowed window"),
+            ),
+            Rejection::Replayed => HttpResponse::Unauthorized()
+                .json(ApiResponse::<()>::error("REPLAYED_REQUEST", "Request signature already used")),
+            Rejection::BodyTooLarge => HttpResponse::PayloadTooLarge()
+                .json(ApiResponse::<()>::error("PAYLOAD_TOO_LARGE", "Request body too large")),
+            Rejection::ReplayCheckFailed => HttpResponse::ServiceUnavailable().json(
+                ApiResponse::<()>::error("SERVICE_UNAVAILABLE", "Unable to verify request, try again later"),
+            ),
+        }
+    }
+}
+
+/// Worker Auth Middleware Factory
+///
+/// Responds 401 unless the request carries the configured worker secret, or
+/// a fresh, unused, valid signature when signing is required. An unset secret
+/// rejects every request. Signed bodies above `max_body_bytes` get 413.
+pub struct WorkerAuth {
+    worker_config: WorkerConfig,
+    max_body_bytes: usize,
+}
+
+impl WorkerAuth {
+    pub fn new(worker_config: WorkerConfig, max_body_bytes: usize) -> Self {
+        Self {
+            worker_config,
+            max_body_bytes,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for WorkerAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = WorkerAuthService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(WorkerAuthService {
+            service: Rc::new(service),
+            worker_config: self.worker_config.clone(),
+            max_body_bytes: self.max_body_bytes,
+        })
+    }
+}
+
+pub struct WorkerAuthService<S> {
+    service: Rc<S>,
+    worker_config: WorkerConfig,
+    max_body_bytes: usize,
+}
+
+impl<S> WorkerAuthService<S> {
+    fn has_secret(req: &ServiceRequest, expected: &str) -> bool {
+        req.headers()
+            .get(WORKER_SECRET_HEADER)
+            .is_some_and(|provided| bool::from(provided.as_bytes().ct_eq(expected.as_bytes())))
+    }
+
+    /// Check the signature headers against the body, then put the body back
+    async fn verify_signature(
+        req: &mut ServiceRequest,
+        config: &WorkerConfig,
+        max_body_bytes: usize,
+    ) -> Result<Result<(), Rejection>, Error> {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let (Some(signature), Some(timestamp)) = (header(SIGNATURE_HEADER), header(TIMESTAMP_HEADER))
+        else {
+            return Ok(Err(Rejection::InvalidSignature));
+        };
+        let Ok(timestamp) = timestamp.parse::<i64>() else {
+            return Ok(Err(Rejection::InvalidSignature));
+        };
+
+        let mut payload = req.take_payload();
+        let mut body = BytesMut::new();
+        while let Some(chunk) = payload.next().await {
+            let chunk = chunk?;
+            if body.len() + chunk.len() > max_body_bytes {
+                return Ok(Err(Rejection::BodyTooLarge));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let body = body.freeze();
+
+        let expected = sign_request(config.secret.expose_secret(), timestamp, &body);
+        req.set_payload(Payload::from(body));
+
+        if !bool::from(signature.as_bytes().ct_eq(expected.as_bytes())) {
+            return Ok(Err(Rejection::InvalidSignature));
+        }
+
+        // Checked after the signature so the timestamp is known to be the worker's
+        let skew = (Utc::now().timestamp() - timestamp).unsigned_abs();
+        if skew > config.signature_max_skew_secs {
+            return Ok(Err(Rejection::StaleTimestamp));
+        }
+
+        // Kept until the timestamp would be rejected as stale anyway
+        let max_skew = i64::try_from(config.signature_max_skew_secs).unwrap_or(i64::MAX);
+        let expires_at = DateTime::from_timestamp(timestamp.saturating_add(max_skew), 0)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let Some(pool) = req.app_data::<web::Data<PgPool>>() else {
+            return Ok(Err(Rejection::ReplayCheckFailed));
+        };
+        match WorkerSignatureRepository::record(pool.get_ref(), &signature, expires_at).await {
+            Ok(true) => Ok(Ok(())),
+            Ok(false) => Ok(Err(Rejection::Replayed)),
+            Err(e) => {
+                tracing::error!("Failed to record worker request signature: {:?}", e);
+                Ok(Err(Rejection::ReplayCheckFailed))
+            }
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for WorkerAuthService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.worker_config.clone();
+        let max_body_bytes = self.max_body_bytes;
+
+        Box::pin(async move {
+            let outcome = if config.secret.expose_secret().is_empty() {
+                Err(Rejection::InvalidSecret)
+            } else if config.require_signature {
+                Self::verify_signature(&mut req, &config, max_body_bytes).await?
+            } else if Self::has_secret(&req, config.secret.expose_secret()) {
+                Ok(())
+            } else {
+                Err(Rejection::InvalidSecret)
+            };
+
+            match outcome {
+                Ok(()) => {
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_left_body())
+                }
+                Err(rejection) => {
+                    tracing::warn!("Rejected worker request to {}", req.path());
+                    Ok(req.into_response(rejection.into_response()).map_into_right_body())
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,24 @@
+//! Data Export Model
+//!
+//! Tracks user data archive generation matching the `data_exports` table.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::job::JobStatus;
+
+/// Data export model matching the `data_exports` table
+///
+/// Reuses the `job_status` enum since exports go through the same lifecycle.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DataExport {
+    pub export_id: Uuid,
+    pub user_id: Uuid,
+    pub status: JobStatus,
+    pub storage_key: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
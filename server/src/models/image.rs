@@ -19,6 +19,20 @@ pub struct Image {
     pub metadata: Option<serde_json::Value>,
     pub uploaded_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Storage key of the generated thumbnail, if one has been stored
+    #[sqlx(default)]
+    pub thumbnail_key: Option<String>,
+    /// The original could not be decoded, so it is served as its own thumbnail
+    #[sqlx(default)]
+    pub thumbnail_unavailable: bool,
+}
+
+impl Image {
+    /// Width and height from the stored metadata, if both were recorded
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        let metadata: ImageMetadata = serde_json::from_value(self.metadata.clone()?).ok()?;
+        Some((metadata.width?, metadata.height?))
+    }
 }
 
 /// Image metadata extracted from file headers
@@ -14,6 +14,8 @@ pub enum JobStatus {
     Processing,
     Completed,
     Failed,
+    /// Stopped by the owner before a result arrived
+    Cancelled,
 }
 
 impl std::fmt::Display for JobStatus {
@@ -23,6 +25,22 @@ impl std::fmt::Display for JobStatus {
             JobStatus::Processing => write!(f, "processing"),
             JobStatus::Completed => write!(f, "completed"),
             JobStatus::Failed => write!(f, "failed"),
+            JobStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(JobStatus::Pending),
+            "processing" => Ok(JobStatus::Processing),
+            "completed" => Ok(JobStatus::Completed),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            _ => Err(format!("Unknown job status '{}'", s)),
         }
     }
 }
@@ -38,6 +56,8 @@ pub struct Job {
     pub finished_at: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
+    /// Latest progress percentage reported by the worker
+    pub progress_pct: Option<i16>,
 }
 
 /// Analysis Result model matching the `analysis_results` table
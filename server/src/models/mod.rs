@@ -1,8 +1,10 @@
+pub mod export;
 pub mod folder;
 pub mod image;
 pub mod job;
 pub mod user;
 
+pub use export::DataExport;
 pub use folder::Folder;
 pub use image::{Image, ImageMetadata};
 pub use user::User;
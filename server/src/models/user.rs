@@ -10,6 +10,8 @@ pub struct User {
     pub username: String,
     pub password_hash: String,
     pub created_at: Option<DateTime<Utc>>,
+    /// Model version used when an analysis request doesn't name one
+    pub preferred_model_version: Option<String>,
 }
 
 /// User data without password hash (for API responses)
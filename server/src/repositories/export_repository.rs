@@ -0,0 +1,90 @@
+//! Data Export Repository
+//!
+//! Database operations for user data export requests.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::DataExport;
+
+/// Repository for data export database operations
+pub struct DataExportRepository;
+
+impl DataExportRepository {
+    /// Create a pending export for a user
+    pub async fn create(pool: &PgPool, user_id: Uuid) -> Result<DataExport, sqlx::Error> {
+        sqlx::query_as::<_, DataExport>(
+            r#"
+            INSERT INTO data_exports (user_id)
+            VALUES ($1)
+            RETURNING export_id, user_id, status, storage_key, error_message, created_at, finished_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Find an export by ID with ownership verification
+    /// Time complexity: O(log n) using primary key index
+    pub async fn find_by_id(
+        pool: &PgPool,
+        export_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<DataExport>, sqlx::Error> {
+        sqlx::query_as::<_, DataExport>(
+            r#"
+            SELECT export_id, user_id, status, storage_key, error_message, created_at, finished_at
+            FROM data_exports
+            WHERE export_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(export_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Update export status to processing
+    pub async fn start_processing(pool: &PgPool, export_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE data_exports SET status = 'processing' WHERE export_id = $1")
+            .bind(export_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Complete export with the storage key of the generated archive
+    pub async fn complete(
+        pool: &PgPool,
+        export_id: Uuid,
+        storage_key: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE data_exports SET status = 'completed', storage_key = $2, finished_at = NOW()
+            WHERE export_id = $1
+            "#,
+        )
+        .bind(export_id)
+        .bind(storage_key)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Fail export with error message
+    pub async fn fail(pool: &PgPool, export_id: Uuid, error_message: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE data_exports SET status = 'failed', error_message = $2, finished_at = NOW()
+            WHERE export_id = $1
+            "#,
+        )
+        .bind(export_id)
+        .bind(error_message)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,7 +1,36 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
 use sqlx::{FromRow, PgPool};
 use uuid::Uuid;
 
-use crate::models::Folder;
+use crate::models::{Folder, Image};
+use crate::repositories::{ImageRepository, PurgedImages};
+
+/// Outcome of permanently deleting a folder from the trash
+#[derive(Debug)]
+pub enum PurgeFolderOutcome {
+    /// Storage left behind by the images removed with the folder
+    Purged(PurgedImages),
+    NotFound,
+    /// The folder is not in the trash
+    NotDeleted,
+    /// Trashed too recently; purging is allowed from this time
+    RetentionPeriod(DateTime<Utc>),
+}
+
+/// Outcome of merging one folder into another
+#[derive(Debug)]
+pub enum MergeFolderOutcome {
+    /// The target folder and its live image count after the merge
+    Merged(Folder, i64),
+    /// Either folder is missing, deleted or owned by someone else
+    NotFound,
+    /// A source image's filename was refused in the target
+    DuplicateFilename(String),
+    /// The merged folder would hold more than this many images
+    TooManyImages(i64),
+}
 
 /// Row struct for folder with image count query
 #[derive(Debug, FromRow)]
@@ -38,6 +67,107 @@ impl FolderRepository {
         .await
     }
 
+    /// Return the user's live folder named `folder_name`, creating it if none
+    /// exists; the flag is true when the folder was created
+    /// Time complexity: O(f) for the user's f folders
+    ///
+    /// A transaction-scoped advisory lock on the (user, name) pair makes
+    /// concurrent calls for the same name create a single folder.
+    pub async fn find_or_create(
+        pool: &PgPool,
+        user_id: Uuid,
+        folder_name: &str,
+    ) -> Result<(Folder, bool), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::text || '/' || $2, 0))")
+            .bind(user_id)
+            .bind(folder_name)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing = sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT folder_id, user_id, folder_name, created_at, deleted_at
+            FROM folders
+            WHERE user_id = $1 AND folder_name = $2 AND deleted_at IS NULL
+            ORDER BY folder_id
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(folder_name)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(folder) = existing {
+            tx.commit().await?;
+            return Ok((folder, false));
+        }
+
+        let folder = sqlx::query_as::<_, Folder>(
+            r#"
+            INSERT INTO folders (user_id, folder_name)
+            VALUES ($1, $2)
+            RETURNING folder_id, user_id, folder_name, created_at, deleted_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(folder_name)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok((folder, true))
+    }
+
+    /// Create a folder holding copies of existing images, in one transaction
+    /// Time complexity: O(k log n) for k copied images
+    ///
+    /// Each copy pairs a source image with the storage key its file was
+    /// copied to; the new rows keep the source's name, type, size and metadata.
+    pub async fn create_copy(
+        pool: &PgPool,
+        user_id: Uuid,
+        folder_name: &str,
+        copies: &[(Image, String)],
+    ) -> Result<Folder, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let folder = sqlx::query_as::<_, Folder>(
+            r#"
+            INSERT INTO folders (user_id, folder_name)
+            VALUES ($1, $2)
+            RETURNING folder_id, user_id, folder_name, created_at, deleted_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(folder_name)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (image, file_path) in copies {
+            sqlx::query(
+                r#"
+                INSERT INTO images (folder_id, file_path, original_filename, mime_type, file_size, metadata)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(folder.folder_id)
+            .bind(file_path)
+            .bind(&image.original_filename)
+            .bind(&image.mime_type)
+            .bind(image.file_size)
+            .bind(&image.metadata)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(folder)
+    }
+
     /// Find all folders for a user with image count
     /// Time complexity: O(n) where n = number of user's folders
     pub async fn find_by_user_id(
@@ -96,6 +226,46 @@ impl FolderRepository {
         .await
     }
 
+    /// Find a soft-deleted folder by ID with ownership verification
+    /// Time complexity: O(log n)
+    pub async fn find_deleted_by_id(
+        pool: &PgPool,
+        folder_id: i32,
+        user_id: Uuid,
+    ) -> Result<Option<Folder>, sqlx::Error> {
+        sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT folder_id, user_id, folder_name, created_at, deleted_at
+            FROM folders
+            WHERE folder_id = $1 AND user_id = $2 AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Of the given folder IDs, return those owned by the user and not deleted
+    /// Time complexity: O(k log n) for k requested IDs
+    pub async fn find_owned_ids(
+        pool: &PgPool,
+        folder_ids: &[i32],
+        user_id: Uuid,
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        sqlx::query_scalar::<_, i32>(
+            r#"
+            SELECT folder_id
+            FROM folders
+            WHERE folder_id = ANY($1) AND user_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(folder_ids)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
     /// Update folder name
     /// Time complexity: O(log n)
     pub async fn update_name(
@@ -121,6 +291,13 @@ impl FolderRepository {
 
     /// Soft delete folder by setting deleted_at timestamp
     /// Time complexity: O(log n)
+    ///
+    /// The folder row is locked `FOR UPDATE` first. Image inserts take a
+    /// `FOR KEY SHARE` lock on the folder for the foreign key check, which a
+    /// plain UPDATE (`FOR NO KEY UPDATE`) does not conflict with, so without
+    /// the explicit lock an insert committing between the two statements would
+    /// survive the delete. With it, in-flight inserts finish first and the
+    /// image update (which takes a fresh snapshot under READ COMMITTED) sees them.
     pub async fn delete(
         pool: &PgPool,
         folder_id: i32,
@@ -128,7 +305,26 @@ impl FolderRepository {
     ) -> Result<Option<i64>, sqlx::Error> {
         let mut tx = pool.begin().await?;
 
-        // 1. Update folder status
+        // 1. Lock the folder against concurrent image inserts
+        let locked = sqlx::query(
+            r#"
+            SELECT folder_id
+            FROM folders
+            WHERE folder_id = $1 AND user_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if locked.is_none() {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        // 2. Update folder status
         let result = sqlx::query(
             r#"
             UPDATE folders
@@ -147,7 +343,7 @@ impl FolderRepository {
             return Ok(None);
         }
 
-        // 2. Soft delete valid images in the folder
+        // 3. Soft delete valid images in the folder
         let image_result = sqlx::query(
             r#"
             UPDATE images
@@ -209,43 +405,195 @@ impl FolderRepository {
         }
     }
 
-    /// Permanently delete a folder (hard delete)
+    /// Permanently delete a soft-deleted folder (hard delete)
+    ///
+    /// Only folders trashed at least `min_retention` ago are removed, along
+    /// with their images and the images' jobs and results.
     /// Time complexity: O(m) where m = number of images in folder
     pub async fn hard_delete(
         pool: &PgPool,
         folder_id: i32,
         user_id: Uuid,
-    ) -> Result<Option<i64>, sqlx::Error> {
-        // First count images that will be deleted
-        let image_count: (i64,) = sqlx::query_as(
+        min_retention: Duration,
+    ) -> Result<PurgeFolderOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Lock the folder so a concurrent restore can't race the delete
+        let deleted_at: Option<(Option<DateTime<Utc>>,)> = sqlx::query_as(
             r#"
-            SELECT COUNT(*) FROM images
-            WHERE folder_id = $1
-            AND folder_id IN (SELECT folder_id FROM folders WHERE user_id = $2)
+            SELECT deleted_at FROM folders
+            WHERE folder_id = $1 AND user_id = $2
+            FOR UPDATE
             "#,
         )
         .bind(folder_id)
         .bind(user_id)
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        // Hard delete folder (cascade will delete images)
-        let result = sqlx::query(
+        let deleted_at = match deleted_at {
+            None => return Ok(PurgeFolderOutcome::NotFound),
+            Some((None,)) => return Ok(PurgeFolderOutcome::NotDeleted),
+            Some((Some(deleted_at),)) => deleted_at,
+        };
+
+        let purgeable_at = deleted_at + min_retention;
+        if purgeable_at > Utc::now() {
+            return Ok(PurgeFolderOutcome::RetentionPeriod(purgeable_at));
+        }
+
+        let purged = ImageRepository::hard_delete_by_folder_id(&mut tx, folder_id).await?;
+
+        sqlx::query(
             r#"
             DELETE FROM folders
-            WHERE folder_id = $1 AND user_id = $2
+            WHERE folder_id = $1
             "#,
         )
         .bind(folder_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(PurgeFolderOutcome::Purged(purged))
+    }
+
+    /// Move a folder's live images into another folder and soft-delete it
+    /// Time complexity: O(k log n) for k moved images
+    ///
+    /// Runs in one transaction with both folders locked. `resolve` names each
+    /// moved image given the filenames already live in the target (including
+    /// earlier moved images); returning `None` aborts the whole merge, as
+    /// does the target ending up with more than `max_images` live images
+    /// (zero disables the limit).
+    pub async fn merge(
+        pool: &PgPool,
+        target_id: i32,
+        source_id: i32,
+        user_id: Uuid,
+        max_images: i64,
+        resolve: impl Fn(&str, &HashSet<String>) -> Option<String>,
+    ) -> Result<MergeFolderOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Lock in ID order so opposing merges cannot deadlock
+        let locked: Vec<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT folder_id
+            FROM folders
+            WHERE folder_id = ANY($1) AND user_id = $2 AND deleted_at IS NULL
+            ORDER BY folder_id
+            FOR UPDATE
+            "#,
+        )
+        .bind([target_id, source_id])
         .bind(user_id)
-        .execute(pool)
+        .fetch_all(&mut *tx)
         .await?;
 
-        if result.rows_affected() > 0 {
-            Ok(Some(image_count.0))
-        } else {
-            Ok(None)
+        if locked.len() != 2 {
+            tx.rollback().await?;
+            return Ok(MergeFolderOutcome::NotFound);
         }
+
+        let mut taken: HashSet<String> = sqlx::query_scalar(
+            r#"
+            SELECT original_filename FROM images
+            WHERE folder_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(target_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        let moving: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT image_id, original_filename FROM images
+            WHERE folder_id = $1 AND deleted_at IS NULL
+            ORDER BY image_id
+            "#,
+        )
+        .bind(source_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if max_images > 0 {
+            // Counted rather than taken from `taken`, which collapses allowed duplicates
+            let target_count: i64 = sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*) FROM images WHERE folder_id = $1 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(target_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if target_count + moving.len() as i64 > max_images {
+                tx.rollback().await?;
+                return Ok(MergeFolderOutcome::TooManyImages(max_images));
+            }
+        }
+
+        for (image_id, filename) in moving {
+            let Some(new_filename) = resolve(&filename, &taken) else {
+                tx.rollback().await?;
+                return Ok(MergeFolderOutcome::DuplicateFilename(filename));
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE images
+                SET folder_id = $1, original_filename = $2
+                WHERE image_id = $3
+                "#,
+            )
+            .bind(target_id)
+            .bind(&new_filename)
+            .bind(image_id)
+            .execute(&mut *tx)
+            .await?;
+
+            taken.insert(new_filename);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE folders
+            SET deleted_at = NOW()
+            WHERE folder_id = $1
+            "#,
+        )
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let target = sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT folder_id, user_id, folder_name, created_at, deleted_at
+            FROM folders
+            WHERE folder_id = $1
+            "#,
+        )
+        .bind(target_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Counted rather than taken from `taken`, which collapses allowed duplicates
+        let image_count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM images WHERE folder_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(target_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(MergeFolderOutcome::Merged(target, image_count))
     }
 
     /// Find all soft-deleted folders for a user (trash)
@@ -286,11 +634,11 @@ impl FolderRepository {
             .collect())
     }
 
-    /// Get image count for a folder
+    /// Get the number of live (not soft-deleted) images in a folder
     pub async fn get_image_count(pool: &PgPool, folder_id: i32) -> Result<i64, sqlx::Error> {
         let count: (i64,) = sqlx::query_as(
             r#"
-            SELECT COUNT(*) FROM images WHERE folder_id = $1
+            SELECT COUNT(*) FROM images WHERE folder_id = $1 AND deleted_at IS NULL
             "#,
         )
         .bind(folder_id)
@@ -2,11 +2,30 @@
 //!
 //! Database operations for images with ownership verification.
 
-use sqlx::PgPool;
+use std::collections::HashMap;
+
+use sqlx::{FromRow, PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::models::Image;
 
+/// Row for an image joined with its folder's name
+#[derive(Debug, FromRow)]
+struct ImageWithFolderRow {
+    #[sqlx(flatten)]
+    image: Image,
+    folder_name: String,
+}
+
+/// What a folder's images left behind in storage once purged
+#[derive(Debug, Default)]
+pub struct PurgedImages {
+    /// Storage keys of the deleted images
+    pub file_paths: Vec<String>,
+    /// Jobs deleted with the images, whose cached overlays must go too
+    pub job_ids: Vec<i64>,
+}
+
 /// Repository for image database operations
 pub struct ImageRepository;
 
@@ -26,7 +45,7 @@ impl ImageRepository {
             r#"
             INSERT INTO images (folder_id, file_path, original_filename, mime_type, file_size, metadata)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
+            RETURNING image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at, thumbnail_key
             "#,
         )
         .bind(folder_id)
@@ -40,23 +59,64 @@ impl ImageRepository {
     }
 
     /// Find images by folder ID with pagination (excludes soft-deleted)
+    /// Optionally restricted to a single MIME type and to images carrying `tag`.
     /// Time complexity: O(K + log N) where K = limit, N = total images in folder
     pub async fn find_by_folder_id(
         pool: &PgPool,
         folder_id: i32,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
         limit: i32,
         offset: i64,
     ) -> Result<Vec<Image>, sqlx::Error> {
         sqlx::query_as::<_, Image>(
             r#"
-            SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
-            FROM images
-            WHERE folder_id = $1 AND deleted_at IS NULL
-            ORDER BY uploaded_at DESC
-            LIMIT $2 OFFSET $3
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $3
+            WHERE i.folder_id = $1 AND i.deleted_at IS NULL
+              AND ($2::text IS NULL OR i.mime_type = $2)
+              AND ($3::text IS NULL OR t.tag IS NOT NULL)
+            ORDER BY i.uploaded_at DESC
+            LIMIT $4 OFFSET $5
             "#,
         )
         .bind(folder_id)
+        .bind(mime_type)
+        .bind(tag)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find images by folder ID with pagination, soft-deleted ones included
+    ///
+    /// For admin listings only; user-facing paths use `find_by_folder_id`.
+    /// Time complexity: O(K + log N) where K = limit, N = total images in folder
+    pub async fn find_by_folder_id_with_deleted(
+        pool: &PgPool,
+        folder_id: i32,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
+        limit: i32,
+        offset: i64,
+    ) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $3
+            WHERE i.folder_id = $1
+              AND ($2::text IS NULL OR i.mime_type = $2)
+              AND ($3::text IS NULL OR t.tag IS NOT NULL)
+            ORDER BY i.uploaded_at DESC
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(folder_id)
+        .bind(mime_type)
+        .bind(tag)
         .bind(limit)
         .bind(offset)
         .fetch_all(pool)
@@ -67,6 +127,7 @@ impl ImageRepository {
     /// Time complexity: O(K + log N) - more efficient than OFFSET for large datasets
     /// 
     /// # Arguments
+    /// * `mime_type` - If Some, only images of this MIME type are returned
     /// * `cursor` - If Some, fetches images uploaded before this timestamp
     /// * `limit` - Number of images to fetch (will fetch limit+1 to detect has_next)
     /// 
@@ -75,6 +136,7 @@ impl ImageRepository {
     pub async fn find_by_folder_id_cursor(
         pool: &PgPool,
         folder_id: i32,
+        mime_type: Option<&str>,
         cursor: Option<chrono::DateTime<chrono::Utc>>,
         limit: i32,
     ) -> Result<Vec<Image>, sqlx::Error> {
@@ -85,12 +147,14 @@ impl ImageRepository {
                     SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
                     FROM images
                     WHERE folder_id = $1 AND deleted_at IS NULL AND uploaded_at < $2
+                      AND ($3::text IS NULL OR mime_type = $3)
                     ORDER BY uploaded_at DESC
-                    LIMIT $3
+                    LIMIT $4
                     "#,
                 )
                 .bind(folder_id)
                 .bind(cursor_time)
+                .bind(mime_type)
                 .bind(limit + 1) // Fetch one extra to detect has_next
                 .fetch_all(pool)
                 .await
@@ -101,11 +165,13 @@ impl ImageRepository {
                     SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
                     FROM images
                     WHERE folder_id = $1 AND deleted_at IS NULL
+                      AND ($2::text IS NULL OR mime_type = $2)
                     ORDER BY uploaded_at DESC
-                    LIMIT $2
+                    LIMIT $3
                     "#,
                 )
                 .bind(folder_id)
+                .bind(mime_type)
                 .bind(limit + 1) // Fetch one extra to detect has_next
                 .fetch_all(pool)
                 .await
@@ -113,20 +179,425 @@ impl ImageRepository {
         }
     }
 
-    /// Count images in folder (excludes soft-deleted)
-    pub async fn count_by_folder_id(pool: &PgPool, folder_id: i32) -> Result<i64, sqlx::Error> {
+    /// Find images across several folders with cursor-based pagination
+    ///
+    /// Same ordering and cursor semantics as `find_by_folder_id_cursor`, merged
+    /// over all given folders. Callers must verify folder ownership first.
+    ///
+    /// Time complexity: O(k log n + limit) for k folders
+    pub async fn find_by_folder_ids_cursor(
+        pool: &PgPool,
+        folder_ids: &[i32],
+        cursor: Option<chrono::DateTime<chrono::Utc>>,
+        limit: i32,
+    ) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
+            FROM images
+            WHERE folder_id = ANY($1) AND deleted_at IS NULL
+              AND ($2::timestamptz IS NULL OR uploaded_at < $2)
+            ORDER BY uploaded_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(folder_ids)
+        .bind(cursor)
+        .bind(limit + 1) // Fetch one extra to detect has_next
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every live image in a folder, oldest first
+    /// Time complexity: O(k) where k = number of images in the folder
+    pub async fn find_all_by_folder_id(pool: &PgPool, folder_id: i32) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at
+            FROM images
+            WHERE folder_id = $1 AND deleted_at IS NULL
+            ORDER BY uploaded_at, image_id
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every live image across a user's live folders
+    /// Time complexity: O(n) where n = number of user's images
+    pub async fn find_all_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            ORDER BY i.folder_id, i.uploaded_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Check whether a live image in the folder already uses this filename
+    /// Time complexity: O(k) where k = number of images in the folder
+    pub async fn filename_exists_in_folder(
+        pool: &PgPool,
+        folder_id: i32,
+        original_filename: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let exists: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM images
+                WHERE folder_id = $1 AND original_filename = $2 AND deleted_at IS NULL
+            )
+            "#,
+        )
+        .bind(folder_id)
+        .bind(original_filename)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists.0)
+    }
+
+    /// Find a user's images across all their folders, newest first, each
+    /// paired with its folder's name
+    ///
+    /// Soft-deleted images and images in soft-deleted folders are excluded.
+    /// Time complexity: O(n log n) for the user's n images
+    pub async fn find_by_user_id(
+        pool: &PgPool,
+        user_id: Uuid,
+        limit: i32,
+        offset: i64,
+    ) -> Result<Vec<(Image, String)>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, ImageWithFolderRow>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, i.file_size, i.metadata, i.uploaded_at, i.deleted_at,
+                   f.folder_name
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            ORDER BY i.uploaded_at DESC, i.image_id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.image, row.folder_name)).collect())
+    }
+
+    /// Count a user's images across all their folders, with the same
+    /// exclusions as `find_by_user_id`
+    pub async fn count_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Total stored bytes of a user's images (excludes soft-deleted)
+    ///
+    /// Trashed images don't count; restoring them is checked against the
+    /// quota instead.
+    /// Time complexity: O(n) where n = number of user's images
+    pub async fn total_bytes_for_user(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(i.file_size), 0)::bigint
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND i.deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Total bytes of the soft-deleted images a restore of the user's folder
+    /// would bring back
+    /// Time complexity: O(m) where m = number of images in folder
+    pub async fn deleted_bytes_in_folder(
+        pool: &PgPool,
+        folder_id: i32,
+        user_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(i.file_size), 0)::bigint
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE i.folder_id = $1 AND f.user_id = $2 AND i.deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Count images in folder (excludes soft-deleted), optionally of one MIME type
+    /// and carrying `tag`
+    pub async fn count_by_folder_id(
+        pool: &PgPool,
+        folder_id: i32,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<i64, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $3
+            WHERE i.folder_id = $1 AND i.deleted_at IS NULL
+              AND ($2::text IS NULL OR i.mime_type = $2)
+              AND ($3::text IS NULL OR t.tag IS NOT NULL)
+            "#,
+        )
+        .bind(folder_id)
+        .bind(mime_type)
+        .bind(tag)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Search a folder's images by filename with pagination
+    ///
+    /// Matches a case-insensitive substring of `original_filename`; wildcard
+    /// characters in `query` are matched literally. `mime_type` and `tag`
+    /// filter as in `find_by_folder_id`; soft-deleted images are only
+    /// included when `include_deleted` is set.
+    /// Time complexity: O(k) where k = number of images in the folder
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search_by_folder(
+        pool: &PgPool,
+        folder_id: i32,
+        query: &str,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
+        include_deleted: bool,
+        limit: i32,
+        offset: i64,
+    ) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $4
+            WHERE i.folder_id = $1 AND ($5 OR i.deleted_at IS NULL)
+              AND i.original_filename ILIKE '%' || $2 || '%' ESCAPE '\'
+              AND ($3::text IS NULL OR i.mime_type = $3)
+              AND ($4::text IS NULL OR t.tag IS NOT NULL)
+            ORDER BY i.uploaded_at DESC, i.image_id DESC
+            LIMIT $6 OFFSET $7
+            "#,
+        )
+        .bind(folder_id)
+        .bind(escape_like(query))
+        .bind(mime_type)
+        .bind(tag)
+        .bind(include_deleted)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Count a folder's images matching a filename search, with the same
+    /// filters as `search_by_folder`
+    pub async fn count_search_by_folder(
+        pool: &PgPool,
+        folder_id: i32,
+        query: &str,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $4
+            WHERE i.folder_id = $1 AND ($5 OR i.deleted_at IS NULL)
+              AND i.original_filename ILIKE '%' || $2 || '%' ESCAPE '\'
+              AND ($3::text IS NULL OR i.mime_type = $3)
+              AND ($4::text IS NULL OR t.tag IS NOT NULL)
+            "#,
+        )
+        .bind(folder_id)
+        .bind(escape_like(query))
+        .bind(mime_type)
+        .bind(tag)
+        .bind(include_deleted)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Permanently delete every image in a folder along with its analyses
+    ///
+    /// Runs on the caller's connection so it can be part of a transaction.
+    /// Results, job events, jobs and tags are deleted explicitly before the
+    /// images, in the same transaction, so the deleted jobs can be reported;
+    /// the foreign keys still cascade for account removal. Returns the storage
+    /// keys of the deleted images and the ids of the deleted jobs.
+    /// Time complexity: O(m) where m = number of images and jobs in the folder
+    pub async fn hard_delete_by_folder_id(
+        conn: &mut PgConnection,
+        folder_id: i32,
+    ) -> Result<PurgedImages, sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM analysis_results
+            WHERE job_id IN (
+                SELECT j.job_id FROM jobs j
+                INNER JOIN images i ON j.image_id = i.image_id
+                WHERE i.folder_id = $1
+            )
+            "#,
+        )
+        .bind(folder_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM job_events
+            WHERE job_id IN (
+                SELECT j.job_id FROM jobs j
+                INNER JOIN images i ON j.image_id = i.image_id
+                WHERE i.folder_id = $1
+            )
+            "#,
+        )
+        .bind(folder_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let job_ids = sqlx::query_scalar::<_, i64>(
+            r#"
+            DELETE FROM jobs
+            WHERE image_id IN (SELECT image_id FROM images WHERE folder_id = $1)
+            RETURNING job_id
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM image_tags
+            WHERE image_id IN (SELECT image_id FROM images WHERE folder_id = $1)
+            "#,
+        )
+        .bind(folder_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let file_paths = sqlx::query_scalar::<_, String>(
+            r#"
+            DELETE FROM images
+            WHERE folder_id = $1
+            RETURNING file_path
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(PurgedImages { file_paths, job_ids })
+    }
+
+    /// Count a user's live images per MIME type
+    /// Time complexity: O(n) where n = number of user's images
+    pub async fn count_by_mime_type_for_user(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT i.mime_type, COUNT(*)
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            GROUP BY i.mime_type
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Count images in folder, soft-deleted ones included (admin listings only)
+    pub async fn count_by_folder_id_with_deleted(
+        pool: &PgPool,
+        folder_id: i32,
+        mime_type: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<i64, sqlx::Error> {
         let count: (i64,) = sqlx::query_as(
             r#"
-            SELECT COUNT(*) FROM images WHERE folder_id = $1 AND deleted_at IS NULL
+            SELECT COUNT(*) FROM images i
+            LEFT JOIN image_tags t ON t.image_id = i.image_id AND t.tag = $3
+            WHERE i.folder_id = $1
+              AND ($2::text IS NULL OR i.mime_type = $2)
+              AND ($3::text IS NULL OR t.tag IS NOT NULL)
             "#,
         )
         .bind(folder_id)
+        .bind(mime_type)
+        .bind(tag)
         .fetch_one(pool)
         .await?;
 
         Ok(count.0)
     }
 
+    /// Latest change time of a folder's image listing
+    ///
+    /// Any write to the folder's images or their tags (including moves out
+    /// and hard deletes) bumps `folders.images_modified_at`; job creation is
+    /// considered too, since that flips `has_analysis`.
+    /// Returns None for a folder that has never held an image.
+    pub async fn folder_last_modified(
+        pool: &PgPool,
+        folder_id: i32,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        let last_modified: (Option<chrono::DateTime<chrono::Utc>>,) = sqlx::query_as(
+            r#"
+            SELECT GREATEST(
+                (SELECT images_modified_at FROM folders WHERE folder_id = $1),
+                (SELECT MAX(j.created_at)
+                 FROM jobs j
+                 INNER JOIN images i ON j.image_id = i.image_id
+                 WHERE i.folder_id = $1)
+            )
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(last_modified.0)
+    }
+
     /// Find image by ID with ownership verification via folder
     /// Time complexity: O(log n) using primary key index
     pub async fn find_by_id(
@@ -137,7 +608,8 @@ impl ImageRepository {
         sqlx::query_as::<_, Image>(
             r#"
             SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type, 
-                   i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+                   i.file_size, i.metadata, i.uploaded_at, i.deleted_at, i.thumbnail_key,
+                   i.thumbnail_unavailable
             FROM images i
             INNER JOIN folders f ON i.folder_id = f.folder_id
             WHERE i.image_id = $1 AND f.user_id = $2 AND i.deleted_at IS NULL
@@ -149,6 +621,47 @@ impl ImageRepository {
         .await
     }
 
+    /// Find a live image in a live folder by ID, with no ownership check
+    ///
+    /// Only for access authorized some other way, such as a share link.
+    /// Time complexity: O(log n) using primary key index
+    pub async fn find_live_by_id(pool: &PgPool, image_id: i64) -> Result<Option<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type,
+                   i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE i.image_id = $1 AND i.deleted_at IS NULL AND f.deleted_at IS NULL
+            "#,
+        )
+        .bind(image_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Find several images by ID, keeping only those owned by the user
+    /// Time complexity: O(k log n) where k = number of requested IDs
+    pub async fn find_by_ids(
+        pool: &PgPool,
+        image_ids: &[i64],
+        user_id: Uuid,
+    ) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type,
+                   i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            FROM images i
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE i.image_id = ANY($1) AND f.user_id = $2 AND i.deleted_at IS NULL
+            "#,
+        )
+        .bind(image_ids)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
     /// Soft delete an image (set deleted_at timestamp)
     /// Time complexity: O(log n)
     pub async fn soft_delete(
@@ -211,6 +724,138 @@ impl ImageRepository {
         }
     }
 
+    /// Merge metadata into several images in one transaction
+    ///
+    /// Each patch's fields overwrite the image's stored ones; images that
+    /// are missing, deleted or not owned by the user are skipped. Returns
+    /// whether each update was applied, in order.
+    /// Time complexity: O(k log n) where k = number of updates
+    pub async fn merge_metadata_bulk(
+        pool: &PgPool,
+        user_id: Uuid,
+        updates: &[(i64, serde_json::Value)],
+    ) -> Result<Vec<bool>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut applied = Vec::with_capacity(updates.len());
+
+        for (image_id, patch) in updates {
+            let result = sqlx::query(
+                r#"
+                UPDATE images i
+                SET metadata = COALESCE(i.metadata, '{}'::jsonb) || $1
+                FROM folders f
+                WHERE i.image_id = $2
+                  AND i.folder_id = f.folder_id
+                  AND f.user_id = $3
+                  AND i.deleted_at IS NULL
+                "#,
+            )
+            .bind(patch)
+            .bind(image_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            applied.push(result.rows_affected() > 0);
+        }
+
+        tx.commit().await?;
+        Ok(applied)
+    }
+
+    /// Record the storage key of an image's generated thumbnail
+    /// Time complexity: O(log n)
+    pub async fn set_thumbnail_key(
+        pool: &PgPool,
+        image_id: i64,
+        thumbnail_key: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE images SET thumbnail_key = $1 WHERE image_id = $2")
+            .bind(thumbnail_key)
+            .bind(image_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that an image's original cannot be decoded into a thumbnail
+    /// Time complexity: O(log n)
+    pub async fn mark_thumbnail_unavailable(
+        pool: &PgPool,
+        image_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE images SET thumbnail_unavailable = TRUE WHERE image_id = $1")
+            .bind(image_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Find live images without a thumbnail, in ID order after `after_image_id`
+    ///
+    /// Across all users; for the admin backfill only. Images already found
+    /// undecodable are left out.
+    /// Time complexity: O(K + log N) where K = limit
+    pub async fn find_without_thumbnail(
+        pool: &PgPool,
+        after_image_id: i64,
+        limit: i32,
+    ) -> Result<Vec<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            SELECT image_id, folder_id, file_path, original_filename, mime_type, file_size, metadata, uploaded_at, deleted_at, thumbnail_key
+            FROM images
+            WHERE image_id > $1 AND thumbnail_key IS NULL AND NOT thumbnail_unavailable
+              AND deleted_at IS NULL
+            ORDER BY image_id
+            LIMIT $2
+            "#,
+        )
+        .bind(after_image_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Move an image into another folder, storing it as `filename` there
+    ///
+    /// Both the image's current folder and the destination must belong to
+    /// the user, and the destination must not be deleted; ownership of both
+    /// is checked in the same statement as the update.
+    /// Time complexity: O(log n)
+    pub async fn move_to_folder(
+        pool: &PgPool,
+        image_id: i64,
+        user_id: Uuid,
+        target_folder_id: i32,
+        filename: &str,
+    ) -> Result<Option<Image>, sqlx::Error> {
+        sqlx::query_as::<_, Image>(
+            r#"
+            UPDATE images i
+            SET folder_id = dst.folder_id, original_filename = $4
+            FROM folders src, folders dst
+            WHERE i.image_id = $1
+              AND i.folder_id = src.folder_id
+              AND src.user_id = $2
+              AND i.deleted_at IS NULL
+              AND dst.folder_id = $3
+              AND dst.user_id = $2
+              AND dst.deleted_at IS NULL
+            RETURNING i.image_id, i.folder_id, i.file_path, i.original_filename, i.mime_type,
+                      i.file_size, i.metadata, i.uploaded_at, i.deleted_at
+            "#,
+        )
+        .bind(image_id)
+        .bind(user_id)
+        .bind(target_folder_id)
+        .bind(filename)
+        .fetch_optional(pool)
+        .await
+    }
+
     /// Check if image has any analysis jobs
     pub async fn has_analysis(pool: &PgPool, image_id: i64) -> Result<bool, sqlx::Error> {
         let count: (i64,) = sqlx::query_as(
@@ -225,6 +870,92 @@ impl ImageRepository {
         Ok(count.0 > 0)
     }
 
+    /// Check which of several images have any analysis jobs, in one query
+    ///
+    /// Only images with jobs appear in the map (all mapped to `true`).
+    /// Time complexity: O(k log n) where k = number of IDs
+    pub async fn has_analysis_bulk(
+        pool: &PgPool,
+        image_ids: &[i64],
+    ) -> Result<HashMap<i64, bool>, sqlx::Error> {
+        let analyzed: Vec<i64> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT image_id FROM jobs WHERE image_id = ANY($1)
+            "#,
+        )
+        .bind(image_ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(analyzed.into_iter().map(|image_id| (image_id, true)).collect())
+    }
+
+    /// Tag an image, returning false if it already had the tag
+    ///
+    /// Callers must verify image ownership first.
+    pub async fn add_tag(pool: &PgPool, image_id: i64, tag: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO image_tags (image_id, tag)
+            VALUES ($1, $2)
+            ON CONFLICT (image_id, tag) DO NOTHING
+            "#,
+        )
+        .bind(image_id)
+        .bind(tag)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove a tag from an image, returning false if it wasn't tagged
+    ///
+    /// Callers must verify image ownership first.
+    pub async fn remove_tag(pool: &PgPool, image_id: i64, tag: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM image_tags WHERE image_id = $1 AND tag = $2")
+            .bind(image_id)
+            .bind(tag)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Tags of an image, alphabetically
+    pub async fn find_tags(pool: &PgPool, image_id: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT tag FROM image_tags WHERE image_id = $1 ORDER BY tag")
+            .bind(image_id)
+            .fetch_all(pool)
+            .await
+    }
+
+    /// Tags of several images in one query, each list alphabetical
+    ///
+    /// Images without tags are absent from the map.
+    /// Time complexity: O(k log n) where k = number of IDs
+    pub async fn find_tags_bulk(
+        pool: &PgPool,
+        image_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<String>>, sqlx::Error> {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT image_id, tag FROM image_tags
+            WHERE image_id = ANY($1)
+            ORDER BY image_id, tag
+            "#,
+        )
+        .bind(image_ids)
+        .fetch_all(pool)
+        .await?;
+
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for (image_id, tag) in rows {
+            tags.entry(image_id).or_default().push(tag);
+        }
+        Ok(tags)
+    }
+
     /// Get analysis history for an image
     pub async fn get_analysis_history(
         pool: &PgPool,
@@ -252,3 +983,15 @@ pub struct AnalysisJobRow {
     pub ai_model_version: Option<String>,
     pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
 }
+
+/// Escape `LIKE` wildcards so user input matches literally
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
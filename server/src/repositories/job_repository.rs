@@ -2,10 +2,61 @@
 //!
 //! Database operations for jobs and analysis results.
 
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgConnection, PgPool};
 use uuid::Uuid;
 
-use crate::models::job::{AnalysisResult, Job};
+use crate::models::job::{AnalysisResult, Job, JobStatus};
+
+/// Outcome of creating a job unless an equivalent one is still unfinished
+#[derive(Debug)]
+pub enum CreateJobOutcome {
+    Created(Job),
+    /// A pending or processing job for the same image and model already exists
+    AlreadyActive(Job),
+}
+
+/// Outcome of recording a worker-reported result against a job
+#[derive(Debug)]
+pub enum RecordResultOutcome {
+    Recorded(AnalysisResult),
+    JobNotFound,
+    AlreadyFinished(JobStatus),
+}
+
+/// Outcome of an operator manually resolving a job
+#[derive(Debug)]
+pub enum ResolveJobOutcome {
+    Resolved(Job),
+    JobNotFound,
+    AlreadyFinished(JobStatus),
+}
+
+/// Outcome of an owner cancelling one of their jobs
+#[derive(Debug)]
+pub enum CancelJobOutcome {
+    Cancelled(Job),
+    JobNotFound,
+    AlreadyFinished(JobStatus),
+}
+
+/// Outcome of an owner retrying one of their failed jobs
+#[derive(Debug)]
+pub enum RetryJobOutcome {
+    Created(Job),
+    JobNotFound,
+    NotFailed(JobStatus),
+    /// The job was already retried as the given job
+    AlreadyRetried(i64),
+}
+
+/// Row for a pending job joined with its image's storage key
+#[derive(Debug, FromRow)]
+struct PendingJobRow {
+    #[sqlx(flatten)]
+    job: Job,
+    file_path: String,
+}
 
 /// Repository for job database operations
 pub struct JobRepository;
@@ -21,7 +72,7 @@ impl JobRepository {
             r#"
             INSERT INTO jobs (image_id, status, ai_model_version)
             VALUES ($1, 'pending', $2)
-            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at
+            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
             "#,
         )
         .bind(image_id)
@@ -30,6 +81,58 @@ impl JobRepository {
         .await
     }
 
+    /// Create a job for an image unless one for the same model is unfinished
+    ///
+    /// Locks the image row so concurrent requests for the same image are
+    /// serialized and only one of them creates a job.
+    pub async fn create_unless_active(
+        pool: &PgPool,
+        image_id: i64,
+        model_version: &str,
+    ) -> Result<CreateJobOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("SELECT image_id FROM images WHERE image_id = $1 FOR UPDATE")
+            .bind(image_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let active = sqlx::query_as::<_, Job>(
+            r#"
+            SELECT job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
+            FROM jobs
+            WHERE image_id = $1 AND ai_model_version = $2
+              AND status IN ('pending', 'processing')
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(image_id)
+        .bind(model_version)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(job) = active {
+            return Ok(CreateJobOutcome::AlreadyActive(job));
+        }
+
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            INSERT INTO jobs (image_id, status, ai_model_version)
+            VALUES ($1, 'pending', $2)
+            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
+            "#,
+        )
+        .bind(image_id)
+        .bind(model_version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(CreateJobOutcome::Created(job))
+    }
+
     /// Find job by ID with ownership verification
     pub async fn find_by_id(
         pool: &PgPool,
@@ -39,7 +142,7 @@ impl JobRepository {
         sqlx::query_as::<_, Job>(
             r#"
             SELECT j.job_id, j.image_id, j.status, j.ai_model_version, 
-                   j.started_at, j.finished_at, j.error_message, j.created_at
+                   j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct
             FROM jobs j
             INNER JOIN images i ON j.image_id = i.image_id
             INNER JOIN folders f ON i.folder_id = f.folder_id
@@ -66,6 +169,464 @@ impl JobRepository {
         Ok(())
     }
 
+    /// Record worker progress on a job, moving it to processing
+    ///
+    /// `started_at` is set on the first report and the percentage never
+    /// moves backwards, so out-of-order messages are harmless. Returns
+    /// whether a job was updated; finished jobs are left alone.
+    pub async fn record_progress(
+        pool: &PgPool,
+        job_id: i64,
+        progress_pct: Option<i16>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'processing',
+                started_at = COALESCE(started_at, NOW()),
+                progress_pct = GREATEST(progress_pct, $2)
+            WHERE job_id = $1 AND status IN ('pending', 'processing')
+            "#,
+        )
+        .bind(job_id)
+        .bind(progress_pct)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that a job was published to the analysis queue
+    pub async fn mark_queued(pool: &PgPool, job_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE jobs SET queued_at = NOW()
+            WHERE job_id = $1
+            "#,
+        )
+        .bind(job_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count jobs waiting for a worker
+    /// Time complexity: O(p) where p = pending jobs, using the (status, created_at) index
+    pub async fn count_pending(pool: &PgPool) -> Result<i64, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM jobs WHERE status = 'pending'
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Find pending jobs created before `created_before` that were never
+    /// published, oldest first, with their image's storage key
+    /// Time complexity: O(k) using the partial unqueued-pending index
+    pub async fn find_unqueued_pending(
+        pool: &PgPool,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<(Job, String)>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, PendingJobRow>(
+            r#"
+            SELECT j.job_id, j.image_id, j.status, j.ai_model_version,
+                   j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct,
+                   i.file_path
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            WHERE j.status = 'pending' AND j.queued_at IS NULL AND j.created_at < $1
+            ORDER BY j.created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(created_before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.job, row.file_path)).collect())
+    }
+
+    /// Find the failed jobs in a folder that have not been retried
+    ///
+    /// Only the latest job per image and model version counts, so a failure
+    /// already followed by a newer job for the same pair is left out.
+    /// Time complexity: O(n log n) where n = number of jobs in the folder
+    pub async fn find_failed_in_folder(pool: &PgPool, folder_id: i32) -> Result<Vec<Job>, sqlx::Error> {
+        sqlx::query_as::<_, Job>(
+            r#"
+            SELECT job_id, image_id, status, ai_model_version,
+                   started_at, finished_at, error_message, created_at, progress_pct
+            FROM (
+                SELECT DISTINCT ON (j.image_id, j.ai_model_version)
+                       j.job_id, j.image_id, j.status, j.ai_model_version,
+                       j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct
+                FROM jobs j
+                INNER JOIN images i ON j.image_id = i.image_id
+                WHERE i.folder_id = $1 AND i.deleted_at IS NULL
+                ORDER BY j.image_id, j.ai_model_version, j.job_id DESC
+            ) latest
+            WHERE status = 'failed'
+            ORDER BY job_id
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every job on a user's live images, oldest first
+    /// Time complexity: O(n) where n = number of user's jobs
+    pub async fn find_all_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Vec<Job>, sqlx::Error> {
+        sqlx::query_as::<_, Job>(
+            r#"
+            SELECT j.job_id, j.image_id, j.status, j.ai_model_version,
+                   j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            ORDER BY j.created_at, j.job_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Page of the jobs on a user's live images run with `model_version`,
+    /// optionally only those in `status`, newest first
+    /// Time complexity: O(n log n) where n = number of user's jobs
+    pub async fn list_for_user_filtered(
+        pool: &PgPool,
+        user_id: Uuid,
+        model_version: &str,
+        status: Option<JobStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Job>, sqlx::Error> {
+        sqlx::query_as::<_, Job>(
+            r#"
+            SELECT j.job_id, j.image_id, j.status, j.ai_model_version,
+                   j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+              AND j.ai_model_version = $2
+              AND ($3::job_status IS NULL OR j.status = $3)
+            ORDER BY j.created_at DESC, j.job_id DESC
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(user_id)
+        .bind(model_version)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Count the jobs `list_for_user_filtered` pages through
+    /// Time complexity: O(n) where n = number of user's jobs
+    pub async fn count_for_user_filtered(
+        pool: &PgPool,
+        user_id: Uuid,
+        model_version: &str,
+        status: Option<JobStatus>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+              AND j.ai_model_version = $2
+              AND ($3::job_status IS NULL OR j.status = $3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(model_version)
+        .bind(status)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Count the jobs on a user's live images per status
+    /// Time complexity: O(n) where n = number of user's jobs
+    pub async fn count_by_status_for_user(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<(JobStatus, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (JobStatus, i64)>(
+            r#"
+            SELECT j.status, COUNT(*)
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            GROUP BY j.status
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Store a worker-reported result and mark its job completed
+    ///
+    /// Runs on the caller's connection so it can be part of a transaction.
+    /// The job row is locked first, so a concurrent report for the same job
+    /// waits and then sees it as already finished.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_result(
+        conn: &mut PgConnection,
+        job_id: i64,
+        count_viable: i32,
+        count_apoptosis: i32,
+        count_other: i32,
+        avg_confidence_score: f64,
+        raw_data: Option<serde_json::Value>,
+        summary_data: Option<String>,
+    ) -> Result<RecordResultOutcome, sqlx::Error> {
+        let status = sqlx::query_scalar::<_, JobStatus>(
+            "SELECT status FROM jobs WHERE job_id = $1 FOR UPDATE",
+        )
+        .bind(job_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        match status {
+            None => return Ok(RecordResultOutcome::JobNotFound),
+            Some(status @ (JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)) => {
+                return Ok(RecordResultOutcome::AlreadyFinished(status));
+            }
+            Some(_) => {}
+        }
+
+        let result = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            INSERT INTO analysis_results 
+                (job_id, count_viable, count_apoptosis, count_other, avg_confidence_score, raw_data, summary_data)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING result_id, job_id, count_viable, count_apoptosis, count_other, 
+                      avg_confidence_score, raw_data, summary_data, analyzed_at
+            "#,
+        )
+        .bind(job_id)
+        .bind(count_viable)
+        .bind(count_apoptosis)
+        .bind(count_other)
+        .bind(avg_confidence_score)
+        .bind(raw_data)
+        .bind(summary_data)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE jobs SET status = 'completed', finished_at = NOW()
+            WHERE job_id = $1
+            "#,
+        )
+        .bind(job_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(RecordResultOutcome::Recorded(result))
+    }
+
+    /// Force a non-terminal job into a terminal status and record a
+    /// `manual_resolve` job event naming the operator
+    ///
+    /// Runs on the caller's connection so it can be part of a transaction.
+    /// `error_message` is stored on the job only when it is failed.
+    pub async fn force_resolve(
+        conn: &mut PgConnection,
+        job_id: i64,
+        to_status: JobStatus,
+        error_message: Option<&str>,
+        actor: &str,
+    ) -> Result<ResolveJobOutcome, sqlx::Error> {
+        let from_status = sqlx::query_scalar::<_, JobStatus>(
+            "SELECT status FROM jobs WHERE job_id = $1 FOR UPDATE",
+        )
+        .bind(job_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let from_status = match from_status {
+            None => return Ok(ResolveJobOutcome::JobNotFound),
+            Some(status @ (JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)) => {
+                return Ok(ResolveJobOutcome::AlreadyFinished(status));
+            }
+            Some(status) => status,
+        };
+
+        let job_error = if to_status == JobStatus::Failed {
+            error_message
+        } else {
+            None
+        };
+
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            UPDATE jobs SET status = $2, finished_at = NOW(), error_message = $3
+            WHERE job_id = $1
+            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
+            "#,
+        )
+        .bind(job_id)
+        .bind(&to_status)
+        .bind(job_error)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO job_events (job_id, event_type, from_status, to_status, actor, message)
+            VALUES ($1, 'manual_resolve', $2, $3, $4, $5)
+            "#,
+        )
+        .bind(job_id)
+        .bind(&from_status)
+        .bind(&to_status)
+        .bind(actor)
+        .bind(error_message)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(ResolveJobOutcome::Resolved(job))
+    }
+
+    /// Cancel a pending or processing job owned by the user
+    ///
+    /// Sets `finished_at` and records a `cancel` job event naming the owner.
+    /// A worker result arriving afterwards is refused as already finished.
+    pub async fn cancel(
+        pool: &PgPool,
+        job_id: i64,
+        user_id: Uuid,
+    ) -> Result<CancelJobOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let from_status = sqlx::query_scalar::<_, JobStatus>(
+            r#"
+            SELECT j.status
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE j.job_id = $1 AND f.user_id = $2
+            FOR UPDATE OF j
+            "#,
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let from_status = match from_status {
+            None => return Ok(CancelJobOutcome::JobNotFound),
+            Some(status @ (JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)) => {
+                return Ok(CancelJobOutcome::AlreadyFinished(status));
+            }
+            Some(status) => status,
+        };
+
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            UPDATE jobs SET status = 'cancelled', finished_at = NOW()
+            WHERE job_id = $1
+            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
+            "#,
+        )
+        .bind(job_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO job_events (job_id, event_type, from_status, to_status, actor)
+            VALUES ($1, 'cancel', $2, 'cancelled', $3)
+            "#,
+        )
+        .bind(job_id)
+        .bind(&from_status)
+        .bind(user_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(CancelJobOutcome::Cancelled(job))
+    }
+
+    /// Create a pending job repeating a failed one, linked through `retry_of`
+    ///
+    /// The new job analyzes the same image with the same model version. Each
+    /// failed job can be retried once; retry the newer job if that fails too.
+    pub async fn create_retry(
+        pool: &PgPool,
+        job_id: i64,
+        user_id: Uuid,
+    ) -> Result<RetryJobOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let status = sqlx::query_scalar::<_, JobStatus>(
+            r#"
+            SELECT j.status
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE j.job_id = $1 AND f.user_id = $2
+            FOR UPDATE OF j
+            "#,
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match status {
+            None => return Ok(RetryJobOutcome::JobNotFound),
+            Some(JobStatus::Failed) => {}
+            Some(status) => return Ok(RetryJobOutcome::NotFailed(status)),
+        }
+
+        // The row lock above serializes retries of the same job
+        let existing = sqlx::query_scalar::<_, i64>("SELECT job_id FROM jobs WHERE retry_of = $1")
+            .bind(job_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some(retry_id) = existing {
+            return Ok(RetryJobOutcome::AlreadyRetried(retry_id));
+        }
+
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            INSERT INTO jobs (image_id, status, ai_model_version, retry_of)
+            SELECT image_id, 'pending', ai_model_version, job_id
+            FROM jobs
+            WHERE job_id = $1
+            RETURNING job_id, image_id, status, ai_model_version, started_at, finished_at, error_message, created_at, progress_pct
+            "#,
+        )
+        .bind(job_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(RetryJobOutcome::Created(job))
+    }
+
     /// Complete job with success
     pub async fn complete(pool: &PgPool, job_id: i64) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -105,7 +666,7 @@ impl JobRepository {
         let jobs = sqlx::query_as::<_, Job>(
             r#"
             SELECT j.job_id, j.image_id, j.status, j.ai_model_version, 
-                   j.started_at, j.finished_at, j.error_message, j.created_at
+                   j.started_at, j.finished_at, j.error_message, j.created_at, j.progress_pct
             FROM jobs j
             INNER JOIN images i ON j.image_id = i.image_id
             INNER JOIN folders f ON i.folder_id = f.folder_id
@@ -138,6 +699,18 @@ impl JobRepository {
     }
 }
 
+/// Lifetime analysis totals for a user
+#[derive(Debug, FromRow)]
+pub struct AnalysisTotals {
+    pub total_jobs: i64,
+    pub completed_jobs: i64,
+    pub failed_jobs: i64,
+    pub viable_cells: i64,
+    pub apoptosis_cells: i64,
+    pub other_cells: i64,
+    pub mean_confidence: f64,
+}
+
 /// Repository for analysis results
 pub struct AnalysisResultRepository;
 
@@ -173,27 +746,63 @@ impl AnalysisResultRepository {
         .await
     }
 
+    /// Find every analysis result on a user's live images
+    /// Time complexity: O(n) where n = number of user's results
+    pub async fn find_all_by_user_id(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<AnalysisResult>, sqlx::Error> {
+        sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT ar.result_id, ar.job_id, ar.count_viable, ar.count_apoptosis, ar.count_other,
+                   ar.avg_confidence_score, ar.raw_data, ar.summary_data, ar.analyzed_at
+            FROM analysis_results ar
+            INNER JOIN jobs j ON ar.job_id = j.job_id
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Aggregate a user's jobs and results across all their live folders
+    ///
+    /// Always returns one row; a user without analyses gets all zeros.
+    /// Time complexity: O(n) where n = number of user's jobs
+    pub async fn totals_by_user_id(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<AnalysisTotals, sqlx::Error> {
+        sqlx::query_as::<_, AnalysisTotals>(
+            r#"
+            SELECT COUNT(j.job_id) AS total_jobs,
+                   COUNT(j.job_id) FILTER (WHERE j.status = 'completed') AS completed_jobs,
+                   COUNT(j.job_id) FILTER (WHERE j.status = 'failed') AS failed_jobs,
+                   COALESCE(SUM(ar.count_viable), 0)::bigint AS viable_cells,
+                   COALESCE(SUM(ar.count_apoptosis), 0)::bigint AS apoptosis_cells,
+                   COALESCE(SUM(ar.count_other), 0)::bigint AS other_cells,
+                   COALESCE(AVG(ar.avg_confidence_score), 0)::float8 AS mean_confidence
+            FROM jobs j
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            LEFT JOIN analysis_results ar ON ar.job_id = j.job_id
+            WHERE f.user_id = $1 AND f.deleted_at IS NULL AND i.deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
     /// Find result by job ID with ownership verification
     pub async fn find_by_job_id(
         pool: &PgPool,
         job_id: i64,
         user_id: Uuid,
     ) -> Result<Option<(AnalysisResult, i64)>, sqlx::Error> {
-        // Use a helper struct to query result with image_id
-        #[derive(sqlx::FromRow)]
-        struct ResultWithImageId {
-            result_id: i64,
-            job_id: i64,
-            count_viable: i32,
-            count_apoptosis: i32,
-            count_other: i32,
-            avg_confidence_score: Option<f64>,
-            raw_data: Option<serde_json::Value>,
-            summary_data: Option<String>,
-            analyzed_at: Option<chrono::DateTime<chrono::Utc>>,
-            image_id: i64,
-        }
-
         let result = sqlx::query_as::<_, ResultWithImageId>(
             r#"
             SELECT ar.result_id, ar.job_id, ar.count_viable, ar.count_apoptosis, ar.count_other,
@@ -211,21 +820,136 @@ impl AnalysisResultRepository {
         .fetch_optional(pool)
         .await?;
 
-        Ok(result.map(|r| {
-            (
-                AnalysisResult {
-                    result_id: r.result_id,
-                    job_id: r.job_id,
-                    count_viable: r.count_viable,
-                    count_apoptosis: r.count_apoptosis,
-                    count_other: r.count_other,
-                    avg_confidence_score: r.avg_confidence_score,
-                    raw_data: r.raw_data,
-                    summary_data: r.summary_data,
-                    analyzed_at: r.analyzed_at,
-                },
-                r.image_id,
-            )
-        }))
+        Ok(result.map(ResultWithImageId::into_parts))
+    }
+
+    /// Find the result of an image's most recently finished completed job,
+    /// with ownership verification
+    /// Time complexity: O(k log k) where k = number of jobs for the image
+    pub async fn find_latest_for_image(
+        pool: &PgPool,
+        image_id: i64,
+        user_id: Uuid,
+    ) -> Result<Option<AnalysisResult>, sqlx::Error> {
+        sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT ar.result_id, ar.job_id, ar.count_viable, ar.count_apoptosis, ar.count_other,
+                   ar.avg_confidence_score, ar.raw_data, ar.summary_data, ar.analyzed_at
+            FROM analysis_results ar
+            INNER JOIN jobs j ON ar.job_id = j.job_id
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE j.image_id = $1 AND f.user_id = $2
+              AND j.status = 'completed' AND i.deleted_at IS NULL
+            ORDER BY j.finished_at DESC NULLS LAST, j.job_id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(image_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Find the job of an image's most recently finished completed result
+    /// from `model_version`, with ownership verification
+    ///
+    /// Results from other models, even later ones, are skipped.
+    /// Time complexity: O(k log k) where k = number of jobs for the image
+    pub async fn find_latest_job_id_for_model(
+        pool: &PgPool,
+        image_id: i64,
+        user_id: Uuid,
+        model_version: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT j.job_id
+            FROM analysis_results ar
+            INNER JOIN jobs j ON ar.job_id = j.job_id
+            INNER JOIN images i ON j.image_id = i.image_id
+            INNER JOIN folders f ON i.folder_id = f.folder_id
+            WHERE j.image_id = $1 AND f.user_id = $2 AND j.ai_model_version = $3
+              AND j.status = 'completed' AND i.deleted_at IS NULL
+            ORDER BY j.finished_at DESC NULLS LAST, j.job_id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(image_id)
+        .bind(user_id)
+        .bind(model_version)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Find one page of completed results for a folder, ordered by result ID
+    ///
+    /// Keyset pagination (`result_id > after_result_id`) so callers can walk
+    /// arbitrarily large folders in bounded memory. Ownership must be checked
+    /// by the caller.
+    /// Time complexity: O(k log n) where k = limit
+    pub async fn find_completed_by_folder_page(
+        pool: &PgPool,
+        folder_id: i32,
+        after_result_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(AnalysisResult, i64)>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, ResultWithImageId>(
+            r#"
+            SELECT ar.result_id, ar.job_id, ar.count_viable, ar.count_apoptosis, ar.count_other,
+                   ar.avg_confidence_score, ar.raw_data, ar.summary_data, ar.analyzed_at,
+                   j.image_id
+            FROM analysis_results ar
+            INNER JOIN jobs j ON ar.job_id = j.job_id
+            INNER JOIN images i ON j.image_id = i.image_id
+            WHERE i.folder_id = $1
+              AND i.deleted_at IS NULL
+              AND j.status = 'completed'
+              AND ar.result_id > $2
+            ORDER BY ar.result_id
+            LIMIT $3
+            "#,
+        )
+        .bind(folder_id)
+        .bind(after_result_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ResultWithImageId::into_parts).collect())
+    }
+}
+
+/// Helper row for querying a result together with its image_id
+#[derive(sqlx::FromRow)]
+struct ResultWithImageId {
+    result_id: i64,
+    job_id: i64,
+    count_viable: i32,
+    count_apoptosis: i32,
+    count_other: i32,
+    avg_confidence_score: Option<f64>,
+    raw_data: Option<serde_json::Value>,
+    summary_data: Option<String>,
+    analyzed_at: Option<chrono::DateTime<chrono::Utc>>,
+    image_id: i64,
+}
+
+impl ResultWithImageId {
+    fn into_parts(self) -> (AnalysisResult, i64) {
+        (
+            AnalysisResult {
+                result_id: self.result_id,
+                job_id: self.job_id,
+                count_viable: self.count_viable,
+                count_apoptosis: self.count_apoptosis,
+                count_other: self.count_other,
+                avg_confidence_score: self.avg_confidence_score,
+                raw_data: self.raw_data,
+                summary_data: self.summary_data,
+                analyzed_at: self.analyzed_at,
+            },
+            self.image_id,
+        )
     }
 }
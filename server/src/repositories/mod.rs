@@ -1,9 +1,18 @@
+pub mod export_repository;
 pub mod folder_repository;
 pub mod image_repository;
 pub mod job_repository;
+pub mod revoked_token_repository;
 pub mod user_repository;
+pub mod worker_signature_repository;
 
-pub use folder_repository::FolderRepository;
-pub use image_repository::ImageRepository;
-pub use job_repository::{AnalysisResultRepository, JobRepository};
+pub use export_repository::DataExportRepository;
+pub use folder_repository::{FolderRepository, MergeFolderOutcome, PurgeFolderOutcome};
+pub use image_repository::{ImageRepository, PurgedImages};
+pub use job_repository::{
+    AnalysisResultRepository, CancelJobOutcome, CreateJobOutcome, JobRepository, RecordResultOutcome,
+    ResolveJobOutcome, RetryJobOutcome,
+};
+pub use revoked_token_repository::RevokedTokenRepository;
 pub use user_repository::UserRepository;
+pub use worker_signature_repository::WorkerSignatureRepository;
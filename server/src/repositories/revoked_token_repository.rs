@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Repository for revoked access tokens
+pub struct RevokedTokenRepository;
+
+impl RevokedTokenRepository {
+    /// Revoke the token `jti` until it expires at `expires_at`
+    ///
+    /// Revoking an already revoked token is a no-op.
+    /// Time complexity: O(log n)
+    pub async fn revoke(
+        pool: &PgPool,
+        jti: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, expires_at)
+            VALUES ($1, $2)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(jti)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Check whether the token `jti` has been revoked
+    /// Time complexity: O(log n)
+    pub async fn is_revoked(pool: &PgPool, jti: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)
+            "#,
+        )
+        .bind(jti)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Delete entries for tokens that expired before `cutoff`, returning how
+    /// many were removed
+    ///
+    /// An expired token is rejected on its expiry alone, so its entry is no
+    /// longer needed; callers move `cutoff` back by the clock skew allowance.
+    /// Time complexity: O(k log n) where k = number of expired entries
+    pub async fn delete_expired(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM revoked_tokens WHERE expires_at < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
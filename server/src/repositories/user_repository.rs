@@ -17,7 +17,7 @@ impl UserRepository {
             r#"
             INSERT INTO users (username, password_hash)
             VALUES ($1, $2)
-            RETURNING user_id, username, password_hash, created_at
+            RETURNING user_id, username, password_hash, created_at, preferred_model_version
             "#,
         )
         .bind(username)
@@ -35,7 +35,7 @@ impl UserRepository {
     ) -> Result<Option<User>, sqlx::Error> {
         let user = sqlx::query_as::<_, User>(
             r#"
-            SELECT user_id, username, password_hash, created_at
+            SELECT user_id, username, password_hash, created_at, preferred_model_version
             FROM users
             WHERE username = $1
             "#,
@@ -48,12 +48,10 @@ impl UserRepository {
     }
 
     /// Find a user by ID
-    /// Reserved for future profile/user management endpoints
-    #[allow(dead_code)]
     pub async fn find_by_id(pool: &PgPool, user_id: Uuid) -> Result<Option<User>, sqlx::Error> {
         let user = sqlx::query_as::<_, User>(
             r#"
-            SELECT user_id, username, password_hash, created_at
+            SELECT user_id, username, password_hash, created_at, preferred_model_version
             FROM users
             WHERE user_id = $1
             "#,
@@ -65,6 +63,50 @@ impl UserRepository {
         Ok(user)
     }
 
+    /// Set (or clear, with `None`) a user's preferred model version
+    ///
+    /// Returns `false` if the user doesn't exist.
+    pub async fn set_preferred_model_version(
+        pool: &PgPool,
+        user_id: Uuid,
+        model_version: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users SET preferred_model_version = $2
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(model_version)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replace a user's password hash
+    ///
+    /// Returns `false` if the user doesn't exist.
+    pub async fn update_password(
+        pool: &PgPool,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users SET password_hash = $2
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Check if a username already exists
     pub async fn username_exists(pool: &PgPool, username: &str) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar::<_, bool>(
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Repository for signatures of accepted worker requests
+pub struct WorkerSignatureRepository;
+
+impl WorkerSignatureRepository {
+    /// Record a request signature until `expires_at`, returning `false` if
+    /// it had already been recorded (the request is a replay)
+    /// Time complexity: O(log n)
+    pub async fn record(
+        pool: &PgPool,
+        signature: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO worker_request_signatures (signature, expires_at)
+            VALUES ($1, $2)
+            ON CONFLICT (signature) DO NOTHING
+            "#,
+        )
+        .bind(signature)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Delete signatures that expired before `cutoff`, returning how many
+    /// were removed
+    ///
+    /// A request whose signature has expired is rejected on its timestamp
+    /// alone, so the entry is no longer needed.
+    /// Time complexity: O(k log n) where k = number of expired entries
+    pub async fn delete_expired(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM worker_request_signatures WHERE expires_at < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,47 +1,103 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
 use utoipa::OpenApi;
 
-use crate::config::settings::JwtConfig;
+use crate::config::settings::{AdminConfig, AppConfig, JwtConfig, LimitsConfig, WorkerConfig};
 use crate::domain::{ApiError, ApiResponse};
 use crate::dto::{
-    AnalysisHistoryItem, AnalysisHistorySummary, AnalysisResultResponse, AnalyzeImageRequest,
-    AnalyzeImageResponse, BoundingBox, CellCounts, CellPercentages, ConfirmUploadRequest,
-    CreateFolderRequest, CursorPaginationInfo, DeleteFolderResponse, DeleteImageResponse,
-    FolderListResponse, FolderResponse, ImageAnalysisHistoryResponse, ImageDetailResponse,
-    ImageListResponse, ImageListResponseV2, ImageMetadataResponse, ImageResponse, JobStatusResponse,
-    LoginRequest, LoginResponse, LogoutResponse, PaginationInfo, PresignedDownloadResponse,
-    RawDetectionData, RegisterRequest, RegisterResponse, RenameImageRequest, RequestUploadRequest,
-    RequestUploadResponse, UpdateFolderRequest,
+    AccountBreakdownResponse, AnalysisHistoryItem, AnalysisHistorySummary, AnalysisResultResponse, AnalysisTotalsResponse,
+    AnalyzeImageRequest, AnalyzeImageResponse, BatchAnalyzeError, BatchAnalyzeJob,
+    BatchAnalyzeRequest, BatchAnalyzeResponse, BatchJobResultsResponse, BoundingBox, CellCounts,
+    CellPercentages, CellTotals, ConfirmUploadRequest, CopyFolderRequest, CreateFolderRequest, CursorPaginationInfo, DataExportResponse,
+    DeleteFolderResponse, DeleteImageResponse, FolderListResponse, FolderResponse,
+    ImageAnalysisHistoryResponse, ImageDetailResponse, ImageListResponse, ImageListResponseV2,
+    ImageMetadataResponse, ImageResponse, ImageTagsResponse, JobResolution, JobResultEntry, JobResultIngestOutcome,
+    ChangePasswordRequest, ChangePasswordResponse, JobListResponse, JobStatusResponse, ListImagesRequest, LoginRequest, MergeFolderRequest, MoveImageRequest, LoginResponse, LogoutResponse,
+    BatchDownloadUrlRequest, BatchDownloadUrlResponse, BulkMetadataUpdateRequest, BulkMetadataUpdateResponse,
+    ImageMetadataUpdate, ImageMetadataUpdateOutcome, PaginationInfo, PreferencesResponse,
+    PresignedDownloadResponse, RawDetectionData, RegisterRequest,
+    ProfileResponse, RegisterResponse, RenameImageRequest, RequestUploadRequest, RequestUploadResponse, ShareLinkResponse,
+    QueueHealthResponse, ResolveJobRequest, RetryFailedJobsResponse, ScaledDetectionsResponse,
+    ThumbnailBackfillRequest, ThumbnailBackfillResponse,
+    UpdateFolderRequest, UpdatePreferencesRequest, UploadConstraintsResponse,
 };
 use crate::handlers;
-use crate::middleware::AuthenticationMiddleware;
+use crate::middleware::{AdminGuard, AuthenticationMiddleware, WorkerAuth};
+use crate::services::{RabbitmqService, StorageBackend};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        root,
         health_check,
+        readiness_check,
+        metrics,
         handlers::auth_handlers::register,
         handlers::auth_handlers::login,
         handlers::auth_handlers::logout,
+        handlers::auth_handlers::change_password,
         handlers::folder_handlers::list_folders,
+        handlers::folder_handlers::list_trash,
         handlers::folder_handlers::create_folder,
+        handlers::folder_handlers::ensure_folder,
         handlers::folder_handlers::rename_folder,
         handlers::folder_handlers::delete_folder,
+        handlers::folder_handlers::copy_folder,
+        handlers::folder_handlers::merge_folder,
+        handlers::folder_handlers::purge_folder,
+        handlers::folder_handlers::restore_folder,
+        handlers::image_handlers::list_all_images,
         handlers::image_handlers::list_images,
+        handlers::image_handlers::search_images,
         handlers::image_handlers::list_images_v2,
+        handlers::image_handlers::list_images_multi,
         handlers::image_handlers::upload_image,
+        handlers::image_handlers::upload_image_raw,
         handlers::image_handlers::request_upload,
         handlers::image_handlers::confirm_upload,
         handlers::image_handlers::get_image,
         handlers::image_handlers::rename_image,
+        handlers::image_handlers::move_image,
+        handlers::image_handlers::add_image_tag,
+        handlers::image_handlers::remove_image_tag,
         handlers::image_handlers::delete_image,
         handlers::image_handlers::get_image_file,
+        handlers::image_handlers::get_image_thumbnail,
         handlers::image_handlers::get_image_download_url,
+        handlers::image_handlers::get_image_download_urls,
+        handlers::image_handlers::update_images_metadata,
+        handlers::image_handlers::share_image,
+        handlers::image_handlers::get_shared_image,
+        handlers::image_handlers::get_upload_constraints,
         handlers::analysis_handlers::analyze_image,
+        handlers::analysis_handlers::batch_analyze_images,
+        handlers::analysis_handlers::retry_failed_jobs,
+        handlers::analysis_handlers::get_scaled_detections,
+        handlers::analysis_handlers::get_overlay_url,
+        handlers::analysis_handlers::get_latest_image_result,
         handlers::analysis_handlers::get_job_status,
+        handlers::analysis_handlers::cancel_job,
+        handlers::analysis_handlers::retry_job,
         handlers::analysis_handlers::get_job_result,
         handlers::analysis_handlers::get_analysis_history,
+        handlers::analysis_handlers::stream_folder_results,
+        handlers::analysis_handlers::get_analysis_totals,
+        handlers::analysis_handlers::list_my_jobs,
+        handlers::export_handlers::request_data_export,
+        handlers::export_handlers::get_data_export,
+        handlers::user_handlers::get_profile,
+        handlers::user_handlers::update_preferences,
+        handlers::user_handlers::get_account_breakdown,
+        handlers::admin_handlers::get_effective_config,
+        handlers::admin_handlers::get_queue_health,
+        handlers::admin_handlers::resolve_job,
+        handlers::admin_handlers::backfill_thumbnails,
+        handlers::worker_handlers::ingest_job_results_batch,
     ),
     components(
         schemas(
@@ -50,17 +106,24 @@ use crate::middleware::AuthenticationMiddleware;
             LoginRequest,
             LoginResponse,
             LogoutResponse,
+            ChangePasswordRequest,
+            ChangePasswordResponse,
             CreateFolderRequest,
             UpdateFolderRequest,
+            CopyFolderRequest,
+            MergeFolderRequest,
             FolderResponse,
             FolderListResponse,
             DeleteFolderResponse,
             ImageResponse,
             ImageListResponse,
             ImageListResponseV2,
+            ListImagesRequest,
             ImageDetailResponse,
             ImageMetadataResponse,
             RenameImageRequest,
+            MoveImageRequest,
+            ImageTagsResponse,
             DeleteImageResponse,
             PaginationInfo,
             CursorPaginationInfo,
@@ -68,20 +131,51 @@ use crate::middleware::AuthenticationMiddleware;
             RequestUploadResponse,
             ConfirmUploadRequest,
             PresignedDownloadResponse,
+            BatchDownloadUrlRequest,
+            BatchDownloadUrlResponse,
+            BulkMetadataUpdateRequest,
+            BulkMetadataUpdateResponse,
+            ImageMetadataUpdate,
+            ImageMetadataUpdateOutcome,
+            ShareLinkResponse,
+            UploadConstraintsResponse,
             AnalysisHistoryItem,
             AnalyzeImageRequest,
             AnalyzeImageResponse,
+            BatchAnalyzeRequest,
+            BatchAnalyzeResponse,
+            BatchAnalyzeJob,
+            BatchAnalyzeError,
+            RetryFailedJobsResponse,
             JobStatusResponse,
+            JobListResponse,
             AnalysisResultResponse,
             CellCounts,
             CellPercentages,
             BoundingBox,
+            ScaledDetectionsResponse,
+            QueueHealthResponse,
             RawDetectionData,
             ImageAnalysisHistoryResponse,
             AnalysisHistorySummary,
+            AnalysisTotalsResponse,
+            CellTotals,
+            JobResultEntry,
+            JobResultIngestOutcome,
+            BatchJobResultsResponse,
+            JobResolution,
+            ResolveJobRequest,
+            ThumbnailBackfillRequest,
+            ThumbnailBackfillResponse,
+            DataExportResponse,
+            ProfileResponse,
+            UpdatePreferencesRequest,
+            PreferencesResponse,
+            AccountBreakdownResponse,
             ApiResponse<RegisterResponse>,
             ApiResponse<LoginResponse>,
             ApiResponse<LogoutResponse>,
+            ApiResponse<ChangePasswordResponse>,
             ApiResponse<FolderResponse>,
             ApiResponse<FolderListResponse>,
             ApiResponse<DeleteFolderResponse>,
@@ -89,13 +183,30 @@ use crate::middleware::AuthenticationMiddleware;
             ApiResponse<ImageListResponse>,
             ApiResponse<ImageListResponseV2>,
             ApiResponse<ImageDetailResponse>,
+            ApiResponse<ImageTagsResponse>,
             ApiResponse<DeleteImageResponse>,
             ApiResponse<RequestUploadResponse>,
             ApiResponse<PresignedDownloadResponse>,
+            ApiResponse<BatchDownloadUrlResponse>,
+            ApiResponse<BulkMetadataUpdateResponse>,
+            ApiResponse<ShareLinkResponse>,
+            ApiResponse<UploadConstraintsResponse>,
             ApiResponse<AnalyzeImageResponse>,
+            ApiResponse<BatchAnalyzeResponse>,
+            ApiResponse<RetryFailedJobsResponse>,
             ApiResponse<JobStatusResponse>,
+            ApiResponse<JobListResponse>,
             ApiResponse<AnalysisResultResponse>,
+            ApiResponse<ScaledDetectionsResponse>,
+            ApiResponse<QueueHealthResponse>,
+            ApiResponse<ThumbnailBackfillResponse>,
             ApiResponse<ImageAnalysisHistoryResponse>,
+            ApiResponse<AnalysisTotalsResponse>,
+            ApiResponse<BatchJobResultsResponse>,
+            ApiResponse<DataExportResponse>,
+            ApiResponse<ProfileResponse>,
+            ApiResponse<PreferencesResponse>,
+            ApiResponse<AccountBreakdownResponse>,
             ApiError,
         )
     ),
@@ -105,12 +216,15 @@ use crate::middleware::AuthenticationMiddleware;
         (name = "Authentication", description = "User authentication endpoints"),
         (name = "Folder Management", description = "Folder CRUD operations"),
         (name = "Image Management", description = "Image upload, listing, and deletion"),
-        (name = "AI Analysis", description = "AI-powered cell analysis endpoints")
+        (name = "AI Analysis", description = "AI-powered cell analysis endpoints"),
+        (name = "Account", description = "User account, preferences and data export"),
+        (name = "Administration", description = "Operator-only endpoints"),
+        (name = "Workers", description = "Endpoints for analysis workers")
     )
 )]
 pub struct ApiDoc;
 
-/// Security addon for OpenAPI to add bearer auth
+/// Security addon for OpenAPI to add bearer auth and the worker secret header
 struct SecurityAddon;
 
 impl utoipa::Modify for SecurityAddon {
@@ -123,27 +237,149 @@ impl utoipa::Modify for SecurityAddon {
                         utoipa::openapi::security::HttpAuthScheme::Bearer,
                     ),
                 ),
-            )
+            );
+            components.add_security_scheme(
+                "worker_secret",
+                utoipa::openapi::security::SecurityScheme::ApiKey(
+                    utoipa::openapi::security::ApiKey::Header(
+                        utoipa::openapi::security::ApiKeyValue::new(
+                            crate::middleware::worker_auth::WORKER_SECRET_HEADER,
+                        ),
+                    ),
+                ),
+            );
         }
     }
 }
 
+/// Landing response pointing at the API docs and health check
 #[utoipa::path(
     get,
-    path = "/api/v1/health",
+    path = "/",
     tag = "Health",
     responses(
-        (status = 200, description = "Service is healthy")
+        (status = 200, description = "Service name, version and useful links")
     )
 )]
-async fn health_check() -> HttpResponse {
+async fn root(config: web::Data<AppConfig>) -> HttpResponse {
     HttpResponse::Ok().json(serde_json::json!({
+        "service": config.server.service_name,
+        "version": env!("CARGO_PKG_VERSION"),
+        "docs_url": "/swagger-ui/",
+        "health_url": "/api/v1/health"
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Service is healthy, with the message queue connection state")
+    )
+)]
+async fn health_check(rabbitmq: Option<web::Data<RabbitmqService>>) -> HttpResponse {
+    let mut body = serde_json::json!({
         "status": "healthy",
         "version": env!("CARGO_PKG_VERSION")
-    }))
+    });
+    if let Some(rabbitmq) = rabbitmq {
+        let state = if rabbitmq.is_healthy().await { "connected" } else { "disconnected" };
+        body["rabbitmq"] = state.into();
+    }
+    HttpResponse::Ok().json(body)
+}
+
+/// Longest a single dependency check may take before it counts as down
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Run one readiness check, reporting `"up"` or `"down"`
+async fn probe<E: Display>(
+    dependency: &str,
+    check: impl Future<Output = Result<(), E>>,
+) -> &'static str {
+    match tokio::time::timeout(READINESS_CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => "up",
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness check for {} failed: {}", dependency, e);
+            "down"
+        }
+        Err(_) => {
+            tracing::warn!("Readiness check for {} timed out", dependency);
+            "down"
+        }
+    }
 }
 
-pub fn configure_routes(cfg: &mut web::ServiceConfig, jwt_config: JwtConfig) {
+/// Readiness probe checking the database, storage and message queue
+///
+/// Unlike `/health`, this touches every dependency, so orchestrators can stop
+/// routing traffic here while one is down.
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/ready",
+    tag = "Health",
+    responses(
+        (status = 200, description = "All dependencies are up"),
+        (status = 503, description = "At least one dependency is down")
+    )
+)]
+pub async fn readiness_check(
+    pool: web::Data<PgPool>,
+    storage: web::Data<dyn StorageBackend>,
+    rabbitmq: web::Data<RabbitmqService>,
+) -> HttpResponse {
+    let (database, storage, queue) = futures::join!(
+        probe("database", async {
+            sqlx::query("SELECT 1").execute(pool.get_ref()).await.map(|_| ())
+        }),
+        probe("storage", storage.check_health()),
+        probe("queue", async {
+            if rabbitmq.is_healthy().await {
+                Ok(())
+            } else {
+                Err("channel is closed")
+            }
+        }),
+    );
+
+    let ready = [database, storage, queue].iter().all(|state| *state == "up");
+    let body = serde_json::json!({
+        "status": if ready { "healthy" } else { "unhealthy" },
+        "database": database,
+        "storage": storage,
+        "queue": queue
+    });
+
+    if ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+/// Metrics in the Prometheus text exposition format
+#[utoipa::path(
+    get,
+    path = "/api/v1/metrics",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Prometheus metrics", content_type = "text/plain")
+    )
+)]
+async fn metrics() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(crate::metrics::registry().render())
+}
+
+pub fn configure_routes(
+    cfg: &mut web::ServiceConfig,
+    jwt_config: JwtConfig,
+    admin_config: AdminConfig,
+    worker_config: WorkerConfig,
+    limits: &LimitsConfig,
+) {
     // Rate limiter for login: 5 requests per 60 seconds (burst of 2)
     // Protects against brute-force password attacks
     let login_governor_conf = GovernorConfigBuilder::default()
@@ -160,9 +396,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, jwt_config: JwtConfig) {
         .finish()
         .expect("Failed to create register rate limiter");
 
+    cfg.route("/", web::get().to(root));
+
     cfg.service(
         web::scope("/api/v1")
             .route("/health", web::get().to(health_check))
+            .route("/health/ready", web::get().to(readiness_check))
+            .route("/metrics", web::get().to(metrics))
+            .route("/upload/constraints", web::get().to(handlers::get_upload_constraints))
             .service(
                 web::scope("/auth")
                     // Register with rate limiting
@@ -180,7 +421,13 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, jwt_config: JwtConfig) {
                     .service(
                         web::scope("")
                             .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
-                            .route("/logout", web::post().to(handlers::logout)),
+                            .route("/logout", web::post().to(handlers::logout))
+                            // Rate limited like login, since it checks a password
+                            .service(
+                                web::resource("/change-password")
+                                    .wrap(Governor::new(&login_governor_conf))
+                                    .route(web::post().to(handlers::change_password))
+                            ),
                     ),
             )
             .service(
@@ -188,33 +435,96 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, jwt_config: JwtConfig) {
                     .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
                     .route("", web::get().to(handlers::list_folders))
                     .route("", web::post().to(handlers::create_folder))
+                    .route("", web::put().to(handlers::ensure_folder))
+                    // Registered before "/{folder_id}" so it is not captured as an ID
+                    .route("/trash", web::get().to(handlers::list_trash))
                     .route("/{folder_id}", web::patch().to(handlers::rename_folder))
                     .route("/{folder_id}", web::delete().to(handlers::delete_folder))
+                    .route("/{folder_id}/copy", web::post().to(handlers::copy_folder))
+                    .route("/{folder_id}/merge", web::post().to(handlers::merge_folder))
+                    .route("/{folder_id}/permanent", web::delete().to(handlers::purge_folder))
+                    .route("/{folder_id}/restore", web::post().to(handlers::restore_folder))
                     // Image routes nested under folder
                     .route("/{folder_id}/images", web::get().to(handlers::list_images))
                     .route("/{folder_id}/images", web::post().to(handlers::upload_image))
+                    .route("/{folder_id}/images/search", web::get().to(handlers::search_images))
+                    .route("/{folder_id}/images/raw", web::put().to(handlers::upload_image_raw))
                     // Presigned URL upload routes
                     .route("/{folder_id}/images/request-upload", web::post().to(handlers::request_upload))
-                    .route("/{folder_id}/images/confirm-upload", web::post().to(handlers::confirm_upload)),
+                    .route("/{folder_id}/images/confirm-upload", web::post().to(handlers::confirm_upload))
+                    .route("/{folder_id}/results.ndjson", web::get().to(handlers::stream_folder_results))
+                    .route("/{folder_id}/retry-failed", web::post().to(handlers::retry_failed_jobs)),
             )
             .service(
                 web::scope("/images")
                     .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
+                    .route("", web::get().to(handlers::list_all_images))
+                    // Registered before "/{image_id}" so it is not captured as an ID
+                    .route("/list", web::post().to(handlers::list_images_multi))
+                    .route("/download-urls", web::post().to(handlers::get_image_download_urls))
+                    .route("/bulk-metadata", web::patch().to(handlers::update_images_metadata))
                     .route("/{image_id}", web::get().to(handlers::get_image))
                     .route("/{image_id}", web::patch().to(handlers::rename_image))
                     .route("/{image_id}", web::delete().to(handlers::delete_image))
+                    .route("/{image_id}/move", web::patch().to(handlers::move_image))
+                    .route("/{image_id}/tags/{tag}", web::post().to(handlers::add_image_tag))
+                    .route("/{image_id}/tags/{tag}", web::delete().to(handlers::remove_image_tag))
                     .route("/{image_id}/file", web::get().to(handlers::get_image_file))
+                    .route("/{image_id}/thumbnail", web::get().to(handlers::get_image_thumbnail))
                     // Presigned download URL route
                     .route("/{image_id}/download-url", web::get().to(handlers::get_image_download_url))
+                    .route("/{image_id}/share", web::post().to(handlers::share_image))
                     // Analysis routes under image
                     .route("/{image_id}/analyze", web::post().to(handlers::analyze_image))
-                    .route("/{image_id}/analysis-history", web::get().to(handlers::get_analysis_history)),
+                    .route("/{image_id}/analysis-history", web::get().to(handlers::get_analysis_history))
+                    .route("/{image_id}/latest-result", web::get().to(handlers::get_latest_image_result)),
+            )
+            .service(
+                // Share links carry their own signature, so no auth middleware
+                web::scope("/shared")
+                    .route("/images/{token}", web::get().to(handlers::get_shared_image)),
+            )
+            .service(
+                web::scope("/analyze")
+                    .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
+                    .route("/batch", web::post().to(handlers::batch_analyze_images)),
+            )
+            .service(
+                // Worker endpoints; registered before "/jobs" so it does not capture them
+                web::scope("/jobs/results")
+                    .wrap(WorkerAuth::new(worker_config, limits.max_json_bytes))
+                    .route("/batch", web::post().to(handlers::ingest_job_results_batch)),
             )
             .service(
                 web::scope("/jobs")
                     .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
                     .route("/{job_id}", web::get().to(handlers::get_job_status))
-                    .route("/{job_id}/result", web::get().to(handlers::get_job_result)),
+                    .route("/{job_id}/cancel", web::post().to(handlers::cancel_job))
+                    .route("/{job_id}/retry", web::post().to(handlers::retry_job))
+                    .route("/{job_id}/result", web::get().to(handlers::get_job_result))
+                    .route("/{job_id}/detections/scaled", web::get().to(handlers::get_scaled_detections))
+                    .route("/{job_id}/overlay-url", web::get().to(handlers::get_overlay_url)),
+            )
+            .service(
+                web::scope("/me")
+                    .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
+                    .route("", web::get().to(handlers::get_profile))
+                    .route("/analysis-totals", web::get().to(handlers::get_analysis_totals))
+                    .route("/jobs", web::get().to(handlers::list_my_jobs))
+                    .route("/breakdown", web::get().to(handlers::get_account_breakdown))
+                    .route("/export", web::post().to(handlers::request_data_export))
+                    .route("/export/{export_id}", web::get().to(handlers::get_data_export))
+                    .route("/preferences", web::patch().to(handlers::update_preferences)),
+            )
+            .service(
+                // AdminGuard runs after authentication (outer wrap runs first)
+                web::scope("/admin")
+                    .wrap(AdminGuard::new(admin_config))
+                    .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
+                    .route("/config", web::get().to(handlers::get_effective_config))
+                    .route("/queue-health", web::get().to(handlers::get_queue_health))
+                    .route("/jobs/{job_id}/resolve", web::post().to(handlers::resolve_job))
+                    .route("/thumbnails/backfill", web::post().to(handlers::backfill_thumbnails)),
             ),
     );
 
@@ -227,4 +537,33 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, jwt_config: JwtConfig) {
                     .route("/{folder_id}/images", web::get().to(handlers::list_images_v2)),
             ),
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    #[actix_web::test]
+    async fn test_root_links_docs_and_health() {
+        let config: AppConfig = serde_json::from_value(serde_json::json!({
+            "server": { "service_name": "cell-api" },
+            "database": { "url": "postgres://test" },
+            "jwt": { "secret": "test-secret" }
+        }))
+        .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .route("/", web::get().to(root)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["service"], "cell-api");
+        assert_eq!(body["docs_url"], "/swagger-ui/");
+        assert_eq!(body["health_url"], "/api/v1/health");
+    }
+}
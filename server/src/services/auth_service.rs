@@ -3,17 +3,17 @@ use argon2::{
     Argon2,
 };
 use chrono::{Duration, Utc};
-use hkdf::Hkdf;
 use rusty_paseto::prelude::*;
-use secrecy::ExposeSecret;
-use sha2::Sha256;
 use sqlx::PgPool;
 use thiserror::Error;
+use uuid::Uuid;
 
 use crate::config::settings::JwtConfig;
-use crate::dto::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse, UserResponse};
+use crate::domain::is_unique_violation;
+use crate::dto::{ChangePasswordRequest, LoginRequest, LoginResponse, RegisterRequest, RegisterResponse, UserResponse};
 use crate::models::User;
 use crate::repositories::UserRepository;
+use crate::services::token_keys;
 
 #[derive(Debug, Error)]
 pub enum AuthError {
@@ -23,6 +23,9 @@ pub enum AuthError {
     #[error("Invalid credentials")]
     InvalidCredentials,
 
+    #[error("User not found")]
+    UserNotFound,
+
     #[error("Password hashing failed: {0}")]
     HashingError(String),
 
@@ -38,6 +41,20 @@ pub enum AuthError {
     ValidationError(String),
 }
 
+impl AuthError {
+    /// Map a failed user insert, treating a unique violation as a taken username
+    ///
+    /// The `username_exists` pre-check races with concurrent registrations,
+    /// so the `users.username` unique constraint is the real guard.
+    pub fn from_user_insert(error: sqlx::Error) -> Self {
+        if is_unique_violation(&error) {
+            AuthError::UsernameExists
+        } else {
+            AuthError::DatabaseError(error)
+        }
+    }
+}
+
 /// Auth service for authentication operations
 pub struct AuthService;
 
@@ -47,7 +64,8 @@ impl AuthService {
         pool: &PgPool,
         request: RegisterRequest,
     ) -> Result<RegisterResponse, AuthError> {
-        // Check if username already exists
+        // Fast path for the common case; the insert below still maps a
+        // concurrent duplicate to UsernameExists
         if UserRepository::username_exists(pool, &request.username).await? {
             return Err(AuthError::UsernameExists);
         }
@@ -60,7 +78,9 @@ impl AuthService {
             .map_err(|e| AuthError::HashingError(e.to_string()))??;
 
         // Create the user
-        let user = UserRepository::create(pool, &request.username, &password_hash).await?;
+        let user = UserRepository::create(pool, &request.username, &password_hash)
+            .await
+            .map_err(AuthError::from_user_insert)?;
 
         Ok(RegisterResponse {
             user_id: user.user_id,
@@ -109,6 +129,40 @@ impl AuthService {
         })
     }
 
+    /// Change a user's password after checking their current one
+    pub async fn change_password(
+        pool: &PgPool,
+        user_id: Uuid,
+        request: ChangePasswordRequest,
+    ) -> Result<(), AuthError> {
+        let user = UserRepository::find_by_id(pool, user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        // Verify, then hash, with spawn_blocking
+        // Argon2 is CPU-intensive and should not block the async runtime
+        let current = request.current_password;
+        let hash = user.password_hash;
+        let is_valid = tokio::task::spawn_blocking(move || Self::verify_password(&current, &hash))
+            .await
+            .map_err(|e| AuthError::HashingError(e.to_string()))??;
+
+        if !is_valid {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let password = request.new_password;
+        let password_hash = tokio::task::spawn_blocking(move || Self::hash_password(&password))
+            .await
+            .map_err(|e| AuthError::HashingError(e.to_string()))??;
+
+        if !UserRepository::update_password(pool, user_id, &password_hash).await? {
+            return Err(AuthError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
     /// Hash a password using Argon2
     fn hash_password(password: &str) -> Result<String, AuthError> {
         let salt = SaltString::generate(&mut OsRng);
@@ -134,39 +188,39 @@ impl AuthService {
 
     /// Generate access and refresh tokens using PASETO
     fn generate_tokens(user: &User, jwt_config: &JwtConfig) -> Result<(String, String), AuthError> {
-        // Derive 32-byte key using HKDF-SHA256 (RFC 5869)
-        // This ensures proper key derivation regardless of secret length
-        let secret = jwt_config.secret.expose_secret();
-        let hk = Hkdf::<Sha256>::new(None, secret.as_bytes());
-        let mut key_bytes = [0u8; 32];
-        // Use domain-specific info for key separation
-        hk.expand(b"paseto-v4-local-key", &mut key_bytes)
-            .expect("HKDF expand failed - output length is valid");
-
-        let secret_key = Key::<32>::from(key_bytes);
-        let key = PasetoSymmetricKey::<V4, Local>::from(secret_key);
+        // Tokens are always issued with the current key version
+        let key = token_keys::signing_key(jwt_config);
 
         // Prepare claim values as bindings to avoid temporary value issues
         let user_id_str = user.user_id.to_string();
-        let access_expiration = Utc::now() + Duration::hours(jwt_config.expiration_hours);
+        // Unique per token, so a single token can be revoked on logout
+        let access_jti = Uuid::new_v4().to_string();
+        let refresh_jti = Uuid::new_v4().to_string();
+        let issued_at = Utc::now();
+        let issued_at_str = issued_at.to_rfc3339();
+        let access_expiration = issued_at + Duration::hours(jwt_config.expiration_hours);
         let access_exp_str = access_expiration.to_rfc3339();
 
         // Access token (shorter expiration) - removed role claim
         let access_token = PasetoBuilder::<V4, Local>::default()
             .set_claim(ExpirationClaim::try_from(access_exp_str.as_str()).unwrap())
             .set_claim(SubjectClaim::from(user_id_str.as_str()))
+            .set_claim(TokenIdentifierClaim::from(access_jti.as_str()))
+            .set_claim(IssuedAtClaim::try_from(issued_at_str.as_str()).unwrap())
             .set_claim(CustomClaim::try_from(("username", user.username.as_str())).unwrap())
             .set_claim(CustomClaim::try_from(("token_type", "access")).unwrap())
             .build(&key)
             .map_err(|e| AuthError::TokenError(e.to_string()))?;
 
         // Refresh token (longer expiration - configurable via JWT__REFRESH_EXPIRATION_DAYS)
-        let refresh_expiration = Utc::now() + Duration::days(jwt_config.refresh_expiration_days);
+        let refresh_expiration = issued_at + Duration::days(jwt_config.refresh_expiration_days);
         let refresh_exp_str = refresh_expiration.to_rfc3339();
 
         let refresh_token = PasetoBuilder::<V4, Local>::default()
             .set_claim(ExpirationClaim::try_from(refresh_exp_str.as_str()).unwrap())
             .set_claim(SubjectClaim::from(user_id_str.as_str()))
+            .set_claim(TokenIdentifierClaim::from(refresh_jti.as_str()))
+            .set_claim(IssuedAtClaim::try_from(issued_at_str.as_str()).unwrap())
             .set_claim(CustomClaim::try_from(("token_type", "refresh")).unwrap())
             .build(&key)
             .map_err(|e| AuthError::TokenError(e.to_string()))?;
@@ -0,0 +1,285 @@
+//! Data Export Service
+//!
+//! Builds a user's data portability archive: a ZIP with `manifest.json`
+//! describing their folders, images and analyses, plus the image files
+//! themselves under `images/`. The archive is written to an anonymous temp
+//! file and streamed to storage, so its size isn't bounded by memory.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::models::job::{AnalysisResult, Job};
+use crate::models::{Folder, Image, User};
+use crate::repositories::{
+    AnalysisResultRepository, DataExportRepository, FolderRepository, ImageRepository,
+    JobRepository, UserRepository,
+};
+use crate::services::storage_backend::{StorageBackend, StorageError};
+
+/// Name of the manifest entry inside the archive
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Failed to write archive: {0}")]
+    Archive(String),
+}
+
+impl From<zip::result::ZipError> for ExportError {
+    fn from(err: zip::result::ZipError) -> Self {
+        ExportError::Archive(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError::Archive(err.to_string())
+    }
+}
+
+// ============================================================================
+// Manifest
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct ExportManifest {
+    pub exported_at: DateTime<Utc>,
+    pub user: ExportUser,
+    pub folders: Vec<ExportFolder>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportFolder {
+    pub folder_id: i32,
+    pub folder_name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub images: Vec<ExportImage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportImage {
+    pub image_id: i64,
+    pub original_filename: String,
+    pub mime_type: String,
+    pub file_size: i32,
+    pub metadata: Option<serde_json::Value>,
+    pub uploaded_at: Option<DateTime<Utc>>,
+    /// Path of the file inside the archive; `None` if it was missing from storage
+    pub archive_path: Option<String>,
+    pub analyses: Vec<ExportAnalysis>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportAnalysis {
+    pub job_id: i64,
+    pub status: String,
+    pub ai_model_version: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub result: Option<AnalysisResult>,
+}
+
+// ============================================================================
+// Export Service
+// ============================================================================
+
+pub struct ExportService;
+
+impl ExportService {
+    /// Storage key of the generated archive for an export
+    pub fn archive_key(user_id: Uuid, export_id: Uuid) -> String {
+        format!("exports/{}/{}.zip", user_id, export_id)
+    }
+
+    /// Generate an export's archive, store it, and record the outcome
+    ///
+    /// Meant to run off the request path; failures are recorded on the
+    /// export row for the client to see when polling.
+    pub async fn run(
+        pool: PgPool,
+        storage: Arc<dyn StorageBackend>,
+        export_id: Uuid,
+        user_id: Uuid,
+    ) {
+        if let Err(e) = DataExportRepository::start_processing(&pool, export_id).await {
+            tracing::error!("Failed to start export {}: {:?}", export_id, e);
+            return;
+        }
+
+        let outcome = async {
+            let archive = Self::build_archive(&pool, storage.as_ref(), user_id).await?;
+            let key = Self::archive_key(user_id, export_id);
+            storage
+                .upload_from_file(&key, tokio::fs::File::from_std(archive), "application/zip")
+                .await?;
+            Ok::<_, ExportError>(key)
+        }
+        .await;
+
+        let recorded = match outcome {
+            Ok(key) => {
+                tracing::info!("Data export {} completed for user {}", export_id, user_id);
+                DataExportRepository::complete(&pool, export_id, &key).await
+            }
+            Err(e) => {
+                tracing::error!("Data export {} failed: {:?}", export_id, e);
+                DataExportRepository::fail(&pool, export_id, "Failed to generate export").await
+            }
+        };
+
+        if let Err(e) = recorded {
+            tracing::error!("Failed to record outcome of export {}: {:?}", export_id, e);
+        }
+    }
+
+    /// Build the ZIP archive of everything a user has stored
+    ///
+    /// Covers live (not soft-deleted) folders and images. Returns the
+    /// archive as a temp file positioned at its start; the file is removed
+    /// once closed. Only one image is held in memory at a time.
+    pub async fn build_archive(
+        pool: &PgPool,
+        storage: &dyn StorageBackend,
+        user_id: Uuid,
+    ) -> Result<File, ExportError> {
+        let user = UserRepository::find_by_id(pool, user_id)
+            .await?
+            .ok_or(ExportError::UserNotFound)?;
+        let folders = FolderRepository::find_by_user_id(pool, user_id).await?;
+        let images = ImageRepository::find_all_by_user_id(pool, user_id).await?;
+        let jobs = JobRepository::find_all_by_user_id(pool, user_id).await?;
+        let results = AnalysisResultRepository::find_all_by_user_id(pool, user_id).await?;
+
+        let mut zip = ZipWriter::new(tempfile::tempfile()?);
+        // Image formats are already compressed, so store them as-is
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        let mut archive_paths = HashMap::with_capacity(images.len());
+        for image in &images {
+            let bytes = match storage.get(&image.file_path).await {
+                Ok((bytes, _)) => bytes,
+                Err(StorageError::NotFound(_)) => {
+                    tracing::warn!("Image {} missing from storage during export", image.image_id);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let path = Self::image_archive_path(image);
+            zip.start_file(path.as_str(), stored)?;
+            zip.write_all(&bytes)?;
+            archive_paths.insert(image.image_id, path);
+        }
+
+        let manifest = Self::build_manifest(user, folders, images, jobs, results, &archive_paths);
+        let manifest = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| ExportError::Archive(e.to_string()))?;
+        zip.start_file(MANIFEST_FILE, deflated)?;
+        zip.write_all(&manifest)?;
+
+        let mut archive = zip.finish()?;
+        archive.seek(SeekFrom::Start(0))?;
+        Ok(archive)
+    }
+
+    /// Archive path for an image, unique by ID and free of path separators
+    fn image_archive_path(image: &Image) -> String {
+        let filename: String = image
+            .original_filename
+            .chars()
+            .map(|c| if c == '/' || c == '\\' || c.is_control() { '_' } else { c })
+            .collect();
+        format!("images/{}/{}_{}", image.folder_id, image.image_id, filename)
+    }
+
+    fn build_manifest(
+        user: User,
+        folders: Vec<(Folder, i64)>,
+        images: Vec<Image>,
+        jobs: Vec<Job>,
+        results: Vec<AnalysisResult>,
+        archive_paths: &HashMap<i64, String>,
+    ) -> ExportManifest {
+        let mut results_by_job: HashMap<i64, AnalysisResult> =
+            results.into_iter().map(|r| (r.job_id, r)).collect();
+
+        let mut analyses_by_image: HashMap<i64, Vec<ExportAnalysis>> = HashMap::new();
+        for job in jobs {
+            analyses_by_image.entry(job.image_id).or_default().push(ExportAnalysis {
+                result: results_by_job.remove(&job.job_id),
+                job_id: job.job_id,
+                status: job.status.to_string(),
+                ai_model_version: job.ai_model_version,
+                created_at: job.created_at,
+                started_at: job.started_at,
+                finished_at: job.finished_at,
+                error_message: job.error_message,
+            });
+        }
+
+        let mut images_by_folder: HashMap<i32, Vec<ExportImage>> = HashMap::new();
+        for image in images {
+            images_by_folder.entry(image.folder_id).or_default().push(ExportImage {
+                archive_path: archive_paths.get(&image.image_id).cloned(),
+                analyses: analyses_by_image.remove(&image.image_id).unwrap_or_default(),
+                image_id: image.image_id,
+                original_filename: image.original_filename,
+                mime_type: image.mime_type,
+                file_size: image.file_size,
+                metadata: image.metadata,
+                uploaded_at: image.uploaded_at,
+            });
+        }
+
+        ExportManifest {
+            exported_at: Utc::now(),
+            user: ExportUser {
+                user_id: user.user_id,
+                username: user.username,
+                created_at: user.created_at,
+            },
+            folders: folders
+                .into_iter()
+                .map(|(folder, _)| ExportFolder {
+                    images: images_by_folder.remove(&folder.folder_id).unwrap_or_default(),
+                    folder_id: folder.folder_id,
+                    folder_name: folder.folder_name,
+                    created_at: folder.created_at,
+                })
+                .collect(),
+        }
+    }
+}
@@ -2,23 +2,69 @@
 //!
 //! Business logic for image file handling, validation, and storage.
 
+use std::collections::HashSet;
 use std::io::Read;
 use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageFormat;
 use thiserror::Error;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+use crate::config::settings::{AnalysisConfig, DuplicateFilenamePolicy, ThumbnailFormat};
+use crate::dto::analysis::BoundingBox;
+use crate::models::{Image, ImageMetadata};
+
 // ============================================================================
 // Constants
 // ============================================================================
 
 /// Allowed MIME types for image uploads
-pub const ALLOWED_MIME_TYPES: &[&str] = &["image/jpeg", "image/png", "image/tiff"];
+pub const ALLOWED_MIME_TYPES: &[&str] = &["image/jpeg", "image/png", "image/tiff", "image/webp"];
 
 /// Maximum file size in bytes (50 MB)
 pub const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
 
+/// Minimum slack allowed between a presigned upload's declared and stored size
+pub const UPLOAD_SIZE_TOLERANCE_BYTES: u64 = 1024;
+
+/// Longest original filename stored (matches the `images.original_filename` column)
+pub const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Share of the original size a JPEG re-encode must save to replace it
+pub const MIN_COMPRESSION_SAVING_PERCENT: usize = 10;
+
+/// Highest `(n)` counter tried before a suffixed filename gives up
+pub const MAX_FILENAME_SUFFIX: u32 = 1000;
+
+/// Longest side in pixels of generated thumbnails
+pub const THUMBNAIL_MAX_DIMENSION: u32 = 256;
+
+/// Width in pixels of the box outlines drawn on overlays
+pub const OVERLAY_LINE_WIDTH: u32 = 2;
+
+/// Pixels per glyph dot in the omitted-box notice drawn on overlays
+const NOTICE_SCALE: u32 = 2;
+
+/// 3x5 glyphs for `+` then the digits 0-9, one row per byte with the
+/// leftmost dot in bit 2
+const NOTICE_GLYPHS: [[u8; 5]; 11] = [
+    [0b000, 0b010, 0b111, 0b010, 0b000],
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
 /// Base storage path for uploaded images
 pub const STORAGE_PATH: &str = "./uploads";
 
@@ -28,7 +74,7 @@ pub const STORAGE_PATH: &str = "./uploads";
 
 #[derive(Debug, Error)]
 pub enum ImageServiceError {
-    #[error("Invalid file type. Allowed: JPEG, PNG, TIFF")]
+    #[error("Invalid file type. Allowed: JPEG, PNG, TIFF, WebP")]
     InvalidFileType,
 
     #[error("Invalid magic bytes. File content does not match declared type")]
@@ -42,6 +88,14 @@ pub enum ImageServiceError {
     #[error("Failed to save file: {0}")]
     SaveError(String),
 
+    #[error("Image is too small for analysis ({width}x{height}, minimum {min_width}x{min_height})")]
+    TooSmallForAnalysis {
+        width: u32,
+        height: u32,
+        min_width: u32,
+        min_height: u32,
+    },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -80,7 +134,7 @@ impl ImageService {
             | [0x89, 0x50, 0x4E, 0x47]     // PNG
             | [0x49, 0x49, 0x2A, 0x00]     // TIFF (little-endian)
             | [0x4D, 0x4D, 0x00, 0x2A]     // TIFF (big-endian)
-        );
+        ) || Self::is_webp(bytes);
 
         if !valid {
             return Err(ImageServiceError::InvalidMagicBytes);
@@ -89,6 +143,95 @@ impl ImageService {
         Ok(())
     }
 
+    /// Check that an image is large enough to be analyzed meaningfully
+    ///
+    /// Images without recorded dimensions pass, since they can't be judged
+    /// without fetching the file.
+    pub fn check_analysis_suitability(
+        image: &Image,
+        config: &AnalysisConfig,
+    ) -> Result<(), ImageServiceError> {
+        let Some((width, height)) = image.dimensions() else {
+            return Ok(());
+        };
+
+        if width < config.min_image_width || height < config.min_image_height {
+            return Err(ImageServiceError::TooSmallForAnalysis {
+                width,
+                height,
+                min_width: config.min_image_width,
+                min_height: config.min_image_height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Filename with a ` (n)` counter before the extension, e.g. `cells (1).jpg`
+    pub fn suffixed_filename(filename: &str, n: u32) -> String {
+        // A leading dot (".hidden") is part of the name, not an extension
+        match filename.rfind('.').filter(|&i| i > 0) {
+            Some(i) => format!("{} ({}){}", &filename[..i], n, &filename[i..]),
+            None => format!("{} ({})", filename, n),
+        }
+    }
+
+    /// Filename with its extension lowercased, e.g. `photo.JPG` -> `photo.jpg`
+    pub fn lowercase_extension(filename: &str) -> String {
+        match filename.rfind('.').filter(|&i| i > 0) {
+            Some(i) => format!("{}{}", &filename[..i], filename[i..].to_lowercase()),
+            None => filename.to_string(),
+        }
+    }
+
+    /// Name for an image joining a folder whose live filenames are `taken`
+    ///
+    /// Returns `None` when the duplicate-filename policy refuses the name.
+    pub fn resolve_filename(
+        policy: DuplicateFilenamePolicy,
+        filename: &str,
+        taken: &HashSet<String>,
+    ) -> Option<String> {
+        if policy == DuplicateFilenamePolicy::Allow || !taken.contains(filename) {
+            return Some(filename.to_string());
+        }
+        if policy == DuplicateFilenamePolicy::Reject {
+            return None;
+        }
+        (1..=MAX_FILENAME_SUFFIX)
+            .map(|n| Self::suffixed_filename(filename, n))
+            .find(|candidate| !taken.contains(candidate))
+    }
+
+    /// Whether a stored object's size is close enough to the size the client declared
+    ///
+    /// Allows the larger of `UPLOAD_SIZE_TOLERANCE_BYTES` and 1% of the declared size.
+    pub fn upload_size_matches(declared: i64, actual: u64) -> bool {
+        let declared = declared.max(0) as u64;
+        let tolerance = UPLOAD_SIZE_TOLERANCE_BYTES.max(declared / 100);
+        declared.abs_diff(actual) <= tolerance
+    }
+
+    /// Client-supplied filename reduced to a safe display name
+    ///
+    /// Drops any directory components and control characters and caps the
+    /// length. Returns `None` if nothing usable is left.
+    pub fn sanitize_filename(filename: &str) -> Option<String> {
+        let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+        let name: String = name
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(MAX_FILENAME_LENGTH)
+            .collect();
+        let name = name.trim();
+
+        if name.is_empty() || name == "." || name == ".." {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+
     /// Generate a unique storage path for an image
     pub fn generate_storage_path(original_filename: &str) -> (String, String) {
         let uuid = Uuid::new_v4();
@@ -104,6 +247,207 @@ impl ImageService {
         (file_path, filename)
     }
 
+    /// Re-encode a JPEG at `quality` (1-100), keeping its dimensions
+    ///
+    /// The original's APP1 (EXIF/XMP) segments are carried over, so the
+    /// Orientation tag still applies to the untouched pixel data.
+    /// Returns `None`, meaning the original should be stored as-is, for
+    /// non-JPEG or undecodable input and when the result is not at least
+    /// `MIN_COMPRESSION_SAVING_PERCENT` smaller.
+    pub fn compress_jpeg(bytes: &[u8], quality: u8) -> Option<Vec<u8>> {
+        if !bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return None;
+        }
+
+        let decoded = image::load_from_memory_with_format(bytes, ImageFormat::Jpeg).ok()?;
+        let mut encoded = Vec::new();
+        let encoder = JpegEncoder::new_with_quality(&mut encoded, quality.clamp(1, 100));
+        decoded.write_with_encoder(encoder).ok()?;
+        let compressed = Self::with_app1_segments(&encoded, &Self::jpeg_app1_segments(bytes));
+
+        let max_size = bytes.len() - bytes.len() * MIN_COMPRESSION_SAVING_PERCENT / 100;
+        (compressed.len() < max_size).then_some(compressed)
+    }
+
+    /// The APP1 segments (markers included) of a JPEG's header, in order
+    fn jpeg_app1_segments(bytes: &[u8]) -> Vec<u8> {
+        let mut segments = Vec::new();
+        let mut pos = 2;
+
+        // Header segments run until start-of-scan; stop early on anything malformed
+        while pos + 4 <= bytes.len() && bytes[pos] == 0xFF && bytes[pos + 1] != 0xDA {
+            let end = pos + 2 + u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+            if end > bytes.len() {
+                break;
+            }
+            if bytes[pos + 1] == 0xE1 {
+                segments.extend_from_slice(&bytes[pos..end]);
+            }
+            pos = end;
+        }
+
+        segments
+    }
+
+    /// Insert `segments` into an encoded JPEG, after its JFIF APP0 segment if any
+    fn with_app1_segments(encoded: &[u8], segments: &[u8]) -> Vec<u8> {
+        let mut insert_at = 2;
+        if encoded.len() >= 6 && encoded[2..4] == [0xFF, 0xE0] {
+            insert_at = (4 + u16::from_be_bytes([encoded[4], encoded[5]]) as usize).min(encoded.len());
+        }
+
+        let mut output = Vec::with_capacity(encoded.len() + segments.len());
+        output.extend_from_slice(&encoded[..insert_at]);
+        output.extend_from_slice(segments);
+        output.extend_from_slice(&encoded[insert_at..]);
+        output
+    }
+
+    /// Downscale an image to fit `THUMBNAIL_MAX_DIMENSION` and encode it as `format`
+    ///
+    /// Images already within the bound keep their size. Returns `None` for
+    /// input the built-in codecs cannot decode, in which case clients get
+    /// the original instead.
+    pub fn generate_thumbnail(bytes: &[u8], format: ThumbnailFormat) -> Option<Vec<u8>> {
+        let decoded = image::load_from_memory(bytes).ok()?;
+        let resized = if decoded.width() > THUMBNAIL_MAX_DIMENSION || decoded.height() > THUMBNAIL_MAX_DIMENSION {
+            decoded.thumbnail(THUMBNAIL_MAX_DIMENSION, THUMBNAIL_MAX_DIMENSION)
+        } else {
+            decoded
+        };
+
+        // JPEG has no alpha channel, and the WebP encoder only takes 8-bit pixels
+        let (pixels, encoding) = match format {
+            ThumbnailFormat::Jpeg => (image::DynamicImage::ImageRgb8(resized.to_rgb8()), ImageFormat::Jpeg),
+            ThumbnailFormat::Webp => (image::DynamicImage::ImageRgba8(resized.to_rgba8()), ImageFormat::WebP),
+            ThumbnailFormat::Png => (image::DynamicImage::ImageRgba8(resized.to_rgba8()), ImageFormat::Png),
+        };
+
+        let mut encoded = std::io::Cursor::new(Vec::new());
+        pixels.write_to(&mut encoded, encoding).ok()?;
+        Some(encoded.into_inner())
+    }
+
+    /// Storage key of an image's thumbnail in `format`
+    ///
+    /// The format's extension is part of the key, so changing
+    /// `storage.thumbnail_format` never serves a thumbnail cached in another format.
+    pub fn thumbnail_key(file_path: &str, format: ThumbnailFormat) -> String {
+        let stem = std::path::Path::new(file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(file_path);
+        format!("thumbnails/{}.{}", stem, format.extension())
+    }
+
+    /// Storage key of the rendered overlay for a job's result
+    pub fn overlay_key(job_id: i64) -> String {
+        format!("overlays/job-{}.jpg", job_id)
+    }
+
+    /// Draw `boxes` onto a JPEG image, returning the result as a JPEG
+    ///
+    /// Boxes are outlined by class: viable green, apoptotic red, anything
+    /// else yellow. When `omitted` boxes were left out, `+<omitted>` is
+    /// written in the top-left corner. Returns `None` for non-JPEG or
+    /// undecodable input.
+    pub fn render_overlay(bytes: &[u8], boxes: &[&BoundingBox], omitted: usize) -> Option<Vec<u8>> {
+        if !bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return None;
+        }
+
+        let mut canvas = image::load_from_memory_with_format(bytes, ImageFormat::Jpeg)
+            .ok()?
+            .to_rgb8();
+        let (width, height) = canvas.dimensions();
+
+        for bbox in boxes {
+            let color = match bbox.class.as_str() {
+                "normal" | "viable" => image::Rgb([0, 200, 0]),
+                "apoptosis" => image::Rgb([220, 0, 0]),
+                _ => image::Rgb([230, 200, 0]),
+            };
+
+            // Clamp to the canvas; boxes entirely outside it draw nothing
+            let left = bbox.x.max(0) as u32;
+            let top = bbox.y.max(0) as u32;
+            let right = (bbox.x.saturating_add(bbox.width).max(0) as u32).min(width);
+            let bottom = (bbox.y.saturating_add(bbox.height).max(0) as u32).min(height);
+            if left >= right || top >= bottom {
+                continue;
+            }
+
+            // Only the edges are visited, so large boxes stay cheap
+            let line = OVERLAY_LINE_WIDTH.min(right - left).min(bottom - top);
+            for y in (top..top + line).chain(bottom - line..bottom) {
+                for x in left..right {
+                    canvas.put_pixel(x, y, color);
+                }
+            }
+            for x in (left..left + line).chain(right - line..right) {
+                for y in top..bottom {
+                    canvas.put_pixel(x, y, color);
+                }
+            }
+        }
+
+        if omitted > 0 {
+            Self::draw_omitted_notice(&mut canvas, omitted);
+        }
+
+        let mut rendered = Vec::new();
+        let encoder = JpegEncoder::new_with_quality(&mut rendered, 90);
+        image::DynamicImage::ImageRgb8(canvas).write_with_encoder(encoder).ok()?;
+        Some(rendered)
+    }
+
+    /// Write `+<omitted>` in white on a black label in the canvas' top-left
+    /// corner, clipped to the canvas
+    fn draw_omitted_notice(canvas: &mut image::RgbImage, omitted: usize) {
+        let text = format!("+{}", omitted);
+        let (width, height) = canvas.dimensions();
+        let advance = 4 * NOTICE_SCALE;
+        let label_width = (2 * NOTICE_SCALE + text.len() as u32 * advance - NOTICE_SCALE).min(width);
+        let label_height = (2 * NOTICE_SCALE + 5 * NOTICE_SCALE).min(height);
+
+        for y in 0..label_height {
+            for x in 0..label_width {
+                canvas.put_pixel(x, y, image::Rgb([0, 0, 0]));
+            }
+        }
+
+        for (index, c) in text.chars().enumerate() {
+            let glyph = match c.to_digit(10) {
+                Some(digit) => NOTICE_GLYPHS[digit as usize + 1],
+                None => NOTICE_GLYPHS[0],
+            };
+            let left = NOTICE_SCALE + index as u32 * advance;
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+                    let x0 = left + column * NOTICE_SCALE;
+                    let y0 = NOTICE_SCALE + row as u32 * NOTICE_SCALE;
+                    for y in y0..(y0 + NOTICE_SCALE).min(label_height) {
+                        for x in x0..(x0 + NOTICE_SCALE).min(label_width) {
+                            canvas.put_pixel(x, y, image::Rgb([255, 255, 255]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Storage keys of files derived from an image, such as thumbnails in
+    /// every format, which must go when the image is purged
+    pub fn derived_keys(file_path: &str) -> Vec<String> {
+        ThumbnailFormat::ALL
+            .iter()
+            .map(|format| Self::thumbnail_key(file_path, *format))
+            .collect()
+    }
+
     /// Save image bytes to disk
     pub async fn save_file(
         bytes: &[u8],
@@ -123,18 +467,49 @@ impl ImageService {
         Ok(())
     }
 
+    /// Canonical form of a client-supplied MIME type
+    ///
+    /// Drops parameters such as `; charset=...`, lowercases, and maps common
+    /// aliases (`image/jpg`, `image/x-png`, ...) to the types in
+    /// `ALLOWED_MIME_TYPES`, so stored types compare and filter consistently.
+    pub fn canonical_mime_type(content_type: &str) -> String {
+        let mime_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match mime_type.as_str() {
+            "image/jpg" | "image/pjpeg" => "image/jpeg".to_string(),
+            "image/x-png" => "image/png".to_string(),
+            "image/tif" | "image/x-tiff" => "image/tiff".to_string(),
+            _ => mime_type,
+        }
+    }
+
     /// Get extension from MIME type
-    /// Reserved for future S3 storage integration
-    #[allow(dead_code)]
     pub fn get_extension_from_mime(mime_type: &str) -> &'static str {
         match mime_type {
             "image/jpeg" => "jpg",
             "image/png" => "png",
             "image/tiff" => "tiff",
+            "image/webp" => "webp",
             _ => "bin",
         }
     }
 
+    /// File extensions clients may use for a MIME type, preferred one first
+    pub fn extensions_for_mime(mime_type: &str) -> &'static [&'static str] {
+        match mime_type {
+            "image/jpeg" => &["jpg", "jpeg"],
+            "image/png" => &["png"],
+            "image/tiff" => &["tiff", "tif"],
+            "image/webp" => &["webp"],
+            _ => &[],
+        }
+    }
+
     /// Extract basic metadata from image bytes (width, height)
     /// Note: This is a simplified version that reads headers only
     pub fn extract_metadata(bytes: &[u8]) -> Option<(u32, u32)> {
@@ -151,11 +526,70 @@ impl ImageService {
         } else if magic == [0x89, 0x50, 0x4E, 0x47] {
             // PNG - dimensions in IHDR chunk
             Self::extract_png_dimensions(bytes)
+        } else if Self::is_webp(bytes) {
+            // WebP - dimensions in the first chunk's header
+            Self::extract_webp_dimensions(bytes)
         } else {
             None
         }
     }
 
+    /// Metadata to store for an upload: dimensions and EXIF capture time
+    ///
+    /// Returns `None` when nothing could be read from the file.
+    pub fn image_metadata(bytes: &[u8]) -> Option<ImageMetadata> {
+        let dimensions = Self::extract_metadata(bytes);
+        let captured_at = Self::extract_captured_at(bytes);
+        if dimensions.is_none() && captured_at.is_none() {
+            return None;
+        }
+
+        Some(ImageMetadata {
+            width: dimensions.map(|(width, _)| width),
+            height: dimensions.map(|(_, height)| height),
+            captured_at,
+        })
+    }
+
+    /// Read the capture time from the EXIF `DateTimeOriginal` tag of a JPEG
+    /// or TIFF
+    ///
+    /// EXIF records local time; `OffsetTimeOriginal` is applied when present,
+    /// otherwise the time is taken as UTC. Missing or malformed EXIF gives
+    /// `None`.
+    pub fn extract_captured_at(bytes: &[u8]) -> Option<DateTime<Utc>> {
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(bytes))
+            .ok()?;
+        let ascii = |tag: exif::Tag| -> Option<Vec<u8>> {
+            match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+                exif::Value::Ascii(values) => values.first().cloned(),
+                _ => None,
+            }
+        };
+
+        let mut datetime =
+            exif::DateTime::from_ascii(&ascii(exif::Tag::DateTimeOriginal)?).ok()?;
+        if let Some(offset) = ascii(exif::Tag::OffsetTimeOriginal) {
+            // A malformed offset leaves the time as UTC
+            let _ = datetime.parse_offset(&offset);
+        }
+
+        let local = NaiveDate::from_ymd_opt(
+            datetime.year.into(),
+            datetime.month.into(),
+            datetime.day.into(),
+        )?
+        .and_hms_opt(
+            datetime.hour.into(),
+            datetime.minute.into(),
+            datetime.second.into(),
+        )?;
+        let offset = FixedOffset::east_opt(i32::from(datetime.offset.unwrap_or(0)) * 60)?;
+
+        Some(local.and_local_timezone(offset).single()?.with_timezone(&Utc))
+    }
+
     /// Extract dimensions from JPEG SOF marker
     fn extract_jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
         let mut cursor = std::io::Cursor::new(bytes);
@@ -211,6 +645,46 @@ impl ImageService {
         }
     }
 
+    /// Whether bytes start with a WebP container header (`RIFF....WEBP`)
+    fn is_webp(bytes: &[u8]) -> bool {
+        bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP"
+    }
+
+    /// Extract dimensions from the VP8, VP8L or VP8X chunk that follows the
+    /// WebP header
+    fn extract_webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+        let le16 = |at: usize| {
+            let b = bytes.get(at..at + 2)?;
+            Some(u32::from_le_bytes([b[0], b[1], 0, 0]))
+        };
+        let le24 = |at: usize| {
+            let b = bytes.get(at..at + 3)?;
+            Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+        };
+
+        // Chunk payload starts at 20, after the 4-byte FourCC and 4-byte size
+        match bytes.get(12..16)? {
+            // Lossy: 3-byte frame tag and 9D 01 2A start code, then 14-bit sizes
+            b"VP8 " => {
+                if bytes.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                    return None;
+                }
+                Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF))
+            }
+            // Lossless: 0x2F signature, then 14-bit width-1 and height-1
+            b"VP8L" => {
+                if *bytes.get(20)? != 0x2F {
+                    return None;
+                }
+                let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            // Extended: 4 bytes of flags, then 24-bit canvas width-1 and height-1
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        }
+    }
+
     /// Extract dimensions from PNG IHDR chunk
     fn extract_png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
         // PNG header is 8 bytes, then IHDR chunk
@@ -247,6 +721,47 @@ mod tests {
         assert!(ImageService::validate_file("image/png", &png_bytes).is_ok());
     }
 
+    /// WebP header with a `chunk` FourCC followed by `payload`
+    fn webp(chunk: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WEBP".to_vec();
+        bytes.extend_from_slice(chunk);
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_validate_webp_magic() {
+        let lossy = webp(b"VP8 ", &[0x30, 0x01, 0x00, 0x9D, 0x01, 0x2A, 0x40, 0x01, 0xF0, 0x00]);
+        assert!(ImageService::validate_file("image/webp", &lossy).is_ok());
+        assert_eq!(ImageService::extract_metadata(&lossy), Some((320, 240)));
+
+        // width-1 = 99 and height-1 = 49, packed as 14-bit fields
+        let bits: u32 = 99 | (49 << 14);
+        let mut lossless = vec![0x2F];
+        lossless.extend_from_slice(&bits.to_le_bytes());
+        lossless.extend_from_slice(&[0; 4]);
+        assert_eq!(ImageService::extract_metadata(&webp(b"VP8L", &lossless)), Some((100, 50)));
+
+        let extended = [0x10, 0, 0, 0, 0xFF, 0x07, 0x00, 0xFF, 0x03, 0x00];
+        assert_eq!(ImageService::extract_metadata(&webp(b"VP8X", &extended)), Some((2048, 1024)));
+    }
+
+    #[test]
+    fn test_spoofed_webp_rejected() {
+        // A RIFF container that is not WebP (here a WAVE file)
+        let mut wave = webp(b"fmt ", &[0; 16]);
+        wave[8..12].copy_from_slice(b"WAVE");
+        assert!(matches!(
+            ImageService::validate_file("image/webp", &wave),
+            Err(ImageServiceError::InvalidMagicBytes)
+        ));
+        assert!(matches!(
+            ImageService::validate_file("image/webp", b"RIFF\0\0"),
+            Err(ImageServiceError::InvalidMagicBytes)
+        ));
+    }
+
     #[test]
     fn test_invalid_mime_type() {
         let bytes = vec![0xFF, 0xD8, 0xFF, 0xE0];
@@ -271,4 +786,288 @@ mod tests {
         assert!(path.starts_with(STORAGE_PATH));
         assert!(filename.ends_with(".jpg"));
     }
+
+    #[test]
+    fn test_thumbnail_key_includes_format() {
+        let jpeg = ImageService::thumbnail_key("images/abc.tiff", ThumbnailFormat::Jpeg);
+        let webp = ImageService::thumbnail_key("images/abc.tiff", ThumbnailFormat::Webp);
+        assert_eq!(jpeg, "thumbnails/abc.jpg");
+        assert_eq!(webp, "thumbnails/abc.webp");
+        assert_eq!(ThumbnailFormat::Webp.content_type(), "image/webp");
+    }
+
+    #[test]
+    fn test_render_overlay_outlines_boxes() {
+        let pixels = image::RgbImage::from_pixel(40, 30, image::Rgb([255, 255, 255]));
+        let mut original = Vec::new();
+        image::DynamicImage::ImageRgb8(pixels)
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut original, 100))
+            .unwrap();
+        let bbox = BoundingBox {
+            class: "apoptosis".to_string(),
+            confidence: 0.9,
+            x: 10,
+            y: 5,
+            width: 20,
+            height: 15,
+        };
+
+        let rendered = ImageService::render_overlay(&original, &[&bbox], 0).expect("should render");
+        let canvas = image::load_from_memory(&rendered).unwrap().to_rgb8();
+
+        assert_eq!(canvas.dimensions(), (40, 30));
+        // Edge is red, interior and outside stay white (allowing for JPEG noise)
+        let edge = canvas.get_pixel(10, 12);
+        assert!(edge[0] > 150 && edge[1] < 100, "edge was {:?}", edge);
+        assert!(canvas.get_pixel(20, 12).0.iter().all(|c| *c > 200));
+        assert!(canvas.get_pixel(2, 2).0.iter().all(|c| *c > 200));
+
+        assert!(ImageService::render_overlay(b"\x89PNG\r\n\x1a\n", &[&bbox], 0).is_none());
+    }
+
+    #[test]
+    fn test_compress_jpeg_keeps_dimensions() {
+        let pixels = image::RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, ((x ^ y) * 8) as u8])
+        });
+        let mut original = Vec::new();
+        image::DynamicImage::ImageRgb8(pixels)
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut original, 100))
+            .unwrap();
+
+        let compressed = ImageService::compress_jpeg(&original, 60).expect("should shrink");
+        assert!(compressed.len() < original.len());
+        assert_eq!(ImageService::extract_metadata(&compressed), Some((64, 48)));
+
+        // Already small enough at this quality, and non-JPEG input, are left alone
+        assert!(ImageService::compress_jpeg(&compressed, 95).is_none());
+        assert!(ImageService::compress_jpeg(b"\x89PNG\r\n\x1a\n", 60).is_none());
+    }
+
+    #[test]
+    fn test_compress_jpeg_keeps_exif() {
+        let pixels = image::RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, ((x ^ y) * 8) as u8])
+        });
+        let mut plain = Vec::new();
+        image::DynamicImage::ImageRgb8(pixels)
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut plain, 100))
+            .unwrap();
+        let exif = jpeg_with_exif(&[(0x9003, "2025:06:01 08:30:00")]);
+        let app1 = ImageService::jpeg_app1_segments(&exif);
+        assert!(!app1.is_empty());
+        let original = ImageService::with_app1_segments(&plain, &app1);
+
+        let compressed = ImageService::compress_jpeg(&original, 60).expect("should shrink");
+        assert_eq!(ImageService::jpeg_app1_segments(&compressed), app1);
+        assert_eq!(
+            ImageService::extract_captured_at(&compressed),
+            ImageService::extract_captured_at(&original)
+        );
+        assert!(ImageService::extract_captured_at(&compressed).is_some());
+        assert_eq!(ImageService::extract_metadata(&compressed), Some((64, 48)));
+    }
+
+    /// Small JPEG carrying an EXIF segment with the given Exif IFD ASCII tags
+    fn jpeg_with_exif(tags: &[(u16, &str)]) -> Vec<u8> {
+        // Big-endian TIFF header, then IFD0 holding only the Exif IFD pointer
+        let exif_ifd_offset = 8 + 18;
+        let mut data_offset = exif_ifd_offset + 2 + 12 * tags.len() + 4;
+        let mut tiff = b"MM\x00\x2a\x00\x00\x00\x08".to_vec();
+        tiff.extend_from_slice(&1u16.to_be_bytes());
+        tiff.extend_from_slice(&[0x87, 0x69, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01]);
+        tiff.extend_from_slice(&(exif_ifd_offset as u32).to_be_bytes());
+        tiff.extend_from_slice(&0u32.to_be_bytes());
+
+        let mut data = Vec::new();
+        tiff.extend_from_slice(&(tags.len() as u16).to_be_bytes());
+        for (tag, value) in tags {
+            let value = format!("{}\0", value);
+            tiff.extend_from_slice(&tag.to_be_bytes());
+            tiff.extend_from_slice(&2u16.to_be_bytes());
+            tiff.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            tiff.extend_from_slice(&(data_offset as u32).to_be_bytes());
+            data_offset += value.len();
+            data.extend_from_slice(value.as_bytes());
+        }
+        tiff.extend_from_slice(&0u32.to_be_bytes());
+        tiff.extend_from_slice(&data);
+
+        let mut segment = b"Exif\0\0".to_vec();
+        segment.extend_from_slice(&tiff);
+        exif_segment_jpeg(&segment)
+    }
+
+    #[test]
+    fn test_generate_thumbnail_fits_bound() {
+        let mut original = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(1024, 512))
+            .write_to(&mut std::io::Cursor::new(&mut original), ImageFormat::Png)
+            .unwrap();
+
+        let thumbnail = ImageService::generate_thumbnail(&original, ThumbnailFormat::Jpeg).unwrap();
+        assert_eq!(ImageService::extract_metadata(&thumbnail), Some((256, 128)));
+
+        // Small images are re-encoded at their own size
+        let small = ImageService::generate_thumbnail(&plain_jpeg(), ThumbnailFormat::Png).unwrap();
+        assert!(small.starts_with(b"\x89PNG"));
+        assert_eq!(ImageService::extract_metadata(&small), Some((16, 8)));
+
+        assert!(ImageService::generate_thumbnail(b"not an image", ThumbnailFormat::Jpeg).is_none());
+    }
+
+    fn plain_jpeg() -> Vec<u8> {
+        let mut plain = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(16, 8))
+            .write_with_encoder(JpegEncoder::new(&mut plain))
+            .unwrap();
+        plain
+    }
+
+    /// 16x8 JPEG with `payload` inserted as an APP1 segment after SOI
+    fn exif_segment_jpeg(payload: &[u8]) -> Vec<u8> {
+        let plain = plain_jpeg();
+        let mut jpeg = plain[..2].to_vec();
+        jpeg.extend_from_slice(&[0xFF, 0xE1]);
+        jpeg.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        jpeg.extend_from_slice(payload);
+        jpeg.extend_from_slice(&plain[2..]);
+        jpeg
+    }
+
+    #[test]
+    fn test_extract_captured_at_from_exif() {
+        let jpeg = jpeg_with_exif(&[(0x9003, "2024:05:06 07:08:09")]);
+        let expected = "2024-05-06T07:08:09Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(ImageService::extract_captured_at(&jpeg), Some(expected));
+
+        let metadata = ImageService::image_metadata(&jpeg).unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(16), Some(8)));
+        assert_eq!(metadata.captured_at, Some(expected));
+
+        // OffsetTimeOriginal shifts the local time to UTC
+        let jpeg = jpeg_with_exif(&[(0x9003, "2024:05:06 07:08:09"), (0x9011, "+02:00")]);
+        let expected = "2024-05-06T05:08:09Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(ImageService::extract_captured_at(&jpeg), Some(expected));
+    }
+
+    #[test]
+    fn test_extract_captured_at_without_or_with_bad_exif() {
+        let plain = plain_jpeg();
+        assert_eq!(ImageService::extract_captured_at(&plain), None);
+        let metadata = ImageService::image_metadata(&plain).unwrap();
+        assert_eq!(metadata.width, Some(16));
+        assert_eq!(metadata.captured_at, None);
+
+        let truncated = exif_segment_jpeg(b"Exif\0\0MM\x00\x2a\x00\x00\xff\xff");
+        assert_eq!(ImageService::extract_captured_at(&truncated), None);
+        let unset_date = jpeg_with_exif(&[(0x9003, "0000:00:00 00:00:00")]);
+        assert_eq!(ImageService::extract_captured_at(&unset_date), None);
+        assert!(ImageService::image_metadata(b"not an image at all").is_none());
+    }
+
+    #[test]
+    fn test_resolve_filename_follows_policy() {
+        let taken: HashSet<String> =
+            ["cells.jpg", "cells (1).jpg"].iter().map(|s| s.to_string()).collect();
+        let resolve = |policy, name| ImageService::resolve_filename(policy, name, &taken);
+
+        let allowed = resolve(DuplicateFilenamePolicy::Allow, "cells.jpg");
+        assert_eq!(allowed.as_deref(), Some("cells.jpg"));
+        assert_eq!(resolve(DuplicateFilenamePolicy::Reject, "cells.jpg"), None);
+        let fresh = resolve(DuplicateFilenamePolicy::Reject, "new.jpg");
+        assert_eq!(fresh.as_deref(), Some("new.jpg"));
+        let suffixed = resolve(DuplicateFilenamePolicy::Suffix, "cells.jpg");
+        assert_eq!(suffixed.as_deref(), Some("cells (2).jpg"));
+    }
+
+    #[test]
+    fn test_canonical_mime_type() {
+        assert_eq!(ImageService::canonical_mime_type("image/jpeg"), "image/jpeg");
+        assert_eq!(ImageService::canonical_mime_type("Image/JPG"), "image/jpeg");
+        assert_eq!(ImageService::canonical_mime_type("image/png; charset=binary"), "image/png");
+        assert_eq!(ImageService::canonical_mime_type(" image/x-tiff "), "image/tiff");
+        assert_eq!(ImageService::canonical_mime_type("text/plain"), "text/plain");
+    }
+
+    #[test]
+    fn test_lowercase_extension() {
+        assert_eq!(ImageService::lowercase_extension("Photo.JPG"), "Photo.jpg");
+        assert_eq!(ImageService::lowercase_extension("scan.OME.Tiff"), "scan.OME.tiff");
+        assert_eq!(ImageService::lowercase_extension("README"), "README");
+        assert_eq!(ImageService::lowercase_extension(".Hidden"), ".Hidden");
+    }
+
+    #[test]
+    fn test_suffixed_filename() {
+        assert_eq!(ImageService::suffixed_filename("cells.jpg", 1), "cells (1).jpg");
+        assert_eq!(ImageService::suffixed_filename("scan.ome.tiff", 2), "scan.ome (2).tiff");
+        assert_eq!(ImageService::suffixed_filename("README", 3), "README (3)");
+        assert_eq!(ImageService::suffixed_filename(".hidden", 1), ".hidden (1)");
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(ImageService::sanitize_filename("cells.jpg").as_deref(), Some("cells.jpg"));
+        assert_eq!(
+            ImageService::sanitize_filename("../../etc/cells.jpg").as_deref(),
+            Some("cells.jpg")
+        );
+        assert_eq!(
+            ImageService::sanitize_filename("C:\\scans\\a\r\nb.tiff").as_deref(),
+            Some("ab.tiff")
+        );
+        assert_eq!(ImageService::sanitize_filename("uploads/").as_deref(), None);
+        assert_eq!(ImageService::sanitize_filename(" .. ").as_deref(), None);
+        assert_eq!(
+            ImageService::sanitize_filename(&"a".repeat(300)).map(|n| n.len()),
+            Some(MAX_FILENAME_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_upload_size_matches_within_tolerance() {
+        assert!(ImageService::upload_size_matches(2048, 2048));
+        assert!(ImageService::upload_size_matches(100, 1124));
+        assert!(!ImageService::upload_size_matches(100, 4096));
+        // 1% of 10 MB outweighs the fixed tolerance
+        assert!(ImageService::upload_size_matches(10_000_000, 10_050_000));
+        assert!(!ImageService::upload_size_matches(10_000_000, 10_200_000));
+    }
+
+    fn image_with_metadata(metadata: Option<serde_json::Value>) -> Image {
+        Image {
+            image_id: 1,
+            folder_id: 1,
+            file_path: "images/cells.png".to_string(),
+            original_filename: "cells.png".to_string(),
+            mime_type: "image/png".to_string(),
+            file_size: 1024,
+            metadata,
+            uploaded_at: None,
+            deleted_at: None,
+            thumbnail_key: None,
+            thumbnail_unavailable: false,
+        }
+    }
+
+    #[test]
+    fn test_too_small_image_rejected_for_analysis() {
+        let image = image_with_metadata(Some(serde_json::json!({ "width": 32, "height": 32 })));
+        assert!(matches!(
+            ImageService::check_analysis_suitability(&image, &AnalysisConfig::default()),
+            Err(ImageServiceError::TooSmallForAnalysis { width: 32, height: 32, .. })
+        ));
+    }
+
+    #[test]
+    fn test_analysis_suitability_allows_large_or_unknown() {
+        let config = AnalysisConfig::default();
+
+        let large = image_with_metadata(Some(serde_json::json!({ "width": 1024, "height": 768 })));
+        assert!(ImageService::check_analysis_suitability(&large, &config).is_ok());
+
+        let unknown = image_with_metadata(None);
+        assert!(ImageService::check_analysis_suitability(&unknown, &config).is_ok());
+    }
 }
@@ -0,0 +1,124 @@
+//! Job Progress Consumer
+//!
+//! Consumes the progress messages workers publish while analyzing an image
+//! and reflects them on the job row, so status polling shows live progress.
+//! Final outcomes still arrive through the worker results endpoint.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use lapin::options::{BasicAckOptions, BasicNackOptions};
+use serde::Deserialize;
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::repositories::JobRepository;
+use crate::services::rabbitmq_service::RabbitmqService;
+
+/// Consumer tag announced to the broker
+const CONSUMER_TAG: &str = "job-progress";
+
+/// Wait before reconnecting after the consumer stops or fails to start
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Status a worker can report through the progress queue
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressStatus {
+    Processing,
+}
+
+/// Message published by a worker while it works on a job
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobProgressMessage {
+    pub job_id: i64,
+    pub status: ProgressStatus,
+    pub progress_pct: Option<i16>,
+}
+
+#[derive(Debug, Error)]
+pub enum ProgressError {
+    #[error("Invalid progress message: {0}")]
+    Invalid(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct JobProgressConsumer;
+
+impl JobProgressConsumer {
+    /// Consume `queue` forever, reconnecting whenever the consumer stops
+    pub async fn run(pool: PgPool, rabbitmq: RabbitmqService, queue: String) {
+        loop {
+            match rabbitmq.consume(&queue, CONSUMER_TAG).await {
+                Ok(mut consumer) => {
+                    tracing::info!("Consuming job progress from queue '{}'", queue);
+                    while let Some(delivery) = consumer.next().await {
+                        let delivery = match delivery {
+                            Ok(delivery) => delivery,
+                            Err(e) => {
+                                tracing::warn!("Job progress consumer failed: {:?}", e);
+                                break;
+                            }
+                        };
+
+                        let outcome = Self::handle_message(&pool, &delivery.data).await;
+                        let acked = match outcome {
+                            Ok(_) => delivery.ack(BasicAckOptions::default()).await,
+                            Err(ProgressError::Invalid(reason)) => {
+                                tracing::warn!("Dropping job progress message: {}", reason);
+                                delivery.nack(BasicNackOptions::default()).await
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to record job progress: {:?}", e);
+                                delivery
+                                    .nack(BasicNackOptions {
+                                        requeue: true,
+                                        ..Default::default()
+                                    })
+                                    .await
+                            }
+                        };
+                        if let Err(e) = acked {
+                            tracing::warn!("Failed to acknowledge job progress message: {:?}", e);
+                        }
+                    }
+                    tracing::warn!("Job progress consumer stopped; reconnecting");
+                }
+                Err(e) => tracing::warn!("Failed to start job progress consumer: {:?}", e),
+            }
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// Apply one progress message, returning whether a job was updated
+    ///
+    /// Messages for unknown or already finished jobs are accepted and ignored.
+    pub async fn handle_message(pool: &PgPool, payload: &[u8]) -> Result<bool, ProgressError> {
+        let message: JobProgressMessage =
+            serde_json::from_slice(payload).map_err(|e| ProgressError::Invalid(e.to_string()))?;
+
+        if let Some(pct) = message.progress_pct {
+            if !(0..=100).contains(&pct) {
+                return Err(ProgressError::Invalid(format!(
+                    "progress_pct {} is outside 0-100",
+                    pct
+                )));
+            }
+        }
+
+        let updated = match message.status {
+            ProgressStatus::Processing => {
+                JobRepository::record_progress(pool, message.job_id, message.progress_pct).await?
+            }
+        };
+
+        if !updated {
+            tracing::debug!("Ignored progress for job {} (missing or finished)", message.job_id);
+        }
+
+        Ok(updated)
+    }
+}
@@ -0,0 +1,72 @@
+//! Job Requeue Service
+//!
+//! Re-publishes pending jobs that never reached the analysis queue, such as
+//! jobs submitted while RabbitMQ was unreachable.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::repositories::JobRepository;
+use crate::services::rabbitmq_service::{AnalysisJobMessage, RabbitmqError, RabbitmqService};
+
+/// Maximum number of jobs re-published per pass
+pub const REQUEUE_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Error)]
+pub enum RequeueError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Queue error: {0}")]
+    Queue(#[from] RabbitmqError),
+}
+
+pub struct JobRequeueService;
+
+impl JobRequeueService {
+    /// Re-publish orphaned jobs every `interval`, forever
+    pub async fn run(pool: PgPool, rabbitmq: RabbitmqService, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval.max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            match Self::requeue_pending(&pool, &rabbitmq, interval).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Re-published {} pending analysis job(s)", count),
+                Err(e) => tracing::warn!("Failed to re-publish pending jobs: {:?}", e),
+            }
+        }
+    }
+
+    /// Publish pending jobs that were never queued and are older than
+    /// `min_age`, returning how many were published
+    ///
+    /// The age threshold keeps a pass from racing a submission that is
+    /// still publishing its own job.
+    pub async fn requeue_pending(
+        pool: &PgPool,
+        rabbitmq: &RabbitmqService,
+        min_age: Duration,
+    ) -> Result<usize, RequeueError> {
+        let created_before = Utc::now() - chrono::Duration::from_std(min_age).unwrap_or_default();
+        let jobs = JobRepository::find_unqueued_pending(pool, created_before, REQUEUE_BATCH_SIZE).await?;
+        if jobs.is_empty() {
+            return Ok(0);
+        }
+
+        rabbitmq.reconnect().await?;
+
+        let mut published = 0;
+        for (job, s3_key) in jobs {
+            rabbitmq
+                .publish_analysis_job(AnalysisJobMessage::for_job(&job, s3_key))
+                .await?;
+            JobRepository::mark_queued(pool, job.job_id).await?;
+            published += 1;
+        }
+
+        Ok(published)
+    }
+}
@@ -0,0 +1,242 @@
+//! Local Filesystem Storage Service
+//!
+//! Stores uploaded files on local disk. Intended for development and tests
+//! where running MinIO is not practical.
+
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+use crate::services::image_service::ImageService;
+use crate::services::storage_backend::{ResponseOverrides, StorageBackend, StorageError};
+
+/// Local filesystem storage rooted at a base directory
+#[derive(Clone)]
+pub struct LocalStorageService {
+    root: PathBuf,
+    presign_expiry_secs: u64,
+}
+
+impl LocalStorageService {
+    /// Create a new local storage service rooted at `root`
+    pub fn new(root: impl Into<PathBuf>, presign_expiry_secs: u64) -> Self {
+        Self {
+            root: root.into(),
+            presign_expiry_secs,
+        }
+    }
+
+    /// Resolve an object key to a path under the storage root
+    ///
+    /// Keys come partly from clients (upload tokens), so anything that could
+    /// escape the root (`..`, absolute paths) is rejected.
+    fn resolve(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let relative = Path::new(key);
+        let is_safe = !key.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+        if !is_safe {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    /// Guess the content type from the object key's extension
+    fn content_type_for(path: &Path) -> &'static str {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref()
+        {
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("png") => "image/png",
+            Some("tif") | Some("tiff") => "image/tiff",
+            Some("webp") => "image/webp",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorageService {
+    async fn upload(&self, key: &str, bytes: &[u8], _content_type: &str) -> Result<(), StorageError> {
+        let path = self.resolve(key)?;
+
+        ImageService::save_file(bytes, &path.to_string_lossy())
+            .await
+            .map_err(|e| StorageError::UploadError(e.to_string()))?;
+
+        tracing::info!("Saved file to local storage: {}", key);
+        Ok(())
+    }
+
+    async fn upload_from_file(
+        &self,
+        key: &str,
+        mut file: fs::File,
+        _content_type: &str,
+    ) -> Result<(), StorageError> {
+        let path = self.resolve(key)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| StorageError::UploadError(e.to_string()))?;
+        }
+        let mut destination = fs::File::create(&path)
+            .await
+            .map_err(|e| StorageError::UploadError(e.to_string()))?;
+        tokio::io::copy(&mut file, &mut destination)
+            .await
+            .map_err(|e| StorageError::UploadError(e.to_string()))?;
+
+        tracing::info!("Saved file to local storage: {}", key);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<(Vec<u8>, String), StorageError> {
+        let path = self.resolve(key)?;
+
+        let bytes = fs::read(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+            _ => StorageError::DownloadError(e.to_string()),
+        })?;
+
+        Ok((bytes, Self::content_type_for(&path).to_string()))
+    }
+
+    async fn object_size(&self, key: &str) -> Result<u64, StorageError> {
+        let path = self.resolve(key)?;
+
+        let metadata = fs::metadata(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+            _ => StorageError::DownloadError(e.to_string()),
+        })?;
+
+        Ok(metadata.len())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.resolve(key)?;
+
+        // Deleting a missing object is not an error (matches S3 semantics)
+        match fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(StorageError::DeleteError(e.to_string())),
+        }
+
+        tracing::info!("Deleted file from local storage: {}", key);
+        Ok(())
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        let source = self.resolve(from)?;
+        let destination = self.resolve(to)?;
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| StorageError::CopyError(e.to_string()))?;
+        }
+
+        fs::copy(&source, &destination).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => StorageError::NotFound(from.to_string()),
+            _ => StorageError::CopyError(e.to_string()),
+        })?;
+
+        tracing::info!("Copied file in local storage: {} -> {}", from, to);
+        Ok(())
+    }
+
+    async fn presign_put(&self, _key: &str, _content_type: &str) -> Result<String, StorageError> {
+        Err(StorageError::Unsupported(
+            "presigned uploads require the S3 backend".to_string(),
+        ))
+    }
+
+    async fn presign_get(
+        &self,
+        _key: &str,
+        _overrides: &ResponseOverrides,
+    ) -> Result<String, StorageError> {
+        Err(StorageError::Unsupported(
+            "presigned downloads require the S3 backend".to_string(),
+        ))
+    }
+
+    fn presign_expiry_secs(&self) -> u64 {
+        self.presign_expiry_secs
+    }
+
+    async fn check_health(&self) -> Result<(), StorageError> {
+        // Uploads create the root on demand, so a missing one is still usable
+        fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| StorageError::Unavailable(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::S3StorageService;
+
+    /// Storage in a fresh directory, removed when the returned guard drops
+    fn temp_storage() -> (LocalStorageService, tempfile::TempDir) {
+        let root = tempfile::TempDir::new().unwrap();
+        (LocalStorageService::new(root.path(), 3600), root)
+    }
+
+    #[tokio::test]
+    async fn test_upload_get_delete_roundtrip() {
+        let (storage, _root) = temp_storage();
+        let (key, _) = S3StorageService::generate_object_key("cells.png", "image/png");
+        let bytes = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A];
+
+        storage.upload(&key, &bytes, "image/png").await.unwrap();
+
+        let (stored, content_type) = storage.get(&key).await.unwrap();
+        assert_eq!(stored, bytes);
+        assert_eq!(content_type, "image/png");
+        assert_eq!(storage.object_size(&key).await.unwrap(), bytes.len() as u64);
+
+        storage.delete(&key).await.unwrap();
+        assert!(matches!(storage.get(&key).await, Err(StorageError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_is_ok() {
+        let (storage, _root) = temp_storage();
+        assert!(storage.delete("images/missing.jpg").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_path_traversal() {
+        let (storage, _root) = temp_storage();
+
+        assert!(matches!(
+            storage.get("images/../../etc/passwd").await,
+            Err(StorageError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            storage.upload("/etc/passwd", b"x", "image/jpeg").await,
+            Err(StorageError::InvalidKey(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_presign_not_supported() {
+        let (storage, _root) = temp_storage();
+        assert!(matches!(
+            storage
+                .presign_get("images/a.jpg", &ResponseOverrides::default())
+                .await,
+            Err(StorageError::Unsupported(_))
+        ));
+    }
+}
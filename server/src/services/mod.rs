@@ -1,9 +1,27 @@
 pub mod auth_service;
+pub mod export_service;
 pub mod image_service;
+pub mod job_progress_consumer;
+pub mod job_requeue_service;
+pub mod local_storage_service;
+pub mod multipart_guard;
+pub mod password_denylist;
 pub mod rabbitmq_service;
+pub mod revoked_token_cleanup;
 pub mod s3_service;
+pub mod share_link;
+pub mod storage_backend;
+pub mod token_keys;
+pub mod upload_limiter;
 
 pub use auth_service::{AuthError, AuthService};
+pub use export_service::ExportService;
 pub use image_service::ImageService;
+pub use job_progress_consumer::JobProgressConsumer;
+pub use job_requeue_service::JobRequeueService;
 pub use rabbitmq_service::{AnalysisJobMessage, RabbitmqError, RabbitmqService};
-pub use s3_service::{S3Error, S3StorageService};
+pub use revoked_token_cleanup::RevokedTokenCleanup;
+pub use s3_service::S3StorageService;
+pub use share_link::{ShareLinkError, ShareLinkSigner};
+pub use storage_backend::{create_storage_backend, ResponseOverrides, StorageBackend, StorageError};
+pub use upload_limiter::UploadLimiter;
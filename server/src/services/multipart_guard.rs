@@ -0,0 +1,208 @@
+//! Multipart Guard
+//!
+//! Wraps a multipart request body and enforces limits on its structure before
+//! the multipart parser sees it. The parser buffers each part's header block
+//! until it finds the blank line ending it, so without a bound a client can
+//! make the server hold arbitrarily large headers or walk through endless
+//! empty parts.
+
+use std::cell::Cell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use actix_web::error::PayloadError;
+use actix_web::web::Bytes;
+use futures::Stream;
+use thiserror::Error;
+
+/// Largest header block accepted for a single part
+pub const MAX_PART_HEADER_BYTES: usize = 8 * 1024;
+
+/// Most parts accepted in one multipart body
+pub const MAX_MULTIPART_PARTS: usize = 16;
+
+/// Marks the end of a part's header block
+const HEADER_TERMINATOR: &[u8] = b"\r\n\r\n";
+
+/// Structural limit a multipart body broke
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum MultipartViolation {
+    #[error("Part headers exceed {MAX_PART_HEADER_BYTES} bytes")]
+    HeadersTooLarge,
+
+    #[error("More than {MAX_MULTIPART_PARTS} parts")]
+    TooManyParts,
+}
+
+enum Section {
+    /// Part data or preamble, watching for the next delimiter
+    Body,
+    /// Between a delimiter and the blank line ending its headers
+    Headers,
+    /// After the closing delimiter
+    Done,
+}
+
+/// Body stream that fails once the multipart structure exceeds the limits
+///
+/// On a violation the stream yields an overflow error, which the multipart
+/// parser surfaces as a payload error; the violation itself is readable
+/// through the handle returned by [`MultipartGuard::new`].
+pub struct MultipartGuard<S> {
+    inner: S,
+    delimiter: Vec<u8>,
+    section: Section,
+    /// Header bytes seen so far in the current part
+    headers: Vec<u8>,
+    /// End of the previous chunk, in case a delimiter spans chunks
+    tail: Vec<u8>,
+    parts: usize,
+    violation: Rc<Cell<Option<MultipartViolation>>>,
+}
+
+impl<S> MultipartGuard<S> {
+    /// Guard `inner`, a multipart body using `boundary`
+    pub fn new(inner: S, boundary: &str) -> (Self, Rc<Cell<Option<MultipartViolation>>>) {
+        let violation = Rc::new(Cell::new(None));
+        let guard = Self {
+            inner,
+            delimiter: [b"--", boundary.as_bytes()].concat(),
+            section: Section::Body,
+            headers: Vec::new(),
+            tail: Vec::new(),
+            parts: 0,
+            violation: violation.clone(),
+        };
+        (guard, violation)
+    }
+
+    /// Scan one chunk, returning the first limit it breaks
+    ///
+    /// Time complexity: O(n * d) for a chunk of n bytes and a delimiter of d bytes
+    fn scan(&mut self, chunk: &[u8]) -> Result<(), MultipartViolation> {
+        let mut pending = chunk.to_vec();
+
+        while !pending.is_empty() {
+            match self.section {
+                Section::Done => return Ok(()),
+                Section::Body => {
+                    let mut window = std::mem::take(&mut self.tail);
+                    window.extend_from_slice(&pending);
+                    match find(&window, &self.delimiter) {
+                        Some(index) => {
+                            self.section = Section::Headers;
+                            self.headers.clear();
+                            pending = window.split_off(index + self.delimiter.len());
+                        }
+                        None => {
+                            let keep = window.len().min(self.delimiter.len() - 1);
+                            self.tail = window.split_off(window.len() - keep);
+                            pending.clear();
+                        }
+                    }
+                }
+                Section::Headers => {
+                    self.headers.extend_from_slice(&pending);
+                    pending.clear();
+
+                    // `--` straight after a delimiter closes the body
+                    if self.headers.starts_with(b"--") {
+                        self.section = Section::Done;
+                        continue;
+                    }
+
+                    match find(&self.headers, HEADER_TERMINATOR) {
+                        Some(index) if index + HEADER_TERMINATOR.len() <= MAX_PART_HEADER_BYTES => {
+                            self.parts += 1;
+                            if self.parts > MAX_MULTIPART_PARTS {
+                                return Err(MultipartViolation::TooManyParts);
+                            }
+                            pending = self.headers.split_off(index + HEADER_TERMINATOR.len());
+                            self.headers.clear();
+                            self.section = Section::Body;
+                        }
+                        Some(_) => return Err(MultipartViolation::HeadersTooLarge),
+                        None if self.headers.len() > MAX_PART_HEADER_BYTES => {
+                            return Err(MultipartViolation::HeadersTooLarge);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> Stream for MultipartGuard<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.violation.get().is_some() {
+            return Poll::Ready(None);
+        }
+
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => match self.scan(&chunk) {
+                Ok(()) => Poll::Ready(Some(Ok(chunk))),
+                Err(violation) => {
+                    self.violation.set(Some(violation));
+                    Poll::Ready(Some(Err(PayloadError::Overflow)))
+                }
+            },
+            other => other,
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_all(chunks: &[&[u8]]) -> Result<usize, MultipartViolation> {
+        let (mut guard, _) = MultipartGuard::new((), "b");
+        for chunk in chunks {
+            guard.scan(chunk)?;
+        }
+        Ok(guard.parts)
+    }
+
+    #[test]
+    fn test_well_formed_body_passes() {
+        let body = b"--b\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue\r\n\
+                     --b\r\nContent-Disposition: form-data; name=\"file\"\r\n\r\n\xFF\xD8\r\n--b--\r\n";
+        assert_eq!(scan_all(&[body]), Ok(2));
+
+        // Same body split so the delimiter and terminator span chunks
+        let (head, rest) = body.split_at(52);
+        let (middle, end) = rest.split_at(30);
+        assert_eq!(scan_all(&[head, middle, end]), Ok(2));
+    }
+
+    #[test]
+    fn test_oversized_headers_rejected_before_terminator() {
+        let mut body = b"--b\r\nX-Padding: ".to_vec();
+        body.extend(std::iter::repeat_n(b'a', MAX_PART_HEADER_BYTES));
+        assert_eq!(scan_all(&[&body]), Err(MultipartViolation::HeadersTooLarge));
+    }
+
+    #[test]
+    fn test_too_many_parts_rejected() {
+        let part: &[u8] = b"--b\r\nContent-Disposition: form-data; name=\"x\"\r\n\r\n1\r\n";
+        let mut body = part.repeat(MAX_MULTIPART_PARTS);
+        body.extend_from_slice(b"--b--\r\n");
+        assert_eq!(scan_all(&[&body]), Ok(MAX_MULTIPART_PARTS));
+
+        let body = part.repeat(MAX_MULTIPART_PARTS + 1);
+        assert_eq!(scan_all(&[&body]), Err(MultipartViolation::TooManyParts));
+    }
+}
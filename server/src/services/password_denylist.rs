@@ -0,0 +1,63 @@
+//! Password Denylist
+//!
+//! Optional list of common or breached passwords refused at registration and
+//! password change, whatever their composition. Loaded once at startup from
+//! `PASSWORD__DENYLIST_PATH`: one password per line, compared
+//! case-insensitively. Without it every password meeting the composition
+//! rules is accepted.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static DENYLIST: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Load the denylist from `path`, returning how many passwords it holds
+///
+/// Only the first load takes effect.
+pub fn load(path: impl AsRef<Path>) -> std::io::Result<usize> {
+    let contents = std::fs::read_to_string(path)?;
+    install(contents.lines().map(str::to_string));
+    Ok(DENYLIST.get().map_or(0, HashSet::len))
+}
+
+/// Use `passwords` as the denylist, unless one is already installed
+pub fn install(passwords: impl IntoIterator<Item = String>) {
+    let passwords = passwords
+        .into_iter()
+        .map(|password| password.trim().to_lowercase())
+        .filter(|password| !password.is_empty())
+        .collect();
+    let _ = DENYLIST.set(passwords);
+}
+
+/// Whether `password` is on the denylist
+pub fn contains(password: &str) -> bool {
+    DENYLIST
+        .get()
+        .is_some_and(|denylist| denylist.contains(&password.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::RegisterRequest;
+    use validator::Validate;
+
+    #[test]
+    fn test_denylisted_password_rejected() {
+        install(["password123!".to_string(), "  Summer2024!Summer ".to_string()]);
+
+        let register = |password: &str| RegisterRequest {
+            username: "new_user".to_string(),
+            password: password.to_string(),
+        };
+
+        // Meets the composition rules, but is a well-known password
+        let errors = register("Password123!").validate().unwrap_err();
+        assert!(errors.to_string().contains("too common"));
+        assert!(register("summer2024!summer").validate().is_err());
+
+        assert!(register("Qx7!mRv2#Lp9zT").validate().is_ok());
+    }
+}
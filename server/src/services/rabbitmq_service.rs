@@ -1,18 +1,26 @@
 //! RabbitMQ Service
 //!
-//! Service for publishing analysis jobs to RabbitMQ message queue.
+//! Service for publishing analysis jobs to RabbitMQ message queue, and for
+//! consuming the queues workers report back on.
 
 use lapin::{
-    options::{BasicPublishOptions, QueueDeclareOptions},
+    options::{BasicConsumeOptions, BasicPublishOptions, QueueDeclareOptions},
     types::FieldTable,
-    BasicProperties, Channel, Connection, ConnectionProperties,
+    BasicProperties, Channel, Connection, ConnectionProperties, Consumer, Queue,
 };
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, Secret};
 use serde::Serialize;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::config::settings::RabbitmqConfig;
+use crate::metrics;
+use crate::models::job::Job;
+
+/// Longest a reconnect may take before it is abandoned, so a publish
+/// during a broker outage fails instead of hanging the request
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Message published to RabbitMQ for analysis job
 #[derive(Debug, Clone, Serialize)]
@@ -24,16 +32,66 @@ pub struct AnalysisJobMessage {
     pub created_at: String,
 }
 
+impl AnalysisJobMessage {
+    /// Build the queue message for a job on the image stored at `s3_key`
+    pub fn for_job(job: &Job, s3_key: String) -> Self {
+        Self {
+            job_id: job.job_id,
+            image_id: job.image_id,
+            s3_key,
+            model_version: job.ai_model_version.clone().unwrap_or_default(),
+            created_at: job
+                .created_at
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Depth and consumer count of a queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueStats {
+    pub message_count: u32,
+    pub consumer_count: u32,
+}
+
+impl From<&Queue> for QueueStats {
+    fn from(queue: &Queue) -> Self {
+        Self {
+            message_count: queue.message_count(),
+            consumer_count: queue.consumer_count(),
+        }
+    }
+}
+
 /// RabbitMQ service for publishing messages
 #[derive(Clone)]
 pub struct RabbitmqService {
     channel: Arc<RwLock<Option<Channel>>>,
+    /// Held while a reconnect is in flight, so only one runs at a time
+    reconnecting: Arc<Mutex<()>>,
+    uri: Secret<String>,
     queue_name: String,
 }
 
 impl RabbitmqService {
     /// Create a new RabbitMQ service from configuration
     pub async fn new(config: &RabbitmqConfig) -> Result<Self, RabbitmqError> {
+        let service = Self::disconnected(config);
+        service.reconnect().await?;
+
+        tracing::info!(
+            "RabbitMQ connected: queue '{}' ready",
+            config.analysis_queue
+        );
+
+        Ok(service)
+    }
+
+    /// Create a service without connecting
+    ///
+    /// The first publish, or a call to `reconnect`, opens the channel.
+    pub fn disconnected(config: &RabbitmqConfig) -> Self {
         let uri = format!(
             "amqp://{}:{}@{}:{}",
             config.user,
@@ -42,40 +100,183 @@ impl RabbitmqService {
             config.port
         );
 
-        let conn = Connection::connect(&uri, ConnectionProperties::default())
+        Self {
+            channel: Arc::new(RwLock::new(None)),
+            reconnecting: Arc::new(Mutex::new(())),
+            uri: Secret::new(uri),
+            queue_name: config.analysis_queue.clone(),
+        }
+    }
+
+    /// Whether the publishing channel is open
+    pub async fn is_healthy(&self) -> bool {
+        self.channel
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|channel| channel.status().connected())
+    }
+
+    /// Open a fresh channel if the current one is missing or closed
+    ///
+    /// Fails fast with `NotConnected` while another reconnect is in flight,
+    /// rather than queueing every caller behind it, and gives up after
+    /// `CONNECT_TIMEOUT`.
+    pub async fn reconnect(&self) -> Result<(), RabbitmqError> {
+        if self.is_healthy().await {
+            return Ok(());
+        }
+
+        let Ok(_reconnecting) = self.reconnecting.try_lock() else {
+            return Err(RabbitmqError::NotConnected);
+        };
+        // Another caller may have reconnected while we checked
+        if self.is_healthy().await {
+            return Ok(());
+        }
+
+        let opened = tokio::time::timeout(CONNECT_TIMEOUT, self.open_channel())
+            .await
+            .unwrap_or_else(|_| {
+                Err(RabbitmqError::Connection(format!(
+                    "timed out after {}s",
+                    CONNECT_TIMEOUT.as_secs()
+                )))
+            });
+        metrics::registry().increment_counter(
+            metrics::RABBITMQ_RECONNECTS_TOTAL,
+            "RabbitMQ reconnect attempts by outcome",
+            &[("outcome", if opened.is_ok() { "success" } else { "failure" })],
+        );
+
+        *self.channel.write().await = Some(opened?);
+        Ok(())
+    }
+
+    async fn connect(&self) -> Result<Connection, RabbitmqError> {
+        Connection::connect(self.uri.expose_secret(), ConnectionProperties::default())
             .await
-            .map_err(|e| RabbitmqError::Connection(e.to_string()))?;
+            .map_err(|e| RabbitmqError::Connection(e.to_string()))
+    }
+
+    async fn open_channel(&self) -> Result<Channel, RabbitmqError> {
+        let conn = self.connect().await?;
 
         let channel = conn
             .create_channel()
             .await
             .map_err(|e| RabbitmqError::Channel(e.to_string()))?;
 
-        // Declare queue as durable
+        Self::declare_queue(&channel, &self.queue_name, FieldTable::default()).await?;
+
+        Ok(channel)
+    }
+
+    /// Declare a queue as durable
+    async fn declare_queue(
+        channel: &Channel,
+        queue: &str,
+        arguments: FieldTable,
+    ) -> Result<(), RabbitmqError> {
         channel
             .queue_declare(
-                &config.analysis_queue,
+                queue,
                 QueueDeclareOptions {
                     durable: true,
                     ..Default::default()
                 },
-                FieldTable::default(),
+                arguments,
             )
             .await
             .map_err(|e| RabbitmqError::QueueDeclare(e.to_string()))?;
 
-        tracing::info!(
-            "RabbitMQ connected: queue '{}' ready",
-            config.analysis_queue
-        );
+        Ok(())
+    }
 
-        Ok(Self {
-            channel: Arc::new(RwLock::new(Some(channel))),
-            queue_name: config.analysis_queue.clone(),
-        })
+    /// Options for a passive declare, which reports on an existing queue
+    /// without creating or changing it
+    fn passive_declare_options() -> QueueDeclareOptions {
+        QueueDeclareOptions {
+            passive: true,
+            ..Default::default()
+        }
+    }
+
+    /// Current depth and consumer count of `queue`
+    ///
+    /// Uses a short-lived connection: a passive declare of a missing queue
+    /// closes its channel, which must not take down the publishing channel.
+    pub async fn queue_stats(&self, queue: &str) -> Result<QueueStats, RabbitmqError> {
+        let conn = self.connect().await?;
+        let channel = conn
+            .create_channel()
+            .await
+            .map_err(|e| RabbitmqError::Channel(e.to_string()))?;
+
+        let declared = channel
+            .queue_declare(queue, Self::passive_declare_options(), FieldTable::default())
+            .await
+            .map_err(|e| RabbitmqError::QueueDeclare(e.to_string()));
+
+        if let Err(e) = conn.close(200, "OK").await {
+            tracing::debug!("Failed to close queue stats connection: {:?}", e);
+        }
+
+        Ok(QueueStats::from(&declared?))
+    }
+
+    /// Start consuming `queue` on a dedicated channel
+    ///
+    /// Deliveries must be acknowledged by the caller.
+    pub async fn consume(&self, queue: &str, consumer_tag: &str) -> Result<Consumer, RabbitmqError> {
+        let channel = self.open_channel().await?;
+        Self::declare_queue(&channel, queue, FieldTable::default()).await?;
+
+        Self::start_consumer(&channel, queue, consumer_tag).await
+    }
+
+    /// Start consuming `queue`, declaring `dead_letter_queue` alongside it
+    ///
+    /// Both are declared without arguments: `queue` may already exist, and
+    /// redeclaring it with `x-dead-letter-*` arguments fails with
+    /// PRECONDITION_FAILED. Callers dead-letter a delivery by forwarding it
+    /// with `publish_to` and then acknowledging it. A results queue that was
+    /// declared with those arguments by an earlier release must be deleted
+    /// (once drained) before upgrading, for the same reason.
+    pub async fn consume_with_dead_letter(
+        &self,
+        queue: &str,
+        dead_letter_queue: &str,
+        consumer_tag: &str,
+    ) -> Result<Consumer, RabbitmqError> {
+        let channel = self.open_channel().await?;
+        Self::declare_queue(&channel, dead_letter_queue, FieldTable::default()).await?;
+        Self::declare_queue(&channel, queue, FieldTable::default()).await?;
+
+        Self::start_consumer(&channel, queue, consumer_tag).await
+    }
+
+    async fn start_consumer(
+        channel: &Channel,
+        queue: &str,
+        consumer_tag: &str,
+    ) -> Result<Consumer, RabbitmqError> {
+        channel
+            .basic_consume(
+                queue,
+                consumer_tag,
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .map_err(|e| RabbitmqError::Consume(e.to_string()))
     }
 
     /// Publish an analysis job message to the queue
+    ///
+    /// If the channel was lost, for example because the broker restarted,
+    /// reconnects once and retries the publish. While another caller is
+    /// already reconnecting, fails with `NotConnected` instead of waiting.
     pub async fn publish_analysis_job(
         &self,
         message: AnalysisJobMessage,
@@ -83,6 +284,27 @@ impl RabbitmqService {
         let payload =
             serde_json::to_vec(&message).map_err(|e| RabbitmqError::Serialize(e.to_string()))?;
 
+        match self.publish_to(&self.queue_name, &payload).await {
+            Err(e) if !self.is_healthy().await => {
+                tracing::warn!("RabbitMQ channel lost ({}); reconnecting", e);
+                self.reconnect().await?;
+                self.publish_to(&self.queue_name, &payload).await?;
+            }
+            published => published?,
+        }
+
+        tracing::debug!(
+            "Published analysis job {} to queue '{}'",
+            message.job_id,
+            self.queue_name
+        );
+
+        Ok(())
+    }
+
+    /// Publish a persistent message to `queue` on the publishing channel,
+    /// without reconnecting
+    pub async fn publish_to(&self, queue: &str, payload: &[u8]) -> Result<(), RabbitmqError> {
         let channel_guard = self.channel.read().await;
         let channel = channel_guard
             .as_ref()
@@ -91,9 +313,9 @@ impl RabbitmqService {
         channel
             .basic_publish(
                 "",
-                &self.queue_name,
+                queue,
                 BasicPublishOptions::default(),
-                &payload,
+                payload,
                 BasicProperties::default().with_delivery_mode(2), // persistent
             )
             .await
@@ -101,12 +323,6 @@ impl RabbitmqService {
             .await
             .map_err(|e| RabbitmqError::Publish(e.to_string()))?;
 
-        tracing::debug!(
-            "Published analysis job {} to queue '{}'",
-            message.job_id,
-            self.queue_name
-        );
-
         Ok(())
     }
 }
@@ -131,4 +347,85 @@ pub enum RabbitmqError {
 
     #[error("Failed to publish message: {0}")]
     Publish(String),
+
+    #[error("Failed to consume queue: {0}")]
+    Consume(String),
+}
+
+impl RabbitmqError {
+    /// Whether the error means the broker could not be reached, as opposed
+    /// to a problem with the message itself
+    pub fn is_unavailable(&self) -> bool {
+        !matches!(self, RabbitmqError::Serialize(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_stats_declare_is_passive() {
+        let options = RabbitmqService::passive_declare_options();
+        assert!(options.passive);
+        assert!(!options.durable && !options.exclusive && !options.auto_delete);
+    }
+
+    fn unreachable_config() -> RabbitmqConfig {
+        RabbitmqConfig {
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            ..RabbitmqConfig::default()
+        }
+    }
+
+    fn reconnect_failures() -> f64 {
+        metrics::registry()
+            .render()
+            .lines()
+            .find_map(|line| line.strip_prefix("rabbitmq_reconnects_total{outcome=\"failure\"} "))
+            .map_or(0.0, |value| value.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_queue_stats_unreachable_broker() {
+        let service = RabbitmqService::disconnected(&unreachable_config());
+
+        let error = service.queue_stats("analysis_jobs").await.unwrap_err();
+        assert!(matches!(error, RabbitmqError::Connection(_)));
+        assert!(error.is_unavailable());
+    }
+
+    #[tokio::test]
+    async fn test_publish_after_channel_loss_reconnects_once() {
+        // No channel, as after the broker dropped the connection
+        let service = RabbitmqService::disconnected(&unreachable_config());
+        assert!(!service.is_healthy().await);
+        let before = reconnect_failures();
+
+        let message = AnalysisJobMessage {
+            job_id: 1,
+            image_id: 1,
+            s3_key: "images/cells.jpg".to_string(),
+            model_version: "v1.0.0".to_string(),
+            created_at: String::new(),
+        };
+        let error = service.publish_analysis_job(message).await.unwrap_err();
+
+        // The channel was redialled instead of failing with `NotConnected`
+        assert!(matches!(error, RabbitmqError::Connection(_)));
+        assert_eq!(reconnect_failures() - before, 1.0);
+        assert!(!service.is_healthy().await);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_fails_fast_while_another_is_in_flight() {
+        let service = RabbitmqService::disconnected(&unreachable_config());
+
+        let _in_flight = service.reconnecting.lock().await;
+        let error = service.reconnect().await.unwrap_err();
+
+        // Dialling would have failed with `Connection` instead
+        assert!(matches!(error, RabbitmqError::NotConnected));
+    }
 }
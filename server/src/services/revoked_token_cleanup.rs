@@ -0,0 +1,45 @@
+//! Revoked Token Cleanup
+//!
+//! Purges revoked tokens once they have expired, so the blocklist only ever
+//! holds tokens that would otherwise still be accepted. A token is accepted
+//! for `jwt.clock_skew_secs` past its expiry, so its entry is kept that much
+//! longer too.
+//!
+//! Signatures of accepted worker requests are purged on the same schedule
+//! once their timestamps would be rejected as stale.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::repositories::{RevokedTokenRepository, WorkerSignatureRepository};
+
+pub struct RevokedTokenCleanup;
+
+impl RevokedTokenCleanup {
+    /// Purge expired revocations and worker signatures every `interval`, forever
+    pub async fn run(pool: PgPool, interval: Duration, clock_skew_secs: i64) {
+        let mut ticker = tokio::time::interval(interval.max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            match Self::purge(&pool, clock_skew_secs).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Purged {} expired revoked token(s)", count),
+                Err(e) => tracing::warn!("Failed to purge revoked tokens: {:?}", e),
+            }
+            match WorkerSignatureRepository::delete_expired(&pool, Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Purged {} expired worker request signature(s)", count),
+                Err(e) => tracing::warn!("Failed to purge worker request signatures: {:?}", e),
+            }
+        }
+    }
+
+    /// Delete revocations of tokens that are no longer accepted even with
+    /// the clock skew allowance, returning how many were removed
+    pub async fn purge(pool: &PgPool, clock_skew_secs: i64) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now() - chrono::Duration::seconds(clock_skew_secs.max(0));
+        RevokedTokenRepository::delete_expired(pool, cutoff).await
+    }
+}
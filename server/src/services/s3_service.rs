@@ -8,7 +8,9 @@ use s3::region::Region;
 use std::sync::Arc;
 use thiserror::Error;
 
-use crate::config::settings::StorageConfig;
+use crate::config::settings::{SseMode, StorageConfig};
+use crate::services::image_service::ImageService;
+use crate::services::storage_backend::ResponseOverrides;
 
 // ============================================================================
 // Error Types
@@ -31,8 +33,14 @@ pub enum S3Error {
     #[error("Failed to delete file: {0}")]
     DeleteError(String),
 
+    #[error("Failed to copy file: {0}")]
+    CopyError(String),
+
     #[error("File not found: {0}")]
     NotFound(String),
+
+    #[error("Storage unavailable: {0}")]
+    Unavailable(String),
 }
 
 // ============================================================================
@@ -43,6 +51,9 @@ pub enum S3Error {
 #[derive(Clone)]
 pub struct S3StorageService {
     bucket: Arc<Bucket>,
+    /// `bucket` plus the server-side encryption headers, for writes only:
+    /// S3 rejects those headers on GET and HEAD
+    upload_bucket: Arc<Bucket>,
     presign_bucket: Arc<Bucket>,
     presign_expiry_secs: u64,
 }
@@ -68,10 +79,12 @@ impl S3StorageService {
             endpoint: config.endpoint.clone(),
         };
 
-        // Create bucket with path-style addressing (required for MinIO)
-        let bucket = Bucket::new(&config.bucket, region, credentials.clone())
-            .map_err(|e| S3Error::BucketError(e.to_string()))?
-            .with_path_style();
+        let bucket = Self::bucket(config, region, credentials.clone())?;
+
+        let mut upload_bucket = bucket.clone();
+        for (name, value) in Self::sse_headers(config) {
+            upload_bucket.add_header(name, &value);
+        }
 
         // Create presign bucket logic
         let presign_bucket = if let Some(public_endpoint) = &config.public_endpoint {
@@ -80,20 +93,54 @@ impl S3StorageService {
                 region: config.region.clone(),
                 endpoint: public_endpoint.clone(),
             };
-            *Bucket::new(&config.bucket, public_region, credentials)
-                .map_err(|e| S3Error::BucketError(e.to_string()))?
-                .with_path_style()
+            Self::bucket(config, public_region, credentials)?
         } else {
-            *bucket.clone()
+            bucket.clone()
         };
 
         Ok(Self {
-            bucket: Arc::new(*bucket),
+            bucket: Arc::new(bucket),
+            upload_bucket: Arc::new(upload_bucket),
             presign_bucket: Arc::new(presign_bucket),
             presign_expiry_secs: config.presign_expiry_secs,
         })
     }
 
+    /// Bucket handle for `region`, path-style when `force_path_style` is set
+    /// (required for MinIO)
+    fn bucket(
+        config: &StorageConfig,
+        region: Region,
+        credentials: Credentials,
+    ) -> Result<Bucket, S3Error> {
+        let bucket = Bucket::new(&config.bucket, region, credentials)
+            .map_err(|e| S3Error::BucketError(e.to_string()))?;
+
+        Ok(if config.force_path_style {
+            *bucket.with_path_style()
+        } else {
+            *bucket
+        })
+    }
+
+    /// Headers requesting server-side encryption per `config.sse`
+    ///
+    /// Presigned uploads go straight from the client without these, so
+    /// rely on the bucket's default encryption for them.
+    fn sse_headers(config: &StorageConfig) -> Vec<(&'static str, String)> {
+        match config.sse {
+            SseMode::None => Vec::new(),
+            SseMode::Aes256 => vec![("x-amz-server-side-encryption", "AES256".to_string())],
+            SseMode::Kms => {
+                let mut headers = vec![("x-amz-server-side-encryption", "aws:kms".to_string())];
+                if let Some(key_id) = &config.sse_kms_key_id {
+                    headers.push(("x-amz-server-side-encryption-aws-kms-key-id", key_id.clone()));
+                }
+                headers
+            }
+        }
+    }
+
     /// Upload a file to S3
     ///
     /// # Arguments
@@ -110,7 +157,7 @@ impl S3StorageService {
         bytes: &[u8],
         content_type: &str,
     ) -> Result<(), S3Error> {
-        self.bucket
+        self.upload_bucket
             .put_object_with_content_type(key, bytes, content_type)
             .await
             .map_err(|e| S3Error::UploadError(e.to_string()))?;
@@ -119,6 +166,23 @@ impl S3StorageService {
         Ok(())
     }
 
+    /// Upload a file to S3 from a reader, in multipart chunks so it is
+    /// never held in memory whole
+    pub async fn upload_stream(
+        &self,
+        key: &str,
+        mut file: tokio::fs::File,
+        content_type: &str,
+    ) -> Result<(), S3Error> {
+        self.upload_bucket
+            .put_object_stream_with_content_type(&mut file, key, content_type)
+            .await
+            .map_err(|e| S3Error::UploadError(e.to_string()))?;
+
+        tracing::info!("Streamed file to S3: {}", key);
+        Ok(())
+    }
+
     /// Download a file from S3
     ///
     /// # Arguments
@@ -148,6 +212,45 @@ impl S3StorageService {
         Ok((response.to_vec(), content_type))
     }
 
+    /// Get the size of a file in S3 with a HEAD request
+    ///
+    /// # Arguments
+    /// * `key` - The S3 object key
+    ///
+    /// # Returns
+    /// * `Ok(size)` in bytes on success
+    /// * `Err(S3Error)` on failure
+    pub async fn head_file(&self, key: &str) -> Result<u64, S3Error> {
+        let (head, status_code) = match self.bucket.head_object(key).await {
+            Ok(response) => response,
+            Err(s3::error::S3Error::HttpFailWithBody(404, _)) => {
+                return Err(S3Error::NotFound(key.to_string()));
+            }
+            Err(e) => return Err(S3Error::DownloadError(e.to_string())),
+        };
+
+        if status_code == 404 {
+            return Err(S3Error::NotFound(key.to_string()));
+        }
+
+        Ok(head.content_length.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Check the bucket is reachable by listing at most one key
+    pub async fn check_bucket(&self) -> Result<(), S3Error> {
+        let (_, status_code) = self
+            .bucket
+            .list_page(String::new(), None, None, None, Some(1))
+            .await
+            .map_err(|e| S3Error::Unavailable(e.to_string()))?;
+
+        if !(200..300).contains(&status_code) {
+            return Err(S3Error::Unavailable(format!("bucket list returned {}", status_code)));
+        }
+
+        Ok(())
+    }
+
     /// Delete a file from S3
     ///
     /// # Arguments
@@ -166,19 +269,47 @@ impl S3StorageService {
         Ok(())
     }
 
+    /// Copy a file to a new key within the bucket (server-side)
+    ///
+    /// # Arguments
+    /// * `from` - The source S3 object key
+    /// * `to` - The destination S3 object key
+    ///
+    /// # Returns
+    /// * `Ok(())` on success
+    /// * `Err(S3Error)` on failure
+    pub async fn copy_file(&self, from: &str, to: &str) -> Result<(), S3Error> {
+        let status_code = match self.upload_bucket.copy_object_internal(from, to).await {
+            Ok(status_code) => status_code,
+            Err(s3::error::S3Error::HttpFailWithBody(404, _)) => {
+                return Err(S3Error::NotFound(from.to_string()));
+            }
+            Err(e) => return Err(S3Error::CopyError(e.to_string())),
+        };
+
+        if status_code == 404 {
+            return Err(S3Error::NotFound(from.to_string()));
+        }
+
+        tracing::info!("Copied file in S3: {} -> {}", from, to);
+        Ok(())
+    }
+
     /// Generate an S3 object key for a new file
     ///
     /// # Arguments
     /// * `original_filename` - Original filename from upload
+    /// * `mime_type` - Validated MIME type, used for the extension when the
+    ///   filename has none
     ///
     /// # Returns
     /// * Tuple of (s3_key, filename) - e.g., ("images/uuid.jpg", "uuid.jpg")
-    pub fn generate_object_key(original_filename: &str) -> (String, String) {
+    pub fn generate_object_key(original_filename: &str, mime_type: &str) -> (String, String) {
         let uuid = uuid::Uuid::new_v4();
         let extension = std::path::Path::new(original_filename)
             .extension()
             .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg")
+            .unwrap_or_else(|| ImageService::get_extension_from_mime(mime_type))
             .to_lowercase();
 
         let filename = format!("{}.{}", uuid, extension);
@@ -213,14 +344,19 @@ impl S3StorageService {
     ///
     /// # Arguments
     /// * `key` - The S3 object key
+    /// * `overrides` - Response headers S3 should use instead of the stored ones
     ///
     /// # Returns
     /// * `Ok(url)` - Presigned URL valid for configured expiry time
     /// * `Err(S3Error)` - On failure
-    pub async fn presign_get(&self, key: &str) -> Result<String, S3Error> {
+    pub async fn presign_get(
+        &self,
+        key: &str,
+        overrides: &ResponseOverrides,
+    ) -> Result<String, S3Error> {
         let url = self
             .presign_bucket
-            .presign_get(key, self.presign_expiry_secs as u32, None)
+            .presign_get(key, self.presign_expiry_secs as u32, overrides.to_query_params())
             .await
             .map_err(|e| S3Error::DownloadError(format!("Failed to generate presigned GET URL: {}", e)))?;
 
@@ -240,22 +376,93 @@ mod tests {
 
     #[test]
     fn test_generate_object_key() {
-        let (key, filename) = S3StorageService::generate_object_key("test.jpg");
+        let (key, filename) = S3StorageService::generate_object_key("test.jpg", "image/jpeg");
         assert!(key.starts_with("images/"));
         assert!(filename.ends_with(".jpg"));
     }
 
     #[test]
     fn test_generate_object_key_png() {
-        let (key, filename) = S3StorageService::generate_object_key("photo.PNG");
+        let (key, filename) = S3StorageService::generate_object_key("photo.PNG", "image/png");
         assert!(key.starts_with("images/"));
         assert!(filename.ends_with(".png"));
     }
 
+    #[test]
+    fn test_buckets_follow_path_style_flag() {
+        let mut config = StorageConfig {
+            public_endpoint: Some("http://public.example:9000".to_string()),
+            ..StorageConfig::default()
+        };
+        let service = S3StorageService::new(&config).unwrap();
+        assert!(service.bucket.is_path_style());
+        assert!(service.presign_bucket.is_path_style());
+
+        config.force_path_style = false;
+        let service = S3StorageService::new(&config).unwrap();
+        assert!(!service.bucket.is_path_style());
+        assert!(!service.presign_bucket.is_path_style());
+    }
+
+    #[test]
+    fn test_uploads_carry_sse_headers_when_configured() {
+        let service = S3StorageService::new(&StorageConfig::default()).unwrap();
+        assert!(service.upload_bucket.extra_headers.get("x-amz-server-side-encryption").is_none());
+
+        let config = StorageConfig {
+            sse: SseMode::Kms,
+            sse_kms_key_id: Some("alias/cell-images".to_string()),
+            ..StorageConfig::default()
+        };
+        let service = S3StorageService::new(&config).unwrap();
+        let headers = &service.upload_bucket.extra_headers;
+        assert_eq!(headers.get("x-amz-server-side-encryption").unwrap(), "aws:kms");
+        assert_eq!(
+            headers.get("x-amz-server-side-encryption-aws-kms-key-id").unwrap(),
+            "alias/cell-images"
+        );
+
+        // Reads must not send them
+        assert!(service.bucket.extra_headers.get("x-amz-server-side-encryption").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_presign_get_with_disposition_override() {
+        let service = S3StorageService::new(&StorageConfig::default()).unwrap();
+        let overrides = ResponseOverrides {
+            content_type: Some("image/png".to_string()),
+            content_disposition: Some("attachment; filename=\"cells.png\"".to_string()),
+        };
+
+        let url = service.presign_get("images/test.png", &overrides).await.unwrap();
+
+        assert!(url.contains("response-content-disposition="));
+        assert!(url.contains("response-content-type="));
+    }
+
+    #[tokio::test]
+    async fn test_presign_get_without_overrides() {
+        let service = S3StorageService::new(&StorageConfig::default()).unwrap();
+
+        let url = service
+            .presign_get("images/test.png", &ResponseOverrides::default())
+            .await
+            .unwrap();
+
+        assert!(!url.contains("response-content-disposition"));
+    }
+
     #[test]
     fn test_generate_object_key_no_extension() {
-        let (key, filename) = S3StorageService::generate_object_key("file_without_ext");
+        let (key, filename) = S3StorageService::generate_object_key("file_without_ext", "image/jpeg");
         assert!(key.starts_with("images/"));
-        assert!(filename.ends_with(".jpg")); // defaults to jpg
+        assert!(filename.ends_with(".jpg")); // taken from the MIME type
+    }
+
+    #[test]
+    fn test_generate_object_key_no_extension_png() {
+        let (key, filename) = S3StorageService::generate_object_key("scan", "image/png");
+        assert!(key.starts_with("images/"));
+        assert!(filename.ends_with(".png"));
     }
 }
@@ -0,0 +1,220 @@
+//! Storage Backend Abstraction
+//!
+//! Common interface over the places uploaded files can live, so handlers do not
+//! depend on S3 directly. The concrete backend is selected by `STORAGE__BACKEND`.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::config::settings::{StorageBackendKind, StorageConfig};
+use crate::services::local_storage_service::LocalStorageService;
+use crate::services::s3_service::{S3Error, S3StorageService};
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Failed to initialize storage: {0}")]
+    ConfigError(String),
+
+    #[error("Failed to upload file: {0}")]
+    UploadError(String),
+
+    #[error("Failed to download file: {0}")]
+    DownloadError(String),
+
+    #[error("Failed to delete file: {0}")]
+    DeleteError(String),
+
+    #[error("Failed to copy file: {0}")]
+    CopyError(String),
+
+    #[error("File not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid object key: {0}")]
+    InvalidKey(String),
+
+    #[error("Operation not supported by this storage backend: {0}")]
+    Unsupported(String),
+
+    #[error("Storage unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl From<S3Error> for StorageError {
+    fn from(err: S3Error) -> Self {
+        match err {
+            S3Error::CredentialsError(msg) | S3Error::BucketError(msg) => {
+                StorageError::ConfigError(msg)
+            }
+            S3Error::UploadError(msg) => StorageError::UploadError(msg),
+            S3Error::DownloadError(msg) => StorageError::DownloadError(msg),
+            S3Error::DeleteError(msg) => StorageError::DeleteError(msg),
+            S3Error::CopyError(msg) => StorageError::CopyError(msg),
+            S3Error::NotFound(key) => StorageError::NotFound(key),
+            S3Error::Unavailable(msg) => StorageError::Unavailable(msg),
+        }
+    }
+}
+
+// ============================================================================
+// Presigned Download Overrides
+// ============================================================================
+
+/// Response headers the storage server should use when serving a presigned GET
+///
+/// S3 honors these as the `response-content-type` / `response-content-disposition`
+/// query parameters, overriding whatever was stored with the object.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseOverrides {
+    pub content_type: Option<String>,
+    pub content_disposition: Option<String>,
+}
+
+impl ResponseOverrides {
+    /// Query parameters to sign into the presigned URL (None if nothing is overridden)
+    pub fn to_query_params(&self) -> Option<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        if let Some(content_type) = &self.content_type {
+            params.insert("response-content-type".to_string(), content_type.clone());
+        }
+        if let Some(content_disposition) = &self.content_disposition {
+            params.insert(
+                "response-content-disposition".to_string(),
+                content_disposition.clone(),
+            );
+        }
+
+        if params.is_empty() {
+            None
+        } else {
+            Some(params)
+        }
+    }
+}
+
+// ============================================================================
+// Storage Backend Trait
+// ============================================================================
+
+/// Operations every storage backend must provide
+///
+/// Handlers receive this as `web::Data<dyn StorageBackend>`.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Store `bytes` under `key`
+    async fn upload(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<(), StorageError>;
+
+    /// Store the contents of `file`, read from its current position, under
+    /// `key` without loading it into memory
+    async fn upload_from_file(
+        &self,
+        key: &str,
+        file: tokio::fs::File,
+        content_type: &str,
+    ) -> Result<(), StorageError>;
+
+    /// Fetch the object stored under `key` as `(bytes, content_type)`
+    async fn get(&self, key: &str) -> Result<(Vec<u8>, String), StorageError>;
+
+    /// Size in bytes of the object stored under `key`, without fetching it
+    async fn object_size(&self, key: &str) -> Result<u64, StorageError>;
+
+    /// Remove the object stored under `key`
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Copy the object stored under `from` to `to`, within the backend
+    async fn copy(&self, from: &str, to: &str) -> Result<(), StorageError>;
+
+    /// Generate a URL the client can PUT the object to directly
+    async fn presign_put(&self, key: &str, content_type: &str) -> Result<String, StorageError>;
+
+    /// Generate a URL the client can GET the object from directly,
+    /// optionally forcing the response content type/disposition
+    async fn presign_get(
+        &self,
+        key: &str,
+        overrides: &ResponseOverrides,
+    ) -> Result<String, StorageError>;
+
+    /// How long presigned URLs stay valid, in seconds
+    fn presign_expiry_secs(&self) -> u64;
+
+    /// Cheaply check the backend can be reached, for readiness probes
+    async fn check_health(&self) -> Result<(), StorageError>;
+}
+
+#[async_trait]
+impl StorageBackend for S3StorageService {
+    async fn upload(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<(), StorageError> {
+        Ok(self.upload_file(key, bytes, content_type).await?)
+    }
+
+    async fn upload_from_file(
+        &self,
+        key: &str,
+        file: tokio::fs::File,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        Ok(self.upload_stream(key, file, content_type).await?)
+    }
+
+    async fn get(&self, key: &str) -> Result<(Vec<u8>, String), StorageError> {
+        Ok(self.get_file(key).await?)
+    }
+
+    async fn object_size(&self, key: &str) -> Result<u64, StorageError> {
+        Ok(self.head_file(key).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        Ok(self.delete_file(key).await?)
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        Ok(self.copy_file(from, to).await?)
+    }
+
+    async fn presign_put(&self, key: &str, content_type: &str) -> Result<String, StorageError> {
+        Ok(S3StorageService::presign_put(self, key, content_type).await?)
+    }
+
+    async fn presign_get(
+        &self,
+        key: &str,
+        overrides: &ResponseOverrides,
+    ) -> Result<String, StorageError> {
+        Ok(S3StorageService::presign_get(self, key, overrides).await?)
+    }
+
+    fn presign_expiry_secs(&self) -> u64 {
+        S3StorageService::presign_expiry_secs(self)
+    }
+
+    async fn check_health(&self) -> Result<(), StorageError> {
+        Ok(self.check_bucket().await?)
+    }
+}
+
+/// Build the storage backend selected in configuration
+pub fn create_storage_backend(
+    config: &StorageConfig,
+) -> Result<Arc<dyn StorageBackend>, StorageError> {
+    match config.backend {
+        StorageBackendKind::S3 => {
+            let service = S3StorageService::new(config)?;
+            tracing::info!("S3 storage backend initialized: endpoint={}", config.endpoint);
+            Ok(Arc::new(service))
+        }
+        StorageBackendKind::Local => {
+            let service = LocalStorageService::new(&config.local_path, config.presign_expiry_secs);
+            tracing::info!("Local storage backend initialized: path={}", config.local_path);
+            Ok(Arc::new(service))
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Upload Limiter
+//!
+//! Caps how many uploads each user can have in flight at once. Upload bodies
+//! are buffered in memory, so one user opening many simultaneous uploads
+//! could otherwise exhaust the server.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+/// Count of in-flight uploads per user, shared across workers
+#[derive(Clone, Default)]
+pub struct UploadLimiter {
+    in_flight: Arc<Mutex<HashMap<Uuid, usize>>>,
+}
+
+/// One in-flight upload slot; released when dropped
+pub struct UploadPermit {
+    limiter: UploadLimiter,
+    user_id: Uuid,
+}
+
+impl UploadLimiter {
+    /// Take one of the user's `max` upload slots, or `None` if all are in use
+    ///
+    /// A `max` of 0 means uploads are not limited.
+    pub fn try_acquire(&self, user_id: Uuid, max: usize) -> Option<UploadPermit> {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        let count = in_flight.entry(user_id).or_insert(0);
+        if max > 0 && *count >= max {
+            return None;
+        }
+
+        *count += 1;
+        Some(UploadPermit {
+            limiter: self.clone(),
+            user_id,
+        })
+    }
+
+    fn release(&self, user_id: Uuid) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = in_flight.get_mut(&user_id) {
+            *count -= 1;
+            if *count == 0 {
+                in_flight.remove(&user_id);
+            }
+        }
+    }
+}
+
+impl Drop for UploadPermit {
+    fn drop(&mut self) {
+        self.limiter.release(self.user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits_limited_per_user() {
+        let limiter = UploadLimiter::default();
+        let user = Uuid::new_v4();
+
+        let first = limiter.try_acquire(user, 2).unwrap();
+        let _second = limiter.try_acquire(user, 2).unwrap();
+        assert!(limiter.try_acquire(user, 2).is_none());
+
+        // Other users have their own slots
+        assert!(limiter.try_acquire(Uuid::new_v4(), 2).is_some());
+
+        drop(first);
+        assert!(limiter.try_acquire(user, 2).is_some());
+    }
+
+    #[test]
+    fn test_released_users_are_forgotten() {
+        let limiter = UploadLimiter::default();
+        let permit = limiter.try_acquire(Uuid::new_v4(), 0).unwrap();
+        drop(permit);
+
+        assert!(limiter.in_flight.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,5 @@
+//! Background workers fed by the message queue
+
+pub mod results_consumer;
+
+pub use results_consumer::ResultsConsumer;
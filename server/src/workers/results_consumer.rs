@@ -0,0 +1,313 @@
+//! Results Consumer
+//!
+//! Consumes the finished analyses workers publish to the results queue and
+//! stores them, completing their jobs. Messages that can never be stored are
+//! forwarded to the dead-letter queue so they can be inspected later.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::StreamExt;
+use lapin::acker::Acker;
+use lapin::options::{BasicAckOptions, BasicNackOptions};
+use serde::Deserialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use validator::Validate;
+
+use crate::config::settings::AnalysisConfig;
+use crate::dto::{CellCounts, JobResultEntry};
+use crate::repositories::{JobRepository, RecordResultOutcome};
+use crate::services::rabbitmq_service::RabbitmqService;
+
+/// Consumer tag announced to the broker
+const CONSUMER_TAG: &str = "analysis-results";
+
+/// Wait before reconnecting after the consumer stops or fails to start
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Attempts at storing one job's result before it is dead-lettered
+pub const MAX_STORE_ATTEMPTS: u32 = 5;
+
+/// Wait before requeueing a failed result, multiplied by the attempt number
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Message published by a worker when it finishes analyzing an image
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalysisResultMessage {
+    pub job_id: i64,
+    pub counts: CellCounts,
+    pub avg_confidence_score: f64,
+    pub raw_data: Option<serde_json::Value>,
+    pub summary_data: Option<String>,
+}
+
+impl From<AnalysisResultMessage> for JobResultEntry {
+    fn from(message: AnalysisResultMessage) -> Self {
+        Self {
+            job_id: message.job_id,
+            counts: message.counts,
+            avg_confidence: message.avg_confidence_score,
+            raw_data: message.raw_data,
+            summary: message.summary_data,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ResultsError {
+    #[error("Invalid result message: {0}")]
+    Invalid(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Failed store attempts per job, so a result that fails every time (a
+/// constraint violation, say) is dead-lettered instead of looping forever
+///
+/// Counts are kept per process: with several replicas consuming, a result
+/// may be tried up to `MAX_STORE_ATTEMPTS` times on each.
+#[derive(Debug, Default)]
+pub struct RetryTracker {
+    failures: HashMap<i64, u32>,
+}
+
+impl RetryTracker {
+    /// Record a failed attempt for `job_id`, returning the attempts so far
+    pub fn record_failure(&mut self, job_id: i64) -> u32 {
+        let attempts = self.failures.entry(job_id).or_insert(0);
+        *attempts += 1;
+        *attempts
+    }
+
+    /// Forget `job_id`'s failures once its result is stored or given up on
+    pub fn clear(&mut self, job_id: i64) {
+        self.failures.remove(&job_id);
+    }
+}
+
+/// What to do with a delivery after trying to store it
+enum Disposition {
+    Ack,
+    /// Requeue after waiting, for another attempt
+    Retry(Duration),
+    DeadLetter(String),
+}
+
+/// Just enough of a result message to identify its job
+#[derive(Deserialize)]
+struct MessageJobId {
+    job_id: i64,
+}
+
+pub struct ResultsConsumer;
+
+impl ResultsConsumer {
+    /// Consume `queue` forever, reconnecting whenever the consumer stops
+    ///
+    /// Invalid messages are dead-lettered to `dead_letter_queue`; database
+    /// failures are requeued with a growing delay, and dead-lettered once a
+    /// job's result has failed `MAX_STORE_ATTEMPTS` times.
+    pub async fn run(
+        pool: PgPool,
+        analysis: AnalysisConfig,
+        rabbitmq: RabbitmqService,
+        queue: String,
+        dead_letter_queue: String,
+    ) {
+        let mut retries = RetryTracker::default();
+        loop {
+            match rabbitmq
+                .consume_with_dead_letter(&queue, &dead_letter_queue, CONSUMER_TAG)
+                .await
+            {
+                Ok(mut consumer) => {
+                    tracing::info!("Consuming analysis results from queue '{}'", queue);
+                    while let Some(delivery) = consumer.next().await {
+                        let delivery = match delivery {
+                            Ok(delivery) => delivery,
+                            Err(e) => {
+                                tracing::warn!("Results consumer failed: {:?}", e);
+                                break;
+                            }
+                        };
+
+                        let job_id = serde_json::from_slice::<MessageJobId>(&delivery.data)
+                            .ok()
+                            .map(|message| message.job_id);
+                        let outcome = Self::handle_message(&pool, &analysis, &delivery.data).await;
+
+                        let acked = match Self::disposition(outcome, job_id, &mut retries) {
+                            Disposition::Ack => delivery.ack(BasicAckOptions::default()).await,
+                            Disposition::Retry(delay) => {
+                                Self::requeue_after(delivery.acker.clone(), delay);
+                                Ok(())
+                            }
+                            Disposition::DeadLetter(reason) => {
+                                tracing::warn!(
+                                    "Dead-lettering result message to '{}': {}",
+                                    dead_letter_queue,
+                                    reason
+                                );
+                                match rabbitmq.publish_to(&dead_letter_queue, &delivery.data).await {
+                                    Ok(()) => delivery.ack(BasicAckOptions::default()).await,
+                                    Err(e) => {
+                                        tracing::warn!("Failed to dead-letter result message: {:?}", e);
+                                        Self::requeue_after(delivery.acker.clone(), RETRY_BACKOFF);
+                                        Ok(())
+                                    }
+                                }
+                            }
+                        };
+                        if let Err(e) = acked {
+                            tracing::warn!("Failed to acknowledge result message: {:?}", e);
+                        }
+                    }
+                    tracing::warn!("Results consumer stopped; reconnecting");
+                }
+                Err(e) => tracing::warn!("Failed to start results consumer: {:?}", e),
+            }
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// Requeue a message once `delay` has passed, without holding up the
+    /// messages consumed meanwhile
+    ///
+    /// Should the channel close first, the broker redelivers the message itself.
+    fn requeue_after(acker: Acker, delay: Duration) {
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let requeue = BasicNackOptions {
+                requeue: true,
+                ..Default::default()
+            };
+            if let Err(e) = acker.nack(requeue).await {
+                tracing::warn!("Failed to requeue result message: {:?}", e);
+            }
+        });
+    }
+
+    fn disposition(
+        outcome: Result<bool, ResultsError>,
+        job_id: Option<i64>,
+        retries: &mut RetryTracker,
+    ) -> Disposition {
+        match outcome {
+            Ok(_) => {
+                if let Some(job_id) = job_id {
+                    retries.clear(job_id);
+                }
+                Disposition::Ack
+            }
+            Err(ResultsError::Invalid(reason)) => Disposition::DeadLetter(reason),
+            Err(e) => {
+                // A database error means the message parsed, so the job is known
+                let Some(job_id) = job_id else {
+                    return Disposition::DeadLetter(e.to_string());
+                };
+                let attempts = retries.record_failure(job_id);
+                tracing::error!(
+                    "Failed to store analysis result for job {} (attempt {}): {:?}",
+                    job_id,
+                    attempts,
+                    e
+                );
+                if attempts >= MAX_STORE_ATTEMPTS {
+                    retries.clear(job_id);
+                    Disposition::DeadLetter(format!("still failing after {} attempts: {}", attempts, e))
+                } else {
+                    Disposition::Retry(RETRY_BACKOFF * attempts)
+                }
+            }
+        }
+    }
+
+    /// Store one result message, returning whether a job was completed
+    ///
+    /// The result is validated and its detections capped like a result posted
+    /// to the worker endpoint, then stored and its job completed in one transaction. Results for
+    /// unknown or already finished jobs are accepted and ignored, so a
+    /// redelivered message is harmless.
+    pub async fn handle_message(
+        pool: &PgPool,
+        analysis: &AnalysisConfig,
+        payload: &[u8],
+    ) -> Result<bool, ResultsError> {
+        let message: AnalysisResultMessage =
+            serde_json::from_slice(payload).map_err(|e| ResultsError::Invalid(e.to_string()))?;
+        let mut entry = JobResultEntry::from(message);
+        entry
+            .validate()
+            .map_err(|e| ResultsError::Invalid(e.to_string()))?;
+        entry
+            .cap_detections(analysis.max_detections, analysis.detection_overflow)
+            .map_err(|count| {
+                ResultsError::Invalid(format!(
+                    "Result has {} bounding boxes; at most {} are accepted",
+                    count, analysis.max_detections
+                ))
+            })?;
+
+        let mut tx = pool.begin().await?;
+        let outcome = JobRepository::record_result(
+            &mut tx,
+            entry.job_id,
+            entry.counts.viable,
+            entry.counts.apoptosis,
+            entry.counts.other,
+            entry.avg_confidence,
+            entry.raw_data,
+            entry.summary,
+        )
+        .await?;
+
+        match outcome {
+            RecordResultOutcome::Recorded(_) => {
+                tx.commit().await?;
+                Ok(true)
+            }
+            // Dropping the transaction rolls it back
+            RecordResultOutcome::JobNotFound => {
+                tracing::debug!("Ignored result for missing job {}", entry.job_id);
+                Ok(false)
+            }
+            RecordResultOutcome::AlreadyFinished(status) => {
+                tracing::debug!("Ignored result for job {} (already {})", entry.job_id, status);
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failing_result_dead_lettered_after_max_attempts() {
+        let mut retries = RetryTracker::default();
+        let failure = || Err(ResultsError::Database(sqlx::Error::PoolTimedOut));
+
+        for attempt in 1..MAX_STORE_ATTEMPTS {
+            match ResultsConsumer::disposition(failure(), Some(7), &mut retries) {
+                Disposition::Retry(delay) => assert_eq!(delay, RETRY_BACKOFF * attempt),
+                _ => panic!("attempt {} should be retried", attempt),
+            }
+        }
+        assert!(matches!(
+            ResultsConsumer::disposition(failure(), Some(7), &mut retries),
+            Disposition::DeadLetter(_)
+        ));
+
+        // The count starts over afterwards, and other jobs are unaffected
+        assert_eq!(retries.record_failure(7), 1);
+        assert!(matches!(
+            ResultsConsumer::disposition(Ok(true), Some(7), &mut retries),
+            Disposition::Ack
+        ));
+        assert_eq!(retries.record_failure(7), 1);
+    }
+}
@@ -0,0 +1,2345 @@
+//! Analysis Integration Tests
+//!
+//! Tests for analysis result endpoints using database fixtures.
+
+use std::sync::Arc;
+
+use actix_web::dev::Service;
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web, App, HttpMessage};
+use async_trait::async_trait;
+use secrecy::Secret;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use cell_analysis_backend::config::settings::{
+    AdminConfig, AppConfig, DetectionOverflowPolicy, RabbitmqConfig, WorkerConfig,
+};
+use cell_analysis_backend::db::ReadPool;
+use cell_analysis_backend::handlers;
+use cell_analysis_backend::middleware::worker_auth::{
+    sign_request, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use cell_analysis_backend::middleware::{AdminGuard, AuthenticatedUser, WorkerAuth};
+use cell_analysis_backend::models::job::JobStatus;
+use cell_analysis_backend::repositories::{
+    AnalysisResultRepository, FolderRepository, ImageRepository, JobRepository,
+    RecordResultOutcome,
+};
+use cell_analysis_backend::services::local_storage_service::LocalStorageService;
+use cell_analysis_backend::services::{
+    JobProgressConsumer, RabbitmqService, ResponseOverrides, StorageBackend, StorageError,
+};
+use cell_analysis_backend::workers::results_consumer::ResultsError;
+use cell_analysis_backend::workers::ResultsConsumer;
+
+/// Helper to create a test user and return their ID
+async fn create_test_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO users (user_id, username, password_hash)
+        VALUES ($1, $2, 'test_hash')
+        "#,
+    )
+    .bind(user_id)
+    .bind(username)
+    .execute(pool)
+    .await
+    .expect("Failed to create test user");
+
+    user_id
+}
+
+/// Helper to build an app config with only the required settings provided
+fn test_config() -> AppConfig {
+    serde_json::from_value(serde_json::json!({
+        "server": {},
+        "database": { "url": "postgres://test" },
+        "jwt": { "secret": "test-secret" }
+    }))
+    .unwrap()
+}
+
+/// Helper to build a queue service whose every publish fails as unavailable
+fn unreachable_rabbitmq() -> RabbitmqService {
+    RabbitmqService::disconnected(&RabbitmqConfig {
+        host: "127.0.0.1".to_string(),
+        port: 1,
+        ..RabbitmqConfig::default()
+    })
+}
+
+/// Helper to create an image with a completed analysis and return the job ID
+async fn create_analyzed_image(pool: &PgPool, folder_id: i32, filename: &str, viable: i32) -> i64 {
+    let image = ImageRepository::create(
+        pool,
+        folder_id,
+        &format!("images/{}", filename),
+        filename,
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .expect("Failed to create test image");
+
+    let job = JobRepository::create(pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(pool, job.job_id).await.unwrap();
+    AnalysisResultRepository::create(pool, job.job_id, viable, 5, 1, 0.9, None, None)
+        .await
+        .unwrap();
+
+    job.job_id
+}
+
+// ============================================================================
+// Account Deletion Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_deleting_user_removes_analyzed_images(pool: PgPool) {
+    let owner = create_test_user(&pool, "leaving_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Results").await.unwrap();
+    let job_id = create_analyzed_image(&pool, folder.folder_id, "a.jpg", 10).await;
+
+    sqlx::query("DELETE FROM users WHERE user_id = $1")
+        .bind(owner)
+        .execute(&pool)
+        .await
+        .expect("Deleting a user should cascade through their analyses");
+
+    for table in ["folders", "images", "jobs", "analysis_results"] {
+        let remaining: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0, "{} left behind for job {}", table, job_id);
+    }
+}
+
+// ============================================================================
+// NDJSON Results Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_stream_folder_results_ndjson(pool: PgPool) {
+    let owner = create_test_user(&pool, "ndjson_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Results").await.unwrap();
+
+    let first = create_analyzed_image(&pool, folder.folder_id, "a.jpg", 10).await;
+    let second = create_analyzed_image(&pool, folder.folder_id, "b.jpg", 20).await;
+
+    // Pending jobs have no result and must not appear
+    let pending = ImageRepository::create(&pool, folder.folder_id, "images/c.jpg", "c.jpg", "image/jpeg", 1024, None)
+        .await
+        .unwrap();
+    JobRepository::create(&pool, pending.image_id, "v1.0.0").await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "ndjson_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/results.ndjson",
+                web::get().to(handlers::stream_folder_results),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/folders/{}/results.ndjson", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/x-ndjson"
+    );
+
+    let body = test::read_body(res).await;
+    let body = std::str::from_utf8(&body).expect("Body should be UTF-8");
+
+    let results: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line should be a JSON object"))
+        .collect();
+
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        assert!(result["result_id"].is_i64());
+        assert!(result["image_id"].is_i64());
+        assert!(result["counts"]["viable"].is_i64());
+        assert!(result["percentages"].is_object());
+    }
+
+    let job_ids: Vec<i64> = results.iter().map(|r| r["job_id"].as_i64().unwrap()).collect();
+    assert_eq!(job_ids, vec![first, second]);
+    assert_eq!(results[1]["total_cells"], 26);
+}
+
+#[sqlx::test]
+async fn test_stream_folder_results_requires_ownership(pool: PgPool) {
+    let owner = create_test_user(&pool, "ndjson_real_owner").await;
+    let intruder = create_test_user(&pool, "ndjson_intruder").await;
+    let folder = FolderRepository::create(&pool, owner, "Private").await.unwrap();
+    create_analyzed_image(&pool, folder.folder_id, "a.jpg", 10).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: intruder,
+                    username: "ndjson_intruder".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/results.ndjson",
+                web::get().to(handlers::stream_folder_results),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/folders/{}/results.ndjson", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Confidence Rounding Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_result_and_history_round_confidence(pool: PgPool) {
+    let owner = create_test_user(&pool, "rounding_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Rounding").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/round.jpg",
+        "round.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    let raw_data = serde_json::json!({
+        "bounding_boxes": [
+            { "class": "viable", "confidence": 0.912345678, "x": 0, "y": 0, "width": 5, "height": 5 }
+        ]
+    });
+    AnalysisResultRepository::create(&pool, job.job_id, 1, 0, 0, 0.8732000000001, Some(raw_data), None)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "rounding_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/result", web::get().to(handlers::get_job_result))
+            .route(
+                "/images/{image_id}/analysis-history",
+                web::get().to(handlers::get_analysis_history),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/result", job.job_id))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"]["avg_confidence_score"], serde_json::json!(0.8732));
+    assert_eq!(
+        body["data"]["raw_data"]["bounding_boxes"][0]["confidence"],
+        serde_json::json!(0.9123)
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/images/{}/analysis-history", image.image_id))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        body["data"]["analyses"][0]["avg_confidence_score"],
+        serde_json::json!(0.8732)
+    );
+
+    // Stored values keep full precision
+    let (stored, _) = AnalysisResultRepository::find_by_job_id(&pool, job.job_id, owner)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.avg_confidence_score, Some(0.8732000000001));
+}
+
+// ============================================================================
+// Latest Result Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_latest_result_returns_most_recent_completed(pool: PgPool) {
+    let owner = create_test_user(&pool, "latest_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Latest").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/latest.jpg",
+        "latest.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut job_ids = Vec::new();
+    for viable in [3, 7] {
+        let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+        JobRepository::complete(&pool, job.job_id).await.unwrap();
+        AnalysisResultRepository::create(&pool, job.job_id, viable, 0, 0, 0.9, None, None)
+            .await
+            .unwrap();
+        job_ids.push(job.job_id);
+    }
+    // A later failed job doesn't hide the last successful one
+    let failed = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "worker crashed").await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "latest_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/images/{image_id}/latest-result",
+                web::get().to(handlers::get_latest_image_result),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/images/{}/latest-result", image.image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["job_id"], job_ids[1]);
+    assert_eq!(body["data"]["counts"]["viable"], 7);
+
+    let unanalyzed = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/new.jpg",
+        "new.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let req = test::TestRequest::get()
+        .uri(&format!("/images/{}/latest-result", unanalyzed.image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Scaled Detection Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_scaled_detections_scale_proportionally(pool: PgPool) {
+    let owner = create_test_user(&pool, "scale_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Scaling").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/scale.jpg",
+        "scale.jpg",
+        "image/jpeg",
+        1024,
+        Some(serde_json::json!({ "width": 1000, "height": 800 })),
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    let raw_data = serde_json::json!({
+        "bounding_boxes": [
+            { "class": "viable", "confidence": 0.9, "x": 100, "y": 200, "width": 50, "height": 40 }
+        ]
+    });
+    AnalysisResultRepository::create(&pool, job.job_id, 1, 0, 0, 0.9, Some(raw_data), None)
+        .await
+        .unwrap();
+
+    let unsized_image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/unsized.jpg",
+        "unsized.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let unsized_job = JobRepository::create(&pool, unsized_image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, unsized_job.job_id).await.unwrap();
+    AnalysisResultRepository::create(&pool, unsized_job.job_id, 0, 0, 0, 0.0, None, None)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "scale_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/jobs/{job_id}/detections/scaled",
+                web::get().to(handlers::get_scaled_detections),
+            ),
+    )
+    .await;
+
+    // Half width, quarter height
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/detections/scaled?width=500&height=200", job.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["original_width"], 1000);
+    assert_eq!(body["data"]["original_height"], 800);
+    let bbox = &body["data"]["bounding_boxes"][0];
+    assert_eq!((bbox["x"].as_i64(), bbox["y"].as_i64()), (Some(50), Some(50)));
+    assert_eq!((bbox["width"].as_i64(), bbox["height"].as_i64()), (Some(25), Some(10)));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/detections/scaled?width=0&height=200", job.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/detections/scaled?width=500&height=200", unsized_job.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "DIMENSIONS_UNKNOWN");
+}
+
+// ============================================================================
+// Confidence Threshold Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_result_counts_use_model_default_threshold(pool: PgPool) {
+    let owner = create_test_user(&pool, "threshold_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Thresholds").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/threshold.jpg",
+        "threshold.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let raw_data = serde_json::json!({
+        "bounding_boxes": [
+            { "class": "normal", "confidence": 0.4, "x": 0, "y": 0, "width": 5, "height": 5 },
+            { "class": "normal", "confidence": 0.6, "x": 5, "y": 0, "width": 5, "height": 5 },
+            { "class": "apoptosis", "confidence": 0.9, "x": 0, "y": 5, "width": 5, "height": 5 }
+        ]
+    });
+
+    let mut job_ids = Vec::new();
+    for version in ["v1", "v2"] {
+        let job = JobRepository::create(&pool, image.image_id, version).await.unwrap();
+        JobRepository::complete(&pool, job.job_id).await.unwrap();
+        AnalysisResultRepository::create(&pool, job.job_id, 2, 1, 0, 0.63, Some(raw_data.clone()), None)
+            .await
+            .unwrap();
+        job_ids.push(job.job_id);
+    }
+
+    let mut config = test_config();
+    config.analysis.confidence_thresholds.insert("v1".to_string(), 0.3);
+    config.analysis.confidence_thresholds.insert("v2".to_string(), 0.7);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "threshold_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/result", web::get().to(handlers::get_job_result)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/result", job_ids[0]))
+        .to_request();
+    let v1: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(v1["data"]["confidence_threshold"], serde_json::json!(0.3));
+    assert_eq!(v1["data"]["total_cells"], 3);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/result", job_ids[1]))
+        .to_request();
+    let v2: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(v2["data"]["confidence_threshold"], serde_json::json!(0.7));
+    assert_eq!(v2["data"]["total_cells"], 1);
+    assert_eq!(v2["data"]["counts"]["apoptosis"], 1);
+    assert_eq!(v2["data"]["percentages"]["apoptosis"], serde_json::json!(100.0));
+
+    // The client's threshold wins over the model default
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/result?min_confidence=0.5", job_ids[1]))
+        .to_request();
+    let overridden: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(overridden["data"]["confidence_threshold"], serde_json::json!(0.5));
+    assert_eq!(overridden["data"]["total_cells"], 2);
+
+    // An out-of-range threshold parses but can't be applied; a non-number doesn't parse
+    for (min_confidence, status) in [
+        ("1.5", StatusCode::UNPROCESSABLE_ENTITY),
+        ("high", StatusCode::BAD_REQUEST),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result?min_confidence={}", job_ids[1], min_confidence))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), status, "min_confidence={}", min_confidence);
+    }
+}
+
+// ============================================================================
+// Worker Batch Result Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_ingest_job_results_batch_partial_failure(pool: PgPool) {
+    let owner = create_test_user(&pool, "worker_batch_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Worker").await.unwrap();
+
+    let mut job_ids = Vec::new();
+    for filename in ["good.jpg", "bad.jpg"] {
+        let image = ImageRepository::create(
+            &pool,
+            folder.folder_id,
+            &format!("images/{}", filename),
+            filename,
+            "image/jpeg",
+            1024,
+            None,
+        )
+        .await
+        .unwrap();
+        let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+        JobRepository::start_processing(&pool, job.job_id).await.unwrap();
+        job_ids.push(job.job_id);
+    }
+    let (good_job, bad_job) = (job_ids[0], job_ids[1]);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(test_config()))
+            .service(
+                web::scope("/jobs/results")
+                    .wrap(WorkerAuth::new(
+                        WorkerConfig {
+                            secret: Secret::new("worker-secret".to_string()),
+                            ..Default::default()
+                        },
+                        test_config().limits.max_json_bytes,
+                    ))
+                    .route("/batch", web::post().to(handlers::ingest_job_results_batch)),
+            ),
+    )
+    .await;
+
+    let payload = serde_json::json!([
+        {
+            "job_id": good_job,
+            "counts": { "viable": 12, "apoptosis": 3, "other": 1 },
+            "avg_confidence": 0.87,
+            "raw_data": { "bounding_boxes": [] },
+            "summary": "Mostly viable"
+        },
+        {
+            "job_id": bad_job,
+            "counts": { "viable": 4, "apoptosis": 2, "other": 0 },
+            "avg_confidence": 1.5,
+            "raw_data": null,
+            "summary": null
+        }
+    ]);
+
+    // Missing secret is rejected before anything is stored
+    let req = test::TestRequest::post()
+        .uri("/jobs/results/batch")
+        .set_json(&payload)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/jobs/results/batch")
+        .insert_header(("X-Worker-Secret", "worker-secret"))
+        .set_json(&payload)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let data = &body["data"];
+    assert_eq!(data["succeeded"], 1);
+    assert_eq!(data["failed"], 1);
+
+    let results = data["results"].as_array().unwrap();
+    assert_eq!(results[0]["job_id"], good_job);
+    assert_eq!(results[0]["success"], true);
+    assert!(results[0]["result_id"].is_i64());
+    assert_eq!(results[1]["job_id"], bad_job);
+    assert_eq!(results[1]["success"], false);
+    assert_eq!(results[1]["error"]["code"], "VALIDATION_ERROR");
+
+    // Only the valid entry was stored and completed its job
+    let statuses: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT job_id, status::text FROM jobs WHERE job_id = ANY($1) ORDER BY job_id",
+    )
+    .bind(&job_ids)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        statuses,
+        vec![
+            (good_job, "completed".to_string()),
+            (bad_job, "processing".to_string()),
+        ]
+    );
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM analysis_results WHERE job_id = ANY($1)")
+        .bind(&job_ids)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[sqlx::test]
+async fn test_ingest_caps_detections_by_policy(pool: PgPool) {
+    let owner = create_test_user(&pool, "worker_cap_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Worker").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/crowded.jpg",
+        "crowded.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let boxes: Vec<serde_json::Value> = [0.2, 0.9, 0.5, 0.7]
+        .iter()
+        .map(|confidence| {
+            serde_json::json!({
+                "class": "viable", "confidence": confidence,
+                "x": 0, "y": 0, "width": 10, "height": 10
+            })
+        })
+        .collect();
+
+    for (policy, expected_success) in [
+        (DetectionOverflowPolicy::Reject, false),
+        (DetectionOverflowPolicy::Truncate, true),
+    ] {
+        let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+        JobRepository::start_processing(&pool, job.job_id).await.unwrap();
+
+        let mut config = test_config();
+        config.analysis.max_detections = 2;
+        config.analysis.detection_overflow = policy;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(config))
+                .route("/jobs/results/batch", web::post().to(handlers::ingest_job_results_batch)),
+        )
+        .await;
+
+        let payload = serde_json::json!([{
+            "job_id": job.job_id,
+            "counts": { "viable": 4, "apoptosis": 0, "other": 0 },
+            "avg_confidence": 0.6,
+            "raw_data": { "bounding_boxes": boxes, "model": "v1.0.0" },
+            "summary": null
+        }]);
+        let req = test::TestRequest::post()
+            .uri("/jobs/results/batch")
+            .set_json(&payload)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(res).await;
+        let outcome = &body["data"]["results"][0];
+        assert_eq!(outcome["success"], expected_success, "{:?}", policy);
+
+        let stored: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT raw_data FROM analysis_results WHERE job_id = $1")
+                .bind(job.job_id)
+                .fetch_optional(&pool)
+                .await
+                .unwrap();
+
+        if expected_success {
+            let stored = stored.expect("truncated result should be stored");
+            let confidences: Vec<f64> = stored["bounding_boxes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|b| b["confidence"].as_f64().unwrap())
+                .collect();
+            assert_eq!(confidences, vec![0.9, 0.7]);
+            assert_eq!(stored["truncated"], true);
+            assert_eq!(stored["model"], "v1.0.0");
+        } else {
+            assert_eq!(outcome["error"]["code"], "TOO_MANY_DETECTIONS");
+            assert!(stored.is_none());
+        }
+    }
+}
+
+#[sqlx::test]
+async fn test_signed_worker_requests_reject_tampering_and_replay(pool: PgPool) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(test_config()))
+            .service(
+                web::scope("/jobs/results")
+                    .wrap(WorkerAuth::new(
+                        WorkerConfig {
+                            secret: Secret::new("worker-secret".to_string()),
+                            require_signature: true,
+                            signature_max_skew_secs: 300,
+                        },
+                        1024,
+                    ))
+                    .route("/batch", web::post().to(handlers::ingest_job_results_batch)),
+            ),
+    )
+    .await;
+
+    let body = serde_json::json!([{
+        "job_id": 1,
+        "counts": { "viable": 1, "apoptosis": 0, "other": 0 },
+        "avg_confidence": 0.9,
+        "raw_data": null,
+        "summary": null
+    }])
+    .to_string();
+    let now = chrono::Utc::now().timestamp();
+    let signed = |timestamp: i64, signed_body: &str, sent_body: &str| {
+        test::TestRequest::post()
+            .uri("/jobs/results/batch")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .insert_header((TIMESTAMP_HEADER, timestamp.to_string()))
+            .insert_header((
+                SIGNATURE_HEADER,
+                sign_request("worker-secret", timestamp, signed_body.as_bytes()),
+            ))
+            .set_payload(sent_body.to_string())
+            .to_request()
+    };
+
+    // The body still reaches the handler after being read for the signature
+    let res = test::call_service(&app, signed(now, &body, &body)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let response: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(response["data"]["results"][0]["job_id"], 1);
+
+    // A captured request is accepted only once, even within the skew window
+    let res = test::call_service(&app, signed(now, &body, &body)).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let response: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(response["error"]["code"], "REPLAYED_REQUEST");
+
+    // Bodies are capped at the configured JSON limit before being hashed
+    let oversized = format!("{}{}", body, " ".repeat(1024));
+    let res = test::call_service(&app, signed(now, &oversized, &oversized)).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let tampered = body.replace("\"viable\":1", "\"viable\":100");
+    assert_ne!(tampered, body);
+    let res = test::call_service(&app, signed(now, &body, &tampered)).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let response: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(response["error"]["code"], "INVALID_SIGNATURE");
+
+    let res = test::call_service(&app, signed(now - 600, &body, &body)).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let response: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(response["error"]["code"], "STALE_TIMESTAMP");
+
+    // The bare secret is no longer enough once signing is required
+    let req = test::TestRequest::post()
+        .uri("/jobs/results/batch")
+        .insert_header(("X-Worker-Secret", "worker-secret"))
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(body.clone())
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+// ============================================================================
+// Job Submission Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_analyze_image_leaves_job_pending_when_queue_unavailable(pool: PgPool) {
+    let owner = create_test_user(&pool, "queue_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let config = test_config();
+    // Never connected, so every publish fails as if the broker were down
+    let rabbitmq = unreachable_rabbitmq();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::new(config.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "queue_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image.image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        res.headers().get(header::RETRY_AFTER).unwrap(),
+        &config.rabbitmq.republish_interval_secs.to_string()
+    );
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_UNAVAILABLE");
+
+    // The job is kept pending, not failed, for the re-publisher to pick up
+    let orphaned = JobRepository::find_unqueued_pending(
+        &pool,
+        chrono::Utc::now() + chrono::Duration::minutes(1),
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(orphaned.len(), 1);
+    let (job, s3_key) = &orphaned[0];
+    assert_eq!(job.image_id, image.image_id);
+    assert_eq!(job.status, JobStatus::Pending);
+    assert_eq!(s3_key, "images/cells.jpg");
+}
+
+#[sqlx::test]
+async fn test_reanalysis_blocked_unless_forced(pool: PgPool) {
+    let owner = create_test_user(&pool, "reanalyze_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let job_id = create_analyzed_image(&pool, folder.folder_id, "cells.jpg", 10).await;
+    let image_id = JobRepository::find_by_id(&pool, job_id, owner)
+        .await
+        .unwrap()
+        .unwrap()
+        .image_id;
+
+    let mut config = test_config();
+    config.analysis.block_reanalysis = true;
+    // Never connected, so a job that gets past the check ends up pending with 503
+    let rabbitmq = unreachable_rabbitmq();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "reanalyze_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        res.headers().get(header::LOCATION).unwrap(),
+        &format!("/api/v1/jobs/{}/result", job_id)
+    );
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "ALREADY_ANALYZED");
+
+    // A different model version is not a repeat
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image_id))
+        .set_json(serde_json::json!({ "model_version": "v2.0.0" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // A later result from another model doesn't hide the earlier one
+    let v2_job: i64 = sqlx::query_scalar(
+        "SELECT job_id FROM jobs WHERE image_id = $1 AND ai_model_version = 'v2.0.0'",
+    )
+    .bind(image_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    JobRepository::complete(&pool, v2_job).await.unwrap();
+    AnalysisResultRepository::create(&pool, v2_job, 5, 0, 0, 0.9, None, None)
+        .await
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        res.headers().get(header::LOCATION).unwrap(),
+        &format!("/api/v1/jobs/{}/result", job_id)
+    );
+
+    // The deprecated query alias still works
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze?force=true", image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // The body flag skips both the block and the unfinished job just queued
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image_id))
+        .set_json(serde_json::json!({ "force": true }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE image_id = $1")
+        .bind(image_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(jobs, 4);
+}
+
+#[sqlx::test]
+async fn test_analyze_image_uses_preferred_model_when_omitted(pool: PgPool) {
+    let owner = create_test_user(&pool, "preference_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut config = test_config();
+    config.analysis.model_versions = vec!["v1.0.0".to_string(), "v2.0.0".to_string()];
+    // Never connected, so the created job stays pending with 503
+    let rabbitmq = unreachable_rabbitmq();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "preference_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/me/preferences", web::patch().to(handlers::update_preferences))
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+
+    let req = test::TestRequest::patch()
+        .uri("/me/preferences")
+        .set_json(serde_json::json!({ "preferred_model_version": "v9.9.9" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "UNKNOWN_MODEL_VERSION");
+
+    let req = test::TestRequest::patch()
+        .uri("/me/preferences")
+        .set_json(serde_json::json!({ "preferred_model_version": "v2.0.0" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image.image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let version: Option<String> =
+        sqlx::query_scalar("SELECT ai_model_version FROM jobs WHERE image_id = $1")
+            .bind(image.image_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(version.as_deref(), Some("v2.0.0"));
+}
+
+#[sqlx::test]
+async fn test_analyze_image_returns_unfinished_job_unless_forced(pool: PgPool) {
+    let owner = create_test_user(&pool, "tap_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let active = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::start_processing(&pool, active.job_id).await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(unreachable_rabbitmq()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "tap_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+    let uri = format!("/images/{}/analyze", image.image_id);
+
+    // A repeat submission gets the processing job back
+    let res = test::call_service(&app, test::TestRequest::post().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["job_id"], active.job_id);
+    assert_eq!(body["data"]["status"], "processing");
+
+    // Another model version is a different analysis
+    let req = test::TestRequest::post()
+        .uri(&uri)
+        .set_json(serde_json::json!({ "model_version": "v2.0.0" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_UNAVAILABLE");
+
+    let req = test::TestRequest::post()
+        .uri(&uri)
+        .set_json(serde_json::json!({ "force": true }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_UNAVAILABLE");
+
+    let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE image_id = $1")
+        .bind(image.image_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(jobs, 3);
+}
+
+#[sqlx::test]
+async fn test_analyze_image_rejected_when_pending_cap_reached(pool: PgPool) {
+    let owner = create_test_user(&pool, "cap_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    // Another model, so the submissions below aren't answered with this job
+    JobRepository::create(&pool, image.image_id, "v0.9.0").await.unwrap();
+
+    let mut config = test_config();
+    config.analysis.max_pending_jobs = 2;
+    // Submissions under the cap are created but stay pending
+    let rabbitmq = unreachable_rabbitmq();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "cap_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+    let uri = format!("/images/{}/analyze", image.image_id);
+
+    // One pending job, so this submission is still accepted into the backlog
+    let res = test::call_service(&app, test::TestRequest::post().uri(&uri).to_request()).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_UNAVAILABLE");
+
+    // Two pending jobs: the cap is reached (force skips returning the unfinished job)
+    let req = test::TestRequest::post()
+        .uri(&uri)
+        .set_json(serde_json::json!({ "force": true }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(res.headers().contains_key(header::RETRY_AFTER));
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_FULL");
+
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 2);
+}
+
+#[sqlx::test]
+async fn test_analyze_image_rejects_malformed_body(pool: PgPool) {
+    let owner = create_test_user(&pool, "body_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let rabbitmq = unreachable_rabbitmq();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "body_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/analyze", web::post().to(handlers::analyze_image)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/images/{}/analyze", image.image_id))
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"model_version": "#)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "INVALID_BODY");
+
+    // Nothing was queued for the rejected request
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 0);
+}
+
+#[sqlx::test]
+async fn test_retry_failed_jobs_requeues_folder(pool: PgPool) {
+    let owner = create_test_user(&pool, "retry_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let mut failed_images = Vec::new();
+    for name in ["a.jpg", "b.jpg", "gone.jpg"] {
+        let key = format!("images/{}.jpg", Uuid::new_v4());
+        if name != "gone.jpg" {
+            storage.upload(&key, b"jpeg-bytes", "image/jpeg").await.unwrap();
+        }
+        let image =
+            ImageRepository::create(&pool, folder.folder_id, &key, name, "image/jpeg", 10, None)
+                .await
+                .unwrap();
+        let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+        JobRepository::fail(&pool, job.job_id, "worker crashed").await.unwrap();
+        failed_images.push(image.image_id);
+    }
+    let missing_image = failed_images.pop().unwrap();
+
+    // Never connected, so re-submitted jobs stay pending for the re-publisher
+    let rabbitmq = unreachable_rabbitmq();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(rabbitmq))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "retry_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/retry-failed",
+                web::post().to(handlers::retry_failed_jobs),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/retry-failed", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["requeued"], 2);
+    assert_eq!(body["data"]["skipped"], 1);
+    assert_eq!(body["data"]["errors"][0]["image_id"], missing_image);
+    assert_eq!(body["data"]["errors"][0]["code"], "FILE_MISSING");
+
+    let mut requeued: Vec<i64> = body["data"]["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|job| job["image_id"].as_i64().unwrap())
+        .collect();
+    requeued.sort();
+    assert_eq!(requeued, failed_images);
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 2);
+
+    // The fresh jobs are now the latest for those images, so nothing is retried twice
+    let remaining = JobRepository::find_failed_in_folder(&pool, folder.folder_id).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].image_id, missing_image);
+}
+
+#[sqlx::test]
+async fn test_batch_and_folder_retry_respect_pending_cap(pool: PgPool) {
+    let owner = create_test_user(&pool, "backlog_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let mut image_ids = Vec::new();
+    for name in ["a.jpg", "b.jpg", "c.jpg"] {
+        let image = ImageRepository::create(
+            &pool,
+            folder.folder_id,
+            &format!("images/{}", name),
+            name,
+            "image/jpeg",
+            1024,
+            None,
+        )
+        .await
+        .unwrap();
+        image_ids.push(image.image_id);
+    }
+    // One job already waiting, and one failed job to retry
+    JobRepository::create(&pool, image_ids[0], "v1.0.0").await.unwrap();
+    let failed = JobRepository::create(&pool, image_ids[1], "v0.9.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "worker crashed").await.unwrap();
+
+    let mut config = test_config();
+    config.analysis.max_pending_jobs = 2;
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(unreachable_rabbitmq()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "backlog_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/analyze/batch", web::post().to(handlers::batch_analyze_images))
+            .route(
+                "/folders/{folder_id}/retry-failed",
+                web::post().to(handlers::retry_failed_jobs),
+            ),
+    )
+    .await;
+
+    // Two more jobs would take the backlog to three, so none are created
+    let req = test::TestRequest::post()
+        .uri("/analyze/batch")
+        .set_json(serde_json::json!({ "image_ids": [image_ids[1], image_ids[2]] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_FULL");
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 1);
+
+    // One fits
+    let req = test::TestRequest::post()
+        .uri("/analyze/batch")
+        .set_json(serde_json::json!({ "image_ids": [image_ids[2]] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 2);
+
+    // Now the cap is reached, so the failed job isn't re-submitted
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/retry-failed", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_FULL");
+    assert_eq!(JobRepository::count_pending(&pool).await.unwrap(), 2);
+}
+
+// ============================================================================
+// Batch Limit Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_batch_endpoints_enforce_shared_cap(pool: PgPool) {
+    let owner = create_test_user(&pool, "batch_cap_owner").await;
+
+    let mut config = test_config();
+    config.limits.max_batch_size = 2;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(unreachable_rabbitmq()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "batch_cap_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/analyze/batch", web::post().to(handlers::batch_analyze_images))
+            .route("/images/list", web::post().to(handlers::list_images_multi))
+            .route("/jobs/results/batch", web::post().to(handlers::ingest_job_results_batch)),
+    )
+    .await;
+
+    let result = serde_json::json!({
+        "job_id": 1,
+        "counts": { "viable": 1, "apoptosis": 0, "other": 0 },
+        "avg_confidence": 0.9
+    });
+    let oversized = [
+        ("/analyze/batch", serde_json::json!({ "image_ids": [1, 2, 3] })),
+        ("/images/list", serde_json::json!({ "folder_ids": [1, 2, 3] })),
+        ("/jobs/results/batch", serde_json::json!([result, result, result])),
+    ];
+
+    for (uri, body) in oversized {
+        let req = test::TestRequest::post().uri(uri).set_json(&body).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "BATCH_TOO_LARGE", "{}", uri);
+    }
+
+    // At the cap the request gets past the size check
+    let req = test::TestRequest::post()
+        .uri("/analyze/batch")
+        .set_json(serde_json::json!({ "image_ids": [1, 2] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+}
+
+// ============================================================================
+// Job Progress Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_progress_message_marks_job_processing(pool: PgPool) {
+    let owner = create_test_user(&pool, "progress_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+
+    let message = serde_json::json!({ "job_id": job.job_id, "status": "processing", "progress_pct": 40 });
+    let updated = JobProgressConsumer::handle_message(&pool, message.to_string().as_bytes())
+        .await
+        .unwrap();
+    assert!(updated);
+
+    let job = JobRepository::find_by_id(&pool, job.job_id, owner).await.unwrap().unwrap();
+    assert_eq!(job.status, JobStatus::Processing);
+    assert!(job.started_at.is_some());
+    assert_eq!(job.progress_pct, Some(40));
+
+    // A late, lower report doesn't move progress backwards
+    let stale = serde_json::json!({ "job_id": job.job_id, "status": "processing", "progress_pct": 10 });
+    JobProgressConsumer::handle_message(&pool, stale.to_string().as_bytes())
+        .await
+        .unwrap();
+    let job = JobRepository::find_by_id(&pool, job.job_id, owner).await.unwrap().unwrap();
+    assert_eq!(job.progress_pct, Some(40));
+}
+
+#[sqlx::test]
+async fn test_result_message_completes_job_once(pool: PgPool) {
+    let owner = create_test_user(&pool, "results_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.jpg",
+        "cells.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    let analysis = test_config().analysis;
+
+    // Malformed payloads and out-of-range values are rejected for dead-lettering
+    let malformed = ResultsConsumer::handle_message(&pool, &analysis, b"{\"job_id\": ").await;
+    assert!(matches!(malformed, Err(ResultsError::Invalid(_))));
+    let negative = serde_json::json!({
+        "job_id": job.job_id,
+        "counts": { "viable": -1, "apoptosis": 0, "other": 0 },
+        "avg_confidence_score": 0.9
+    });
+    let negative = ResultsConsumer::handle_message(&pool, &analysis, negative.to_string().as_bytes()).await;
+    assert!(matches!(negative, Err(ResultsError::Invalid(_))));
+
+    let message = serde_json::json!({
+        "job_id": job.job_id,
+        "counts": { "viable": 7, "apoptosis": 2, "other": 1 },
+        "avg_confidence_score": 0.85,
+        "summary_data": "mostly viable"
+    });
+    let completed = ResultsConsumer::handle_message(&pool, &analysis, message.to_string().as_bytes())
+        .await
+        .unwrap();
+    assert!(completed);
+
+    let stored = JobRepository::find_by_id(&pool, job.job_id, owner).await.unwrap().unwrap();
+    assert_eq!(stored.status, JobStatus::Completed);
+    let (result, _) = AnalysisResultRepository::find_by_job_id(&pool, job.job_id, owner)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.count_viable, 7);
+    assert_eq!(result.summary_data.as_deref(), Some("mostly viable"));
+
+    // A redelivery is accepted without storing a second result
+    let redelivered = ResultsConsumer::handle_message(&pool, &analysis, message.to_string().as_bytes())
+        .await
+        .unwrap();
+    assert!(!redelivered);
+    let results = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM analysis_results WHERE job_id = $1")
+        .bind(job.job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(results, 1);
+}
+
+#[sqlx::test]
+async fn test_result_message_caps_detections_by_policy(pool: PgPool) {
+    let owner = create_test_user(&pool, "results_cap_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/crowded.jpg",
+        "crowded.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let boxes: Vec<serde_json::Value> = [0.2, 0.9, 0.5]
+        .iter()
+        .map(|confidence| {
+            serde_json::json!({
+                "class": "viable", "confidence": confidence,
+                "x": 0, "y": 0, "width": 10, "height": 10
+            })
+        })
+        .collect();
+
+    let mut analysis = test_config().analysis;
+    analysis.max_detections = 2;
+
+    // Rejected results are dead-lettered and leave the job alone
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    let message = serde_json::json!({
+        "job_id": job.job_id,
+        "counts": { "viable": 3, "apoptosis": 0, "other": 0 },
+        "avg_confidence_score": 0.5,
+        "raw_data": { "bounding_boxes": boxes, "model": "v1.0.0" }
+    });
+    analysis.detection_overflow = DetectionOverflowPolicy::Reject;
+    let rejected = ResultsConsumer::handle_message(&pool, &analysis, message.to_string().as_bytes()).await;
+    assert!(matches!(rejected, Err(ResultsError::Invalid(_))));
+    let stored = JobRepository::find_by_id(&pool, job.job_id, owner).await.unwrap().unwrap();
+    assert_ne!(stored.status, JobStatus::Completed);
+
+    // Truncated results keep the most confident boxes
+    analysis.detection_overflow = DetectionOverflowPolicy::Truncate;
+    let completed = ResultsConsumer::handle_message(&pool, &analysis, message.to_string().as_bytes())
+        .await
+        .unwrap();
+    assert!(completed);
+
+    let raw_data: serde_json::Value =
+        sqlx::query_scalar("SELECT raw_data FROM analysis_results WHERE job_id = $1")
+            .bind(job.job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let confidences: Vec<f64> = raw_data["bounding_boxes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["confidence"].as_f64().unwrap())
+        .collect();
+    assert_eq!(confidences, vec![0.9, 0.5]);
+    assert_eq!(raw_data["truncated"], true);
+}
+
+// ============================================================================
+// Analysis Totals Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_analysis_totals_across_folders(pool: PgPool) {
+    let owner = create_test_user(&pool, "totals_owner").await;
+    let other = create_test_user(&pool, "totals_other").await;
+    let folder_a = FolderRepository::create(&pool, owner, "Study A").await.unwrap();
+    let folder_b = FolderRepository::create(&pool, owner, "Study B").await.unwrap();
+    let foreign = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+
+    // Each analyzed image adds `viable` viable, 5 apoptotic and 1 other cell
+    let job_a = create_analyzed_image(&pool, folder_a.folder_id, "a.jpg", 10).await;
+    create_analyzed_image(&pool, folder_b.folder_id, "b.jpg", 20).await;
+    create_analyzed_image(&pool, foreign.folder_id, "c.jpg", 99).await;
+
+    // A failed re-run on the first image counts as a job but adds no cells
+    let image_a = sqlx::query_scalar::<_, i64>("SELECT image_id FROM jobs WHERE job_id = $1")
+        .bind(job_a)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let failed = JobRepository::create(&pool, image_a, "v1.0.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "Model crashed").await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "totals_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/me/analysis-totals", web::get().to(handlers::get_analysis_totals)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/me/analysis-totals").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let totals = &body["data"];
+    assert_eq!(totals["total_jobs"], 3);
+    assert_eq!(totals["completed_jobs"], 2);
+    assert_eq!(totals["failed_jobs"], 1);
+    assert_eq!(totals["cells"]["viable"], 30);
+    assert_eq!(totals["cells"]["apoptosis"], 10);
+    assert_eq!(totals["cells"]["other"], 2);
+    assert!((totals["mean_confidence"].as_f64().unwrap() - 0.9).abs() < 1e-9);
+}
+
+#[sqlx::test]
+async fn test_analysis_totals_zero_for_new_user(pool: PgPool) {
+    let owner = create_test_user(&pool, "totals_new").await;
+
+    let totals = AnalysisResultRepository::totals_by_user_id(&pool, owner).await.unwrap();
+
+    assert_eq!(totals.total_jobs, 0);
+    assert_eq!(totals.completed_jobs, 0);
+    assert_eq!(totals.failed_jobs, 0);
+    assert_eq!(totals.viable_cells, 0);
+    assert_eq!(totals.mean_confidence, 0.0);
+}
+
+#[sqlx::test]
+async fn test_account_breakdown_groups_images_and_jobs(pool: PgPool) {
+    let owner = create_test_user(&pool, "breakdown_owner").await;
+    let other = create_test_user(&pool, "breakdown_other").await;
+    let folder = FolderRepository::create(&pool, owner, "Study").await.unwrap();
+    let foreign = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+
+    // Two analyzed JPEGs, plus a PNG with one failed and one pending job
+    create_analyzed_image(&pool, folder.folder_id, "a.jpg", 10).await;
+    create_analyzed_image(&pool, folder.folder_id, "b.jpg", 20).await;
+    create_analyzed_image(&pool, foreign.folder_id, "c.jpg", 99).await;
+    let png = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/d.png",
+        "d.png",
+        "image/png",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let failed = JobRepository::create(&pool, png.image_id, "v1.0.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "Model crashed").await.unwrap();
+    JobRepository::create(&pool, png.image_id, "v1.0.0").await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "breakdown_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/me/breakdown", web::get().to(handlers::get_account_breakdown)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/me/breakdown").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(
+        body["data"]["images_by_mime_type"],
+        serde_json::json!({ "image/jpeg": 2, "image/png": 1 })
+    );
+    assert_eq!(
+        body["data"]["jobs_by_status"],
+        serde_json::json!({ "completed": 2, "failed": 1, "pending": 1 })
+    );
+}
+
+// ============================================================================
+// Admin Job Resolution Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_admin_force_fails_processing_job(pool: PgPool) {
+    let owner = create_test_user(&pool, "stuck_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/stuck.jpg",
+        "stuck.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::start_processing(&pool, job.job_id).await.unwrap();
+
+    let admin_config = AdminConfig {
+        usernames: vec!["ops".to_string()],
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .wrap(AdminGuard::new(admin_config))
+            .wrap_fn(|req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: Uuid::new_v4(),
+                    username: "ops".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/admin/jobs/{job_id}/resolve", web::post().to(handlers::resolve_job)),
+    )
+    .await;
+    let uri = format!("/admin/jobs/{}/resolve", job.job_id);
+    let body = serde_json::json!({ "outcome": "failed", "error_message": "Worker lost the job" });
+
+    let req = test::TestRequest::post().uri(&uri).set_json(&body).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let resolved: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(resolved["data"]["status"], "failed");
+    assert_eq!(resolved["data"]["error_message"], "Worker lost the job");
+
+    // The override is recorded as a job event
+    let (from_status, to_status, actor): (String, String, String) = sqlx::query_as(
+        "SELECT from_status::text, to_status::text, actor FROM job_events WHERE job_id = $1",
+    )
+    .bind(job.job_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(from_status, "processing");
+    assert_eq!(to_status, "failed");
+    assert_eq!(actor, "ops");
+
+    // A finished job cannot be resolved again
+    let req = test::TestRequest::post().uri(&uri).set_json(&body).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+async fn test_owner_cancels_unfinished_job_once(pool: PgPool) {
+    let owner = create_test_user(&pool, "cancel_owner").await;
+    let intruder = create_test_user(&pool, "cancel_intruder").await;
+    let folder = FolderRepository::create(&pool, owner, "Cancel").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cancel.jpg",
+        "cancel.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::start_processing(&pool, job.job_id).await.unwrap();
+
+    let app = |user_id: Uuid| {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id,
+                    username: "cancel_user".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/cancel", web::post().to(handlers::cancel_job))
+    };
+    let uri = format!("/jobs/{}/cancel", job.job_id);
+
+    let intruder_app = test::init_service(app(intruder)).await;
+    let req = test::TestRequest::post().uri(&uri).to_request();
+    let res = test::call_service(&intruder_app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let owner_app = test::init_service(app(owner)).await;
+    let req = test::TestRequest::post().uri(&uri).to_request();
+    let res = test::call_service(&owner_app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["status"], "cancelled");
+    assert!(body["data"]["finished_at"].is_string());
+
+    let req = test::TestRequest::post().uri(&uri).to_request();
+    let res = test::call_service(&owner_app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    // A late worker result doesn't revive the job
+    let mut conn = pool.acquire().await.unwrap();
+    let outcome = JobRepository::record_result(&mut conn, job.job_id, 1, 0, 0, 0.9, None, None)
+        .await
+        .unwrap();
+    assert!(matches!(outcome, RecordResultOutcome::AlreadyFinished(JobStatus::Cancelled)));
+}
+
+// ============================================================================
+// Retry Job Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_failed_job_retried_once_with_same_model(pool: PgPool) {
+    let owner = create_test_user(&pool, "retry_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Retry").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/retry.jpg",
+        "retry.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let failed = JobRepository::create(&pool, image.image_id, "v2.0.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "Worker crashed").await.unwrap();
+    let pending = JobRepository::create(&pool, image.image_id, "v2.0.0").await.unwrap();
+
+    let config = test_config();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            // Never connected, so the retry stays pending for the re-publisher
+            .app_data(web::Data::new(unreachable_rabbitmq()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "retry_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/retry", web::post().to(handlers::retry_job)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/jobs/{}/retry", pending.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/jobs/{}/retry", failed.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let (retry_id, model_version): (i64, Option<String>) = sqlx::query_as(
+        "SELECT job_id, ai_model_version FROM jobs WHERE retry_of = $1 AND status = 'pending'",
+    )
+    .bind(failed.job_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_ne!(retry_id, failed.job_id);
+    assert_eq!(model_version.as_deref(), Some("v2.0.0"));
+
+    // The failed job already has a retry, so it can't be retried again
+    let req = test::TestRequest::post()
+        .uri(&format!("/jobs/{}/retry", failed.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "ALREADY_RETRIED");
+}
+
+#[sqlx::test]
+async fn test_retry_job_respects_pending_cap(pool: PgPool) {
+    let owner = create_test_user(&pool, "retry_cap_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Retry").await.unwrap();
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/retry.jpg",
+        "retry.jpg",
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .unwrap();
+    let failed = JobRepository::create(&pool, image.image_id, "v2.0.0").await.unwrap();
+    JobRepository::fail(&pool, failed.job_id, "Worker crashed").await.unwrap();
+    JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+
+    let mut config = test_config();
+    config.analysis.max_pending_jobs = 1;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(unreachable_rabbitmq()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "retry_cap_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/retry", web::post().to(handlers::retry_job)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/jobs/{}/retry", failed.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUEUE_FULL");
+
+    // No retry was created, so the job can be retried once there's room
+    let retries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE retry_of = $1")
+        .bind(failed.job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(retries, 0);
+}
+
+// ============================================================================
+// Overlay URL Tests
+// ============================================================================
+
+/// Local storage that also hands out (fake) presigned URLs
+struct PresigningStorage(LocalStorageService);
+
+#[async_trait]
+impl StorageBackend for PresigningStorage {
+    async fn upload(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<(), StorageError> {
+        self.0.upload(key, bytes, content_type).await
+    }
+
+    async fn upload_from_file(
+        &self,
+        key: &str,
+        file: tokio::fs::File,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        self.0.upload_from_file(key, file, content_type).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Vec<u8>, String), StorageError> {
+        self.0.get(key).await
+    }
+
+    async fn object_size(&self, key: &str) -> Result<u64, StorageError> {
+        self.0.object_size(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.0.delete(key).await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.0.copy(from, to).await
+    }
+
+    async fn presign_put(&self, key: &str, content_type: &str) -> Result<String, StorageError> {
+        self.0.presign_put(key, content_type).await
+    }
+
+    async fn presign_get(
+        &self,
+        key: &str,
+        _overrides: &ResponseOverrides,
+    ) -> Result<String, StorageError> {
+        Ok(format!("https://storage.example.com/{}?signature=test", key))
+    }
+
+    fn presign_expiry_secs(&self) -> u64 {
+        self.0.presign_expiry_secs()
+    }
+
+    async fn check_health(&self) -> Result<(), StorageError> {
+        self.0.check_health().await
+    }
+}
+
+#[sqlx::test]
+async fn test_overlay_url_targets_cached_overlay(pool: PgPool) {
+    let owner = create_test_user(&pool, "overlay_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Overlays").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> =
+        Arc::new(PresigningStorage(LocalStorageService::new(root.path(), 3600)));
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(64, 48, image::Rgb([255, 255, 255])))
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+    storage.upload("images/overlay.jpg", &jpeg, "image/jpeg").await.unwrap();
+
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/overlay.jpg",
+        "overlay.jpg",
+        "image/jpeg",
+        jpeg.len() as i32,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    let raw_data = serde_json::json!({
+        "bounding_boxes": [
+            { "class": "viable", "confidence": 0.9, "x": 10, "y": 10, "width": 20, "height": 15 }
+        ]
+    });
+    AnalysisResultRepository::create(&pool, job.job_id, 1, 0, 0, 0.9, Some(raw_data), None)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "overlay_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/overlay-url", web::get().to(handlers::get_overlay_url)),
+    )
+    .await;
+
+    let overlay_key = format!("overlays/job-{}.jpg", job.job_id);
+    for _ in 0..2 {
+        // The second request is served from the cached overlay
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/overlay-url", job.job_id))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        let url = body["data"]["url"].as_str().unwrap();
+        assert!(url.starts_with(&format!("https://storage.example.com/{}?", overlay_key)));
+    }
+
+    let (overlay, content_type) = storage.get(&overlay_key).await.unwrap();
+    assert_eq!(content_type, "image/jpeg");
+    assert_ne!(overlay, jpeg);
+
+    // Someone else's job is not found
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/overlay-url", job.job_id + 1000))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_overlay_draws_top_boxes_and_notes_omitted(pool: PgPool) {
+    let owner = create_test_user(&pool, "overlay_limit_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Crowded").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> =
+        Arc::new(PresigningStorage(LocalStorageService::new(root.path(), 3600)));
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(96, 64, image::Rgb([255, 255, 255])))
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+    storage.upload("images/crowded.jpg", &jpeg, "image/jpeg").await.unwrap();
+
+    let image = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/crowded.jpg",
+        "crowded.jpg",
+        "image/jpeg",
+        jpeg.len() as i32,
+        None,
+    )
+    .await
+    .unwrap();
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    let raw_data = serde_json::json!({
+        "bounding_boxes": [
+            { "class": "apoptosis", "confidence": 0.5, "x": 10, "y": 30, "width": 20, "height": 20 },
+            { "class": "viable", "confidence": 0.9, "x": 40, "y": 20, "width": 30, "height": 20 },
+            { "class": "other", "confidence": 0.3, "x": 75, "y": 40, "width": 15, "height": 15 }
+        ]
+    });
+    AnalysisResultRepository::create(&pool, job.job_id, 1, 1, 1, 0.9, Some(raw_data), None)
+        .await
+        .unwrap();
+
+    let mut config = test_config();
+    config.overlay.max_boxes = 1;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "overlay_limit_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/jobs/{job_id}/overlay-url", web::get().to(handlers::get_overlay_url)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/jobs/{}/overlay-url", job.job_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let (overlay, _) = storage.get(&format!("overlays/job-{}.jpg", job.job_id)).await.unwrap();
+    let canvas = image::load_from_memory(&overlay).unwrap().to_rgb8();
+
+    // Only the most confident box is outlined (allowing for JPEG noise)
+    let drawn = canvas.get_pixel(40, 30);
+    assert!(drawn[1] > 150 && drawn[0] < 100, "top box edge was {:?}", drawn);
+    for (x, y) in [(10, 40), (75, 47)] {
+        let skipped = canvas.get_pixel(x, y);
+        assert!(skipped.0.iter().all(|c| *c > 200), "omitted box drawn at ({x}, {y}): {:?}", skipped);
+    }
+
+    // "+2" is written white on a black label in the top-left corner
+    assert!(canvas.get_pixel(1, 1).0.iter().all(|c| *c < 80));
+    assert!(canvas.get_pixel(5, 7).0.iter().all(|c| *c > 150));
+}
+
+// ============================================================================
+// Jobs By Model Version Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_list_my_jobs_filters_by_model_version_across_folders(pool: PgPool) {
+    let owner = create_test_user(&pool, "versions_owner").await;
+    let other = create_test_user(&pool, "versions_other").await;
+    let first = FolderRepository::create(&pool, owner, "First").await.unwrap();
+    let second = FolderRepository::create(&pool, owner, "Second").await.unwrap();
+    let theirs = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+
+    let create_job = |folder_id: i32, filename: &'static str, model_version: &'static str| {
+        let pool = pool.clone();
+        async move {
+            let image = ImageRepository::create(
+                &pool,
+                folder_id,
+                &format!("images/{}", filename),
+                filename,
+                "image/jpeg",
+                1024,
+                None,
+            )
+            .await
+            .unwrap();
+            JobRepository::create(&pool, image.image_id, model_version).await.unwrap().job_id
+        }
+    };
+
+    create_analyzed_image(&pool, first.folder_id, "old_model.jpg", 10).await;
+    let pending = create_job(first.folder_id, "first.jpg", "v2.0.0").await;
+    let completed = create_job(second.folder_id, "second.jpg", "v2.0.0").await;
+    JobRepository::complete(&pool, completed).await.unwrap();
+    create_job(theirs.folder_id, "theirs.jpg", "v2.0.0").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "versions_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/me/jobs", web::get().to(handlers::list_my_jobs)),
+    )
+    .await;
+
+    let list = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let res = test::call_service(app, test::TestRequest::get().uri(uri).to_request()).await;
+            let status = res.status();
+            let body: serde_json::Value = test::read_body_json(res).await;
+            (status, body)
+        }
+    };
+    let job_ids = |body: &serde_json::Value| -> Vec<i64> {
+        body["data"]["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|job| job["job_id"].as_i64().unwrap())
+            .collect()
+    };
+
+    // Both folders' v2 jobs, newest first, and none of the other user's
+    let (status, body) = list("/me/jobs?model_version=v2.0.0").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(job_ids(&body), vec![completed, pending]);
+    assert_eq!(body["data"]["total"], 2);
+    assert!(body["data"]["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|job| job["ai_model_version"] == "v2.0.0"));
+
+    let (_, body) = list("/me/jobs?model_version=v2.0.0&status=completed").await;
+    assert_eq!(job_ids(&body), vec![completed]);
+    assert_eq!(body["data"]["jobs"][0]["result_url"], format!("/api/v1/jobs/{}/result", completed));
+
+    let (_, body) = list("/me/jobs?model_version=v2.0.0&limit=1&offset=1").await;
+    assert_eq!(job_ids(&body), vec![pending]);
+    assert_eq!(body["data"]["total"], 2);
+
+    let (status, body) = list("/me/jobs?model_version=v2.0.0&status=finished").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+}
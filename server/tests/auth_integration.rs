@@ -0,0 +1,274 @@
+//! Auth Integration Tests
+//!
+//! Tests for registration against a real database.
+
+use actix_web::dev::Service;
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web, App, HttpMessage};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use cell_analysis_backend::config::settings::JwtConfig;
+use cell_analysis_backend::dto::{LoginRequest, RegisterRequest};
+use cell_analysis_backend::handlers;
+use cell_analysis_backend::middleware::{AuthenticatedUser, AuthenticationMiddleware};
+use cell_analysis_backend::repositories::{RevokedTokenRepository, UserRepository};
+use cell_analysis_backend::services::{AuthError, AuthService, RevokedTokenCleanup};
+
+const TEST_PASSWORD: &str = "Str0ng!Passw0rd";
+
+// ============================================================================
+// Duplicate Username Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_duplicate_insert_maps_to_username_exists(pool: PgPool) {
+    UserRepository::create(&pool, "taken_name", "hash").await.unwrap();
+
+    // Bypass the pre-check to hit the unique constraint directly
+    let error = UserRepository::create(&pool, "taken_name", "hash")
+        .await
+        .expect_err("Duplicate username should violate the unique constraint");
+
+    assert!(matches!(
+        AuthError::from_user_insert(error),
+        AuthError::UsernameExists
+    ));
+}
+
+#[sqlx::test]
+async fn test_concurrent_creates_yield_one_username_exists(pool: PgPool) {
+    let (first, second) = futures::join!(
+        UserRepository::create(&pool, "race_insert", "hash"),
+        UserRepository::create(&pool, "race_insert", "hash"),
+    );
+
+    let mut outcomes: Vec<_> = [first, second]
+        .into_iter()
+        .map(|result| result.map_err(AuthError::from_user_insert))
+        .collect();
+    outcomes.sort_by_key(|outcome| outcome.is_err());
+
+    assert!(outcomes[0].is_ok());
+    assert!(matches!(outcomes[1], Err(AuthError::UsernameExists)));
+}
+
+#[sqlx::test]
+async fn test_concurrent_register_same_username_returns_conflict(pool: PgPool) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .route("/register", web::post().to(handlers::register)),
+    )
+    .await;
+
+    let body = serde_json::json!({ "username": "race_user", "password": TEST_PASSWORD });
+    let first = test::TestRequest::post()
+        .uri("/register")
+        .set_json(&body)
+        .to_request();
+    let second = test::TestRequest::post()
+        .uri("/register")
+        .set_json(&body)
+        .to_request();
+
+    // Both requests typically pass the pre-check while hashing; the loser
+    // must see the unique violation as a conflict, not an internal error
+    let (first, second) = futures::join!(
+        test::call_service(&app, first),
+        test::call_service(&app, second)
+    );
+
+    let mut statuses = vec![first.status(), second.status()];
+    statuses.sort();
+    assert_eq!(statuses, vec![StatusCode::CREATED, StatusCode::CONFLICT]);
+}
+
+// ============================================================================
+// Profile Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_profile_returned_until_user_deleted(pool: PgPool) {
+    let user = UserRepository::create(&pool, "profile_user", "hash").await.unwrap();
+    let user_id = user.user_id;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id,
+                    username: "profile_user".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/me", web::get().to(handlers::get_profile)),
+    )
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/me").to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["user_id"], user_id.to_string());
+    assert_eq!(body["data"]["username"], "profile_user");
+    assert!(!body["data"]["created_at"].as_str().unwrap().is_empty());
+
+    // A token can outlive its account
+    sqlx::query("DELETE FROM users WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/me").to_request()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Change Password Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_change_password_requires_current_password(pool: PgPool) {
+    let registered = AuthService::register(
+        &pool,
+        RegisterRequest {
+            username: "rotating_user".to_string(),
+            password: TEST_PASSWORD.to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    let user_id = registered.user_id;
+    let jwt_config: JwtConfig =
+        serde_json::from_value(serde_json::json!({ "secret": "test-secret" })).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(jwt_config))
+            .route("/login", web::post().to(handlers::login))
+            .service(
+                web::resource("/change-password")
+                    .wrap_fn(move |req, srv| {
+                        req.extensions_mut().insert(AuthenticatedUser {
+                            user_id,
+                            username: "rotating_user".to_string(),
+                            jti: None,
+                        });
+                        srv.call(req)
+                    })
+                    .route(web::post().to(handlers::change_password)),
+            ),
+    )
+    .await;
+
+    let change = |current: &str, new: &str| {
+        test::TestRequest::post()
+            .uri("/change-password")
+            .set_json(serde_json::json!({ "current_password": current, "new_password": new }))
+            .to_request()
+    };
+    let login = |password: &str| {
+        test::TestRequest::post()
+            .uri("/login")
+            .set_json(serde_json::json!({ "username": "rotating_user", "password": password }))
+            .to_request()
+    };
+    const NEW_PASSWORD: &str = "N3w!Passw0rd-2026";
+
+    let res = test::call_service(&app, change("Wr0ng!Password", NEW_PASSWORD)).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = test::call_service(&app, change(TEST_PASSWORD, "too-weak")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = test::call_service(&app, change(TEST_PASSWORD, NEW_PASSWORD)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = test::call_service(&app, login(TEST_PASSWORD)).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = test::call_service(&app, login(NEW_PASSWORD)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+// ============================================================================
+// Token Revocation Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_logout_revokes_access_token(pool: PgPool) {
+    let jwt_config: JwtConfig =
+        serde_json::from_value(serde_json::json!({ "secret": "test-secret" })).unwrap();
+    let register = RegisterRequest {
+        username: "leaving_user".to_string(),
+        password: TEST_PASSWORD.to_string(),
+    };
+    AuthService::register(&pool, register).await.unwrap();
+    let login = LoginRequest {
+        username: "leaving_user".to_string(),
+        password: TEST_PASSWORD.to_string(),
+    };
+    let tokens = AuthService::login(&pool, &jwt_config, login).await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .service(
+                web::scope("")
+                    .wrap(AuthenticationMiddleware::new(jwt_config.clone()))
+                    .route("/me", web::get().to(handlers::get_profile))
+                    .route("/logout", web::post().to(handlers::logout)),
+            ),
+    )
+    .await;
+    let bearer = format!("Bearer {}", tokens.access_token);
+
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header((header::AUTHORIZATION, bearer.as_str()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/logout")
+        .insert_header((header::AUTHORIZATION, bearer.as_str()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // The same token is refused from now on
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header((header::AUTHORIZATION, bearer.as_str()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "TOKEN_REVOKED");
+}
+
+#[sqlx::test]
+async fn test_expired_revocations_are_purged(pool: PgPool) {
+    let now = chrono::Utc::now();
+    let expired = Uuid::new_v4();
+    let within_skew = Uuid::new_v4();
+    let live = Uuid::new_v4();
+    RevokedTokenRepository::revoke(&pool, expired, now - chrono::Duration::minutes(10))
+        .await
+        .unwrap();
+    RevokedTokenRepository::revoke(&pool, within_skew, now - chrono::Duration::minutes(1))
+        .await
+        .unwrap();
+    RevokedTokenRepository::revoke(&pool, live, now + chrono::Duration::hours(1))
+        .await
+        .unwrap();
+
+    let purged = RevokedTokenCleanup::purge(&pool, 300).await.unwrap();
+
+    assert_eq!(purged, 1);
+    assert!(!RevokedTokenRepository::is_revoked(&pool, expired).await.unwrap());
+    // Still accepted within the clock skew allowance, so still blocked
+    assert!(RevokedTokenRepository::is_revoked(&pool, within_skew).await.unwrap());
+    assert!(RevokedTokenRepository::is_revoked(&pool, live).await.unwrap());
+}
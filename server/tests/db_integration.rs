@@ -1,7 +1,120 @@
+use std::time::Duration;
+
+use actix_web::dev::Service;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App, HttpMessage};
+use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
+use uuid::Uuid;
+
+use cell_analysis_backend::config::settings::{DatabaseConfig, RabbitmqConfig};
+use cell_analysis_backend::db::connection::create_read_pool;
+use cell_analysis_backend::db::{pool_metrics, ReadPool};
+use cell_analysis_backend::handlers;
+use cell_analysis_backend::metrics;
+use cell_analysis_backend::middleware::AuthenticatedUser;
+use cell_analysis_backend::repositories::FolderRepository;
+use cell_analysis_backend::routes::readiness_check;
+use cell_analysis_backend::services::local_storage_service::LocalStorageService;
+use cell_analysis_backend::services::{RabbitmqService, StorageBackend};
 
 #[sqlx::test]
 async fn test_database_connection(pool: PgPool) {
     let result = sqlx::query("SELECT 1 as value").fetch_one(&pool).await;
     assert!(result.is_ok());
 }
+
+#[sqlx::test]
+async fn test_readiness_reports_each_dependency(pool: PgPool) {
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: std::sync::Arc<dyn StorageBackend> =
+        std::sync::Arc::new(LocalStorageService::new(root.path(), 3600));
+    // A broker that was never reached leaves the queue down
+    let rabbitmq = RabbitmqService::disconnected(&RabbitmqConfig {
+        host: "127.0.0.1".to_string(),
+        port: 1,
+        ..RabbitmqConfig::default()
+    });
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(rabbitmq))
+            .route("/health/ready", web::get().to(readiness_check)),
+    )
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/health/ready").to_request()).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["status"], "unhealthy");
+    assert_eq!(body["database"], "up");
+    assert_eq!(body["storage"], "up");
+    assert_eq!(body["queue"], "down");
+}
+
+#[sqlx::test]
+async fn test_pool_gauges_track_acquired_connection(pool: PgPool) {
+    // Hold a connection while sampling so it shows as active
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("SELECT 1").execute(&mut *conn).await.unwrap();
+    pool_metrics::record(&pool);
+
+    let rendered = metrics::registry().render();
+    assert!(rendered.contains("# TYPE db_pool_connections gauge\n"));
+    assert!(rendered.contains("db_pool_connections{state=\"active\"} 1\n"));
+    assert!(rendered.contains("db_pool_connections{state=\"idle\"}"));
+    assert!(rendered.contains("db_pool_connections{state=\"total\"}"));
+}
+
+#[sqlx::test]
+async fn test_read_pool_falls_back_to_primary(pool: PgPool) {
+    let config: DatabaseConfig = serde_json::from_value(serde_json::json!({
+        "url": "postgres://test"
+    }))
+    .unwrap();
+    assert!(config.read_url.is_none());
+
+    let read_pool = create_read_pool(&config, &pool).await.unwrap();
+    let row: (i32,) = sqlx::query_as("SELECT 1").fetch_one(&*read_pool).await.unwrap();
+    assert_eq!(row.0, 1);
+}
+
+#[sqlx::test]
+async fn test_listing_reads_from_read_pool(pool: PgPool) {
+    let owner = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (user_id, username, password_hash) VALUES ($1, 'reader', 'test_hash')")
+        .bind(owner)
+        .execute(&pool)
+        .await
+        .unwrap();
+    FolderRepository::create(&pool, owner, "Replicated").await.unwrap();
+
+    // The primary is unreachable, so the listing only works through the read pool
+    let primary = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://writer@127.0.0.1:1/none")
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(primary))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "reader".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders", web::get().to(handlers::list_folders)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/folders").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["folders"][0]["folder_name"], "Replicated");
+}
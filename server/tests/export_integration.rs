@@ -0,0 +1,133 @@
+//! Data Export Integration Tests
+//!
+//! Tests for user data archive generation using database fixtures.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use cell_analysis_backend::models::job::JobStatus;
+use cell_analysis_backend::repositories::{
+    AnalysisResultRepository, DataExportRepository, FolderRepository, ImageRepository,
+    JobRepository,
+};
+use cell_analysis_backend::services::local_storage_service::LocalStorageService;
+use cell_analysis_backend::services::{ExportService, StorageBackend};
+
+/// Helper to create a test user and return their ID
+async fn create_test_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO users (user_id, username, password_hash)
+        VALUES ($1, $2, 'test_hash')
+        "#,
+    )
+    .bind(user_id)
+    .bind(username)
+    .execute(pool)
+    .await
+    .expect("Failed to create test user");
+
+    user_id
+}
+
+/// Helper to create local storage in a fresh temporary directory, removed
+/// when the returned guard is dropped
+fn create_test_storage() -> (tempfile::TempDir, LocalStorageService) {
+    let root = tempfile::TempDir::new().unwrap();
+    let storage = LocalStorageService::new(root.path(), 3600);
+    (root, storage)
+}
+
+/// Helper to read a named entry from a ZIP archive
+fn read_entry(archive: &[u8], name: &str) -> Vec<u8> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive)).expect("Archive should be a valid ZIP");
+    let mut entry = zip.by_name(name).expect("Entry should exist in archive");
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+// ============================================================================
+// Archive Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_export_archive_contains_manifest_entries(pool: PgPool) {
+    let user_id = create_test_user(&pool, "export_owner").await;
+    let other = create_test_user(&pool, "export_other").await;
+    let (_root, storage) = create_test_storage();
+
+    let folder = FolderRepository::create(&pool, user_id, "Study").await.unwrap();
+    let empty = FolderRepository::create(&pool, user_id, "Empty").await.unwrap();
+    let image = ImageRepository::create(&pool, folder.folder_id, "images/cells.png", "cells.png", "image/png", 4, None)
+        .await
+        .unwrap();
+    storage.upload("images/cells.png", b"\x89PNG", "image/png").await.unwrap();
+
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    AnalysisResultRepository::create(&pool, job.job_id, 12, 3, 1, 0.9, None, Some("Mostly viable".to_string()))
+        .await
+        .unwrap();
+
+    // Another user's data must not leak into the archive
+    let foreign = FolderRepository::create(&pool, other, "Not mine").await.unwrap();
+    ImageRepository::create(&pool, foreign.folder_id, "images/other.png", "other.png", "image/png", 4, None)
+        .await
+        .unwrap();
+
+    let mut file = ExportService::build_archive(&pool, &storage, user_id).await.unwrap();
+    let mut archive = Vec::new();
+    file.read_to_end(&mut archive).unwrap();
+
+    let manifest: serde_json::Value = serde_json::from_slice(&read_entry(&archive, "manifest.json")).unwrap();
+    assert_eq!(manifest["user"]["username"], "export_owner");
+
+    let folders = manifest["folders"].as_array().unwrap();
+    assert_eq!(folders.len(), 2);
+    let study = folders.iter().find(|f| f["folder_id"] == folder.folder_id).unwrap();
+    let empty_folder = folders.iter().find(|f| f["folder_id"] == empty.folder_id).unwrap();
+    assert_eq!(empty_folder["images"].as_array().unwrap().len(), 0);
+
+    let images = study["images"].as_array().unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0]["original_filename"], "cells.png");
+
+    let analyses = images[0]["analyses"].as_array().unwrap();
+    assert_eq!(analyses.len(), 1);
+    assert_eq!(analyses[0]["job_id"], job.job_id);
+    assert_eq!(analyses[0]["status"], "completed");
+    assert_eq!(analyses[0]["result"]["count_viable"], 12);
+    assert_eq!(analyses[0]["result"]["summary_data"], "Mostly viable");
+
+    // The image file itself is included at the path the manifest names
+    let archive_path = images[0]["archive_path"].as_str().unwrap();
+    assert_eq!(read_entry(&archive, archive_path), b"\x89PNG");
+}
+
+#[sqlx::test]
+async fn test_export_run_stores_archive_and_completes(pool: PgPool) {
+    let user_id = create_test_user(&pool, "export_runner").await;
+    let (_root, storage) = create_test_storage();
+    let storage: Arc<dyn StorageBackend> = Arc::new(storage);
+
+    let export = DataExportRepository::create(&pool, user_id).await.unwrap();
+    assert_eq!(export.status, JobStatus::Pending);
+
+    ExportService::run(pool.clone(), storage.clone(), export.export_id, user_id).await;
+
+    let export = DataExportRepository::find_by_id(&pool, export.export_id, user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(export.status, JobStatus::Completed);
+
+    let key = export.storage_key.unwrap();
+    assert_eq!(key, ExportService::archive_key(user_id, export.export_id));
+    let (archive, _) = storage.get(&key).await.unwrap();
+    assert!(!read_entry(&archive, "manifest.json").is_empty());
+}
@@ -2,18 +2,33 @@
 //!
 //! Tests for folder repository CRUD operations using database fixtures.
 
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::dev::Service;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App, HttpMessage};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use cell_analysis_backend::repositories::FolderRepository;
+use cell_analysis_backend::config::settings::{AppConfig, DuplicateFilenamePolicy, ThumbnailFormat};
+use cell_analysis_backend::db::ReadPool;
+use cell_analysis_backend::handlers;
+use cell_analysis_backend::middleware::AuthenticatedUser;
+use cell_analysis_backend::repositories::{
+    AnalysisResultRepository, FolderRepository, ImageRepository, JobRepository, MergeFolderOutcome,
+};
+use cell_analysis_backend::services::local_storage_service::LocalStorageService;
+use cell_analysis_backend::services::{ImageService, StorageBackend};
 
 /// Helper to create a test user and return their ID
 async fn create_test_user(pool: &PgPool, username: &str) -> Uuid {
     let user_id = Uuid::new_v4();
     sqlx::query(
         r#"
-        INSERT INTO users (user_id, username, password_hash, role)
-        VALUES ($1, $2, 'test_hash', 'student')
+        INSERT INTO users (user_id, username, password_hash)
+        VALUES ($1, $2, 'test_hash')
         "#,
     )
     .bind(user_id)
@@ -25,6 +40,16 @@ async fn create_test_user(pool: &PgPool, username: &str) -> Uuid {
     user_id
 }
 
+/// Helper to build an app config with only the required settings provided
+fn test_config() -> AppConfig {
+    serde_json::from_value(serde_json::json!({
+        "server": {},
+        "database": { "url": "postgres://test" },
+        "jwt": { "secret": "test-secret" }
+    }))
+    .expect("Failed to build test config")
+}
+
 // ============================================================================
 // Create Folder Tests
 // ============================================================================
@@ -213,6 +238,50 @@ async fn test_delete_folder_wrong_owner(pool: PgPool) {
     assert_eq!(folders.len(), 1);
 }
 
+#[sqlx::test]
+async fn test_delete_folder_covers_concurrent_insert(pool: PgPool) {
+    let user_id = create_test_user(&pool, "test_delete_concurrent").await;
+    let folder = FolderRepository::create(&pool, user_id, "Busy Folder").await.unwrap();
+
+    // An upload is mid-transaction when the delete starts
+    let mut insert_tx = pool.begin().await.unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO images (folder_id, file_path, original_filename, mime_type, file_size)
+        VALUES ($1, 'images/late.jpg', 'late.jpg', 'image/jpeg', 1024)
+        "#,
+    )
+    .bind(folder.folder_id)
+    .execute(&mut *insert_tx)
+    .await
+    .unwrap();
+
+    let delete_pool = pool.clone();
+    let delete = tokio::spawn(async move {
+        FolderRepository::delete(&delete_pool, folder.folder_id, user_id).await
+    });
+
+    // Give the delete time to reach the folder lock, then let the upload commit
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    insert_tx.commit().await.unwrap();
+
+    let deleted_count = delete
+        .await
+        .unwrap()
+        .expect("Failed to delete folder")
+        .expect("Folder not found");
+    assert_eq!(deleted_count, 1);
+
+    let remaining: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM images WHERE folder_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(folder.folder_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining.0, 0);
+}
+
 // ============================================================================
 // Image Count Tests
 // ============================================================================
@@ -228,3 +297,627 @@ async fn test_get_image_count_empty(pool: PgPool) {
 
     assert_eq!(count, 0);
 }
+
+#[sqlx::test]
+async fn test_get_image_count_excludes_deleted_images(pool: PgPool) {
+    let user_id = create_test_user(&pool, "test_image_count_deleted").await;
+    let folder = FolderRepository::create(&pool, user_id, "Folder").await.unwrap();
+    let mut images = Vec::new();
+    for name in ["a.jpg", "b.jpg"] {
+        let key = format!("images/{}", name);
+        let image =
+            ImageRepository::create(&pool, folder.folder_id, &key, name, "image/jpeg", 10, None)
+                .await
+                .unwrap();
+        images.push(image);
+    }
+    ImageRepository::soft_delete(&pool, images[1].image_id, user_id).await.unwrap();
+
+    let count = FolderRepository::get_image_count(&pool, folder.folder_id)
+        .await
+        .expect("Failed to get image count");
+
+    assert_eq!(count, 1);
+}
+
+// ============================================================================
+// Copy Folder Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_copy_folder_duplicates_images(pool: PgPool) {
+    let owner = create_test_user(&pool, "copy_owner").await;
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let mut source_ids = HashSet::new();
+    for name in ["a.jpg", "b.jpg"] {
+        let key = format!("images/{}.jpg", Uuid::new_v4());
+        storage.upload(&key, b"jpeg-bytes", "image/jpeg").await.unwrap();
+        let image =
+            ImageRepository::create(&pool, source.folder_id, &key, name, "image/jpeg", 10, None)
+                .await
+                .unwrap();
+        source_ids.insert(image.image_id);
+    }
+
+    let config = test_config();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::from(storage.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "copy_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/copy", web::post().to(handlers::copy_folder)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/copy", source.folder_id))
+        .set_json(serde_json::json!({ "new_name": "Copy" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["image_count"], 2);
+    assert_eq!(body["data"]["folder_name"], "Copy");
+    let copy_id = body["data"]["folder_id"].as_i64().unwrap() as i32;
+    assert_ne!(copy_id, source.folder_id);
+
+    let copied = ImageRepository::find_all_by_folder_id(&pool, copy_id).await.unwrap();
+    assert_eq!(copied.len(), 2);
+    let copied_ids: HashSet<i64> = copied.iter().map(|i| i.image_id).collect();
+    assert_eq!(copied_ids.len(), 2);
+    assert!(copied_ids.is_disjoint(&source_ids));
+
+    for image in &copied {
+        let (bytes, _) = storage.get(&image.file_path).await.unwrap();
+        assert_eq!(bytes, b"jpeg-bytes");
+    }
+}
+
+/// Files stored under `dir`, recursively
+fn stored_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => stored_files(&entry.path()),
+                    _ => 1,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[sqlx::test]
+async fn test_copy_folder_is_bounded_and_cleans_up_on_failure(pool: PgPool) {
+    let owner = create_test_user(&pool, "copy_cleanup_owner").await;
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    // The second image's file is missing, so its copy fails after the first's
+    storage.upload("images/a.jpg", b"jpeg-bytes", "image/jpeg").await.unwrap();
+    for (key, name) in [("images/a.jpg", "a.jpg"), ("images/missing.jpg", "b.jpg")] {
+        ImageRepository::create(&pool, source.folder_id, key, name, "image/jpeg", 10, None)
+            .await
+            .unwrap();
+    }
+
+    let mut config = test_config();
+    config.limits.max_copy_images = 1;
+    let mut unbounded = config.clone();
+    unbounded.limits.max_copy_images = 0;
+
+    let app = |config: AppConfig| {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::from(storage.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "copy_cleanup_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/copy", web::post().to(handlers::copy_folder))
+    };
+    let copy = || {
+        test::TestRequest::post()
+            .uri(&format!("/folders/{}/copy", source.folder_id))
+            .set_json(serde_json::json!({ "new_name": "Copy" }))
+            .to_request()
+    };
+
+    let bounded = test::init_service(app(config)).await;
+    let res = test::call_service(&bounded, copy()).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "FOLDER_TOO_LARGE");
+
+    let unbounded = test::init_service(app(unbounded)).await;
+    let res = test::call_service(&unbounded, copy()).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    // The first image's copy is removed again in the background
+    let mut files = stored_files(root.path());
+    for _ in 0..50 {
+        if files == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        files = stored_files(root.path());
+    }
+    assert_eq!(files, 1);
+    let folders = FolderRepository::find_by_user_id(&pool, owner).await.unwrap();
+    assert_eq!(folders.len(), 1);
+}
+
+#[sqlx::test]
+async fn test_copy_and_restore_respect_storage_quota(pool: PgPool) {
+    let owner = create_test_user(&pool, "quota_copy_owner").await;
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+    let trashed = FolderRepository::create(&pool, owner, "Trashed").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    for (folder_id, size) in [(source.folder_id, 400), (trashed.folder_id, 300)] {
+        let key = format!("images/{}.jpg", Uuid::new_v4());
+        storage.upload(&key, b"jpeg-bytes", "image/jpeg").await.unwrap();
+        ImageRepository::create(&pool, folder_id, &key, "a.jpg", "image/jpeg", size, None)
+            .await
+            .unwrap();
+    }
+    FolderRepository::delete(&pool, trashed.folder_id, owner).await.unwrap();
+
+    // 400 bytes stored; the trashed 300 don't count until restored
+    let mut config = test_config();
+    config.upload.max_storage_bytes = 650;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::from(storage.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "quota_copy_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/copy", web::post().to(handlers::copy_folder))
+            .route("/folders/{folder_id}/restore", web::post().to(handlers::restore_folder)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/copy", source.folder_id))
+        .set_json(serde_json::json!({ "new_name": "Copy" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUOTA_EXCEEDED");
+    assert_eq!(FolderRepository::find_by_user_id(&pool, owner).await.unwrap().len(), 1);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/restore", trashed.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUOTA_EXCEEDED");
+    assert!(FolderRepository::find_by_id(&pool, trashed.folder_id, owner)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+// ============================================================================
+// Idempotent Create Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_ensure_folder_returns_existing_folder(pool: PgPool) {
+    let owner = create_test_user(&pool, "ensure_owner").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "ensure_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders", web::put().to(handlers::ensure_folder)),
+    )
+    .await;
+
+    let ensure = || {
+        test::TestRequest::put()
+            .uri("/folders")
+            .set_json(serde_json::json!({ "folder_name": "Plate 1" }))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, ensure()).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let created: serde_json::Value = test::read_body_json(res).await;
+
+    let res = test::call_service(&app, ensure()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let existing: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(existing["data"]["folder_id"], created["data"]["folder_id"]);
+    assert_eq!(existing["data"]["folder_name"], "Plate 1");
+
+    let folders = FolderRepository::find_by_user_id(&pool, owner).await.unwrap();
+    assert_eq!(folders.len(), 1);
+}
+
+// ============================================================================
+// Trash Listing Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_list_trash_returns_deleted_folders_newest_first(pool: PgPool) {
+    let owner = create_test_user(&pool, "trash_owner").await;
+    let live = FolderRepository::create(&pool, owner, "Live").await.unwrap();
+    let older = FolderRepository::create(&pool, owner, "Older").await.unwrap();
+    let newer = FolderRepository::create(&pool, owner, "Newer").await.unwrap();
+    let key = "images/n.jpg";
+    ImageRepository::create(&pool, newer.folder_id, key, "n.jpg", "image/jpeg", 10, None)
+        .await
+        .unwrap();
+    FolderRepository::delete(&pool, older.folder_id, owner).await.unwrap();
+    FolderRepository::delete(&pool, newer.folder_id, owner).await.unwrap();
+    sqlx::query("UPDATE folders SET deleted_at = NOW() - INTERVAL '1 hour' WHERE folder_id = $1")
+        .bind(older.folder_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "trash_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/trash", web::get().to(handlers::list_trash)),
+    )
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/folders/trash").to_request())
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+
+    let folders = body["data"]["folders"].as_array().unwrap();
+    assert_eq!(body["data"]["total"], 2);
+    assert_eq!(folders[0]["folder_id"], newer.folder_id);
+    assert_eq!(folders[0]["image_count"], 1);
+    assert_eq!(folders[1]["folder_id"], older.folder_id);
+    assert!(folders.iter().all(|f| f["deleted_at"].is_string()));
+    assert!(folders.iter().all(|f| f["folder_id"] != live.folder_id));
+}
+
+// ============================================================================
+// Merge Folder Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_merge_folder_empties_and_deletes_source(pool: PgPool) {
+    let owner = create_test_user(&pool, "merge_owner").await;
+    let target = FolderRepository::create(&pool, owner, "Target").await.unwrap();
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+    for (folder_id, name) in [
+        (target.folder_id, "a.jpg"),
+        (source.folder_id, "a.jpg"),
+        (source.folder_id, "b.jpg"),
+    ] {
+        let key = format!("images/{}", name);
+        ImageRepository::create(&pool, folder_id, &key, name, "image/jpeg", 10, None)
+            .await
+            .unwrap();
+    }
+
+    let mut config = test_config();
+    config.upload.duplicate_filenames = DuplicateFilenamePolicy::Suffix;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "merge_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/merge", web::post().to(handlers::merge_folder)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/merge", target.folder_id))
+        .set_json(serde_json::json!({ "source_folder_id": source.folder_id }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["folder_id"], target.folder_id);
+    assert_eq!(body["data"]["image_count"], 3);
+
+    // The colliding name was suffixed per the policy
+    let merged = ImageRepository::find_all_by_folder_id(&pool, target.folder_id).await.unwrap();
+    let names: HashSet<&str> = merged.iter().map(|i| i.original_filename.as_str()).collect();
+    assert_eq!(names, HashSet::from(["a.jpg", "a (1).jpg", "b.jpg"]));
+
+    let left = ImageRepository::find_all_by_folder_id(&pool, source.folder_id).await.unwrap();
+    assert!(left.is_empty());
+    assert!(FolderRepository::find_by_id(&pool, source.folder_id, owner)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(FolderRepository::find_deleted_by_id(&pool, source.folder_id, owner)
+        .await
+        .unwrap()
+        .is_some());
+
+    // The source is gone now, so merging it again finds nothing
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/merge", target.folder_id))
+        .set_json(serde_json::json!({ "source_folder_id": source.folder_id }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn test_merge_folder_respects_image_limit(pool: PgPool) {
+    let owner = create_test_user(&pool, "merge_limit_owner").await;
+    let target = FolderRepository::create(&pool, owner, "Target").await.unwrap();
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+    for (folder_id, name) in [
+        (target.folder_id, "a.jpg"),
+        (source.folder_id, "b.jpg"),
+        (source.folder_id, "c.jpg"),
+    ] {
+        let key = format!("images/{}", name);
+        ImageRepository::create(&pool, folder_id, &key, name, "image/jpeg", 10, None)
+            .await
+            .unwrap();
+    }
+    let keep_name = |filename: &str, _: &HashSet<String>| Some(filename.to_string());
+
+    let outcome =
+        FolderRepository::merge(&pool, target.folder_id, source.folder_id, owner, 2, keep_name)
+            .await
+            .unwrap();
+    assert!(matches!(outcome, MergeFolderOutcome::TooManyImages(2)));
+
+    // Nothing moved and the source is still live
+    assert_eq!(FolderRepository::get_image_count(&pool, source.folder_id).await.unwrap(), 2);
+    assert!(FolderRepository::find_by_id(&pool, source.folder_id, owner)
+        .await
+        .unwrap()
+        .is_some());
+
+    let outcome =
+        FolderRepository::merge(&pool, target.folder_id, source.folder_id, owner, 3, keep_name)
+            .await
+            .unwrap();
+    assert!(matches!(outcome, MergeFolderOutcome::Merged(_, 3)));
+}
+
+// ============================================================================
+// Restore Folder Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_restore_folder_only_by_owner(pool: PgPool) {
+    let owner = create_test_user(&pool, "restore_owner").await;
+    let intruder = create_test_user(&pool, "restore_intruder").await;
+    let folder = FolderRepository::create(&pool, owner, "Trashed").await.unwrap();
+    let key = "images/a.jpg";
+    ImageRepository::create(&pool, folder.folder_id, key, "a.jpg", "image/jpeg", 10, None)
+        .await
+        .unwrap();
+    FolderRepository::delete(&pool, folder.folder_id, owner).await.unwrap();
+
+    let config = test_config();
+
+    let app = |user_id: Uuid| {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id,
+                    username: "restore_user".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/restore", web::post().to(handlers::restore_folder))
+    };
+    let uri = format!("/folders/{}/restore", folder.folder_id);
+
+    // Another user's trashed folder looks the same as a missing one
+    let intruder_app = test::init_service(app(intruder)).await;
+    let res =
+        test::call_service(&intruder_app, test::TestRequest::post().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(FolderRepository::find_by_id(&pool, folder.folder_id, owner)
+        .await
+        .unwrap()
+        .is_none());
+
+    let owner_app = test::init_service(app(owner)).await;
+    let res =
+        test::call_service(&owner_app, test::TestRequest::post().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["folder_name"], "Trashed");
+    assert_eq!(body["data"]["image_count"], 1);
+    assert!(body["data"].get("deleted_at").is_none());
+
+    // Restoring a live folder is a 404 as well
+    let res =
+        test::call_service(&owner_app, test::TestRequest::post().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Purge Folder Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_purge_folder_waits_for_retention_period(pool: PgPool) {
+    let owner = create_test_user(&pool, "purge_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Trash").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    let key = format!("images/{}.jpg", Uuid::new_v4());
+    storage.upload(&key, b"jpeg-bytes", "image/jpeg").await.unwrap();
+    ImageRepository::create(&pool, folder.folder_id, &key, "a.jpg", "image/jpeg", 10, None)
+        .await
+        .unwrap();
+
+    let mut config = test_config();
+    config.trash.min_retention_hours = 24;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::from(storage.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "purge_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/permanent", web::delete().to(handlers::purge_folder)),
+    )
+    .await;
+    let uri = format!("/folders/{}/permanent", folder.folder_id);
+
+    // A live folder can't be purged
+    let res = test::call_service(&app, test::TestRequest::delete().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "NOT_DELETED");
+
+    // Purging straight after a soft delete is too early
+    FolderRepository::delete(&pool, folder.folder_id, owner).await.unwrap();
+    let res = test::call_service(&app, test::TestRequest::delete().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "RETENTION_PERIOD");
+    assert!(storage.get(&key).await.is_ok());
+
+    // Once the retention period has passed the folder and its file are removed
+    sqlx::query("UPDATE folders SET deleted_at = NOW() - INTERVAL '25 hours' WHERE folder_id = $1")
+        .bind(folder.folder_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let res = test::call_service(&app, test::TestRequest::delete().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["deleted_images_count"], 1);
+
+    let remaining = FolderRepository::find_deleted_by_user_id(&pool, owner).await.unwrap();
+    assert!(remaining.is_empty());
+    assert!(storage.get(&key).await.is_err());
+}
+
+#[sqlx::test]
+async fn test_purge_folder_removes_analyses_and_thumbnails(pool: PgPool) {
+    let owner = create_test_user(&pool, "purge_analyses").await;
+    let folder = FolderRepository::create(&pool, owner, "Trash").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    let key = format!("images/{}.jpg", Uuid::new_v4());
+    let thumbnail = ImageService::thumbnail_key(&key, ThumbnailFormat::Webp);
+    storage.upload(&key, b"jpeg-bytes", "image/jpeg").await.unwrap();
+    storage.upload(&thumbnail, b"webp-bytes", "image/webp").await.unwrap();
+    let image = ImageRepository::create(&pool, folder.folder_id, &key, "a.jpg", "image/jpeg", 10, None)
+        .await
+        .unwrap();
+    ImageRepository::add_tag(&pool, image.image_id, "control").await.unwrap();
+
+    let job = JobRepository::create(&pool, image.image_id, "v1.0.0").await.unwrap();
+    JobRepository::complete(&pool, job.job_id).await.unwrap();
+    AnalysisResultRepository::create(&pool, job.job_id, 10, 5, 1, 0.9, None, None)
+        .await
+        .unwrap();
+    let overlay = ImageService::overlay_key(job.job_id);
+    storage.upload(&overlay, b"overlay-bytes", "image/jpeg").await.unwrap();
+
+    FolderRepository::delete(&pool, folder.folder_id, owner).await.unwrap();
+    let mut config = test_config();
+    config.trash.min_retention_hours = 0;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::from(storage.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "purge_analyses".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/permanent", web::delete().to(handlers::purge_folder)),
+    )
+    .await;
+
+    let uri = format!("/folders/{}/permanent", folder.folder_id);
+    let res = test::call_service(&app, test::TestRequest::delete().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    for table in ["jobs", "analysis_results", "images"] {
+        let count = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0, "{} should be empty", table);
+    }
+    assert!(storage.get(&key).await.is_err());
+    assert!(storage.get(&thumbnail).await.is_err());
+    assert!(storage.get(&overlay).await.is_err());
+}
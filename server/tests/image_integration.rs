@@ -0,0 +1,2116 @@
+//! Image Repository Integration Tests
+//!
+//! Tests for image repository operations using database fixtures.
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::dev::{Payload, Service};
+use actix_web::error::PayloadError;
+use actix_web::http::{header, StatusCode};
+use actix_web::web::Bytes;
+use actix_web::{test, web, App, HttpMessage};
+use async_trait::async_trait;
+use futures::{stream, Stream, StreamExt};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use cell_analysis_backend::config::settings::{AppConfig, DuplicateFilenamePolicy};
+use cell_analysis_backend::db::ReadPool;
+use cell_analysis_backend::handlers;
+use cell_analysis_backend::middleware::AuthenticatedUser;
+use cell_analysis_backend::repositories::{FolderRepository, ImageRepository, JobRepository};
+use cell_analysis_backend::services::local_storage_service::LocalStorageService;
+use cell_analysis_backend::services::{
+    ResponseOverrides, ShareLinkSigner, StorageBackend, StorageError, UploadLimiter,
+};
+
+/// Helper to create a test user and return their ID
+async fn create_test_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO users (user_id, username, password_hash)
+        VALUES ($1, $2, 'test_hash')
+        "#,
+    )
+    .bind(user_id)
+    .bind(username)
+    .execute(pool)
+    .await
+    .expect("Failed to create test user");
+
+    user_id
+}
+
+/// Helper to build an app config with only the required settings provided
+fn test_config() -> AppConfig {
+    serde_json::from_value(serde_json::json!({
+        "server": {},
+        "database": { "url": "postgres://test" },
+        "jwt": { "secret": "test-secret" }
+    }))
+    .expect("Failed to build test config")
+}
+
+/// Helper to create a test image in a folder and return its ID
+async fn create_test_image(pool: &PgPool, folder_id: i32, filename: &str) -> i64 {
+    let image = ImageRepository::create(
+        pool,
+        folder_id,
+        &format!("images/{}", filename),
+        filename,
+        "image/jpeg",
+        1024,
+        None,
+    )
+    .await
+    .expect("Failed to create test image");
+
+    image.image_id
+}
+
+// ============================================================================
+// Batch Lookup Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_find_by_ids_mixed_ownership(pool: PgPool) {
+    let owner = create_test_user(&pool, "batch_owner").await;
+    let other = create_test_user(&pool, "batch_other").await;
+
+    // Owned images live in two different folders
+    let folder_a = FolderRepository::create(&pool, owner, "Folder A").await.unwrap();
+    let folder_b = FolderRepository::create(&pool, owner, "Folder B").await.unwrap();
+    let other_folder = FolderRepository::create(&pool, other, "Other").await.unwrap();
+
+    let owned_a = create_test_image(&pool, folder_a.folder_id, "a.jpg").await;
+    let owned_b = create_test_image(&pool, folder_b.folder_id, "b.jpg").await;
+    let foreign = create_test_image(&pool, other_folder.folder_id, "c.jpg").await;
+
+    let images = ImageRepository::find_by_ids(&pool, &[owned_a, foreign, owned_b, 99999], owner)
+        .await
+        .expect("Failed to find images");
+
+    let mut found: Vec<i64> = images.iter().map(|img| img.image_id).collect();
+    found.sort();
+    let mut expected = vec![owned_a, owned_b];
+    expected.sort();
+
+    assert_eq!(found, expected);
+}
+
+#[sqlx::test]
+async fn test_find_by_ids_excludes_deleted(pool: PgPool) {
+    let owner = create_test_user(&pool, "batch_deleted").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let kept = create_test_image(&pool, folder.folder_id, "kept.jpg").await;
+    let deleted = create_test_image(&pool, folder.folder_id, "deleted.jpg").await;
+    ImageRepository::soft_delete(&pool, deleted, owner).await.unwrap();
+
+    let images = ImageRepository::find_by_ids(&pool, &[kept, deleted], owner)
+        .await
+        .expect("Failed to find images");
+
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].image_id, kept);
+}
+
+#[sqlx::test]
+async fn test_move_to_folder_requires_owned_live_folders(pool: PgPool) {
+    let owner = create_test_user(&pool, "move_owner").await;
+    let other = create_test_user(&pool, "move_other").await;
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+    let target = FolderRepository::create(&pool, owner, "Target").await.unwrap();
+    let trashed = FolderRepository::create(&pool, owner, "Trashed").await.unwrap();
+    let foreign = FolderRepository::create(&pool, other, "Foreign").await.unwrap();
+    FolderRepository::delete(&pool, trashed.folder_id, owner).await.unwrap();
+
+    let image_id = create_test_image(&pool, source.folder_id, "cells.jpg").await;
+
+    for folder_id in [foreign.folder_id, trashed.folder_id] {
+        let moved = ImageRepository::move_to_folder(&pool, image_id, owner, folder_id, "cells.jpg").await;
+        assert!(moved.unwrap().is_none());
+    }
+    // Another user cannot move the image, even into their own folder
+    let moved = ImageRepository::move_to_folder(&pool, image_id, other, foreign.folder_id, "cells.jpg").await;
+    assert!(moved.unwrap().is_none());
+
+    let moved = ImageRepository::move_to_folder(&pool, image_id, owner, target.folder_id, "cells.jpg")
+        .await
+        .unwrap()
+        .expect("Image should move into an owned folder");
+    assert_eq!(moved.folder_id, target.folder_id);
+}
+
+#[sqlx::test]
+async fn test_move_image_applies_destination_limits(pool: PgPool) {
+    let owner = create_test_user(&pool, "move_limits_owner").await;
+    let source = FolderRepository::create(&pool, owner, "Source").await.unwrap();
+    let target = FolderRepository::create(&pool, owner, "Target").await.unwrap();
+    create_test_image(&pool, target.folder_id, "cells.jpg").await;
+    let clashing = create_test_image(&pool, source.folder_id, "cells.jpg").await;
+    let overflow = create_test_image(&pool, source.folder_id, "other.jpg").await;
+
+    let mut config = test_config();
+    config.upload.duplicate_filenames = DuplicateFilenamePolicy::Suffix;
+    config.upload.max_images_per_folder = 2;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "move_limits_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/move", web::patch().to(handlers::move_image)),
+    )
+    .await;
+    let move_request = |image_id: i64| {
+        test::TestRequest::patch()
+            .uri(&format!("/images/{}/move", image_id))
+            .set_json(serde_json::json!({ "target_folder_id": target.folder_id }))
+            .to_request()
+    };
+
+    // The clashing name is suffixed, as for an upload
+    let res = test::call_service(&app, move_request(clashing)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["folder_id"], target.folder_id);
+    assert_eq!(body["data"]["original_filename"], "cells (1).jpg");
+
+    // The target now holds its maximum of two images
+    let res = test::call_service(&app, move_request(overflow)).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "FOLDER_IMAGE_LIMIT");
+    let stayed = ImageRepository::find_by_id(&pool, overflow, owner).await.unwrap().unwrap();
+    assert_eq!(stayed.folder_id, source.folder_id);
+}
+
+#[sqlx::test]
+async fn test_has_analysis_bulk_maps_only_analyzed_images(pool: PgPool) {
+    let owner = create_test_user(&pool, "bulk_analysis").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let analyzed = create_test_image(&pool, folder.folder_id, "analyzed.jpg").await;
+    let twice = create_test_image(&pool, folder.folder_id, "twice.jpg").await;
+    let fresh = create_test_image(&pool, folder.folder_id, "fresh.jpg").await;
+    for image_id in [analyzed, twice, twice] {
+        JobRepository::create(&pool, image_id, "v1.0.0").await.unwrap();
+    }
+
+    let map = ImageRepository::has_analysis_bulk(&pool, &[analyzed, twice, fresh])
+        .await
+        .expect("Failed to check analysis");
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&analyzed), Some(&true));
+    assert_eq!(map.get(&twice), Some(&true));
+    assert!(!map.contains_key(&fresh));
+}
+
+// ============================================================================
+// Conditional Listing Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_folder_last_modified_tracks_uploads_and_deletes(pool: PgPool) {
+    let owner = create_test_user(&pool, "last_modified_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let empty = ImageRepository::folder_last_modified(&pool, folder.folder_id).await.unwrap();
+    assert!(empty.is_none());
+
+    let image_id = create_test_image(&pool, folder.folder_id, "a.jpg").await;
+    let after_upload = ImageRepository::folder_last_modified(&pool, folder.folder_id)
+        .await
+        .unwrap()
+        .expect("Folder with an image has a last modified time");
+
+    ImageRepository::soft_delete(&pool, image_id, owner).await.unwrap();
+    let after_delete = ImageRepository::folder_last_modified(&pool, folder.folder_id)
+        .await
+        .unwrap()
+        .expect("Deleted images still count as changes");
+
+    assert!(after_delete > after_upload);
+}
+
+#[sqlx::test]
+async fn test_folder_last_modified_tracks_edits_moves_and_purges(pool: PgPool) {
+    let owner = create_test_user(&pool, "last_modified_edits").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let other = FolderRepository::create(&pool, owner, "Other").await.unwrap();
+    let image_id = create_test_image(&pool, folder.folder_id, "a.jpg").await;
+
+    let last_modified = |folder_id: i32| {
+        let pool = pool.clone();
+        async move {
+            ImageRepository::folder_last_modified(&pool, folder_id)
+                .await
+                .unwrap()
+                .expect("Folder has held an image")
+        }
+    };
+
+    let initial = last_modified(folder.folder_id).await;
+    ImageRepository::update_filename(&pool, image_id, owner, "b.jpg").await.unwrap();
+    let after_rename = last_modified(folder.folder_id).await;
+    assert!(after_rename > initial);
+
+    ImageRepository::add_tag(&pool, image_id, "favorite").await.unwrap();
+    let after_tag = last_modified(folder.folder_id).await;
+    assert!(after_tag > after_rename);
+
+    ImageRepository::remove_tag(&pool, image_id, "favorite").await.unwrap();
+    let previous = last_modified(folder.folder_id).await;
+    assert!(previous > after_tag);
+
+    // A move changes both listings
+    ImageRepository::move_to_folder(&pool, image_id, owner, other.folder_id, "b.jpg")
+        .await
+        .unwrap()
+        .unwrap();
+    let source_after_move = last_modified(folder.folder_id).await;
+    assert!(source_after_move > previous);
+    let target_after_move = last_modified(other.folder_id).await;
+
+    // A hard delete leaves no row behind, but still counts
+    let mut conn = pool.acquire().await.unwrap();
+    ImageRepository::hard_delete_by_folder_id(&mut conn, other.folder_id).await.unwrap();
+    assert!(last_modified(other.folder_id).await > target_after_move);
+}
+
+#[sqlx::test]
+async fn test_list_images_if_modified_since_returns_304(pool: PgPool) {
+    let owner = create_test_user(&pool, "conditional_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    create_test_image(&pool, folder.folder_id, "a.jpg").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "conditional_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::get().to(handlers::list_images)),
+    )
+    .await;
+    let uri = format!("/folders/{}/images", folder.folder_id);
+
+    // First listing returns the data and its Last-Modified
+    let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let last_modified = res
+        .headers()
+        .get(header::LAST_MODIFIED)
+        .expect("Listing should include Last-Modified")
+        .clone();
+
+    // Repeat listing with that timestamp is not modified
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header((header::IF_MODIFIED_SINCE, last_modified))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+}
+
+// ============================================================================
+// MIME Type Filter Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_list_images_filtered_by_mime_type(pool: PgPool) {
+    let owner = create_test_user(&pool, "mime_filter_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    create_test_image(&pool, folder.folder_id, "a.jpg").await;
+    for filename in ["b.tiff", "c.tiff"] {
+        ImageRepository::create(
+            &pool,
+            folder.folder_id,
+            &format!("images/{}", filename),
+            filename,
+            "image/tiff",
+            1024,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "mime_filter_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::get().to(handlers::list_images)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/folders/{}/images?mime_type=image/tiff", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let images = body["data"]["images"].as_array().unwrap();
+    assert_eq!(images.len(), 2);
+    assert!(images.iter().all(|image| image["mime_type"] == "image/tiff"));
+    assert_eq!(body["data"]["pagination"]["total"], 2);
+
+    // Only the allowed upload types can be filtered on
+    let req = test::TestRequest::get()
+        .uri(&format!("/folders/{}/images?mime_type=text/html", folder.folder_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Multi-Folder Listing Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_list_images_include_deleted_admin_only(pool: PgPool) {
+    let owner = create_test_user(&pool, "support").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    create_test_image(&pool, folder.folder_id, "kept.jpg").await;
+    let deleted = create_test_image(&pool, folder.folder_id, "deleted.jpg").await;
+    ImageRepository::soft_delete(&pool, deleted, owner).await.unwrap();
+
+    let app = |admins: &[&str]| {
+        let mut config = test_config();
+        config.admin.usernames = admins.iter().map(|admin| admin.to_string()).collect();
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "support".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::get().to(handlers::list_images))
+    };
+    let uri = format!("/folders/{}/images?include_deleted=true", folder.folder_id);
+
+    // The same owner is refused while not an admin
+    let user_app = test::init_service(app(&[])).await;
+    let res = test::call_service(&user_app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let plain = format!("/folders/{}/images", folder.folder_id);
+    let req = test::TestRequest::get().uri(&plain).to_request();
+    let res = test::call_service(&user_app, req).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 1);
+    assert!(body["data"]["images"][0].get("deleted_at").is_none());
+
+    let admin_app = test::init_service(app(&["support"])).await;
+    let res = test::call_service(&admin_app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 2);
+    let images = body["data"]["images"].as_array().unwrap();
+    let deleted_row = images.iter().find(|i| i["image_id"] == deleted).unwrap();
+    assert!(deleted_row["deleted_at"].is_string());
+}
+
+#[sqlx::test]
+async fn test_list_images_multi_paginates_across_folders(pool: PgPool) {
+    let owner = create_test_user(&pool, "multi_owner").await;
+    let folder_a = FolderRepository::create(&pool, owner, "Study A").await.unwrap();
+    let folder_b = FolderRepository::create(&pool, owner, "Study B").await.unwrap();
+
+    // Interleave uploads between folders with distinct timestamps, oldest first
+    let mut expected = Vec::new();
+    for (i, folder_id) in [folder_a.folder_id, folder_b.folder_id, folder_a.folder_id, folder_b.folder_id]
+        .into_iter()
+        .enumerate()
+    {
+        let image_id = create_test_image(&pool, folder_id, &format!("img_{}.jpg", i)).await;
+        sqlx::query("UPDATE images SET uploaded_at = NOW() - make_interval(mins => $2) WHERE image_id = $1")
+            .bind(image_id)
+            .bind(10 - i as i32)
+            .execute(&pool)
+            .await
+            .unwrap();
+        expected.push(image_id);
+    }
+    expected.reverse(); // Listing is newest first
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "multi_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/list", web::post().to(handlers::list_images_multi)),
+    )
+    .await;
+
+    let mut seen = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let req = test::TestRequest::post()
+            .uri("/images/list")
+            .set_json(serde_json::json!({
+                "folder_ids": [folder_a.folder_id, folder_b.folder_id],
+                "limit": 3,
+                "cursor": cursor,
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(res).await;
+        let data = &body["data"];
+        seen.extend(
+            data["images"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|img| img["image_id"].as_i64().unwrap()),
+        );
+        pages += 1;
+
+        if !data["pagination"]["has_next"].as_bool().unwrap() {
+            break;
+        }
+        cursor = data["pagination"]["next_cursor"].as_str().map(String::from);
+    }
+
+    assert_eq!(pages, 2);
+    assert_eq!(seen, expected);
+}
+
+#[sqlx::test]
+async fn test_list_all_images_spans_owned_live_folders(pool: PgPool) {
+    let owner = create_test_user(&pool, "gallery_owner").await;
+    let other = create_test_user(&pool, "gallery_other").await;
+    let folder_a = FolderRepository::create(&pool, owner, "Study A").await.unwrap();
+    let folder_b = FolderRepository::create(&pool, owner, "Study B").await.unwrap();
+    let trashed = FolderRepository::create(&pool, owner, "Old").await.unwrap();
+    let foreign = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+
+    let mut expected = Vec::new();
+    for (i, folder_id) in [folder_a.folder_id, folder_b.folder_id, folder_a.folder_id]
+        .into_iter()
+        .enumerate()
+    {
+        let image_id = create_test_image(&pool, folder_id, &format!("img_{}.jpg", i)).await;
+        sqlx::query("UPDATE images SET uploaded_at = NOW() - make_interval(mins => $2) WHERE image_id = $1")
+            .bind(image_id)
+            .bind(10 - i as i32)
+            .execute(&pool)
+            .await
+            .unwrap();
+        expected.push(image_id);
+    }
+    expected.reverse();
+
+    let deleted = create_test_image(&pool, folder_b.folder_id, "deleted.jpg").await;
+    ImageRepository::soft_delete(&pool, deleted, owner).await.unwrap();
+    create_test_image(&pool, trashed.folder_id, "trashed.jpg").await;
+    FolderRepository::delete(&pool, trashed.folder_id, owner).await.unwrap();
+    create_test_image(&pool, foreign.folder_id, "foreign.jpg").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "gallery_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images", web::get().to(handlers::list_all_images)),
+    )
+    .await;
+
+    let mut seen = Vec::new();
+    for page in 1..=2 {
+        let req = test::TestRequest::get()
+            .uri(&format!("/images?page={}&limit=2", page))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(res).await;
+        let data = &body["data"];
+        assert_eq!(data["pagination"]["total"], 3);
+        assert_eq!(data["pagination"]["total_pages"], 2);
+        for image in data["images"].as_array().unwrap() {
+            let expected_name = if image["folder_id"] == folder_a.folder_id { "Study A" } else { "Study B" };
+            assert_eq!(image["folder_name"], expected_name);
+            seen.push(image["image_id"].as_i64().unwrap());
+        }
+    }
+
+    assert_eq!(seen, expected);
+}
+
+#[sqlx::test]
+async fn test_search_images_matches_filename_substring(pool: PgPool) {
+    let owner = create_test_user(&pool, "search_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Searchable").await.unwrap();
+    let scan = create_test_image(&pool, folder.folder_id, "scan_01.jpg").await;
+    create_test_image(&pool, folder.folder_id, "image_02.png").await;
+    create_test_image(&pool, folder.folder_id, "scanX01.jpg").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "search_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images/search", web::get().to(handlers::search_images)),
+    )
+    .await;
+    let search = |q: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/folders/{}/images/search?q={}", folder.folder_id, q))
+            .to_request()
+    };
+
+    // Case-insensitive, and `_` must not act as a wildcard for "scanX01.jpg"
+    let res = test::call_service(&app, search("SCAN_")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 1);
+    let images = body["data"]["images"].as_array().unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0]["image_id"], scan);
+
+    let res = test::call_service(&app, search("scan")).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let names: Vec<&str> = body["data"]["images"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|image| image["original_filename"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"scan_01.jpg"));
+    assert!(!names.contains(&"image_02.png"));
+
+    let res = test::call_service(&app, search("")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Pages share the listing's parameters and never repeat an image
+    let mut seen = Vec::new();
+    for page in 1..=2 {
+        let res = test::call_service(&app, search(&format!("scan&limit=1&page={}", page))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["data"]["pagination"]["total"], 2);
+        let images = body["data"]["images"].as_array().unwrap();
+        assert_eq!(images.len(), 1);
+        seen.push(images[0]["image_id"].as_i64().unwrap());
+    }
+    assert_ne!(seen[0], seen[1]);
+
+    let res = test::call_service(&app, search("scan&mime_type=image/png")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 0);
+
+    let res = test::call_service(&app, search("scan&include_deleted=true")).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_cursor_listing_reports_total_only_when_requested(pool: PgPool) {
+    let owner = create_test_user(&pool, "cursor_total_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Counted").await.unwrap();
+    for i in 0..3 {
+        create_test_image(&pool, folder.folder_id, &format!("img_{}.jpg", i)).await;
+    }
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "cursor_total_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::get().to(handlers::list_images_v2)),
+    )
+    .await;
+    let list = |query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/folders/{}/images?limit=2{}", folder.folder_id, query))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, list("")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["count"], 2);
+    assert!(body["data"]["pagination"].get("total").is_none());
+
+    let res = test::call_service(&app, list("&include_total=true")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["count"], 2);
+    assert_eq!(body["data"]["pagination"]["total"], 3);
+}
+
+#[sqlx::test]
+async fn test_list_images_multi_rejects_unowned_folder(pool: PgPool) {
+    let owner = create_test_user(&pool, "multi_real_owner").await;
+    let other = create_test_user(&pool, "multi_other").await;
+    let owned = FolderRepository::create(&pool, owner, "Mine").await.unwrap();
+    let foreign = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+    create_test_image(&pool, owned.folder_id, "mine.jpg").await;
+    create_test_image(&pool, foreign.folder_id, "theirs.jpg").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "multi_real_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/list", web::post().to(handlers::list_images_multi)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/images/list")
+        .set_json(serde_json::json!({ "folder_ids": [owned.folder_id, foreign.folder_id] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Duplicate Filename Policy Tests
+// ============================================================================
+
+/// Store `stored_size` bytes under a fresh upload token, then confirm the
+/// upload as `filename` with the declared `file_size`
+///
+/// Returns the response status and body.
+async fn confirm_stored_upload(
+    pool: &PgPool,
+    owner: Uuid,
+    folder_id: i32,
+    config: AppConfig,
+    filename: &str,
+    stored_size: usize,
+    file_size: i64,
+) -> (StatusCode, serde_json::Value) {
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let upload_token = format!("images/{}.jpg", Uuid::new_v4());
+    storage
+        .upload(&upload_token, &vec![0u8; stored_size], "image/jpeg")
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "upload_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/confirm-upload",
+                web::post().to(handlers::confirm_upload),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images/confirm-upload", folder_id))
+        .set_json(serde_json::json!({
+            "upload_token": upload_token,
+            "filename": filename,
+            "content_type": "image/jpeg",
+            "file_size": file_size
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status = res.status();
+    let body: serde_json::Value = test::read_body_json(res).await;
+    (status, body)
+}
+
+/// Confirm an upload of `cells.jpg` into a folder that already holds one
+///
+/// Returns the response status and, on success, the stored filename.
+async fn confirm_duplicate_upload(
+    pool: &PgPool,
+    policy: DuplicateFilenamePolicy,
+) -> (StatusCode, Option<String>) {
+    let owner = create_test_user(pool, "duplicate_owner").await;
+    let folder = FolderRepository::create(pool, owner, "Folder").await.unwrap();
+    create_test_image(pool, folder.folder_id, "cells.jpg").await;
+
+    let mut config = test_config();
+    config.upload.duplicate_filenames = policy;
+
+    let (status, body) =
+        confirm_stored_upload(pool, owner, folder.folder_id, config, "cells.jpg", 1024, 1024).await;
+    let filename = body["data"]["original_filename"].as_str().map(str::to_string);
+    (status, filename)
+}
+
+#[sqlx::test]
+async fn test_duplicate_filename_allowed(pool: PgPool) {
+    let (status, filename) = confirm_duplicate_upload(&pool, DuplicateFilenamePolicy::Allow).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(filename.as_deref(), Some("cells.jpg"));
+}
+
+#[sqlx::test]
+async fn test_duplicate_filename_rejected(pool: PgPool) {
+    let (status, _) = confirm_duplicate_upload(&pool, DuplicateFilenamePolicy::Reject).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+async fn test_duplicate_filename_suffixed(pool: PgPool) {
+    let (status, filename) = confirm_duplicate_upload(&pool, DuplicateFilenamePolicy::Suffix).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(filename.as_deref(), Some("cells (1).jpg"));
+}
+
+// ============================================================================
+// Confirm Upload Size Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_confirm_upload_rejects_size_mismatch(pool: PgPool) {
+    let owner = create_test_user(&pool, "mismatch_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    // Declared 100 bytes, but 100 KB actually landed in storage
+    let (status, body) = confirm_stored_upload(
+        &pool,
+        owner,
+        folder.folder_id,
+        test_config(),
+        "cells.jpg",
+        100 * 1024,
+        100,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "SIZE_MISMATCH");
+
+    let images = ImageRepository::find_by_folder_id(&pool, folder.folder_id, None, None, 10, 0)
+        .await
+        .unwrap();
+    assert!(images.is_empty());
+}
+
+#[sqlx::test]
+async fn test_confirm_upload_missing_object_is_unprocessable(pool: PgPool) {
+    let owner = create_test_user(&pool, "missing_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "missing_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/confirm-upload",
+                web::post().to(handlers::confirm_upload),
+            ),
+    )
+    .await;
+    let uri = format!("/folders/{}/images/confirm-upload", folder.folder_id);
+
+    // Well-formed, but nothing was ever uploaded under the token
+    let req = test::TestRequest::post()
+        .uri(&uri)
+        .set_json(serde_json::json!({
+            "upload_token": format!("images/{}.jpg", Uuid::new_v4()),
+            "filename": "cells.jpg",
+            "content_type": "image/jpeg",
+            "file_size": 1024
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "UPLOAD_NOT_FOUND");
+
+    // Unparseable JSON stays a 400
+    let req = test::TestRequest::post()
+        .uri(&uri)
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"upload_token": "#)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Folder Image Limit Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_upload_past_folder_limit_rejected_until_delete(pool: PgPool) {
+    let owner = create_test_user(&pool, "limit_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let first = create_test_image(&pool, folder.folder_id, "a.jpg").await;
+
+    let mut config = test_config();
+    config.upload.max_images_per_folder = 2;
+
+    let (status, _) =
+        confirm_stored_upload(&pool, owner, folder.folder_id, config.clone(), "b.jpg", 1024, 1024).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) =
+        confirm_stored_upload(&pool, owner, folder.folder_id, config.clone(), "c.jpg", 1024, 1024).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"]["code"], "FOLDER_IMAGE_LIMIT");
+
+    // Deleted images don't count towards the limit
+    ImageRepository::soft_delete(&pool, first, owner).await.unwrap();
+    let (status, _) =
+        confirm_stored_upload(&pool, owner, folder.folder_id, config, "c.jpg", 1024, 1024).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+// ============================================================================
+// Storage Quota Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_upload_over_storage_quota_rejected(pool: PgPool) {
+    let owner = create_test_user(&pool, "quota_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    create_test_image(&pool, folder.folder_id, "existing.jpg").await; // 1024 bytes
+
+    let mut config = test_config();
+    config.upload.max_storage_bytes = 3000;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config.clone()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "quota_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+    let upload = |size: usize| {
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        jpeg.resize(size, 0);
+        test::TestRequest::put()
+            .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+            .insert_header((header::CONTENT_TYPE, "image/jpeg"))
+            .insert_header(("X-Filename", "cells.jpg"))
+            .set_payload(jpeg)
+            .to_request()
+    };
+
+    // 1024 + 1900 stays just under the quota
+    let res = test::call_service(&app, upload(1900)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = test::call_service(&app, upload(100)).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "QUOTA_EXCEEDED");
+    assert!(body["error"]["message"].as_str().unwrap().contains("2924 of 3000 bytes used"));
+
+    // Presigned uploads are checked against the stored size on confirm
+    let (status, body) =
+        confirm_stored_upload(&pool, owner, folder.folder_id, config, "late.jpg", 100, 100).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["error"]["code"], "QUOTA_EXCEEDED");
+}
+
+// ============================================================================
+// Raw Upload Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_raw_body_upload_stores_jpeg(pool: PgPool) {
+    let owner = create_test_user(&pool, "raw_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "raw_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+
+    let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+    jpeg.resize(2048, 0);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/jpeg"))
+        .insert_header(("X-Filename", "../scans/cells.jpg"))
+        .set_payload(jpeg.clone())
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["original_filename"], "cells.jpg");
+    assert_eq!(body["data"]["mime_type"], "image/jpeg");
+    assert_eq!(body["data"]["file_size"], 2048);
+
+    let images = ImageRepository::find_by_folder_id(&pool, folder.folder_id, None, None, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(images.len(), 1);
+    let (stored, _) = storage.get(&images[0].file_path).await.unwrap();
+    assert_eq!(stored, jpeg);
+}
+
+#[sqlx::test]
+async fn test_recompressed_upload_keeps_capture_time(pool: PgPool) {
+    let owner = create_test_user(&pool, "exif_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    let mut config = test_config();
+    config.upload.jpeg_quality = 60;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "exif_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+
+    // Detailed pixels at quality 100, so the re-encode is worth keeping
+    let pixels = image::RgbImage::from_fn(128, 96, |x, y| {
+        image::Rgb([(x * 2) as u8, (y * 2) as u8, ((x ^ y) * 8) as u8])
+    });
+    let mut encoded = Vec::new();
+    image::DynamicImage::ImageRgb8(pixels)
+        .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, 100))
+        .unwrap();
+
+    // Big-endian TIFF: IFD0 points at an Exif IFD holding DateTimeOriginal
+    let mut exif = b"Exif\0\0MM\x00\x2a\x00\x00\x00\x08".to_vec();
+    exif.extend_from_slice(&[0, 1, 0x87, 0x69, 0, 4, 0, 0, 0, 1, 0, 0, 0, 26, 0, 0, 0, 0]);
+    exif.extend_from_slice(&[0, 1, 0x90, 0x03, 0, 2, 0, 0, 0, 20, 0, 0, 0, 44, 0, 0, 0, 0]);
+    exif.extend_from_slice(b"2025:06:01 08:30:00\0");
+    let mut jpeg = encoded[..2].to_vec();
+    jpeg.extend_from_slice(&[0xFF, 0xE1]);
+    jpeg.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+    jpeg.extend_from_slice(&exif);
+    jpeg.extend_from_slice(&encoded[2..]);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/jpeg"))
+        .insert_header(("X-Filename", "cells.jpg"))
+        .set_payload(jpeg.clone())
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert!(body["data"]["file_size"].as_u64().unwrap() < jpeg.len() as u64);
+    assert_eq!(body["data"]["metadata"]["captured_at"], "2025-06-01T08:30:00+00:00");
+}
+
+#[sqlx::test]
+async fn test_upload_normalizes_mime_type_and_key_extension(pool: PgPool) {
+    let owner = create_test_user(&pool, "case_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "case_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+
+    let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+    jpeg.resize(2048, 0);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/JPG"))
+        .insert_header(("X-Filename", "photo.JPG"))
+        .set_payload(jpeg)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // The display name is kept as uploaded
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["original_filename"], "photo.JPG");
+    assert_eq!(body["data"]["mime_type"], "image/jpeg");
+
+    let images = ImageRepository::find_by_folder_id(&pool, folder.folder_id, None, None, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(images[0].mime_type, "image/jpeg");
+    assert!(images[0].file_path.ends_with(".jpg"), "{}", images[0].file_path);
+
+    // The canonical type is what filters match
+    let jpegs = ImageRepository::find_by_folder_id(&pool, folder.folder_id, Some("image/jpeg"), None, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(jpegs.len(), 1);
+}
+
+#[sqlx::test]
+async fn test_jpeg_upload_recompressed_when_configured(pool: PgPool) {
+    let owner = create_test_user(&pool, "compress_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    let mut config = test_config();
+    config.upload.jpeg_quality = 85;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "compress_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+
+    // Noisy pixels at maximum quality leave plenty for recompression to save
+    let pixels = image::RgbImage::from_fn(320, 240, |x, y| {
+        let n = x.wrapping_mul(2_654_435_761).wrapping_add(y.wrapping_mul(40_503));
+        image::Rgb([(n >> 8) as u8, (n >> 16) as u8, (n >> 24) as u8])
+    });
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(pixels)
+        .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 100))
+        .unwrap();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/jpeg"))
+        .insert_header(("X-Filename", "cells.jpg"))
+        .set_payload(jpeg.clone())
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["metadata"]["width"], 320);
+    assert_eq!(body["data"]["metadata"]["height"], 240);
+
+    let images = ImageRepository::find_by_folder_id(&pool, folder.folder_id, None, None, 10, 0)
+        .await
+        .unwrap();
+    let (stored, _) = storage.get(&images[0].file_path).await.unwrap();
+    assert!(stored.len() < jpeg.len());
+    assert_eq!(images[0].file_size as usize, stored.len());
+    assert_eq!(body["data"]["file_size"], stored.len());
+}
+
+// ============================================================================
+// Upload Timeout Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_stalled_upload_times_out(pool: PgPool) {
+    let owner = create_test_user(&pool, "stalled_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let mut config = test_config();
+    config.server.upload_read_timeout_ms = 200;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "stalled_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::post().to(handlers::upload_image)),
+    )
+    .await;
+
+    // Send the start of the file part, then never send anything else
+    let head = Bytes::from_static(
+        b"--boundary\r\n\
+          Content-Disposition: form-data; name=\"file\"; filename=\"cells.jpg\"\r\n\
+          Content-Type: image/jpeg\r\n\r\n\
+          \xFF\xD8\xFF",
+    );
+    let body: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> =
+        Box::pin(stream::once(async move { Ok(head) }).chain(stream::pending()));
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .to_request();
+    let (req, _) = req.replace_payload(Payload::from(body));
+
+    let started = Instant::now();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[sqlx::test]
+async fn test_concurrent_uploads_past_limit_rejected(pool: PgPool) {
+    let owner = create_test_user(&pool, "concurrent_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let mut config = test_config();
+    config.upload.max_concurrent_uploads = 1;
+    config.server.upload_read_timeout_ms = 500;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "concurrent_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::post().to(handlers::upload_image)),
+    )
+    .await;
+
+    // The first upload holds its slot while its body trickles in
+    let head = Bytes::from_static(
+        b"--boundary\r\n\
+          Content-Disposition: form-data; name=\"file\"; filename=\"cells.jpg\"\r\n\
+          Content-Type: image/jpeg\r\n\r\n\
+          \xFF\xD8\xFF",
+    );
+    let body: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> =
+        Box::pin(stream::once(async move { Ok(head) }).chain(stream::pending()));
+    let first = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .to_request();
+    let (first, _) = first.replace_payload(Payload::from(body));
+
+    let second = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .set_payload("--boundary--\r\n")
+        .to_request();
+
+    let (first, second) = futures::join!(test::call_service(&app, first), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        test::call_service(&app, second).await
+    });
+
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    let body: serde_json::Value = test::read_body_json(second).await;
+    assert_eq!(body["error"]["code"], "TOO_MANY_UPLOADS");
+    assert_eq!(first.status(), StatusCode::REQUEST_TIMEOUT);
+
+    // The stalled upload released its slot when it ended
+    let third = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .set_payload("--boundary--\r\n")
+        .to_request();
+    let res = test::call_service(&app, third).await;
+    assert_ne!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[sqlx::test]
+async fn test_upload_with_oversized_part_headers_rejected(pool: PgPool) {
+    let owner = create_test_user(&pool, "abusive_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "abusive_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::post().to(handlers::upload_image)),
+    )
+    .await;
+
+    // One header line far larger than any legitimate part would send
+    let mut body = b"--boundary\r\nContent-Disposition: form-data; name=\"file\"; filename=\"cells.jpg\"\r\n"
+        .to_vec();
+    body.extend_from_slice(b"X-Padding: ");
+    body.extend(std::iter::repeat_n(b'a', 1024 * 1024));
+    body.extend_from_slice(b"\r\n\r\n\xFF\xD8\xFF\r\n--boundary--\r\n");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .set_payload(body)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "MALFORMED_MULTIPART");
+
+    let count = FolderRepository::get_image_count(&pool, folder.folder_id).await.unwrap();
+    assert_eq!(count, 0);
+}
+
+#[sqlx::test]
+async fn test_oversized_bodies_rejected_with_413(pool: PgPool) {
+    let owner = create_test_user(&pool, "limits_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let mut config = test_config();
+    config.limits.max_json_bytes = 256;
+    config.limits.max_upload_bytes = 1024;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(handlers::json_config(&config.limits))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "limits_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::post().to(handlers::upload_image))
+            .route("/folders/{folder_id}/images/raw", web::put().to(handlers::upload_image_raw))
+            .route("/images/bulk-metadata", web::patch().to(handlers::update_images_metadata)),
+    )
+    .await;
+
+    let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+    jpeg.resize(4096, 0);
+
+    // Raw upload: refused from its Content-Length alone
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/jpeg"))
+        .set_payload(jpeg.clone())
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+
+    // Multipart: the framing fits, the file part does not
+    let mut body = b"--boundary\r\nContent-Disposition: form-data; name=\"file\"; filename=\"cells.jpg\"\r\nContent-Type: image/jpeg\r\n\r\n"
+        .to_vec();
+    body.extend_from_slice(&jpeg);
+    body.extend_from_slice(b"\r\n--boundary--\r\n");
+    let req = test::TestRequest::post()
+        .uri(&format!("/folders/{}/images", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+        .set_payload(body)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let count = FolderRepository::get_image_count(&pool, folder.folder_id).await.unwrap();
+    assert_eq!(count, 0);
+
+    // JSON bodies above the limit get the same error envelope
+    let updates: Vec<serde_json::Value> = (0..20)
+        .map(|image_id| serde_json::json!({ "image_id": image_id, "metadata": { "width": 640 } }))
+        .collect();
+    let req = test::TestRequest::patch()
+        .uri("/images/bulk-metadata")
+        .set_json(serde_json::json!({ "updates": updates }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[sqlx::test]
+async fn test_upload_to_deleted_folder_conflicts(pool: PgPool) {
+    let owner = create_test_user(&pool, "trashed_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    FolderRepository::delete(&pool, folder.folder_id, owner).await.unwrap();
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "trashed_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::post().to(handlers::upload_image)),
+    )
+    .await;
+
+    let upload = |folder_id: i32| {
+        test::TestRequest::post()
+            .uri(&format!("/folders/{}/images", folder_id))
+            .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=boundary"))
+            .set_payload(
+                &b"--boundary\r\n\
+                   Content-Disposition: form-data; name=\"file\"; filename=\"cells.jpg\"\r\n\
+                   Content-Type: image/jpeg\r\n\r\n\
+                   \xFF\xD8\xFF\xE0\r\n--boundary--\r\n"[..],
+            )
+            .to_request()
+    };
+
+    let res = test::call_service(&app, upload(folder.folder_id)).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["error"]["code"], "FOLDER_DELETED");
+
+    // A folder that never existed is still a plain 404
+    let res = test::call_service(&app, upload(folder.folder_id + 1000)).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Upload Constraints Tests
+// ============================================================================
+
+#[actix_web::test]
+async fn test_upload_constraints_match_config() {
+    let mut config = test_config();
+    config.analysis.min_image_width = 128;
+    config.upload.max_images_per_folder = 25;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .route("/upload/constraints", web::get().to(handlers::get_upload_constraints)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/upload/constraints").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let data = &body["data"];
+    assert_eq!(
+        data["allowed_mime_types"],
+        serde_json::json!(["image/jpeg", "image/png", "image/tiff", "image/webp"])
+    );
+    assert_eq!(
+        data["allowed_extensions"],
+        serde_json::json!(["jpg", "jpeg", "png", "tiff", "tif", "webp"])
+    );
+    assert_eq!(data["max_file_size_bytes"], 50 * 1024 * 1024);
+    assert_eq!(data["max_filename_length"], 255);
+    assert_eq!(data["min_image_width"], 128);
+    assert_eq!(data["min_image_height"], 64);
+    assert_eq!(data["max_images_per_folder"], 25);
+}
+
+// ============================================================================
+// Thumbnail Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_thumbnail_generated_lazily_and_reused(pool: PgPool) {
+    let owner = create_test_user(&pool, "thumbnail_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Gallery").await.unwrap();
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::new(600, 300))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    storage.upload("images/cells.png", &png, "image/png").await.unwrap();
+    let cells = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/cells.png",
+        "cells.png",
+        "image/png",
+        png.len() as i32,
+        None,
+    )
+    .await
+    .unwrap();
+    storage.upload("images/broken.jpg", b"not a jpeg", "image/jpeg").await.unwrap();
+    let broken = ImageRepository::create(
+        &pool,
+        folder.folder_id,
+        "images/broken.jpg",
+        "broken.jpg",
+        "image/jpeg",
+        10,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "thumbnail_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/{image_id}/thumbnail", web::get().to(handlers::get_image_thumbnail)),
+    )
+    .await;
+    let thumbnail = |image_id: i64| {
+        test::TestRequest::get()
+            .uri(&format!("/images/{}/thumbnail", image_id))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, thumbnail(cells.image_id)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "image/jpeg");
+    let body = test::read_body(res).await;
+    let decoded = image::load_from_memory(&body).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (256, 128));
+
+    let stored = ImageRepository::find_by_id(&pool, cells.image_id, owner).await.unwrap().unwrap();
+    assert_eq!(stored.thumbnail_key.as_deref(), Some("thumbnails/cells.jpg"));
+
+    // Later requests serve the stored thumbnail rather than regenerating it
+    storage.upload("thumbnails/cells.jpg", b"cached", "image/jpeg").await.unwrap();
+    let res = test::call_service(&app, thumbnail(cells.image_id)).await;
+    assert_eq!(test::read_body(res).await.as_ref(), b"cached");
+
+    // Undecodable images fall back to the original
+    let res = test::call_service(&app, thumbnail(broken.image_id)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await.as_ref(), b"not a jpeg");
+    let stored = ImageRepository::find_by_id(&pool, broken.image_id, owner).await.unwrap().unwrap();
+    assert!(stored.thumbnail_key.is_none());
+    assert!(stored.thumbnail_unavailable);
+
+    // and are served directly from then on, without another decode
+    storage.upload("images/broken.jpg", &png, "image/png").await.unwrap();
+    let res = test::call_service(&app, thumbnail(broken.image_id)).await;
+    assert_eq!(test::read_body(res).await.as_ref(), png.as_slice());
+    let pending = ImageRepository::find_without_thumbnail(&pool, 0, 10).await.unwrap();
+    assert!(pending.is_empty());
+}
+
+#[sqlx::test]
+async fn test_upload_generates_thumbnail_in_background(pool: PgPool) {
+    let owner = create_test_user(&pool, "upload_thumbnail_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Gallery").await.unwrap();
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(UploadLimiter::default()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "upload_thumbnail_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route(
+                "/folders/{folder_id}/images/raw",
+                web::put().to(handlers::upload_image_raw),
+            ),
+    )
+    .await;
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::new(600, 300))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    let req = test::TestRequest::put()
+        .uri(&format!("/folders/{}/images/raw", folder.folder_id))
+        .insert_header((header::CONTENT_TYPE, "image/png"))
+        .insert_header(("X-Filename", "cells.png"))
+        .set_payload(png)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let image_id = body["data"]["image_id"].as_i64().unwrap();
+
+    let mut thumbnail_key = None;
+    for _ in 0..50 {
+        let stored = ImageRepository::find_by_id(&pool, image_id, owner).await.unwrap().unwrap();
+        thumbnail_key = stored.thumbnail_key;
+        if thumbnail_key.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let thumbnail_key = thumbnail_key.expect("thumbnail was not generated");
+    let (bytes, _) = storage.get(&thumbnail_key).await.unwrap();
+    assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 256);
+}
+
+// ============================================================================
+// Share Link Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_share_link_serves_image_until_expired_or_tampered(pool: PgPool) {
+    let owner = create_test_user(&pool, "share_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let image_id = create_test_image(&pool, folder.folder_id, "cells.jpg").await;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(root.path(), 3600));
+    storage.upload("images/cells.jpg", b"jpeg-bytes", "image/jpeg").await.unwrap();
+
+    let config = test_config();
+    let signer = ShareLinkSigner::new(&config.jwt);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(storage))
+            .app_data(web::Data::new(config))
+            .service(
+                web::scope("/api")
+                    .wrap_fn(move |req, srv| {
+                        req.extensions_mut().insert(AuthenticatedUser {
+                            user_id: owner,
+                            username: "share_owner".to_string(),
+                            jti: None,
+                        });
+                        srv.call(req)
+                    })
+                    .route("/images/{image_id}/share", web::post().to(handlers::share_image)),
+            )
+            .route("/shared/images/{token}", web::get().to(handlers::get_shared_image)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/images/{}/share", image_id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    let token = body["data"]["token"].as_str().unwrap().to_string();
+    assert_eq!(body["data"]["url"], format!("/api/v1/shared/images/{}", token));
+
+    // A valid token streams the image without any user attached
+    let req = test::TestRequest::get()
+        .uri(&format!("/shared/images/{}", token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await, Bytes::from_static(b"jpeg-bytes"));
+
+    // Pointing the token at another image breaks the signature
+    let tampered = token.replacen(&image_id.to_string(), &(image_id + 1).to_string(), 1);
+    let expired = signer.sign(image_id, chrono::Utc::now() - chrono::Duration::minutes(1));
+    for (token, code) in [(tampered, "INVALID_SHARE_LINK"), (expired, "SHARE_LINK_EXPIRED")] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/shared/images/{}", token))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"]["code"], code);
+    }
+}
+
+// ============================================================================
+// Image Tag Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_tag_filter_and_untag_images(pool: PgPool) {
+    let owner = create_test_user(&pool, "tag_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Folder").await.unwrap();
+    let starred = create_test_image(&pool, folder.folder_id, "starred.jpg").await;
+    create_test_image(&pool, folder.folder_id, "plain.jpg").await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "tag_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/folders/{folder_id}/images", web::get().to(handlers::list_images))
+            .route("/images/{image_id}/tags/{tag}", web::post().to(handlers::add_image_tag))
+            .route("/images/{image_id}/tags/{tag}", web::delete().to(handlers::remove_image_tag)),
+    )
+    .await;
+    let tag_uri = |tag: &str| format!("/images/{}/tags/{}", starred, tag);
+
+    for tag in ["favorite", "review", "favorite"] {
+        let req = test::TestRequest::post().uri(&tag_uri(tag)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    let req = test::TestRequest::post().uri(&tag_uri("Not%20Valid")).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let list = |query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/folders/{}/images{}", folder.folder_id, query))
+            .to_request()
+    };
+    let res = test::call_service(&app, list("?tag=favorite")).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 1);
+    assert_eq!(body["data"]["images"][0]["image_id"], starred);
+    assert_eq!(body["data"]["images"][0]["tags"], serde_json::json!(["favorite", "review"]));
+
+    let req = test::TestRequest::delete().uri(&tag_uri("favorite")).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["tags"], serde_json::json!(["review"]));
+
+    let req = test::TestRequest::delete().uri(&tag_uri("favorite")).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = test::call_service(&app, list("?tag=favorite")).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 0);
+
+    // Unfiltered listings still show every image, untagged ones with no tags
+    let res = test::call_service(&app, list("")).await;
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["pagination"]["total"], 2);
+    let plain = body["data"]["images"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|image| image["image_id"] != starred)
+        .unwrap();
+    assert_eq!(plain["tags"], serde_json::json!([]));
+}
+
+// ============================================================================
+// Batch Download URL Tests
+// ============================================================================
+
+/// Storage that only presigns, recording how many signing calls overlap
+#[derive(Default)]
+struct CountingPresigner {
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+#[async_trait]
+impl StorageBackend for CountingPresigner {
+    async fn upload(&self, _key: &str, _bytes: &[u8], _content_type: &str) -> Result<(), StorageError> {
+        Err(StorageError::Unsupported("upload".to_string()))
+    }
+
+    async fn upload_from_file(
+        &self,
+        _key: &str,
+        _file: tokio::fs::File,
+        _content_type: &str,
+    ) -> Result<(), StorageError> {
+        Err(StorageError::Unsupported("upload_from_file".to_string()))
+    }
+
+    async fn get(&self, _key: &str) -> Result<(Vec<u8>, String), StorageError> {
+        Err(StorageError::Unsupported("get".to_string()))
+    }
+
+    async fn object_size(&self, _key: &str) -> Result<u64, StorageError> {
+        Err(StorageError::Unsupported("object_size".to_string()))
+    }
+
+    async fn delete(&self, _key: &str) -> Result<(), StorageError> {
+        Err(StorageError::Unsupported("delete".to_string()))
+    }
+
+    async fn copy(&self, _from: &str, _to: &str) -> Result<(), StorageError> {
+        Err(StorageError::Unsupported("copy".to_string()))
+    }
+
+    async fn presign_put(&self, _key: &str, _content_type: &str) -> Result<String, StorageError> {
+        Err(StorageError::Unsupported("presign_put".to_string()))
+    }
+
+    async fn presign_get(
+        &self,
+        key: &str,
+        _overrides: &ResponseOverrides,
+    ) -> Result<String, StorageError> {
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(format!("https://storage.example.com/{}?signature=test", key))
+    }
+
+    fn presign_expiry_secs(&self) -> u64 {
+        600
+    }
+
+    async fn check_health(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+#[sqlx::test]
+async fn test_batch_download_urls_bounded_and_ordered(pool: PgPool) {
+    let owner = create_test_user(&pool, "presign_owner").await;
+    let folder = FolderRepository::create(&pool, owner, "Presign").await.unwrap();
+
+    let mut image_ids = Vec::new();
+    for i in 0..40 {
+        image_ids.push(create_test_image(&pool, folder.folder_id, &format!("cells_{:02}.jpg", i)).await);
+    }
+
+    let mut config = test_config();
+    config.limits.max_presign_concurrency = 3;
+    let storage = Arc::new(CountingPresigner::default());
+    let backend: Arc<dyn StorageBackend> = storage.clone();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(ReadPool(pool.clone())))
+            .app_data(web::Data::from(backend))
+            .app_data(web::Data::new(config))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "presign_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/download-urls", web::post().to(handlers::get_image_download_urls)),
+    )
+    .await;
+
+    // Ask in reverse order; the response is keyed in ascending ID order regardless
+    let requested: Vec<i64> = image_ids.iter().rev().copied().collect();
+    let req = test::TestRequest::post()
+        .uri("/images/download-urls")
+        .set_json(serde_json::json!({ "image_ids": requested }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Check key order in the raw body, since a parsed map re-sorts its keys
+    let raw = test::read_body(res).await;
+    let text = std::str::from_utf8(&raw).unwrap();
+    let positions: Vec<usize> = image_ids
+        .iter()
+        .map(|id| text.find(&format!("\"{}\":", id)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let body: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+    let urls = body["data"]["urls"].as_object().unwrap();
+    assert_eq!(urls.len(), image_ids.len());
+    assert!(urls[&image_ids[0].to_string()]
+        .as_str()
+        .unwrap()
+        .ends_with("images/cells_00.jpg?signature=test"));
+
+    let peak = storage.peak.load(Ordering::SeqCst);
+    assert!(peak <= 3, "{} presigns ran at once", peak);
+    assert!(peak > 1, "presigns did not run concurrently");
+
+    // An image the caller doesn't own fails the whole batch
+    let req = test::TestRequest::post()
+        .uri("/images/download-urls")
+        .set_json(serde_json::json!({ "image_ids": [image_ids[0], i64::MAX] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Bulk Metadata Tests
+// ============================================================================
+
+#[sqlx::test]
+async fn test_bulk_metadata_sets_captured_at(pool: PgPool) {
+    let owner = create_test_user(&pool, "metadata_owner").await;
+    let other = create_test_user(&pool, "metadata_other").await;
+    let folder = FolderRepository::create(&pool, owner, "Metadata").await.unwrap();
+    let other_folder = FolderRepository::create(&pool, other, "Theirs").await.unwrap();
+
+    let first = create_test_image(&pool, folder.folder_id, "first.jpg").await;
+    let second = create_test_image(&pool, folder.folder_id, "second.jpg").await;
+    let theirs = create_test_image(&pool, other_folder.folder_id, "theirs.jpg").await;
+    sqlx::query("UPDATE images SET metadata = '{\"width\": 640, \"height\": 480}' WHERE image_id = $1")
+        .bind(first)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(test_config()))
+            .wrap_fn(move |req, srv| {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: owner,
+                    username: "metadata_owner".to_string(),
+                    jti: None,
+                });
+                srv.call(req)
+            })
+            .route("/images/bulk-metadata", web::patch().to(handlers::update_images_metadata)),
+    )
+    .await;
+
+    let req = test::TestRequest::patch()
+        .uri("/images/bulk-metadata")
+        .set_json(serde_json::json!({ "updates": [
+            { "image_id": first, "metadata": { "captured_at": "2026-03-01T09:30:00Z" } },
+            { "image_id": theirs, "metadata": { "captured_at": "2026-03-01T09:31:00Z" } },
+            { "image_id": second, "metadata": { "captured_at": "2026-03-01T09:32:00Z" } },
+        ] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["data"]["succeeded"], 2);
+    let results = body["data"]["results"].as_array().unwrap();
+    let outcomes: Vec<(i64, bool)> = results
+        .iter()
+        .map(|r| (r["image_id"].as_i64().unwrap(), r["success"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(outcomes, vec![(first, true), (theirs, false), (second, true)]);
+    assert_eq!(results[1]["error"]["code"], "NOT_FOUND");
+
+    // Existing fields are kept alongside the new one
+    let first_image = ImageRepository::find_by_id(&pool, first, owner).await.unwrap().unwrap();
+    let metadata = first_image.metadata.unwrap();
+    assert_eq!(metadata["width"], 640);
+    assert_eq!(metadata["captured_at"], "2026-03-01T09:30:00Z");
+    let second_image = ImageRepository::find_by_id(&pool, second, owner).await.unwrap().unwrap();
+    assert_eq!(second_image.metadata.unwrap()["captured_at"], "2026-03-01T09:32:00Z");
+    let their_image = ImageRepository::find_by_id(&pool, theirs, other).await.unwrap().unwrap();
+    assert!(their_image.metadata.is_none());
+
+    // Metadata that isn't an `ImageMetadata` rejects the whole batch
+    for metadata in [
+        serde_json::json!({ "captured_at": "yesterday" }),
+        serde_json::json!({ "exposure": 12 }),
+    ] {
+        let req = test::TestRequest::patch()
+            .uri("/images/bulk-metadata")
+            .set_json(serde_json::json!({ "updates": [{ "image_id": second, "metadata": metadata }] }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}